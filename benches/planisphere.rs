@@ -0,0 +1,93 @@
+//! Hot planisphere paths of the terrain builds: subpixel selection around the terrain center,
+//! gnomonic projection of vertex batches and neighbour walks. Run with `cargo bench`; Criterion
+//! compares each run with the previous one.
+//!
+//! The planisphere is the size of the default map but flat: none of these paths read the map.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tiles3d::config;
+use tiles3d::planisphere::{DistanceMethod, Planisphere};
+
+/// Latitudes of the terrain centers: longitude subdivisions shrink toward the poles
+const LATITUDES: [f64; 3] = [0.0, 45.0, 75.0];
+/// Longitude of the terrain centers
+const LONGITUDE: f64 = 10.0;
+/// Terrain radii in subpixels: the default, the render distance slider's middle and top
+const RADII: [usize; 3] = [config::terrain::RADIUS, 40, 80];
+/// Points per `geo_to_gnomonic` batch, about the vertex count of a default terrain
+const BATCH_SIZE: usize = 10_000;
+/// Steps per neighbour walk
+const WALK_STEPS: usize = 1_000;
+
+fn planisphere() -> Planisphere {
+    let mut planisphere = Planisphere::new(864, 432, config::terrain::SUB_K);
+    planisphere.set_radius(config::terrain::PLANET_RADIUS as f64);
+    planisphere
+}
+
+fn subpixels_by_distance(c: &mut Criterion) {
+    let planisphere = planisphere();
+    for method in [DistanceMethod::Manhattan, DistanceMethod::Euclidean, DistanceMethod::Chebyshev] {
+        let mut group = c.benchmark_group(format!("get_subpixels_by_distance_method/{:?}", method));
+        for latitude in LATITUDES {
+            let (i, j, k) = planisphere.geo_to_subpixel(LONGITUDE, latitude);
+            for radius in RADII {
+                group.bench_with_input(BenchmarkId::new(format!("lat{}", latitude), radius), &radius, |b, &radius| {
+                    b.iter(|| planisphere.get_subpixels_by_distance_method(i, j, k, black_box(radius), method))
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+fn gnomonic_batches(c: &mut Criterion) {
+    let planisphere = planisphere();
+    let mut group = c.benchmark_group("geo_to_gnomonic");
+    group.throughput(Throughput::Elements(BATCH_SIZE as u64));
+    for latitude in LATITUDES {
+        // Points on a grid 2 degrees across around the center, like the vertices of a terrain
+        let side = (BATCH_SIZE as f64).sqrt() as usize;
+        let points: Vec<(f64, f64)> = (0..BATCH_SIZE)
+            .map(|n| (LONGITUDE - 1.0 + 2.0 * (n % side) as f64 / side as f64, latitude - 1.0 + 2.0 * (n / side) as f64 / side as f64))
+            .collect();
+        group.bench_with_input(BenchmarkId::from_parameter(format!("lat{}", latitude)), &points, |b, points| {
+            b.iter(|| {
+                points.iter()
+                    .map(|&(lon, lat)| planisphere.geo_to_gnomonic(black_box(lon), black_box(lat), LONGITUDE, latitude))
+                    .fold(0.0, |sum, (x, y)| sum + x + y)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn neighbour_chains(c: &mut Criterion) {
+    let planisphere = planisphere();
+    let mut group = c.benchmark_group("get_neighbour_subpixel");
+    group.throughput(Throughput::Elements(WALK_STEPS as u64));
+    for latitude in LATITUDES {
+        let start = planisphere.geo_to_subpixel(LONGITUDE, latitude);
+        // Staircase walk: one subpixel east then one north, crossing pixel boundaries regularly
+        group.bench_with_input(BenchmarkId::new("staircase", format!("lat{}", latitude)), &start, |b, &start| {
+            b.iter(|| {
+                (0..WALK_STEPS).fold(start, |(i, j, k), step| {
+                    let (di, dj) = if step % 2 == 0 { (1, 0) } else { (0, 1) };
+                    planisphere.get_neighbour_subpixel(i, j, k, black_box(di), black_box(dj))
+                })
+            })
+        });
+        // Eastward walk along the latitude
+        group.bench_with_input(BenchmarkId::new("east", format!("lat{}", latitude)), &start, |b, &start| {
+            b.iter(|| {
+                (0..WALK_STEPS).fold(start, |(i, j, k), _| planisphere.get_neighbour_subpixel(i, j, k, black_box(1), 0))
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, subpixels_by_distance, gnomonic_batches, neighbour_chains);
+criterion_main!(benches);
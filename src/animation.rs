@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Cross-fade duration used when switching clips with `AnimationCommand::Play`
+const DEFAULT_TRANSITION: Duration = Duration::from_millis(250);
+
+/// Template-file description of the animation clips of a glb, e.g.
+/// `animations: Some((clips: {"sway": 0}, autoplay: Some("sway")))`
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AnimatedTemplateFile {
+    #[serde(default)]
+    pub source: Option<String>,         // glb holding the clips, defaults to the template scene file
+    pub clips: HashMap<String, usize>,  // Clip name -> animation index in the glb
+    #[serde(default)]
+    pub autoplay: Option<String>,       // Clip looped as soon as the instance is spawned
+}
+
+/// Runtime animation data of a template: one graph holding every declared clip
+#[derive(Debug, Clone)]
+pub struct AnimatedTemplate {
+    pub graph: Handle<AnimationGraph>,
+    pub clips: HashMap<String, AnimationNodeIndex>,
+    pub autoplay: Option<String>,
+}
+
+impl AnimatedTemplateFile {
+    /// Load the clips from the glb of `scene_path` (unless `source` is given) and build their graph
+    pub fn into_animated_template(self, scene_path: &str, asset_server: &AssetServer) -> AnimatedTemplate {
+        let source = self.source.unwrap_or_else(|| scene_path.split('#').next().unwrap_or(scene_path).to_string());
+        let (names, handles): (Vec<String>, Vec<Handle<AnimationClip>>) = self.clips.into_iter()
+            .map(|(name, index)| (name, asset_server.load(GltfAssetLabel::Animation(index).from_asset(source.clone()))))
+            .unzip();
+        let (graph, node_indices) = AnimationGraph::from_clips(handles);
+        AnimatedTemplate {
+            graph: asset_server.add(graph),
+            clips: names.into_iter().zip(node_indices).collect(),
+            autoplay: self.autoplay,
+        }
+    }
+}
+
+/// Request for the clips of a spawned template instance
+#[derive(Debug, Clone)]
+pub enum AnimationCommand {
+    Play { clip: String, looped: bool },   // Cross-fade to a single clip
+    Blend { clip: String, weight: f32 },   // Play a clip on top of the current ones with a weight
+    Stop { clip: String },
+    StopAll,
+}
+
+/// Animation state of a spawned template instance (on the parent entity).
+/// Commands are queued with `queue` (scripts do it through the `*_animation` functions) and
+/// applied once the glTF scene has spawned its `AnimationPlayer`.
+#[derive(Component, Debug, Clone)]
+pub struct TemplateAnimation {
+    pub template: AnimatedTemplate,
+    pub player: Option<Entity>,          // Entity holding the scene's AnimationPlayer, once found
+    pub pending: Vec<AnimationCommand>,
+}
+
+impl TemplateAnimation {
+    pub fn new(template: AnimatedTemplate) -> Self {
+        let pending = template.autoplay.iter()
+            .map(|clip| AnimationCommand::Play { clip: clip.clone(), looped: true })
+            .collect();
+        Self { template, player: None, pending }
+    }
+
+    pub fn queue(&mut self, command: AnimationCommand) {
+        self.pending.push(command);
+    }
+}
+
+/// Hook newly spawned `AnimationPlayer`s of template scenes to their template's graph
+pub fn bind_template_animations(
+    mut commands: Commands,
+    new_players: Query<Entity, Added<AnimationPlayer>>,
+    parents: Query<&ChildOf>,
+    mut animated_query: Query<&mut TemplateAnimation>,
+) {
+    for player_entity in new_players.iter() {
+        // The player sits somewhere below the scene root; find the instance that owns it
+        let Some(owner) = std::iter::successors(Some(player_entity), |entity| parents.get(*entity).ok().map(|child_of| child_of.parent()))
+            .find(|entity| animated_query.contains(*entity)) else { continue; };
+        let Ok(mut animation) = animated_query.get_mut(owner) else { continue; };
+        if animation.player.is_some() {
+            continue; // Only the first player of a scene is driven
+        }
+        animation.player = Some(player_entity);
+        commands.entity(player_entity).insert((
+            AnimationGraphHandle(animation.template.graph.clone()),
+            AnimationTransitions::new(),
+        ));
+    }
+}
+
+/// Apply queued animation commands to the bound players
+pub fn apply_animation_commands(
+    mut animated_query: Query<&mut TemplateAnimation>,
+    mut player_query: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    for mut animation in animated_query.iter_mut() {
+        let Some(player_entity) = animation.player else { continue; };
+        if animation.pending.is_empty() {
+            continue;
+        }
+        // AnimationTransitions is inserted through commands; wait until it is there
+        let Ok((mut player, mut transitions)) = player_query.get_mut(player_entity) else { continue; };
+        let pending = std::mem::take(&mut animation.pending);
+        for command in pending {
+            let clip = match &command {
+                AnimationCommand::Play { clip, .. } | AnimationCommand::Blend { clip, .. } | AnimationCommand::Stop { clip } => Some(clip),
+                AnimationCommand::StopAll => None,
+            };
+            let node = match clip.map(|clip| animation.template.clips.get(clip).copied().ok_or(clip)) {
+                Some(Ok(node)) => Some(node),
+                Some(Err(clip)) => {
+                    warn!("Unknown animation clip '{}'", clip);
+                    continue;
+                }
+                None => None,
+            };
+            match (command, node) {
+                (AnimationCommand::Play { looped, .. }, Some(node)) => {
+                    let active = transitions.play(&mut player, node, DEFAULT_TRANSITION);
+                    if looped {
+                        active.repeat();
+                    }
+                }
+                (AnimationCommand::Blend { weight, .. }, Some(node)) => {
+                    player.play(node).repeat().set_weight(weight);
+                }
+                (AnimationCommand::Stop { .. }, Some(node)) => {
+                    player.stop(node);
+                }
+                _ => {
+                    player.stop_all();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use bevy::prelude::*;
+
+use crate::game_object::{shared_template_material, spawn_template_with_material, CollisionBehavior, EntityInfoOverlay,
+                         IntoWorldPosition, MissingTemplate, ObjectDefinitionBuilder, ObjectTemplates};
+use crate::planisphere::Planisphere;
+use crate::props::Prop;
+use crate::terrain::{geo_to_world, ijk_to_world, TerrainCenter, TerrainReady};
+
+/// What a beacon marks; each kind animates differently so they can be told apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BeaconKind {
+    TerrainCenter,   // Debug beacon on the tile the terrain is centered on
+    Waypoint,        // Player-placed waypoint
+    Trail,           // Tile of a `PathTrail`
+    Marker,          // Temporary marker requested through `SpawnMarker`
+}
+
+/// Animation parameters of a beacon kind
+#[derive(Debug, Clone, Copy)]
+pub struct BeaconStyle {
+    pub pulse_hz: f32,           // Emissive pulses per second
+    pub emissive_min: f32,       // Emissive strength range, relative to the base color
+    pub emissive_max: f32,
+    pub spin_radians_per_sec: f32,
+    pub bob_height: f32,         // Vertical amplitude in world units
+    pub bob_hz: f32,
+}
+
+impl BeaconKind {
+    pub fn style(self) -> BeaconStyle {
+        match self {
+            BeaconKind::TerrainCenter => BeaconStyle {
+                pulse_hz: 2.0,
+                emissive_min: 0.0,
+                emissive_max: 1.5,
+                spin_radians_per_sec: 0.0,
+                bob_height: 0.0,
+                bob_hz: 0.0,
+            },
+            BeaconKind::Waypoint => BeaconStyle {
+                pulse_hz: 0.8,
+                emissive_min: 0.5,
+                emissive_max: 4.0,
+                spin_radians_per_sec: 1.2,
+                bob_height: 0.4,
+                bob_hz: 0.5,
+            },
+            BeaconKind::Trail => BeaconStyle {
+                pulse_hz: 1.5,
+                emissive_min: 0.3,
+                emissive_max: 1.0,
+                spin_radians_per_sec: 0.0,
+                bob_height: 0.0,
+                bob_hz: 0.0,
+            },
+            BeaconKind::Marker => BeaconStyle {
+                pulse_hz: 3.0,
+                emissive_min: 0.2,
+                emissive_max: 2.5,
+                spin_radians_per_sec: 2.5,
+                bob_height: 0.15,
+                bob_hz: 1.0,
+            },
+        }
+    }
+
+    /// Whether the pool may despawn beacons of this kind (outside the rendered area or over
+    /// the cap). Waypoint beacons are owned by their waypoint and always kept.
+    fn pooled(self) -> bool {
+        self != BeaconKind::Waypoint
+    }
+}
+
+/// Template of the markers placed through `SpawnMarker`
+pub const MARKER_TEMPLATE: &str = "marker";
+
+/// Ask the beacon module for a temporary marker on a tile. `kind` groups markers for
+/// `ClearMarkers` (e.g. "ai", "script"); without `ttl` (seconds) the marker stays until cleared
+/// or culled with the other pooled beacons.
+#[derive(Event, Debug, Clone)]
+pub struct SpawnMarker {
+    pub kind: String,
+    pub tile: (usize, usize, usize),
+    pub label: Option<String>,
+    pub ttl: Option<f32>,
+}
+
+/// Remove every marker of a kind
+#[derive(Event, Debug, Clone)]
+pub struct ClearMarkers(pub String);
+
+/// Marker spawned from a `SpawnMarker` request
+#[derive(Component, Debug)]
+pub struct Marker {
+    pub kind: String,
+    pub lifetime: Option<Timer>,
+}
+
+/// Where a beacon stands, independent of the terrain center. Its world position is recomputed
+/// from this by `reanchor_beacons` whenever a new terrain is ready.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum BeaconAnchor {
+    Tile((usize, usize, usize)),   // Center of a subpixel
+    Geo(f64, f64),                 // Longitude, latitude in degrees
+}
+
+impl IntoWorldPosition for BeaconAnchor {
+    fn into_world_position(&self, planisphere: &Planisphere, terrain_center: &TerrainCenter) -> Vec3 {
+        match *self {
+            BeaconAnchor::Tile((i, j, k)) => ijk_to_world(i as i32, j as i32, k as i32, planisphere, terrain_center),
+            BeaconAnchor::Geo(lon, lat) => geo_to_world(lon, lat, planisphere, terrain_center),
+        }
+    }
+
+    fn tile(&self) -> Option<(usize, usize, usize)> {
+        match *self {
+            BeaconAnchor::Tile(tile) => Some(tile),
+            BeaconAnchor::Geo(..) => None,
+        }
+    }
+}
+
+/// Beacon animated by `animate_beacons`. `base_y` is captured on the first update so bobbing
+/// oscillates around the spawn height.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AnimatedBeacon {
+    pub kind: BeaconKind,
+    base_y: Option<f32>,
+}
+
+impl AnimatedBeacon {
+    pub fn new(kind: BeaconKind) -> Self {
+        Self { kind, base_y: None }
+    }
+}
+
+/// Beacon spawned through the pool, oldest first
+#[derive(Debug, Clone, Copy)]
+struct PooledBeacon {
+    entity: Entity,
+    kind: BeaconKind,
+    tile: Option<(usize, usize, usize)>,
+}
+
+/// Shares one material per template and beacon kind, and keeps track of spawned beacons so
+/// `cull_beacons` can drop those outside the rendered area and enforce
+/// `config::beacons::MAX_BEACONS`
+#[derive(Resource, Default)]
+pub struct BeaconPool {
+    materials: HashMap<(String, BeaconKind), Handle<StandardMaterial>>,
+    spawned: VecDeque<PooledBeacon>,
+    culled_area: Option<((usize, usize, usize), usize)>,   // (terrain center, rendered count) last culled for
+}
+
+/// Spawn a beacon from a template with the pooled material of its kind. Beacons are props
+/// (their tags still apply, e.g. debug beacons only show with F3) and never collide.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_beacon(
+    commands: &mut Commands,
+    pool: &mut BeaconPool,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    planisphere: &Planisphere,
+    terrain_center: &TerrainCenter,
+    object_templates: &ObjectTemplates,
+    template_name: &str,
+    anchor: BeaconAnchor,
+    kind: BeaconKind,
+) -> Result<Entity, MissingTemplate> {
+    let template = object_templates.get(template_name)?;
+    let material = pool.materials
+        .entry((template_name.to_string(), kind))
+        .or_insert_with(|| materials.add(shared_template_material(template)))
+        .clone();
+    let entity = spawn_template_with_material(
+        commands,
+        material,
+        planisphere,
+        terrain_center,
+        template,
+        anchor,
+        ObjectDefinitionBuilder::from_template(template).collision(CollisionBehavior::None).build(),
+        (Prop::new(template), AnimatedBeacon::new(kind), anchor),
+    );
+    pool.spawned.push_back(PooledBeacon { entity, kind, tile: anchor.tile() });
+    Ok(entity)
+}
+
+/// Forget despawned beacons, despawn pooled beacons whose tile left the rendered area when it
+/// changes, then the oldest ones beyond `config::beacons::MAX_BEACONS`. Runs after the Update
+/// commands are applied so beacons spawned this frame exist.
+pub fn cull_beacons(
+    mut commands: Commands,
+    mut pool: ResMut<BeaconPool>,
+    terrain_center: Res<TerrainCenter>,
+    beacons: Query<(), With<AnimatedBeacon>>,
+) {
+    let pool = pool.as_mut();
+    pool.spawned.retain(|beacon| beacons.contains(beacon.entity));
+
+    let area = (terrain_center.subpixel, terrain_center.rendered_subpixels.subpixels.len());
+    if pool.culled_area != Some(area) && area.1 > 0 {
+        pool.culled_area = Some(area);
+        let rendered: HashSet<(usize, usize, usize)> = terrain_center.rendered_subpixels.subpixels.iter()
+            .map(|(i, j, k, _)| (*i, *j, *k))
+            .collect();
+        pool.spawned.retain(|beacon| {
+            let outside = beacon.kind.pooled() && beacon.tile.is_some_and(|tile| !rendered.contains(&tile));
+            if outside {
+                commands.entity(beacon.entity).despawn();
+            }
+            !outside
+        });
+    }
+
+    let mut excess = pool.spawned.iter().filter(|beacon| beacon.kind.pooled()).count()
+        .saturating_sub(crate::config::beacons::MAX_BEACONS);
+    pool.spawned.retain(|beacon| {
+        if excess == 0 || !beacon.kind.pooled() {
+            return true;
+        }
+        excess -= 1;
+        commands.entity(beacon.entity).despawn();
+        false
+    });
+}
+
+/// Move every anchored beacon to the world position of its anchor once a new terrain is ready
+/// (the projection center changed). Keeps the height, which bobbing animates.
+pub fn reanchor_beacons(
+    mut ready_events: EventReader<TerrainReady>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    mut beacons: Query<(&BeaconAnchor, &mut Transform)>,
+) {
+    let Some(ready) = ready_events.read().last() else { return; };
+    let mut count = 0;
+    for (anchor, mut transform) in beacons.iter_mut() {
+        let position = anchor.into_world_position(&planisphere, &terrain_center);
+        transform.translation.x = position.x;
+        transform.translation.z = position.z;
+        count += 1;
+    }
+    debug!("Re-anchored {} beacons around {:?}", count, ready.center);
+}
+
+/// Spin and bob beacons, and pulse the emissive of the pooled materials. Materials are shared
+/// per kind, so the pulse is too; spinning and bobbing are phase shifted by entity so
+/// neighbours do not move in sync.
+pub fn animate_beacons(
+    time: Res<Time>,
+    pool: Res<BeaconPool>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut beacons: Query<(Entity, &mut AnimatedBeacon, &mut Transform)>,
+) {
+    let t = time.elapsed_secs();
+    let dt = time.delta_secs();
+    for (entity, mut beacon, mut transform) in beacons.iter_mut() {
+        let style = beacon.kind.style();
+        let phase = (entity.index() % 16) as f32 * 0.4;
+
+        if style.spin_radians_per_sec != 0.0 {
+            transform.rotate_y(style.spin_radians_per_sec * dt);
+        }
+        if style.bob_height != 0.0 {
+            let base_y = *beacon.base_y.get_or_insert(transform.translation.y);
+            transform.translation.y = base_y + style.bob_height * (std::f32::consts::TAU * style.bob_hz * t + phase).sin();
+        }
+    }
+
+    for ((_, kind), handle) in pool.materials.iter() {
+        let style = kind.style();
+        let wave = 0.5 + 0.5 * (std::f32::consts::TAU * style.pulse_hz * t).sin();
+        let strength = style.emissive_min + (style.emissive_max - style.emissive_min) * wave;
+        if let Some(material) = materials.get_mut(handle) {
+            material.emissive = material.base_color.to_linear() * strength;
+        }
+    }
+}
+
+/// Spawn the markers requested with `SpawnMarker` and remove those of `ClearMarkers` kinds
+#[allow(clippy::too_many_arguments)]
+pub fn handle_marker_requests(
+    mut commands: Commands,
+    mut spawn_events: EventReader<SpawnMarker>,
+    mut clear_events: EventReader<ClearMarkers>,
+    mut pool: ResMut<BeaconPool>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    object_templates: Res<ObjectTemplates>,
+    markers: Query<(Entity, &Marker)>,
+) {
+    for ClearMarkers(kind) in clear_events.read() {
+        for (entity, marker) in markers.iter() {
+            if marker.kind == *kind {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+
+    for request in spawn_events.read() {
+        match spawn_beacon(&mut commands, &mut pool, &mut materials, &planisphere, &terrain_center, &object_templates,
+                           MARKER_TEMPLATE, BeaconAnchor::Tile(request.tile), BeaconKind::Marker) {
+            Ok(entity) => {
+                commands.entity(entity).insert(Marker {
+                    kind: request.kind.clone(),
+                    lifetime: request.ttl.map(|seconds| Timer::from_seconds(seconds, TimerMode::Once)),
+                });
+                if let Some(label) = &request.label {
+                    commands.entity(entity).insert(EntityInfoOverlay::nameplate(label.clone()));
+                }
+            }
+            Err(e) => {
+                warn!("Cannot spawn {} marker: {}", request.kind, e);
+                break;
+            }
+        }
+    }
+}
+
+/// Despawn markers whose time to live ran out
+pub fn expire_markers(mut commands: Commands, time: Res<Time>, mut markers: Query<(Entity, &mut Marker)>) {
+    for (entity, mut marker) in markers.iter_mut() {
+        if marker.lifetime.as_mut().is_some_and(|timer| timer.tick(time.delta()).finished()) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Asset preprocessing: `cargo run --bin tiles3d-preprocess` validates the elevation map and
+//! writes the texture atlas, planisphere cache, mip pyramid and manifest the game loads at
+//! startup (`tiles3d::preprocess`). Run it again after changing the map or the textures.
+
+use std::path::Path;
+
+use tiles3d::preprocess::{self, PreprocessOptions, MANIFEST_FILE};
+
+const USAGE: &str = "\
+Usage: tiles3d-preprocess [OPTIONS]
+
+Options:
+  --map <path>         Elevation and texture map (default assets/maps/sphere_texture.png)
+  --textures <dir>     Atlas source textures (default assets/textures/img)
+  --out <dir>          Output directory (default assets/cache)
+  -h, --help           Print this help";
+
+/// Parse the arguments following the program name; `None` when help was asked for
+fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<PreprocessOptions>, String> {
+    let mut options = PreprocessOptions::default();
+    let mut args = args.into_iter();
+    while let Some(name) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", name));
+        match name.as_str() {
+            "--map" => options.map = value()?,
+            "--textures" => options.textures_dir = value()?,
+            "--out" => options.out_dir = value()?,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("Unknown argument '{}'", name)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn main() {
+    let options = match parse(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+    match preprocess::run(&options) {
+        Ok(manifest) => {
+            println!("Texture atlas with {} textures: {}", manifest.atlas.textures.len(), manifest.atlas.path);
+            println!("Planisphere cache: {}", manifest.planisphere_cache);
+            println!("Mip pyramid: {} levels", manifest.mips.len());
+            println!("Manifest: {}", Path::new(&options.out_dir).join(MANIFEST_FILE).display());
+        }
+        Err(e) => {
+            eprintln!("Preprocessing failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
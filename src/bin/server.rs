@@ -0,0 +1,325 @@
+//! Dedicated tiles3d server: runs the headless simulation and is authoritative for the players
+//! and placed objects of the game clients connected with `tiles3d --connect <host:port>`.
+//!
+//! Each client has its own terrain center, recentred like the game's terrain as its player moves;
+//! players and objects are only replicated to the clients whose area they are in.
+
+use std::collections::{HashMap, HashSet};
+use std::net::TcpListener;
+use std::time::Duration;
+
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use tiles3d::config;
+use tiles3d::net::{step_geo, ClientMessage, Connection, NetObject, PlayerState, ServerMessage, PROTOCOL_VERSION};
+use tiles3d::planisphere::Planisphere;
+use tiles3d::preprocess;
+use tiles3d::tiling::WorldSeed;
+
+const USAGE: &str = "\
+Usage: tiles3d-server [OPTIONS]
+
+Options:
+  --port <n>           TCP port to listen on (default 7878)
+  --map <path>         Elevation and texture map, the same as the clients'
+  --spawn <lon,lat>    Spawn point in degrees
+  --seed <n>           World seed sent to the clients
+  -h, --help           Print this help";
+
+/// Command-line options of the server
+#[derive(Debug, Clone)]
+struct ServerArgs {
+    port: u16,
+    map: String,
+    spawn: (f64, f64),
+    seed: u64,
+}
+
+impl Default for ServerArgs {
+    fn default() -> Self {
+        Self {
+            port: config::net::DEFAULT_PORT,
+            map: "assets/maps/sphere_texture.png".to_string(),
+            spawn: (config::player::INITIAL_LON as f64, config::player::INITIAL_LAT as f64),
+            seed: 0,
+        }
+    }
+}
+
+impl ServerArgs {
+    /// Parse the arguments following the program name; `None` when help was asked for
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(name) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", name));
+            match name.as_str() {
+                "--port" => parsed.port = value()?.parse().map_err(|_| "--port expects a port number".to_string())?,
+                "--map" => parsed.map = value()?,
+                "--spawn" => {
+                    let spawn = value()?;
+                    let (lon, lat) = spawn.split_once(',').ok_or(format!("--spawn expects lon,lat, got '{}'", spawn))?;
+                    let lon = lon.trim().parse().map_err(|_| format!("--spawn expects numbers, got '{}'", spawn))?;
+                    let lat = lat.trim().parse().map_err(|_| format!("--spawn expects numbers, got '{}'", spawn))?;
+                    parsed.spawn = (lon, lat);
+                }
+                "--seed" => parsed.seed = value()?.parse().map_err(|_| "--seed expects a number".to_string())?,
+                "-h" | "--help" => return Ok(None),
+                _ => return Err(format!("Unknown argument '{}'", name)),
+            }
+        }
+        Ok(Some(parsed))
+    }
+}
+
+/// Connected client and its player
+struct Client {
+    connection: Connection,
+    name: Option<String>,              // Set by `Hello`; nothing is replicated before
+    geo: (f64, f64),                   // Authoritative player position (longitude, latitude)
+    facing: f32,
+    velocity: (f32, f32),              // Last requested ground velocity (east, north)
+    terrain_center: (usize, usize, usize),
+    center_geo: (f64, f64),
+    known_objects: HashSet<u64>,       // Objects the client has
+    closed: bool,                      // Dropped at the end of the tick
+}
+
+/// Object placed by a client; only that client can remove it
+struct PlacedObject {
+    owner: u32,
+    object: NetObject,
+}
+
+/// Listener, clients and the shared world state
+#[derive(Resource)]
+struct Server {
+    listener: TcpListener,
+    seed: WorldSeed,
+    spawn: (f64, f64),
+    clients: HashMap<u32, Client>,
+    next_client_id: u32,
+    objects: HashMap<u64, PlacedObject>,
+    next_object_id: u64,
+    tick: u64,
+}
+
+fn main() {
+    let args = match ServerArgs::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // Logging is set up by the plugins, before the map is loaded
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(1.0 / config::net::TICK_HZ))),
+        LogPlugin::default(),
+    ));
+    let mut planisphere = match preprocess::load_planisphere(&args.map, config::terrain::SUB_K) {
+        Ok(planisphere) => planisphere,
+        Err(e) => {
+            error!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    planisphere.set_radius(config::terrain::PLANET_RADIUS as f64);
+    let listener = match TcpListener::bind(("0.0.0.0", args.port)).and_then(|listener| listener.set_nonblocking(true).map(|_| listener)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Cannot listen on port {}: {}", args.port, e);
+            std::process::exit(1);
+        }
+    };
+    info!("tiles3d server listening on port {} (map {}, seed {})", args.port, args.map, args.seed);
+
+    let seed = WorldSeed(args.seed);
+    seed.install();
+    app.insert_resource(planisphere)
+        .insert_resource(Server {
+            listener,
+            seed,
+            spawn: args.spawn,
+            clients: HashMap::new(),
+            next_client_id: 1,
+            objects: HashMap::new(),
+            next_object_id: 1,
+            tick: 0,
+        })
+        .add_systems(Update, (accept_clients, receive_client_messages, simulate_players, recenter_terrain, replicate).chain())
+        .run();
+}
+
+/// Accept pending connections; players start at the spawn point
+fn accept_clients(mut server: ResMut<Server>, planisphere: Res<Planisphere>) {
+    loop {
+        let stream = match server.listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => return,
+            Err(e) => {
+                warn!("Failed to accept a connection: {}", e);
+                return;
+            }
+        };
+        let connection = match Connection::new(stream) {
+            Ok(connection) => connection,
+            Err(e) => {
+                warn!("Failed to set up a connection: {}", e);
+                continue;
+            }
+        };
+        let spawn = server.spawn;
+        let id = server.next_client_id;
+        server.next_client_id += 1;
+        info!("Client {} connected from {}", id, connection.peer());
+        server.clients.insert(id, Client {
+            connection,
+            name: None,
+            geo: spawn,
+            facing: 0.0,
+            velocity: (0.0, 0.0),
+            terrain_center: planisphere.geo_to_subpixel(spawn.0, spawn.1),
+            center_geo: spawn,
+            known_objects: HashSet::new(),
+            closed: false,
+        });
+    }
+}
+
+/// Whether `tile` is a subpixel of the map
+fn is_map_tile(planisphere: &Planisphere, (i, j, k): (usize, usize, usize)) -> bool {
+    let divisions = planisphere.subpixel_divisions;
+    i < planisphere.width_pixels && j < planisphere.height_pixels && k < divisions * divisions
+}
+
+/// Handle the messages of every client. Inputs with non-finite values, objects off the map and
+/// removals of another client's objects are refused.
+fn receive_client_messages(mut server: ResMut<Server>, planisphere: Res<Planisphere>) {
+    let server = server.as_mut();
+    let (seed, spawn) = (server.seed, server.spawn);
+    for (id, client) in server.clients.iter_mut() {
+        let messages = match client.connection.receive::<ClientMessage>() {
+            Ok(messages) => messages,
+            Err(e) => {
+                info!("Client {} disconnected: {}", id, e);
+                client.closed = true;
+                continue;
+            }
+        };
+        for message in messages {
+            match message {
+                ClientMessage::Hello { version, name } if version != PROTOCOL_VERSION => {
+                    let reason = format!("protocol version {} expected, got {}", PROTOCOL_VERSION, version);
+                    info!("Rejecting client {} ({}): {}", id, name, reason);
+                    let _ = client.connection.send(&ServerMessage::Rejected { reason });
+                    client.closed = true;
+                }
+                ClientMessage::Hello { name, .. } => {
+                    info!("Client {} joined as {}", id, name);
+                    client.name = Some(name);
+                    let welcome = ServerMessage::Welcome { version: PROTOCOL_VERSION, client_id: *id, seed: seed.0, spawn };
+                    client.closed |= client.connection.send(&welcome).is_err();
+                }
+                ClientMessage::Input { velocity, facing } if !(velocity.0.is_finite() && velocity.1.is_finite() && facing.is_finite()) => {
+                    warn!("Client {}: ignoring non-finite input {:?}, {}", id, velocity, facing);
+                }
+                ClientMessage::Input { velocity, facing } => {
+                    // Requests above the sprint speed are capped
+                    let max_speed = config::player::MOVE_SPEED * config::player::SPRINT_MULTIPLIER;
+                    let scale = (max_speed / Vec2::from(velocity).length().max(max_speed)).min(1.0);
+                    client.velocity = (velocity.0 * scale, velocity.1 * scale);
+                    client.facing = facing;
+                }
+                ClientMessage::PlaceObject { object, .. } if !is_map_tile(&planisphere, object.tile) || !object.rotation.iter().all(|value| value.is_finite()) => {
+                    warn!("Client {}: ignoring {} placed at {:?} with rotation {:?}", id, object.template, object.tile, object.rotation);
+                }
+                ClientMessage::PlaceObject { local_ref, object } => {
+                    let object_id = server.next_object_id;
+                    server.next_object_id += 1;
+                    server.objects.insert(object_id, PlacedObject { owner: *id, object: NetObject { id: object_id, ..object } });
+                    client.known_objects.insert(object_id);
+                    client.closed |= client.connection.send(&ServerMessage::ObjectPlaced { local_ref, id: object_id }).is_err();
+                }
+                ClientMessage::RemoveObject { id: object_id } => {
+                    match server.objects.get(&object_id) {
+                        Some(placed) if placed.owner == *id => { server.objects.remove(&object_id); }
+                        Some(placed) => warn!("Client {}: cannot remove object {} of client {}", id, object_id, placed.owner),
+                        None => {}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Move the players with the velocity their client asked for
+fn simulate_players(time: Res<Time>, mut server: ResMut<Server>, planisphere: Res<Planisphere>) {
+    let dt = time.delta_secs_f64();
+    for client in server.clients.values_mut().filter(|client| client.name.is_some()) {
+        let velocity = (client.velocity.0 as f64, client.velocity.1 as f64);
+        client.geo = step_geo(client.geo, velocity, dt, planisphere.radius);
+    }
+}
+
+/// Recentre a client's terrain once its player is further than the game's recreation threshold
+/// from it
+fn recenter_terrain(mut server: ResMut<Server>, planisphere: Res<Planisphere>) {
+    let threshold = (config::terrain::RADIUS / config::terrain::RECREATION_THRESHOLD_DIVISOR).max(1) as f64 * planisphere.mean_tile_size;
+    for client in server.clients.values_mut() {
+        if planisphere.geodesic_distance(client.geo, client.center_geo) > threshold {
+            client.terrain_center = planisphere.geo_to_subpixel(client.geo.0, client.geo.1);
+            client.center_geo = client.geo;
+        }
+    }
+}
+
+/// Send each client the players around its terrain center and the objects entering or leaving
+/// its area, then drop closed connections
+fn replicate(mut server: ResMut<Server>, planisphere: Res<Planisphere>) {
+    let server = server.as_mut();
+    server.tick += 1;
+    let interest = config::net::INTEREST_RADIUS_TILES * planisphere.mean_tile_size;
+    let players: Vec<PlayerState> = server.clients.iter()
+        .filter_map(|(id, client)| client.name.as_ref().map(|name| PlayerState { id: *id, name: name.clone(), geo: client.geo, facing: client.facing }))
+        .collect();
+
+    for (id, client) in server.clients.iter_mut().filter(|(_, client)| client.name.is_some() && !client.closed) {
+        let center_geo = client.center_geo;
+        let in_area = |geo: (f64, f64)| planisphere.geodesic_distance(geo, center_geo) <= interest;
+        let mut messages = vec![ServerMessage::Snapshot {
+            tick: server.tick,
+            terrain_center: client.terrain_center,
+            players: players.iter().filter(|player| player.id == *id || in_area(player.geo)).cloned().collect(),
+        }];
+        let leaving: Vec<u64> = client.known_objects.iter().copied()
+            .filter(|object_id| server.objects.get(object_id)
+                .is_none_or(|placed| !in_area(planisphere.subpixel_to_geo(placed.object.tile.0, placed.object.tile.1, placed.object.tile.2))))
+            .collect();
+        for object_id in leaving {
+            client.known_objects.remove(&object_id);
+            messages.push(ServerMessage::ObjectRemoved { id: object_id });
+        }
+        for object in server.objects.values().map(|placed| &placed.object) {
+            if !client.known_objects.contains(&object.id) && in_area(planisphere.subpixel_to_geo(object.tile.0, object.tile.1, object.tile.2)) {
+                client.known_objects.insert(object.id);
+                messages.push(ServerMessage::ObjectSpawned { object: object.clone() });
+            }
+        }
+        for message in &messages {
+            if let Err(e) = client.connection.send(message) {
+                info!("Client {} disconnected: {}", id, e);
+                client.closed = true;
+                break;
+            }
+        }
+    }
+    server.clients.retain(|_, client| !client.closed);
+}
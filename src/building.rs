@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_object::{spawn_template_scene, EntitySubpixelPosition, ObjectDefinitionBuilder, ObjectTemplates,
+                         RaycastTileLocator};
+use crate::items::{ItemDefinitions, ItemStack};
+use crate::message_log::{LogCategory, LogMessage};
+use crate::occupancy::TileOccupancy;
+use crate::persistence::{PersistedObjects, PersistentObject};
+use crate::planisphere::Planisphere;
+use crate::player::{CursorTarget, Player, PlayerInventory};
+use crate::terrain::TerrainCenter;
+
+/// Template placed by the build key
+pub const BUILD_TEMPLATE: &str = "rock";
+/// Materials consumed by one placement (refunded on undo/dismantle)
+pub const BUILD_COST: &[(&str, u32)] = &[("stone", 1)];
+
+/// Marks an object built by the player, with what it cost so it can be refunded. Saved with the
+/// object's persistent record and restored when it is respawned.
+#[derive(Component, Debug, Clone, Serialize, Deserialize)]
+pub struct PlacedObject {
+    pub template: String,
+    pub cost: Vec<ItemStack>,
+}
+
+/// Emitted when a player-built object is placed or removed
+#[derive(Event, Debug, Clone)]
+pub enum BuildEvent {
+    Placed { entity: Entity, template: String, tile: (usize, usize, usize) },
+    Removed { entity: Entity },
+}
+
+/// Record a building message in the message log and the game log
+fn log_building(log: &mut EventWriter<LogMessage>, message: String) {
+    info!("{}", message);
+    log.write(LogMessage::new(LogCategory::Building, message));
+}
+
+/// Place, undo and dismantle player-built objects:
+/// - `B` places `BUILD_TEMPLATE` centered on the free tile under the cursor, paying `BUILD_COST` from the inventory
+/// - `Ctrl+Z` removes the most recent placement still in the world (the newest persistent record)
+/// - `X` dismantles the placed object on the tile under the cursor
+///
+/// Placed objects carry `PersistentObject`, so removing them also drops their saved record.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_building_input(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    persisted: Res<PersistedObjects>,
+    object_templates: Res<ObjectTemplates>,
+    item_definitions: Res<ItemDefinitions>,
+    occupancy: Res<TileOccupancy>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    mut player_query: Query<&mut PlayerInventory, With<Player>>,
+    cursor: Res<CursorTarget>,
+    placed_query: Query<(Entity, &PlacedObject, &EntitySubpixelPosition)>,
+    mut build_events: EventWriter<BuildEvent>,
+    mut log: EventWriter<LogMessage>,
+) {
+    let Ok(mut inventory) = player_query.single_mut() else { return; };
+    let target_tile = cursor.tile;
+
+    // Place
+    if keyboard_input.just_pressed(KeyCode::KeyB) {
+        let Some(tile) = target_tile else { return; };
+        if let Some(occupant) = occupancy.occupant(tile) {
+            log_building(&mut log, format!("Cannot build {}: tile {:?} is occupied by {:?}", BUILD_TEMPLATE, tile, occupant));
+            return;
+        }
+        let template = match object_templates.get(BUILD_TEMPLATE) {
+            Ok(template) => template,
+            Err(e) => {
+                log.write(LogMessage::new(LogCategory::Error, format!("Cannot build: {}", e)));
+                return;
+            }
+        };
+        if let Some((item_id, count)) = BUILD_COST.iter().find(|(item_id, count)| inventory.count(item_id) < *count) {
+            log_building(&mut log, format!("Cannot build {}: needs {} x {}", BUILD_TEMPLATE, count, item_definitions.display_name(item_id)));
+            return;
+        }
+        let cost: Vec<ItemStack> = BUILD_COST.iter()
+            .map(|(item_id, count)| ItemStack { item_id: item_id.to_string(), count: *count })
+            .collect();
+        for stack in cost.iter() {
+            inventory.remove(&stack.item_id, stack.count);
+        }
+
+        let entity = spawn_template_scene(
+            &mut commands,
+            &mut materials,
+            &planisphere,
+            &terrain_center,
+            template,
+            tile,
+            template.y_offset,
+            ObjectDefinitionBuilder::from_template(template).fixed().build(),
+            (
+                PlacedObject { template: BUILD_TEMPLATE.to_string(), cost },
+                PersistentObject { template: BUILD_TEMPLATE.to_string(), dynamic: false },
+                EntitySubpixelPosition { subpixel: tile, previous_subpixel: tile, ..default() },
+                RaycastTileLocator { last_tile: Some(tile) },
+            ),
+        );
+        log_building(&mut log, format!("Placed {} at {:?}", BUILD_TEMPLATE, tile));
+        build_events.write(BuildEvent::Placed { entity, template: BUILD_TEMPLATE.to_string(), tile });
+        return;
+    }
+
+    // Undo: the placement with the newest record among those in the world; one placed this
+    // frame has no record yet
+    let undo = keyboard_input.just_pressed(KeyCode::KeyZ)
+        && (keyboard_input.pressed(KeyCode::ControlLeft) || keyboard_input.pressed(KeyCode::ControlRight));
+    let to_remove = if undo {
+        let found = placed_query.iter()
+            .max_by_key(|(entity, _, _)| persisted.record_id(*entity).unwrap_or(u64::MAX))
+            .map(|(entity, _, _)| entity);
+        if found.is_none() {
+            log_building(&mut log, "Nothing to undo".to_string());
+        }
+        found
+    } else if keyboard_input.just_pressed(KeyCode::KeyX) {
+        target_tile.and_then(|tile| placed_query.iter()
+            .find(|(_, _, position)| position.subpixel == tile)
+            .map(|(entity, _, _)| entity))
+    } else {
+        None
+    };
+
+    // Remove and refund
+    let Some(entity) = to_remove else { return; };
+    let Ok((_, placed, _)) = placed_query.get(entity) else { return; };
+    for stack in placed.cost.iter() {
+        inventory.add(&item_definitions, &stack.item_id, stack.count);
+    }
+    commands.entity(entity).despawn();
+    log_building(&mut log, format!("Removed {} (refunded {:?})", placed.template, placed.cost));
+    build_events.write(BuildEvent::Removed { entity });
+}
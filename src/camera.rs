@@ -1,12 +1,26 @@
 // Import statements - bring in code from other modules and crates
 use bevy::prelude::*;                               // Bevy game engine core functionality
-use bevy::input::mouse::{MouseScrollUnit, MouseWheel}; // Mouse input handling
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel}; // Mouse input handling
 use bevy::input::keyboard::KeyCode; // Keyboard input handling
 use bevy::input::ButtonInput; // Button input handling
+use bevy_rapier3d::prelude::*;                     // Physics queries for camera collision
+use bevy_rapier3d::plugin::context::systemparams::ReadRapierContext;
+use bevy::time::Real;                               // Real-time clock, unaffected by the simulation speed
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
 use crate::player::Player;                         // Import Player component
+use crate::settings::{CameraSettings, Settings};   // Camera tuning from the settings file
 
 // Removed unused CameraController component
 
+/// What the mouse wheel changes
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZoomMode {
+    #[default]
+    Distance,   // Move the camera closer to / further from the player
+    Fov,        // Keep the distance, narrow or widen the field of view
+}
+
 /// ThirdPersonCamera Component - Marks a camera as third person following the player
 #[derive(Component)]
 pub struct ThirdPersonCamera {
@@ -16,19 +30,105 @@ pub struct ThirdPersonCamera {
     pub min_distance: f32,   // Minimum zoom distance
     pub max_distance: f32,   // Maximum zoom distance
     pub zoom_speed: f32,     // Speed of zoom changes
+    pub zoom_mode: ZoomMode, // Distance or field-of-view zoom
+    pub zoom_smoothing: f32, // How fast distance/FOV approach their targets
+    pub target_distance: f32, // Distance the zoom is easing toward
+    pub fov: f32,            // Current vertical field of view, radians (FOV mode)
+    pub target_fov: f32,     // Field of view the zoom is easing toward
+    pub min_fov: f32,
+    pub max_fov: f32,
     pub min_height: f32,     // Minimum camera height above player
     pub max_height: f32,     // Maximum camera height above player
     pub height_speed: f32,   // Speed of height changes
+    pub collision_distance: Option<f32>, // Current distance from the look target when pulled in by an obstacle
+    pub orbiting: bool,      // Free orbit mode: the mouse moves the camera instead of turning the player
+    pub orbit_yaw: f32,      // Orbit angle around the player, relative to the follow position
+    pub orbit_pitch: f32,    // Orbit elevation change, relative to the follow position
+    pub shoulder_offset: f32, // Current lateral offset, positive puts the player left of center
+    pub target_shoulder_offset: f32, // Lateral offset being eased toward (swapped with Q)
+    pub overhead: bool,      // Tactical mode: high above the player looking straight down
+    pub overhead_height: f32, // Height above the player in overhead mode
+    pub overhead_pan: Vec3,  // Horizontal offset from the player in overhead mode (WASD / edge scrolling)
 }
 
-/// CameraLight Component - Marks a light that follows the camera
-#[derive(Component)]
-pub struct CameraLight;
+impl ThirdPersonCamera {
+    /// Camera in follow mode with the given tuning
+    pub fn new(settings: &CameraSettings) -> Self {
+        let fov = std::f32::consts::FRAC_PI_4; // Bevy's default perspective FOV
+        Self {
+            distance: settings.distance,
+            height: settings.height,
+            follow_speed: settings.follow_speed,
+            min_distance: settings.min_distance,
+            max_distance: settings.max_distance,
+            zoom_speed: settings.zoom_speed,
+            zoom_mode: settings.zoom_mode,
+            zoom_smoothing: settings.zoom_smoothing,
+            target_distance: settings.distance,
+            fov,
+            target_fov: fov,
+            min_fov: settings.min_fov_degrees.to_radians(),
+            max_fov: settings.max_fov_degrees.to_radians(),
+            min_height: settings.min_height,
+            max_height: settings.max_height,
+            height_speed: settings.height_speed,
+            collision_distance: None,
+            orbiting: false,
+            orbit_yaw: 0.0,
+            orbit_pitch: 0.0,
+            shoulder_offset: settings.shoulder_offset,
+            target_shoulder_offset: settings.shoulder_offset,
+            overhead: false,
+            overhead_height: crate::config::camera::OVERHEAD_HEIGHT,
+            overhead_pan: Vec3::ZERO,
+        }
+    }
+
+    /// Apply tuning values (speeds and clamps) without resetting the current view,
+    /// except where it falls outside the new limits
+    pub fn apply_settings(&mut self, settings: &CameraSettings) {
+        self.follow_speed = settings.follow_speed;
+        self.zoom_speed = settings.zoom_speed;
+        self.zoom_mode = settings.zoom_mode;
+        self.zoom_smoothing = settings.zoom_smoothing;
+        self.height_speed = settings.height_speed;
+        self.min_distance = settings.min_distance;
+        self.max_distance = settings.max_distance.max(settings.min_distance);
+        self.min_height = settings.min_height;
+        self.max_height = settings.max_height.max(settings.min_height);
+        self.min_fov = settings.min_fov_degrees.to_radians();
+        self.max_fov = settings.max_fov_degrees.max(settings.min_fov_degrees).to_radians();
+        self.target_distance = self.target_distance.clamp(self.min_distance, self.max_distance);
+        self.height = self.height.clamp(self.min_height, self.max_height);
+        self.target_fov = self.target_fov.clamp(self.min_fov, self.max_fov);
+    }
+}
+
+/// Trauma-based camera shake. Trauma (0..1) is added by `CameraShakeEvent`s and decays over
+/// time; the applied shake grows with trauma squared so small hits stay subtle.
+#[derive(Resource, Debug, Default)]
+pub struct CameraShake {
+    pub trauma: f32,
+    applied_offset: Vec3,   // Offset added last frame, removed before the follow logic runs
+}
+
+/// Request a camera shake (landing from a high fall, explosions, nearby impacts)
+#[derive(Event, Debug, Clone, Copy)]
+pub struct CameraShakeEvent {
+    pub trauma: f32,   // Added to the current trauma, the total is capped at 1
+}
+
+impl CameraShakeEvent {
+    /// Shake fading out linearly with the distance between the camera and the source
+    pub fn at_distance(trauma: f32, distance: f32, max_distance: f32) -> Self {
+        Self { trauma: trauma * (1.0 - distance / max_distance).clamp(0.0, 1.0) }
+    }
+}
 
 // Removed unused setup_camera function
 
 /// Setup the third person camera that follows the player
-pub fn setup_third_person_camera(mut commands: Commands) {
+pub fn setup_third_person_camera(mut commands: Commands, settings: Res<Settings>) {
     // Spawn the third person camera entity
     commands.spawn((
         Camera3d::default(),  // This makes it a 3D camera
@@ -37,83 +137,169 @@ pub fn setup_third_person_camera(mut commands: Commands) {
         Transform::from_xyz(0.0, 5.0, 8.0)  // Start position: behind and above player
             .looking_at(Vec3::new(0.0, 2.0, 0.0), Vec3::Y), // Look at player height
         
-        // Add our custom third person camera controller
-        ThirdPersonCamera {
-            distance: crate::config::camera::DISTANCE,
-            height: crate::config::camera::HEIGHT,
-            follow_speed: crate::config::camera::FOLLOW_SPEED,
-            min_distance: crate::config::camera::MIN_DISTANCE,
-            max_distance: crate::config::camera::MAX_DISTANCE,
-            zoom_speed: crate::config::camera::ZOOM_SPEED,
-            min_height: 2.0,
-            max_height: 50.0,
-            height_speed: 15.0,
-        },
-    ));
-    
-    // Add a directional light that follows the camera
-    commands.spawn((
-        DirectionalLight {
-            color: Color::srgb(1.0, 0.95, 0.8),  // Warm white light
-            illuminance: 15000.0,                  // Brightness (lux)
-            shadows_enabled: true,                 // Enable shadows
-            ..default()
-        },
-        Transform::from_xyz(0.0, 5.0, 8.0)       // Start at camera position
-            .looking_at(Vec3::new(0.0, 2.0, 0.0), Vec3::Y), // Point in same direction
-        CameraLight,  // Mark it as a camera light
+        // Add our custom third person camera controller, tuned from the settings file
+        ThirdPersonCamera::new(&settings.camera),
     ));
 }
 
 // Removed unused camera_zoom and camera_rotation functions
 
 /// Update third person camera to follow the player
-/// This function runs every frame and makes the camera follow the player smoothly
+/// This function runs every frame and makes the camera follow the player smoothly.
+/// A sphere cast from the player toward the camera pulls it in front of hills and trees.
 pub fn update_third_person_camera(
-    time: Res<Time>,
-    player_query: Query<(&Transform, &Player), Without<ThirdPersonCamera>>,
-    mut camera_query: Query<(&mut Transform, &ThirdPersonCamera), With<ThirdPersonCamera>>,
+    time: Res<Time<Real>>,
+    rapier_context: ReadRapierContext,
+    mut shake: ResMut<CameraShake>,
+    mut shake_events: EventReader<CameraShakeEvent>,
+    player_query: Query<(Entity, &Transform, &Player), Without<ThirdPersonCamera>>,
+    mut camera_query: Query<(&mut Transform, &mut ThirdPersonCamera), With<ThirdPersonCamera>>,
 ) {
     // Get the player's transform and player component
-    if let Ok((player_transform, player)) = player_query.single() {
+    if let Ok((player_entity, player_transform, player)) = player_query.single() {
         // Get the camera's transform and controller
-        if let Ok((mut camera_transform, controller)) = camera_query.single_mut() {
+        if let Ok((mut camera_transform, mut controller)) = camera_query.single_mut() {
             let delta_time = time.delta_secs();
+            // Work from the unshaken position
+            camera_transform.translation -= shake.applied_offset;
             
             // Calculate desired camera position based on player position and facing direction
             let player_pos = player_transform.translation;
+
+            if controller.overhead {
+                // Straight down with north (-Z) at the top of the screen
+                let desired_pos = player_pos + controller.overhead_pan + Vec3::Y * controller.overhead_height;
+                let follow_speed = controller.follow_speed;
+                camera_transform.translation = camera_transform.translation.lerp(desired_pos, follow_speed * delta_time);
+                camera_transform.look_to(Vec3::NEG_Y, Vec3::NEG_Z);
+                controller.collision_distance = None;
+            } else {
+                // Use the player's facing angle for camera positioning, plus the orbit offset
+                let facing_angle = player.facing_angle + controller.orbit_yaw;
             
-            // Use the player's facing angle for camera positioning
-            let facing_angle = player.facing_angle;
-            
-            // Calculate camera position behind and above the player
-            let camera_offset = Vec3::new(
-                facing_angle.sin() * controller.distance,  // Behind player in X
-                controller.height,                          // Above player
-                facing_angle.cos() * controller.distance,  // Behind player in Z
-            );
-            
-            let desired_pos = player_pos + camera_offset;
+                // Calculate camera position behind and above the player
+                let follow_offset = Vec3::new(
+                    facing_angle.sin() * controller.distance,  // Behind player in X
+                    controller.height,                          // Above player
+                    facing_angle.cos() * controller.distance,  // Behind player in Z
+                );
+                // Orbit pitch tilts the offset around the camera's horizontal axis
+                let right = Vec3::Y.cross(follow_offset).normalize_or_zero();
+                let camera_offset = Quat::from_axis_angle(right, -controller.orbit_pitch) * follow_offset;
             
-            // Smoothly interpolate camera position
-            let follow_speed = controller.follow_speed;
-            camera_transform.translation = camera_transform.translation
-                .lerp(desired_pos, follow_speed * delta_time);
+                // Over-the-shoulder: shift both the camera and its look target sideways so the
+                // view stays parallel to the follow direction. The shift shrinks against walls.
+                let head = player_pos + Vec3::new(0.0, 2.0, 0.0);
+                let camera_right = Vec3::new(facing_angle.cos(), 0.0, -facing_angle.sin());
+                let mut shoulder = controller.shoulder_offset;
+                if let Ok(ctx) = rapier_context.single() {
+                    let filter = QueryFilter::new().exclude_rigid_body(player_entity).exclude_sensors();
+                    let options = ShapeCastOptions::with_max_time_of_impact(shoulder.abs());
+                    if let Some((_, hit)) = ctx.cast_shape(head, Quat::IDENTITY, camera_right * shoulder.signum(), &Collider::ball(crate::config::camera::COLLISION_RADIUS), options, filter) {
+                        shoulder = hit.time_of_impact * shoulder.signum();
+                    }
+                }
+                let lateral = camera_right * shoulder;
+
+                let desired_pos = player_pos + camera_offset + lateral;
+                let look_target = head + lateral;
+
+                // Distance the camera can be from the look target before hitting something
+                let to_camera = desired_pos - look_target;
+                let full_distance = to_camera.length();
+                let direction = to_camera.normalize_or_zero();
+                let clear_distance = rapier_context.single().ok().and_then(|ctx| {
+                    let filter = QueryFilter::new().exclude_rigid_body(player_entity).exclude_sensors();
+                    let options = ShapeCastOptions::with_max_time_of_impact(full_distance);
+                    ctx.cast_shape(look_target, Quat::IDENTITY, direction, &Collider::ball(crate::config::camera::COLLISION_RADIUS), options, filter)
+                        .map(|(_, hit)| hit.time_of_impact.max(1.0)) // Never closer than 1 unit
+                });
+
+                // Snap in front of obstructions, then ease back out once they clear
+                let recovering = controller.collision_distance
+                    .map(|distance| distance + crate::config::camera::COLLISION_RECOVERY_SPEED * delta_time)
+                    .filter(|distance| *distance < full_distance);
+                controller.collision_distance = match (clear_distance, recovering) {
+                    (Some(hit), Some(distance)) => Some(hit.min(distance)),
+                    (Some(hit), None) => Some(hit),
+                    (None, recovering) => recovering,
+                };
+
+                match controller.collision_distance {
+                    Some(distance) => {
+                        // No smoothing while pulled in, the camera must not lag into the obstacle
+                        camera_transform.translation = look_target + direction * distance;
+                    }
+                    None => {
+                        // Smoothly interpolate camera position
+                        let follow_speed = controller.follow_speed;
+                        camera_transform.translation = camera_transform.translation
+                            .lerp(desired_pos, follow_speed * delta_time);
+                    }
+                }
             
-            // Look at the player (slightly above their position)
-            let look_target = player_pos + Vec3::new(0.0, 2.0, 0.0);
-            camera_transform.look_at(look_target, Vec3::Y);
+                // Look at the player (slightly above their position)
+                camera_transform.look_at(look_target, Vec3::Y);
+            }
+
+            // Camera shake: smooth noise from a few unrelated sine waves per axis
+            for event in shake_events.read() {
+                shake.trauma = (shake.trauma + event.trauma).min(1.0);
+            }
+            shake.trauma = (shake.trauma - crate::config::camera::SHAKE_DECAY * delta_time).max(0.0);
+            let amount = shake.trauma * shake.trauma;
+            let t = time.elapsed_secs();
+            let noise = |seed: f32| ((t * 23.0 + seed).sin() + (t * 37.0 + seed * 2.3).sin() * 0.5) / 1.5;
+            shake.applied_offset = Vec3::new(noise(0.0), noise(10.0), noise(20.0)) * crate::config::camera::SHAKE_MAX_OFFSET * amount;
+            camera_transform.translation += shake.applied_offset;
+            let angle = crate::config::camera::SHAKE_MAX_ANGLE * amount;
+            camera_transform.rotate_local(Quat::from_euler(EulerRot::YXZ, noise(30.0) * angle, noise(40.0) * angle, noise(50.0) * angle));
         }
     }
 }
 
+/// Orbit the camera around the player while the middle mouse button or Left Alt is held.
+/// The player keeps its facing angle; releasing snaps the camera back to follow mode.
+pub fn handle_camera_orbit(
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut camera_query: Query<&mut ThirdPersonCamera>,
+) {
+    let Ok(mut camera) = camera_query.single_mut() else { return; };
+    let orbiting = mouse_button_input.pressed(MouseButton::Middle) || keyboard_input.pressed(KeyCode::AltLeft);
+    if !orbiting {
+        mouse_motion.clear();
+        if camera.orbiting {
+            camera.orbiting = false;
+            camera.orbit_yaw = 0.0;
+            camera.orbit_pitch = 0.0;
+        }
+        return;
+    }
+
+    camera.orbiting = true;
+    for motion in mouse_motion.read() {
+        camera.orbit_yaw -= motion.delta.x * crate::config::camera::ORBIT_SENSITIVITY;
+        camera.orbit_pitch = (camera.orbit_pitch + motion.delta.y * crate::config::camera::ORBIT_SENSITIVITY)
+            .clamp(-crate::config::camera::ORBIT_MAX_PITCH, crate::config::camera::ORBIT_MAX_PITCH);
+    }
+}
+
 /// Handle mouse wheel zoom for the third person camera
-/// This function adjusts the camera distance based on mouse scroll input
+/// This function adjusts the target distance (or field of view in `ZoomMode::Fov`) based on
+/// mouse scroll input; `smooth_camera_zoom` eases toward it
 pub fn handle_camera_zoom(
-    time: Res<Time>,
+    time: Res<Time<Real>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
     mut scroll_events: EventReader<MouseWheel>,
     mut camera_query: Query<&mut ThirdPersonCamera>,
 ) {
+    // Ctrl + wheel selects the hotbar slot (see hud::handle_hotbar_input)
+    if keyboard_input.pressed(KeyCode::ControlLeft) || keyboard_input.pressed(KeyCode::ControlRight) {
+        scroll_events.clear();
+        return;
+    }
     // Get the camera controller
     if let Ok(mut camera) = camera_query.single_mut() {
         let delta_time = time.delta_secs();
@@ -128,21 +314,121 @@ pub fn handle_camera_zoom(
             // Calculate zoom change (negative scroll = zoom in, positive = zoom out)
             let zoom_change = -scroll_delta * camera.zoom_speed * delta_time * 10.0; // Scale factor for responsiveness
             
-            // Update distance and clamp to min/max bounds
-            camera.distance = (camera.distance + zoom_change).clamp(camera.min_distance, camera.max_distance);
-            
-            // Optional: Print zoom level for debugging
-            if scroll_delta != 0.0 {
-                println!("Camera zoom: {:.1} (range: {:.1} - {:.1})", camera.distance, camera.min_distance, camera.max_distance);
+            if scroll_delta == 0.0 {
+                continue;
+            }
+            if camera.overhead {
+                camera.overhead_height = (camera.overhead_height + zoom_change * 2.0)
+                    .clamp(crate::config::camera::OVERHEAD_MIN_HEIGHT, crate::config::camera::OVERHEAD_MAX_HEIGHT);
+                debug!("Overhead camera height: {:.1}", camera.overhead_height);
+                continue;
+            }
+            match camera.zoom_mode {
+                ZoomMode::Distance => {
+                    // Update target distance and clamp to min/max bounds
+                    camera.target_distance = (camera.target_distance + zoom_change).clamp(camera.min_distance, camera.max_distance);
+                    debug!("Camera zoom: {:.1} (range: {:.1} - {:.1})", camera.target_distance, camera.min_distance, camera.max_distance);
+                }
+                ZoomMode::Fov => {
+                    // Same wheel direction: scrolling in narrows the view
+                    let fov_change = zoom_change / (camera.max_distance - camera.min_distance) * (camera.max_fov - camera.min_fov);
+                    camera.target_fov = (camera.target_fov + fov_change).clamp(camera.min_fov, camera.max_fov);
+                    debug!("Camera FOV: {:.1}°", camera.target_fov.to_degrees());
+                }
             }
         }
     }
 }
 
+/// Toggle the overhead (tactical) camera with T. While overhead, WASD and the window edges pan
+/// the view instead of moving the player.
+pub fn handle_overhead_camera(
+    time: Res<Time<Real>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    settings: Res<Settings>,
+    mut camera_query: Query<&mut ThirdPersonCamera>,
+) {
+    let Ok(mut camera) = camera_query.single_mut() else { return; };
+    if keyboard_input.just_pressed(KeyCode::KeyT) {
+        camera.overhead = !camera.overhead;
+        camera.overhead_pan = Vec3::ZERO;
+        info!("Overhead camera {}", if camera.overhead { "on" } else { "off" });
+    }
+    if !camera.overhead {
+        return;
+    }
+
+    let keys = &settings.controls.key_bindings;
+    let mut direction = Vec3::ZERO;
+    if keyboard_input.pressed(keys.forward) { direction.z -= 1.0; }
+    if keyboard_input.pressed(keys.back) { direction.z += 1.0; }
+    if keyboard_input.pressed(keys.left) { direction.x -= 1.0; }
+    if keyboard_input.pressed(keys.right) { direction.x += 1.0; }
+
+    // Edge scrolling
+    if let Ok(window) = windows.single()
+        && let Some(cursor) = window.cursor_position() {
+        let margin = crate::config::camera::EDGE_SCROLL_MARGIN;
+        if cursor.x < margin { direction.x -= 1.0; }
+        if cursor.x > window.width() - margin { direction.x += 1.0; }
+        if cursor.y < margin { direction.z -= 1.0; }
+        if cursor.y > window.height() - margin { direction.z += 1.0; }
+    }
+
+    // Pan faster when higher up so the screen scrolls at the same rate
+    let speed = crate::config::camera::OVERHEAD_PAN_SPEED * camera.overhead_height;
+    camera.overhead_pan += direction.normalize_or_zero() * speed * time.delta_secs();
+}
+
+/// Swap the over-the-shoulder offset to the other side with Q
+pub fn handle_shoulder_swap(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut camera_query: Query<&mut ThirdPersonCamera>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyQ) {
+        return;
+    }
+    if let Ok(mut camera) = camera_query.single_mut() {
+        camera.target_shoulder_offset = -camera.target_shoulder_offset;
+        info!("Camera shoulder: {}", if camera.target_shoulder_offset >= 0.0 { "right" } else { "left" });
+    }
+}
+
+/// Re-apply the camera tuning whenever the settings change (file edited or reloaded)
+pub fn apply_camera_settings(
+    settings: Res<Settings>,
+    mut camera_query: Query<&mut ThirdPersonCamera>,
+) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    if let Ok(mut camera) = camera_query.single_mut() {
+        camera.apply_settings(&settings.camera);
+    }
+}
+
+/// Ease the camera distance, field of view and shoulder offset toward their targets
+pub fn smooth_camera_zoom(
+    time: Res<Time<Real>>,
+    mut camera_query: Query<(&mut ThirdPersonCamera, &mut Projection)>,
+) {
+    let Ok((mut camera, mut projection)) = camera_query.single_mut() else { return; };
+    // Frame-rate independent exponential smoothing
+    let blend = 1.0 - (-camera.zoom_smoothing * time.delta_secs()).exp();
+    camera.distance += (camera.target_distance - camera.distance) * blend;
+    camera.fov += (camera.target_fov - camera.fov) * blend;
+    camera.shoulder_offset += (camera.target_shoulder_offset - camera.shoulder_offset) * blend;
+    if let Projection::Perspective(perspective) = projection.as_mut()
+        && (perspective.fov - camera.fov).abs() > f32::EPSILON {
+        perspective.fov = camera.fov;
+    }
+}
+
 /// Handle camera height control using up/down arrow keys
 /// This function adjusts the camera height while keeping it focused on the player
 pub fn handle_camera_height(
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
     mut camera_query: Query<&mut ThirdPersonCamera>,
 ) {
@@ -164,27 +450,7 @@ pub fn handle_camera_height(
             camera.height = (camera.height + height_change).clamp(camera.min_height, camera.max_height);
             
             // Optional: Print height level for debugging
-            println!("Camera height: {:.1} (range: {:.1} - {:.1})", camera.height, camera.min_height, camera.max_height);
-        }
-    }
-}
-
-/// Update camera light to follow the camera position and direction
-/// This function runs every frame and keeps the light synchronized with the camera
-pub fn update_camera_light(
-    camera_query: Query<&Transform, (With<ThirdPersonCamera>, Without<CameraLight>)>,
-    mut light_query: Query<&mut Transform, (With<CameraLight>, Without<ThirdPersonCamera>)>,
-) {
-    // Get the camera's transform
-    if let Ok(camera_transform) = camera_query.single() {
-        // Get the light's transform
-        if let Ok(mut light_transform) = light_query.single_mut() {
-            // Position the light slightly behind the camera for better lighting
-            let light_offset = camera_transform.back() * 2.0; // 2 units behind camera
-            light_transform.translation = camera_transform.translation + light_offset;
-            
-            // Make the light point in the same direction as the camera
-            light_transform.rotation = camera_transform.rotation;
+            debug!("Camera height: {:.1} (range: {:.1} - {:.1})", camera.height, camera.min_height, camera.max_height);
         }
     }
 }
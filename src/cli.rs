@@ -0,0 +1,130 @@
+use bevy::log::{Level, LogPlugin};
+
+use crate::startup_config::StartupConfig;
+
+/// Usage printed by `--help` and on invalid arguments
+pub const USAGE: &str = "\
+Usage: tiles3d [OPTIONS]
+
+Options override config/tiles3d.toml for this run:
+  --map <path>         Elevation and texture map
+  --spawn <lon,lat>    Spawn point in degrees
+  --radius <n>         Terrain render distance in subpixels
+  --seed <n>           World seed of the scattered objects
+  --load <save>        Save directory to load from and save to
+  --headless           Run without a window or GPU (scripted tests, benchmarks)
+  --export <path>      Export the first rendered terrain to a .gltf or .obj file
+                       (and quit with --headless)
+  --export-objects     Include the player-built objects in the export
+  --connect <addr>     Play on a tiles3d-server at host:port
+  --name <name>        Player name on the server (default: $USER)
+  --remote <port>      Serve the remote control API on localhost:<port>
+  --log-level <filter> error, warn, info (default), debug or trace, or per module
+                       targets like info,tiles3d::terrain=debug (RUST_LOG overrides it)
+  -h, --help           Print this help";
+
+/// Command-line options; unset options keep the startup config values
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CliArgs {
+    pub map: Option<String>,
+    pub spawn: Option<(f64, f64)>,   // (longitude, latitude) in degrees
+    pub radius: Option<usize>,
+    pub seed: Option<u64>,
+    pub load: Option<String>,
+    pub headless: bool,
+    pub export: Option<String>,      // .gltf or .obj file
+    pub export_objects: bool,
+    pub connect: Option<String>,
+    pub name: Option<String>,
+    pub remote: Option<u16>,         // Remote control API port
+    pub log_level: Option<String>,   // A level, or `EnvFilter` directives
+    pub help: bool,
+}
+
+impl CliArgs {
+    /// Parse the arguments following the program name, `--option value` or `--option=value`
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut cli = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => (name.to_string(), Some(value.to_string())),
+                _ => (arg, None),
+            };
+            let mut value = || inline_value.clone().or_else(|| args.next()).ok_or(format!("{} needs a value", name));
+            match name.as_str() {
+                "--map" => cli.map = Some(value()?),
+                "--spawn" => cli.spawn = Some(parse_spawn(&value()?)?),
+                "--radius" => cli.radius = Some(parse_number(&name, &value()?)?),
+                "--seed" => cli.seed = Some(parse_number(&name, &value()?)?),
+                "--load" => cli.load = Some(value()?),
+                "--headless" => cli.headless = true,
+                "--export" => cli.export = Some(parse_export(&value()?)?),
+                "--export-objects" => cli.export_objects = true,
+                "--connect" => cli.connect = Some(value()?),
+                "--name" => cli.name = Some(value()?),
+                "--remote" => cli.remote = Some(parse_number(&name, &value()?)?),
+                "--log-level" => cli.log_level = Some(value()?),
+                "-h" | "--help" => cli.help = true,
+                _ => return Err(format!("Unknown argument '{}'", name)),
+            }
+        }
+        Ok(cli)
+    }
+
+    /// Logger of the game: Bevy's default, with the `--log-level` level or module filters
+    pub fn log_plugin(&self) -> LogPlugin {
+        let mut plugin = LogPlugin::default();
+        if let Some(log_level) = &self.log_level {
+            match log_level.parse::<Level>() {
+                Ok(level) => plugin.level = level,
+                Err(_) => plugin.filter = format!("{},{}", plugin.filter, log_level),
+            }
+        }
+        plugin
+    }
+
+    /// Override the startup config with the given options
+    pub fn apply(&self, config: &mut StartupConfig) {
+        if let Some(map) = &self.map {
+            config.map_path = map.clone();
+        }
+        if let Some((lon, lat)) = self.spawn {
+            config.initial_lon = lon;
+            config.initial_lat = lat;
+        }
+        if let Some(radius) = self.radius {
+            config.terrain.terrain_radius = radius.max(1);
+            config.render_distance = Some(radius.max(1));
+        }
+        if let Some(seed) = self.seed {
+            config.seed = seed;
+        }
+        if let Some(save_dir) = &self.load {
+            config.save_dir = save_dir.clone();
+        }
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("{} expects a number, got '{}'", name, value))
+}
+
+/// Check that the export path has a known format
+fn parse_export(value: &str) -> Result<String, String> {
+    match crate::export::ExportFormat::from_path(std::path::Path::new(value)) {
+        Some(_) => Ok(value.to_string()),
+        None => Err(format!("--export expects a .gltf or .obj file, got '{}'", value)),
+    }
+}
+
+/// Parse `lon,lat` in degrees
+fn parse_spawn(value: &str) -> Result<(f64, f64), String> {
+    let (lon, lat) = value.split_once(',').ok_or(format!("--spawn expects lon,lat, got '{}'", value))?;
+    let lon: f64 = parse_number("--spawn", lon)?;
+    let lat: f64 = parse_number("--spawn", lat)?;
+    if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+        return Err(format!("--spawn out of range: {},{}", lon, lat));
+    }
+    Ok((lon, lat))
+}
@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+use std::f32::consts::{PI, TAU};
+use bevy::prelude::*;
+
+use crate::minimap::MinimapMarker;
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::terrain::{ijk_to_world, world_to_geo, TerrainCenter};
+
+/// Cardinal and intercardinal labels with their bearing (radians clockwise from north)
+const DIRECTIONS: [(&str, f32); 8] = [
+    ("N", 0.0), ("NE", 0.25 * PI), ("E", 0.5 * PI), ("SE", 0.75 * PI),
+    ("S", PI), ("SW", 1.25 * PI), ("W", 1.5 * PI), ("NW", 1.75 * PI),
+];
+
+/// Compass strip at the top of the screen
+#[derive(Component)]
+pub struct CompassStrip;
+
+/// Direction label placed on the strip by its bearing
+#[derive(Component)]
+pub struct CompassLabel(pub f32);
+
+/// Icon on the strip pointing toward the terrain center
+#[derive(Component)]
+pub struct CompassTerrainCenterIcon;
+
+/// Icon on the strip pointing toward a `MinimapMarker` entity (waypoints, agents)
+#[derive(Component)]
+pub struct CompassMarkerIcon(pub Entity);
+
+/// Spawn the compass strip and its direction labels
+pub fn setup_compass(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Percent(50.0),
+            margin: UiRect::left(Val::Px(-crate::config::compass::WIDTH_PX / 2.0)),
+            width: Val::Px(crate::config::compass::WIDTH_PX),
+            height: Val::Px(28.0),
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        CompassStrip,
+    )).with_children(|strip| {
+        // Center tick: the player's heading
+        strip.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(crate::config::compass::WIDTH_PX / 2.0 - 1.0),
+                bottom: Val::Px(0.0),
+                width: Val::Px(2.0),
+                height: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(Color::WHITE),
+        ));
+        for (label, bearing) in DIRECTIONS {
+            let major = label.len() == 1;
+            strip.spawn((
+                Node { position_type: PositionType::Absolute, top: Val::Px(4.0), ..default() },
+                Text::new(label),
+                TextFont { font_size: if major { 16.0 } else { 12.0 }, ..default() },
+                TextColor(if major { Color::WHITE } else { Color::srgb(0.7, 0.7, 0.7) }),
+                CompassLabel(bearing),
+            ));
+        }
+        strip.spawn((
+            Node { position_type: PositionType::Absolute, top: Val::Px(2.0), width: Val::Px(8.0), height: Val::Px(8.0), ..default() },
+            BackgroundColor(Color::srgb(0.2, 0.9, 0.9)),
+            BorderRadius::MAX,
+            CompassTerrainCenterIcon,
+        ));
+    });
+}
+
+/// Bearing (radians clockwise from north) from one geographic position to another
+fn geo_bearing((lon_from, lat_from): (f64, f64), (lon_to, lat_to): (f64, f64)) -> f32 {
+    let d_lon = (lon_to - lon_from) * lat_from.to_radians().cos();
+    let d_lat = lat_to - lat_from;
+    d_lon.atan2(d_lat) as f32
+}
+
+/// Horizontal position on the strip for a bearing relative to the heading, `None` when outside
+/// the visible arc
+fn strip_position(bearing: f32, heading: f32, item_width: f32) -> Option<f32> {
+    let delta = (bearing - heading + PI).rem_euclid(TAU) - PI;
+    let half_arc = crate::config::compass::VISIBLE_ARC_DEGREES.to_radians() / 2.0;
+    if delta.abs() > half_arc {
+        return None;
+    }
+    let width = crate::config::compass::WIDTH_PX;
+    Some((delta / half_arc + 1.0) / 2.0 * width - item_width / 2.0)
+}
+
+/// Place direction labels and icons on the compass strip from the player's heading. The heading
+/// comes from the geographic position of a point ahead of the player, so it stays true north
+/// whatever the projection around the terrain center.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_compass(
+    mut commands: Commands,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    player_query: Query<&Transform, With<Player>>,
+    strip_query: Query<Entity, With<CompassStrip>>,
+    markers: Query<(Entity, &GlobalTransform, &MinimapMarker)>,
+    mut labels: Query<(&CompassLabel, &mut Node, &mut Visibility), (Without<CompassTerrainCenterIcon>, Without<CompassMarkerIcon>)>,
+    mut center_icon: Query<(&mut Node, &mut Visibility), (With<CompassTerrainCenterIcon>, Without<CompassMarkerIcon>)>,
+    mut marker_icons: Query<(Entity, &CompassMarkerIcon, &mut Node, &mut Visibility)>,
+) {
+    let Ok(player_transform) = player_query.single() else { return; };
+    let Ok(strip) = strip_query.single() else { return; };
+
+    let player_pos = player_transform.translation;
+    let player_geo = world_to_geo(player_pos, &planisphere, &terrain_center);
+    let ahead_geo = world_to_geo(player_pos + *player_transform.forward(), &planisphere, &terrain_center);
+    if player_geo.0.is_nan() || ahead_geo.0.is_nan() {
+        return;
+    }
+    let heading = geo_bearing(player_geo, ahead_geo);
+    let bearing_to = |position: Vec3| {
+        let geo = world_to_geo(position, &planisphere, &terrain_center);
+        (!geo.0.is_nan()).then(|| geo_bearing(player_geo, geo))
+    };
+    let place = |node: &mut Node, visibility: &mut Visibility, bearing: Option<f32>, item_width: f32| {
+        match bearing.and_then(|bearing| strip_position(bearing, heading, item_width)) {
+            Some(left) => {
+                node.left = Val::Px(left);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    };
+
+    for (label, mut node, mut visibility) in labels.iter_mut() {
+        place(&mut node, &mut visibility, Some(label.0), 16.0);
+    }
+
+    if let Ok((mut node, mut visibility)) = center_icon.single_mut() {
+        let (i, j, k) = terrain_center.subpixel;
+        let center = ijk_to_world(i as i32, j as i32, k as i32, &planisphere, &terrain_center);
+        place(&mut node, &mut visibility, bearing_to(center), 8.0);
+    }
+
+    // Markers within range get an icon, icons of removed or distant markers go away
+    let range = crate::config::compass::MARKER_RANGE;
+    let mut shown = HashSet::new();
+    for (icon, target, mut node, mut visibility) in marker_icons.iter_mut() {
+        match markers.get(target.0) {
+            Ok((_, transform, _)) if transform.translation().distance(player_pos) <= range => {
+                shown.insert(target.0);
+                place(&mut node, &mut visibility, bearing_to(transform.translation()), 8.0);
+            }
+            _ => commands.entity(icon).despawn(),
+        }
+    }
+    for (entity, transform, marker) in markers.iter() {
+        if shown.contains(&entity) || transform.translation().distance(player_pos) > range {
+            continue;
+        }
+        let icon = commands.spawn((
+            Node { position_type: PositionType::Absolute, top: Val::Px(2.0), width: Val::Px(8.0), height: Val::Px(8.0), ..default() },
+            BackgroundColor(marker.0),
+            BorderRadius::MAX,
+            Visibility::Hidden, // Placed on the next update
+            CompassMarkerIcon(entity),
+        )).id();
+        commands.entity(strip).add_child(icon);
+    }
+}
@@ -7,6 +7,20 @@ pub mod terrain {
     pub const RECREATION_COOLDOWN_SECS: f32 = 1.0;
     pub const LANDSCAPE_RADIUS: usize = 3;
     pub const SUB_K: usize = 4;
+    /// Default world height of the terrain at altitude 1.0 (the highest point of the elevation
+    /// map), `height_scale` in the `[terrain]` section of the startup config
+    pub const ALTITUDE_SCALE: f32 = 5.0;
+    /// Default outer radius in subpixels of the distant ring merged into 2x2 subpixel quads
+    /// (`lod_block_radius` in the startup config)
+    pub const LOD_BLOCK_RADIUS: usize = 2 * RADIUS;
+    /// Default outer radius in subpixels of the farthest ring, one quad per pixel
+    /// (`lod_pixel_radius` in the startup config)
+    pub const LOD_PIXEL_RADIUS: usize = 4 * RADIUS;
+    /// Depth of the skirts hanging below the terrain border, in world units: twice the height of
+    /// the default relief, so the border never shows the sky beneath it
+    pub const SKIRT_DEPTH: f32 = 2.0 * ALTITUDE_SCALE;
+    /// Fraction of terrain subpixels that receive a collectible item
+    pub const SPAWN_PROBABILITY: f32 = 0.02;
 }
 
 /// Player movement constants
@@ -17,6 +31,50 @@ pub mod player {
     pub const JUMP_COOLDOWN_SECS: f32 = 0.5;
     pub const INITIAL_LON: f32 = 7.0;
     pub const INITIAL_LAT: f32 = -41.0;
+    /// Speed multiplier while the sprint key is held
+    pub const SPRINT_MULTIPLIER: f32 = 1.6;
+    pub const MAX_HEALTH: f32 = 100.0;
+    pub const MAX_STAMINA: f32 = 100.0;
+    pub const SPRINT_STAMINA_PER_SEC: f32 = 20.0;
+    pub const JUMP_STAMINA: f32 = 10.0;
+    pub const STAMINA_REGEN_PER_SEC: f32 = 15.0;
+    /// Seconds without spending stamina before it regenerates
+    pub const STAMINA_REGEN_DELAY_SECS: f32 = 1.0;
+    /// Seconds the player can stay on sea tiles before drowning
+    pub const BREATH_SECS: f32 = 20.0;
+    /// Breath seconds recovered per second on land
+    pub const BREATH_REGEN_PER_SEC: f32 = 5.0;
+    pub const DROWNING_DAMAGE_PER_SEC: f32 = 10.0;
+    /// Health lost per unit of landing speed above `camera::LANDING_SHAKE_SPEED`
+    pub const FALL_DAMAGE_PER_SPEED: f32 = 5.0;
+}
+
+/// Physics defaults, overridden by the `[physics]` section of the startup config
+pub mod physics {
+    /// Rate of the fixed-timestep simulation (`FixedUpdate`), one physics step each
+    pub const TIMESTEP_HZ: f64 = 60.0;
+    /// Physics substeps per step
+    pub const SUBSTEPS: usize = 1;
+    /// Downward acceleration in m/s²
+    pub const GRAVITY: f32 = 9.81;
+    /// Solver iterations per substep: more gives stiffer contacts and stacks, at a higher cost
+    pub const SOLVER_ITERATIONS: usize = 4;
+}
+
+/// Simulation speed constants (`time_control`)
+pub mod time_control {
+    /// Fastest simulation speed accepted (from the remote API); each fixed step still runs, so
+    /// high speeds cost as many physics steps
+    pub const MAX_SCALE: f32 = 8.0;
+}
+
+/// HUD constants
+pub mod hud {
+    /// Distance from the bottom of the screen to the stat bars (the hotbar sits below)
+    pub const BARS_BOTTOM_PX: f32 = 80.0;
+    /// Number of hotbar slots (selected with the number keys)
+    pub const HOTBAR_SLOTS: usize = 9;
+    pub const HOTBAR_SLOT_PX: f32 = 52.0;
 }
 
 /// Third-person camera constants
@@ -27,9 +85,331 @@ pub mod camera {
     pub const ZOOM_SPEED: f32 = 2.0;
     pub const MIN_DISTANCE: f32 = 5.0;
     pub const MAX_DISTANCE: f32 = 50.0;
+    pub const MIN_HEIGHT: f32 = 2.0;
+    pub const MAX_HEIGHT: f32 = 50.0;
+    pub const HEIGHT_SPEED: f32 = 15.0;
+    /// How fast the zoom catches up with its target (higher is snappier)
+    pub const ZOOM_SMOOTHING: f32 = 8.0;
+    /// Field of view range (degrees) of the FOV zoom mode
+    pub const MIN_FOV_DEGREES: f32 = 20.0;
+    pub const MAX_FOV_DEGREES: f32 = 80.0;
+    /// Radius of the sphere cast from the player to the camera to detect obstructions
+    pub const COLLISION_RADIUS: f32 = 0.5;
+    /// Speed (units/s) at which the camera moves back out once an obstruction clears
+    pub const COLLISION_RECOVERY_SPEED: f32 = 8.0;
+    /// Radians of orbit per pixel of mouse motion while orbiting
+    pub const ORBIT_SENSITIVITY: f32 = 0.005;
+    /// Maximum orbit pitch away from the follow elevation, in radians
+    pub const ORBIT_MAX_PITCH: f32 = 1.0;
+    /// Overhead (tactical) camera height above the player, and its zoom range
+    pub const OVERHEAD_HEIGHT: f32 = 60.0;
+    pub const OVERHEAD_MIN_HEIGHT: f32 = 20.0;
+    pub const OVERHEAD_MAX_HEIGHT: f32 = 120.0;
+    /// Overhead pan speed, as a fraction of the camera height per second
+    pub const OVERHEAD_PAN_SPEED: f32 = 1.0;
+    /// Cursor distance (pixels) from the window border that starts edge scrolling
+    pub const EDGE_SCROLL_MARGIN: f32 = 12.0;
+    /// Opacity of objects standing between the camera and the player
+    pub const OCCLUDER_ALPHA: f32 = 0.25;
+    /// Opacity change per second while occluders fade out or back in
+    pub const OCCLUDER_FADE_SPEED: f32 = 4.0;
+    /// Lateral over-the-shoulder offset (units, positive = camera right of the player)
+    pub const SHOULDER_OFFSET: f32 = 1.5;
+    /// Camera shake trauma lost per second
+    pub const SHAKE_DECAY: f32 = 1.5;
+    /// Camera offset (units) at full trauma
+    pub const SHAKE_MAX_OFFSET: f32 = 0.6;
+    /// Camera roll/yaw/pitch jitter (radians) at full trauma
+    pub const SHAKE_MAX_ANGLE: f32 = 0.08;
+    /// Downward speed above which landing shakes the camera
+    pub const LANDING_SHAKE_SPEED: f32 = 8.0;
+}
+
+/// Minimap constants
+pub mod minimap {
+    /// On-screen diameter in pixels
+    pub const SIZE_PX: f32 = 180.0;
+    /// Width and height of the minimap image in pixels
+    pub const RESOLUTION: u32 = 96;
+    /// World distance from the player to the edge of the map
+    pub const RANGE: f32 = 60.0;
+    /// Seconds between redraws
+    pub const UPDATE_SECS: f32 = 0.25;
+}
+
+/// Compass strip constants
+pub mod compass {
+    /// On-screen width of the strip in pixels
+    pub const WIDTH_PX: f32 = 400.0;
+    /// Arc of headings visible on the strip
+    pub const VISIBLE_ARC_DEGREES: f32 = 180.0;
+    /// Markers further than this (world units) are left off the strip
+    pub const MARKER_RANGE: f32 = 150.0;
+}
+
+/// Waypoint indicator constants
+pub mod waypoints {
+    /// Distance in pixels between screen-edge indicators and the window border
+    pub const EDGE_MARGIN_PX: f32 = 40.0;
+    /// Distance in tiles at which a GPX track point counts as reached while navigating
+    pub const TRACK_ARRIVAL_TILES: f64 = 2.0;
+}
+
+/// Beacon pool constants
+pub mod beacons {
+    /// Debug and trail beacons alive at once; the oldest are despawned beyond this.
+    /// Waypoint beacons are not counted.
+    pub const MAX_BEACONS: usize = 200;
+}
+
+/// Vegetation density map (planisphere green channel) constants
+pub mod vegetation {
+    /// Spawn probability of vegetation at full green, relative to the biome density
+    pub const MAX_FACTOR: f64 = 2.0;
+    /// Vegetation probability multiplier on the pixels forming groves
+    pub const GROVE_BOOST: f64 = 2.5;
+    /// Vegetation probability multiplier outside groves
+    pub const CLEARING_FACTOR: f64 = 0.3;
+}
+
+/// Landscape scatter constants
+pub mod scatter {
+    /// Scatter samples thrown per subpixel of a pixel
+    pub const DARTS_PER_SUBPIXEL: f64 = 1.0;
+}
+
+/// Distance measuring tool constants
+pub mod measure {
+    /// Trail beacons drawn along a measured great circle, at most
+    pub const MAX_TRAIL_MARKERS: usize = 40;
+    /// Great-circle samples per tile when counting crossed tiles
+    pub const SAMPLES_PER_TILE: f64 = 4.0;
+    /// Upper bound on samples, for measurements across the planet
+    pub const MAX_SAMPLES: usize = 20000;
+}
+
+/// Message log panel constants
+pub mod message_log {
+    /// Entries kept in the log; older ones are dropped
+    pub const MAX_ENTRIES: usize = 200;
+    pub const WIDTH_PX: f32 = 340.0;
+    /// Height of the scrollable list in pixels
+    pub const HEIGHT_PX: f32 = 200.0;
+}
+
+/// Season constants
+pub mod seasons {
+    /// Default game time per season when seasons cycle, in seconds
+    pub const LENGTH_SECS: f32 = 600.0;
+}
+
+/// Day/night cycle constants (`daylight`)
+pub mod daylight {
+    /// Default game time per day, in seconds
+    pub const DAY_LENGTH_SECS: f32 = 1200.0;
+    /// Hour of the day when the game starts
+    pub const START_HOUR: f32 = 9.0;
+    /// Tilt of the planet's axis, the sun's declination at the solstices (degrees)
+    pub const AXIAL_TILT_DEGREES: f32 = 23.44;
+    /// Sun illuminance when it is high in the sky (lux)
+    pub const SUN_ILLUMINANCE: f32 = 15000.0;
+    /// Moon illuminance when it is high in the sky (lux), kept bright enough to play at night
+    pub const MOON_ILLUMINANCE: f32 = 400.0;
+    /// Sun color temperature at the horizon and from `FULL_DAY_ELEVATION_DEGREES` up (kelvin)
+    pub const HORIZON_TEMPERATURE: f32 = 2000.0;
+    pub const NOON_TEMPERATURE: f32 = 5800.0;
+    /// Sun elevation from which the light is at full strength and color (degrees)
+    pub const FULL_DAY_ELEVATION_DEGREES: f32 = 20.0;
+    /// Sun elevation below which it is full night: twilight lies between this and the horizon (degrees)
+    pub const NIGHT_ELEVATION_DEGREES: f32 = -8.0;
+    /// Ambient light brightness at full day and at night
+    pub const AMBIENT_DAY: f32 = 150.0;
+    pub const AMBIENT_NIGHT: f32 = 25.0;
+}
+
+/// Procedural sky constants (`sky`). The sky is drawn inside the camera's far plane (1000).
+pub mod sky {
+    /// Radius of the sky dome, of the star sphere and distance of the sun and moon discs
+    pub const DOME_RADIUS: f32 = 950.0;
+    pub const STAR_RADIUS: f32 = 900.0;
+    pub const DISC_DISTANCE: f32 = 850.0;
+    /// Apparent radius of the sun and moon discs, in world units at `DISC_DISTANCE`
+    pub const SUN_DISC_RADIUS: f32 = 18.0;
+    pub const MOON_DISC_RADIUS: f32 = 14.0;
+    /// Number of stars and their size range, in world units at `STAR_RADIUS`
+    pub const STAR_COUNT: usize = 1500;
+    pub const STAR_SIZE: (f32, f32) = (0.8, 2.6);
+    /// Seed of the star field, the same sky in every world
+    pub const STAR_SEED: u64 = 0x5EED_57A2;
+    /// Seconds between two updates of the sky gradient colors
+    pub const GRADIENT_UPDATE_SECS: f32 = 0.2;
+}
+
+/// Music constants (`music`)
+pub mod music {
+    /// Music volume when the settings do not set one
+    pub const DEFAULT_VOLUME: f32 = 0.6;
+    /// Hostile props closer than this to the player switch the music to `Danger`
+    pub const DANGER_RADIUS: f32 = 30.0;
+    /// A new context must hold this long before the music follows it, so that walking along a
+    /// coast or at the edge of a danger zone does not flip tracks back and forth
+    pub const CONTEXT_HOLD_SECS: f32 = 3.0;
+    /// Time for ducked music to go down to, and back up from, the ducking level
+    pub const DUCK_FADE_SECS: f32 = 0.5;
+}
+
+/// Weather constants (`weather`)
+pub mod weather {
+    /// Side of a weather region, in degrees of longitude and latitude
+    pub const REGION_DEGREES: f64 = 10.0;
+    /// Game time between two steps of a region's weather state machine
+    pub const STEP_SECS: f32 = 180.0;
+    /// Steps replayed when coming back to a region; older history is skipped
+    pub const MAX_CATCH_UP_STEPS: u64 = 32;
+    /// Time constant of the transition between weather states, in seconds
+    pub const TRANSITION_SECS: f32 = 20.0;
+    /// Seconds of full rain to soak surfaces, and of dry weather to dry them
+    pub const WETTING_SECS: f32 = 30.0;
+    pub const DRYING_SECS: f32 = 120.0;
+    /// Fog visibility in clear weather and in a storm, in world units
+    pub const CLEAR_VISIBILITY: f32 = 600.0;
+    pub const STORM_VISIBILITY: f32 = 70.0;
+    /// Fraction of sunlight blocked by a full cloud cover
+    pub const CLOUD_DIMMING: f32 = 0.7;
+    /// Rain drops kept around the camera (all shown in a storm)
+    pub const RAIN_DROPS: usize = 600;
+    /// Half size of the box around the camera where drops fall, horizontally and vertically
+    pub const RAIN_RADIUS: f32 = 12.0;
+    pub const RAIN_HEIGHT: f32 = 10.0;
+    /// Fall speed of the drops, and their drift at full wind strength (units per second)
+    pub const RAIN_FALL_SPEED: f32 = 18.0;
+    pub const RAIN_WIND_DRIFT: f32 = 8.0;
+    /// Darkening and roughness factor of fully wet terrain
+    pub const WET_DARKENING: f32 = 0.25;
+    pub const WET_ROUGHNESS: f32 = 0.35;
+    /// Rain loop and thunder sounds, played when the files exist under `assets/`
+    pub const RAIN_SOUND: &str = "sounds/rain.ogg";
+    pub const THUNDER_SOUND: &str = "sounds/thunder.ogg";
+    /// Mean seconds between thunderclaps in a storm
+    pub const THUNDER_INTERVAL_SECS: f32 = 25.0;
+}
+
+/// Wind sway constants
+pub mod wind {
+    /// Tilt of vegetation at full wind strength, in radians
+    pub const MAX_TILT: f32 = 0.08;
+    /// Sway oscillations per second
+    pub const SWAY_HZ: f32 = 0.6;
+    /// Distance between gust fronts travelling along the wind, in world units
+    pub const GUST_WAVELENGTH: f32 = 25.0;
+}
+
+/// Ocean surface constants (`terrain::ocean`)
+pub mod ocean {
+    /// Default altitude of the water surface over sea pixels, on the 0.0–1.0 scale of the map
+    /// (world height `sea_level * height_scale`), `sea_level` in the startup config
+    pub const SEA_LEVEL: f32 = 0.1;
+    /// Wave height in calm weather and added at full wind strength, in world units
+    pub const CALM_WAVE_HEIGHT: f32 = 0.03;
+    pub const WIND_WAVE_HEIGHT: f32 = 0.12;
+    /// Distance between wave crests, in world units, and their speed along the wind
+    pub const WAVE_LENGTH: f32 = 6.0;
+    pub const WAVE_SPEED: f32 = 1.5;
+    /// Color and opacity of the water
+    pub const COLOR: (f32, f32, f32) = (0.1, 0.35, 0.55);
+    pub const OPACITY: f32 = 0.7;
+}
+
+/// Toast notification constants
+pub mod notifications {
+    /// Seconds a toast stays on screen
+    pub const TOAST_SECS: f32 = 4.0;
+    /// Seconds of fade-out at the end of a toast's lifetime
+    pub const FADE_SECS: f32 = 1.0;
+    /// Toasts shown at once; older ones are dropped
+    pub const MAX_TOASTS: usize = 5;
+}
+
+/// Entity info overlay constants
+pub mod overlay {
+    /// Maximum number of overlays displayed at once (closest entities win)
+    pub const MAX_OVERLAYS: usize = 32;
+    /// Entities further than this from the camera get no overlay
+    pub const MAX_DISTANCE: f32 = 80.0;
 }
 
 /// Texture atlas constants
 pub mod atlas {
     pub const SIZE: usize = 16;
 }
+
+/// Dynamic object lifetime constants
+pub mod objects {
+    /// Thrown stones further than this from the player are despawned (and kept as persisted records)
+    pub const STONE_DESPAWN_DISTANCE: f32 = 80.0;
+    /// Dynamic bodies further than this from the player are frozen (switched to fixed)
+    pub const PHYSICS_FREEZE_DISTANCE: f32 = 40.0;
+    /// Frozen bodies closer than this to the player become dynamic again
+    pub const PHYSICS_WAKE_DISTANCE: f32 = 30.0;
+    /// Distant dynamic bodies are only frozen once slower than this (world units per second),
+    /// so a falling or sliding body is not stuck in mid-air
+    pub const PHYSICS_FREEZE_MAX_SPEED: f32 = 0.05;
+    /// Landscape elements closer than this to the player get their collider back
+    pub const LANDSCAPE_COLLIDER_RADIUS: f32 = 30.0;
+    /// Landscape elements further than this from the player have their collider removed
+    pub const LANDSCAPE_COLLIDER_DROP_DISTANCE: f32 = 36.0;
+    /// Objects further than this from the player cannot be harvested
+    pub const HARVEST_REACH: f32 = 4.0;
+    /// Units harvested at once with the right tool (1 without it)
+    pub const TOOL_HARVEST_UNITS: u32 = 2;
+    /// Interval between checks for removed landscape elements due to grow back
+    pub const REGROWTH_CHECK_SECS: f32 = 5.0;
+}
+
+/// Multiplayer constants (`net` protocol, `tiles3d-server`)
+pub mod net {
+    /// TCP port the server listens on and clients connect to by default
+    pub const DEFAULT_PORT: u16 = 7878;
+    /// Server simulation and snapshot rate
+    pub const TICK_HZ: f64 = 20.0;
+    /// Players and objects further than this many tiles from a client's terrain center are not
+    /// replicated to it
+    pub const INTEREST_RADIUS_TILES: f64 = 30.0;
+    /// The client snaps its player to the server position when they drift further apart (tiles)
+    pub const CORRECTION_DISTANCE_TILES: f64 = 3.0;
+    /// Connections sending a longer message line are closed, in bytes
+    pub const MAX_MESSAGE_BYTES: usize = 64 * 1024;
+    /// Connections whose peer leaves more than this unread are closed, in bytes
+    pub const MAX_SEND_BACKLOG_BYTES: usize = 4 * 1024 * 1024;
+}
+
+/// Remote control API constants (`remote`, `--remote <port>`)
+pub mod remote {
+    /// Requests larger than this are refused, in bytes
+    pub const MAX_REQUEST_BYTES: usize = 64 * 1024;
+    /// Connections without a complete request after this many seconds are answered with an error
+    pub const REQUEST_TIMEOUT_SECS: f32 = 5.0;
+    /// Teleports further from the terrain center are refused (degrees of arc): the terrain
+    /// projection stretches too much beyond
+    pub const MAX_TELEPORT_DEGREES: f64 = 60.0;
+    /// Height above the ground the player lands from after a teleport
+    pub const TELEPORT_DROP_HEIGHT: f32 = 2.0;
+}
+
+/// Template script limits (`scripting`)
+pub mod scripting {
+    /// Interval between `on_tick` calls
+    pub const TICK_SECS: f32 = 0.5;
+    /// Operations a single handler call may run before it is aborted
+    pub const MAX_OPERATIONS: u64 = 50_000;
+    /// Maximum function call depth within a script
+    pub const MAX_CALL_LEVELS: usize = 32;
+    /// Maximum length of a script string, in bytes
+    pub const MAX_STRING_SIZE: usize = 4096;
+    /// Maximum number of elements of a script array
+    pub const MAX_ARRAY_SIZE: usize = 1024;
+    /// Maximum number of properties of a script object map
+    pub const MAX_MAP_SIZE: usize = 256;
+    /// Largest step `neighbour(tile, di, dj)` takes along each axis, in subpixels
+    pub const MAX_NEIGHBOUR_OFFSET: i64 = 64;
+}
@@ -0,0 +1,157 @@
+use bevy::prelude::*;
+
+use crate::config::daylight::*;
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::seasons::SeasonState;
+use crate::settings::Settings;
+use crate::terrain::{world_to_geo, TerrainCenter};
+use crate::ui::DebugPanel;
+use crate::weather::Weather;
+
+/// Night ambient light color (moonlit sky) and day ambient light color (blue sky)
+const AMBIENT_NIGHT_COLOR: Color = Color::srgb(0.35, 0.4, 0.7);
+const AMBIENT_DAY_COLOR: Color = Color::srgb(0.85, 0.9, 1.0);
+/// Moonlight color
+const MOON_COLOR: Color = Color::srgb(0.65, 0.75, 1.0);
+
+/// Time of day, advancing with game time by one day per `WorldSettings::day_length_secs`
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct GameTime {
+    pub day: u32,    // Days elapsed since the start
+    pub hour: f32,   // Local solar time at the player, 0.0 to 24.0 (12.0 is noon)
+}
+
+impl GameTime {
+    pub fn advance(&mut self, secs: f32, day_length_secs: f32) {
+        self.hour += 24.0 * secs / day_length_secs.max(1.0);
+        while self.hour >= 24.0 {
+            self.hour -= 24.0;
+            self.day += 1;
+        }
+    }
+
+    /// e.g. "Day 2, 06:45"
+    pub fn label(&self) -> String {
+        let minutes = (self.hour * 60.0) as u32;
+        format!("Day {}, {:02}:{:02}", self.day + 1, minutes / 60, minutes % 60)
+    }
+}
+
+/// Where the sun stands for the player, updated with the lights (the moon is opposite)
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct SunPosition {
+    pub direction: Vec3,   // Unit vector toward the sun, see `sun_direction`
+    pub latitude: f32,     // Player latitude the sun was placed for, in degrees
+    pub daylight: f32,     // 0.0 at night to 1.0 in full day, twilight in between
+}
+
+impl Default for SunPosition {
+    fn default() -> Self {
+        Self { direction: Vec3::Y, latitude: 0.0, daylight: 1.0 }
+    }
+}
+
+/// Marks the directional light of the sun, the only one casting shadows
+#[derive(Component)]
+pub struct Sun;
+
+/// Marks the directional light of the moon, which stands opposite the sun (always full)
+#[derive(Component)]
+pub struct Moon;
+
+/// Start the clock at `WorldSettings::start_hour` and spawn the sun and moon lights
+pub fn setup_daylight(mut commands: Commands, settings: Res<Settings>) {
+    commands.insert_resource(GameTime { day: 0, hour: settings.world.start_hour.rem_euclid(24.0) });
+    commands.spawn((
+        DirectionalLight {
+            illuminance: SUN_ILLUMINANCE,
+            shadows_enabled: settings.graphics.shadow_quality.map_size().is_some(),
+            ..default()
+        },
+        Transform::default(),
+        Sun,
+    ));
+    commands.spawn((
+        DirectionalLight { color: MOON_COLOR, illuminance: 0.0, shadows_enabled: false, ..default() },
+        Transform::default(),
+        Moon,
+    ));
+}
+
+/// Advance the clock and publish it to the debug panel
+pub fn advance_game_time(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut game_time: ResMut<GameTime>,
+    mut debug_panel: ResMut<DebugPanel>,
+) {
+    game_time.advance(time.delta_secs(), settings.world.day_length_secs);
+    debug_panel.set("Time", game_time.label());
+}
+
+/// Unit vector toward the sun in world axes (x east, y up, z north, as `geo_to_world` lays out
+/// the terrain), from the local solar time, the latitude and the sun declination in degrees
+pub fn sun_direction(hour: f32, latitude: f32, declination: f32) -> Vec3 {
+    let hour_angle = ((hour - 12.0) * 15.0).to_radians();
+    let (latitude, declination) = (latitude.to_radians(), declination.to_radians());
+    let up = latitude.sin() * declination.sin() + latitude.cos() * declination.cos() * hour_angle.cos();
+    let east = -declination.cos() * hour_angle.sin();
+    let north = latitude.cos() * declination.sin() - latitude.sin() * declination.cos() * hour_angle.cos();
+    Vec3::new(east, up, north).normalize_or(Vec3::Y)
+}
+
+/// Color of a black body at `kelvin` (Tanner Helland's fit, 1000 K to 40000 K)
+pub fn color_temperature(kelvin: f32) -> Color {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+    let red = if t <= 66.0 { 255.0 } else { 329.69873 * (t - 60.0).powf(-0.13320476) };
+    let green = if t <= 66.0 { 99.4708 * t.ln() - 161.11957 } else { 288.12217 * (t - 60.0).powf(-0.07551485) };
+    let blue = if t >= 66.0 { 255.0 } else if t <= 19.0 { 0.0 } else { 138.51773 * (t - 10.0).ln() - 305.0448 };
+    Color::srgb(red.clamp(0.0, 255.0) / 255.0, green.clamp(0.0, 255.0) / 255.0, blue.clamp(0.0, 255.0) / 255.0)
+}
+
+fn smoothstep(from: f32, to: f32, x: f32) -> f32 {
+    let t = ((x - from) / (to - from)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Point the sun and moon for the time of day, the player's latitude and the season, and set
+/// their strength and color and the ambient light: warm low sun at dawn and dusk, white at
+/// midday, dim blue nights. Clouds dim the sun.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_daylight(
+    game_time: Res<GameTime>,
+    season: Res<SeasonState>,
+    weather: Res<Weather>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    player_query: Query<&Transform, (With<Player>, Without<Sun>, Without<Moon>)>,
+    mut sun_query: Query<(&mut DirectionalLight, &mut Transform), (With<Sun>, Without<Moon>)>,
+    mut moon_query: Query<(&mut DirectionalLight, &mut Transform), (With<Moon>, Without<Sun>)>,
+    mut ambient: ResMut<AmbientLight>,
+    mut sun_position: ResMut<SunPosition>,
+) {
+    let latitude = player_query.single().ok()
+        .map(|transform| world_to_geo(transform.translation, &planisphere, &terrain_center).1)
+        .filter(|latitude| !latitude.is_nan())
+        .unwrap_or(terrain_center.latitude) as f32;
+    let direction = sun_direction(game_time.hour, latitude, season.season.sun_declination());
+    let elevation = direction.y.asin().to_degrees();
+    let day = smoothstep(NIGHT_ELEVATION_DEGREES, FULL_DAY_ELEVATION_DEGREES, elevation);
+
+    if let Ok((mut light, mut transform)) = sun_query.single_mut() {
+        let height = smoothstep(0.0, FULL_DAY_ELEVATION_DEGREES, elevation);
+        let clouds = 1.0 - crate::config::weather::CLOUD_DIMMING * weather.conditions.cloud_cover;
+        light.illuminance = SUN_ILLUMINANCE * height * clouds;
+        light.color = color_temperature(HORIZON_TEMPERATURE + (NOON_TEMPERATURE - HORIZON_TEMPERATURE) * height);
+        *transform = Transform::default().looking_to(-direction, Vec3::Y);
+    }
+    if let Ok((mut light, mut transform)) = moon_query.single_mut() {
+        let height = smoothstep(0.0, FULL_DAY_ELEVATION_DEGREES, -elevation);
+        light.illuminance = MOON_ILLUMINANCE * height * (1.0 - day);
+        *transform = Transform::default().looking_to(direction, Vec3::Y);
+    }
+    *sun_position = SunPosition { direction, latitude, daylight: day };
+    ambient.brightness = AMBIENT_NIGHT + (AMBIENT_DAY - AMBIENT_NIGHT) * day;
+    ambient.color = AMBIENT_NIGHT_COLOR.mix(&AMBIENT_DAY_COLOR, day);
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::camera::{CameraShakeEvent, ThirdPersonCamera};
+use crate::game_object::{ObjectDefinition, ObjectEvent};
+use crate::loot::{LootContext, LootDropEvent};
+use crate::message_log::{LogCategory, LogMessage};
+use crate::persistence::DespawnPolicy;
+
+/// Number of debris pieces thrown out when an object breaks
+const DEBRIS_COUNT: usize = 6;
+/// Seconds before debris pieces disappear
+const DEBRIS_LIFETIME: f32 = 2.0;
+/// Objects breaking further than this from the camera don't shake it
+const SHAKE_RANGE: f32 = 30.0;
+
+/// Template-file description of a destructible object, e.g. `(hp: 3.0, loot_table: Some("tree"))`
+#[derive(Debug, Clone, Deserialize)]
+pub struct DestructibleSpec {
+    pub hp: f32,
+    #[serde(default)]
+    pub loot_table: Option<String>,
+}
+
+/// Object that breaks after taking `hp` damage, dropping loot from `loot_table`
+#[derive(Component, Debug, Clone)]
+pub struct Destructible {
+    pub hp: f32,
+    pub max_hp: f32,              // Starting hp, used for health bars
+    pub loot_table: Option<String>,
+    pub loot_tags: Vec<String>,   // Passed to loot conditions (template tags)
+}
+
+/// Something that damages destructible objects it hits (thrown stones, tools)
+#[derive(Component, Debug, Clone)]
+pub struct Projectile {
+    pub damage: f32,
+}
+
+/// Short-lived fragment spawned when an object breaks
+#[derive(Component)]
+pub struct Debris;
+
+/// Apply projectile damage from collision events. A projectile only deals damage once,
+/// after which it becomes a regular object.
+pub fn apply_projectile_hits(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    projectile_query: Query<&Projectile>,
+    mut destructible_query: Query<&mut Destructible>,
+    mut log: EventWriter<LogMessage>,
+) {
+    for collision_event in collision_events.read() {
+        let CollisionEvent::Started(entity1, entity2, _) = collision_event else { continue; };
+        for (projectile_entity, target_entity) in [(*entity1, *entity2), (*entity2, *entity1)] {
+            let Ok(projectile) = projectile_query.get(projectile_entity) else { continue; };
+            let Ok(mut destructible) = destructible_query.get_mut(target_entity) else { continue; };
+            destructible.hp -= projectile.damage;
+            log.write(LogMessage::new(LogCategory::Combat, format!("Hit {:?} for {} damage ({} hp left)", target_entity, projectile.damage, destructible.hp.max(0.0))));
+            commands.entity(projectile_entity).remove::<Projectile>();
+        }
+    }
+}
+
+/// Break destructible objects whose hp reached zero: throw debris, drop loot, despawn
+#[allow(clippy::too_many_arguments)]
+pub fn destroy_broken_objects(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    broken_query: Query<(Entity, &Destructible, &Transform, Option<&ObjectDefinition>)>,
+    mut loot_events: EventWriter<LootDropEvent>,
+    mut object_events: EventWriter<ObjectEvent>,
+    mut shake_events: EventWriter<CameraShakeEvent>,
+    camera_query: Query<&GlobalTransform, With<ThirdPersonCamera>>,
+    mut debris_mesh: Local<Option<Handle<Mesh>>>,
+    mut log: EventWriter<LogMessage>,
+) {
+    let mut rng = rand::thread_rng();
+    for (entity, destructible, transform, definition) in broken_query.iter() {
+        if destructible.hp > 0.0 {
+            continue;
+        }
+        let position = transform.translation;
+        log.write(LogMessage::new(LogCategory::Combat, format!("{} destroyed", definition.map_or("Object", |definition| definition.object_type.as_str()))));
+
+        // Debris pieces fly out and expire after a short while
+        let mesh = debris_mesh.get_or_insert_with(|| meshes.add(Cuboid::new(0.2, 0.2, 0.2))).clone();
+        let material = materials.add(StandardMaterial {
+            base_color: Color::srgb(0.45, 0.4, 0.35),
+            perceptual_roughness: 0.9,
+            ..default()
+        });
+        for _ in 0..DEBRIS_COUNT {
+            let direction = Vec3::new(rng.gen_range(-1.0..1.0), rng.gen_range(0.5..1.5), rng.gen_range(-1.0..1.0));
+            commands.spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_translation(position + Vec3::Y),
+                RigidBody::Dynamic,
+                Collider::cuboid(0.1, 0.1, 0.1),
+                Velocity { linvel: direction * 4.0, angvel: direction * 3.0 },
+                Debris,
+                DespawnPolicy::lifetime(DEBRIS_LIFETIME),
+            ));
+        }
+
+        if let Some(table_id) = &destructible.loot_table {
+            loot_events.write(LootDropEvent {
+                table_id: table_id.clone(),
+                position,
+                context: LootContext { tags: destructible.loot_tags.clone() },
+            });
+        }
+        if let Ok(camera_transform) = camera_query.single() {
+            shake_events.write(CameraShakeEvent::at_distance(0.5, camera_transform.translation().distance(position), SHAKE_RANGE));
+        }
+        object_events.write(ObjectEvent::Destroyed { entity });
+        commands.entity(entity).despawn();
+    }
+}
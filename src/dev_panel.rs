@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+use bevy::ecs::system::SystemParam;
+use bevy_rapier3d::render::DebugRenderContext;
+
+use crate::camera::ThirdPersonCamera;
+use crate::planisphere::DistanceMethod;
+use crate::populate::SpawnConfig;
+use crate::props::PropSettings;
+use crate::wind::Wind;
+use crate::TerrainConfig;
+
+/// Spawn densities are changed by this factor per click
+const DENSITY_FACTOR: f64 = 1.25;
+
+/// Value tunable from the developer panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DevField {
+    TerrainRadius,
+    DistanceMethod,
+    RecreationThreshold,
+    CameraDistance,
+    CameraHeight,
+    CameraFollowSpeed,
+    CameraZoomSpeed,
+    CameraZoomSmoothing,
+    BiomeDensity(usize),   // Index in `SpawnConfig::biomes`
+    WindStrength,
+    DebugProps,
+    PhysicsDebug,
+}
+
+impl DevField {
+    fn is_toggle(self) -> bool {
+        matches!(self, DevField::DebugProps | DevField::PhysicsDebug)
+    }
+}
+
+/// What a panel button does when clicked
+#[derive(Component, Debug, Clone, Copy)]
+pub enum DevButton {
+    Toggle(DevField),
+    Step(DevField, i32),   // -1 or +1
+}
+
+/// Text showing the current value of a field
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DevValue(pub DevField);
+
+/// Root node of the developer panel (F1)
+#[derive(Component)]
+pub struct DevPanel;
+
+/// Everything the developer panel edits
+#[derive(SystemParam)]
+pub struct DevTargets<'w, 's> {
+    terrain_config: ResMut<'w, TerrainConfig>,
+    spawn_config: ResMut<'w, SpawnConfig>,
+    prop_settings: ResMut<'w, PropSettings>,
+    wind: ResMut<'w, Wind>,
+    physics_debug: Option<ResMut<'w, DebugRenderContext>>,
+    camera: Query<'w, 's, &'static mut ThirdPersonCamera>,
+}
+
+impl DevTargets<'_, '_> {
+    fn value(&self, field: DevField) -> String {
+        let camera = self.camera.single().ok();
+        let camera_value = |read: fn(&ThirdPersonCamera) -> f32| camera.map_or("-".to_string(), |camera| format!("{:.2}", read(camera)));
+        let on_off = |on: bool| (if on { "On" } else { "Off" }).to_string();
+        match field {
+            DevField::TerrainRadius => self.terrain_config.terrain_radius.to_string(),
+            DevField::DistanceMethod => self.terrain_config.distance_method.label().to_string(),
+            DevField::RecreationThreshold => self.terrain_config.recreation_threshold.to_string(),
+            DevField::CameraDistance => camera_value(|camera| camera.target_distance),
+            DevField::CameraHeight => camera_value(|camera| camera.height),
+            DevField::CameraFollowSpeed => camera_value(|camera| camera.follow_speed),
+            DevField::CameraZoomSpeed => camera_value(|camera| camera.zoom_speed),
+            DevField::CameraZoomSmoothing => camera_value(|camera| camera.zoom_smoothing),
+            DevField::BiomeDensity(index) => self.spawn_config.biomes.get(index).map_or("-".to_string(), |biome| format!("{:.4}", biome.density)),
+            DevField::WindStrength => format!("{:.1}", self.wind.strength),
+            DevField::DebugProps => on_off(self.prop_settings.show_debug),
+            DevField::PhysicsDebug => on_off(self.physics_debug.as_ref().is_some_and(|context| context.enabled)),
+        }
+    }
+
+    fn toggle(&mut self, field: DevField) {
+        match field {
+            DevField::DebugProps => self.prop_settings.show_debug = !self.prop_settings.show_debug,
+            DevField::PhysicsDebug => {
+                if let Some(context) = self.physics_debug.as_mut() {
+                    context.enabled = !context.enabled;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn step(&mut self, field: DevField, direction: i32) {
+        let sign = direction as f32;
+        if let Ok(mut camera) = self.camera.single_mut() {
+            match field {
+                DevField::CameraDistance => {
+                    camera.target_distance = (camera.target_distance + sign).clamp(camera.min_distance, camera.max_distance);
+                }
+                DevField::CameraHeight => camera.height = (camera.height + sign).clamp(camera.min_height, camera.max_height),
+                DevField::CameraFollowSpeed => camera.follow_speed = (camera.follow_speed + 0.5 * sign).max(0.5),
+                DevField::CameraZoomSpeed => camera.zoom_speed = (camera.zoom_speed + 0.5 * sign).max(0.5),
+                DevField::CameraZoomSmoothing => camera.zoom_smoothing = (camera.zoom_smoothing + sign).max(1.0),
+                _ => {}
+            }
+        }
+        match field {
+            DevField::TerrainRadius => {
+                // Rebuilt by terrain::apply_terrain_view, like a render distance change from the settings menu
+                self.terrain_config.terrain_radius = self.terrain_config.terrain_radius.saturating_add_signed(direction as isize).max(1);
+            }
+            DevField::DistanceMethod => {
+                let methods = DistanceMethod::ALL;
+                let index = methods.iter().position(|method| *method == self.terrain_config.distance_method).unwrap_or(0) as i32;
+                self.terrain_config.distance_method = methods[(index + direction).rem_euclid(methods.len() as i32) as usize];
+            }
+            DevField::RecreationThreshold => {
+                self.terrain_config.recreation_threshold = self.terrain_config.recreation_threshold.saturating_add_signed(direction as isize).max(1);
+            }
+            DevField::BiomeDensity(index) => {
+                if let Some(biome) = self.spawn_config.biomes.get_mut(index) {
+                    let factor = if direction > 0 { DENSITY_FACTOR } else { 1.0 / DENSITY_FACTOR };
+                    biome.density = (biome.density * factor).clamp(0.0001, 1.0);
+                }
+            }
+            DevField::WindStrength => {
+                let direction = self.wind.direction;
+                let strength = self.wind.strength + 0.1 * sign;
+                self.wind.set(direction, strength);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn field_label(field: DevField, spawn_config: &SpawnConfig) -> String {
+    match field {
+        DevField::TerrainRadius => "Terrain radius".to_string(),
+        DevField::DistanceMethod => "Distance method".to_string(),
+        DevField::RecreationThreshold => "Recreation threshold".to_string(),
+        DevField::CameraDistance => "Camera distance".to_string(),
+        DevField::CameraHeight => "Camera height".to_string(),
+        DevField::CameraFollowSpeed => "Camera follow speed".to_string(),
+        DevField::CameraZoomSpeed => "Camera zoom speed".to_string(),
+        DevField::CameraZoomSmoothing => "Camera zoom smoothing".to_string(),
+        DevField::BiomeDensity(index) => format!("Density: {}", spawn_config.biomes.get(index).map_or("?", |biome| biome.name.as_str())),
+        DevField::WindStrength => "Wind strength".to_string(),
+        DevField::DebugProps => "Debug props".to_string(),
+        DevField::PhysicsDebug => "Physics debug render".to_string(),
+    }
+}
+
+fn spawn_button(parent: &mut ChildSpawnerCommands, label: &str, action: DevButton) {
+    parent.spawn((
+        Button,
+        Node { padding: UiRect::axes(Val::Px(6.0), Val::Px(1.0)), ..default() },
+        BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9)),
+        action,
+    )).with_children(|button| {
+        button.spawn((Text::new(label), TextFont { font_size: 12.0, ..default() }, TextColor(Color::WHITE)));
+    });
+}
+
+/// Spawn the (hidden) developer panel on the left, one row per tunable value
+pub fn setup_dev_panel(mut commands: Commands, spawn_config: Res<SpawnConfig>) {
+    let mut fields = vec![
+        DevField::TerrainRadius,
+        DevField::DistanceMethod,
+        DevField::RecreationThreshold,
+        DevField::CameraDistance,
+        DevField::CameraHeight,
+        DevField::CameraFollowSpeed,
+        DevField::CameraZoomSpeed,
+        DevField::CameraZoomSmoothing,
+    ];
+    fields.extend((0..spawn_config.biomes.len()).map(DevField::BiomeDensity));
+    fields.extend([DevField::WindStrength, DevField::DebugProps, DevField::PhysicsDebug]);
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            top: Val::Px(60.0),
+            width: Val::Px(300.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(3.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.1, 0.9)),
+        GlobalZIndex(5),
+        Visibility::Hidden,
+        DevPanel,
+    )).with_children(|panel| {
+        panel.spawn((Text::new("Developer tools (F1)"), TextFont { font_size: 15.0, ..default() }, TextColor(Color::WHITE)));
+        for field in fields {
+            panel.spawn(Node {
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                ..default()
+            }).with_children(|row| {
+                row.spawn((Text::new(field_label(field, &spawn_config)), TextFont { font_size: 12.0, ..default() }, TextColor(Color::srgb(0.8, 0.8, 0.8))));
+                row.spawn(Node { column_gap: Val::Px(4.0), align_items: AlignItems::Center, ..default() }).with_children(|controls| {
+                    if field.is_toggle() {
+                        spawn_button(controls, "Toggle", DevButton::Toggle(field));
+                        controls.spawn((Text::new(""), TextFont { font_size: 12.0, ..default() }, TextColor(Color::WHITE), DevValue(field)));
+                    } else {
+                        spawn_button(controls, "-", DevButton::Step(field, -1));
+                        controls.spawn((Text::new(""), TextFont { font_size: 12.0, ..default() }, TextColor(Color::WHITE), DevValue(field)));
+                        spawn_button(controls, "+", DevButton::Step(field, 1));
+                    }
+                });
+            });
+        }
+        panel.spawn((
+            Text::new("Densities apply to newly populated terrain"),
+            TextFont { font_size: 10.0, ..default() },
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+        ));
+    });
+}
+
+/// Toggle the developer panel with F1
+pub fn toggle_dev_panel(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<&mut Visibility, With<DevPanel>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F1) {
+        return;
+    }
+    if let Ok(mut visibility) = panel_query.single_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Apply panel clicks and refresh the values while the panel is open
+pub fn update_dev_panel(
+    mut targets: DevTargets,
+    panel_query: Query<&Visibility, With<DevPanel>>,
+    interaction_query: Query<(&Interaction, &DevButton), Changed<Interaction>>,
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), With<DevButton>>,
+    mut values: Query<(&DevValue, &mut Text)>,
+) {
+    if panel_query.single().map_or(true, |visibility| *visibility == Visibility::Hidden) {
+        return;
+    }
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match *button {
+            DevButton::Toggle(field) => targets.toggle(field),
+            DevButton::Step(field, direction) => targets.step(field, direction),
+        }
+    }
+    for (value, mut text) in values.iter_mut() {
+        let current = targets.value(value.0);
+        if **text != current {
+            **text = current;
+        }
+    }
+    for (interaction, mut background) in buttons.iter_mut() {
+        *background = match interaction {
+            Interaction::Hovered | Interaction::Pressed => BackgroundColor(Color::srgba(0.4, 0.4, 0.4, 0.9)),
+            Interaction::None => BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9)),
+        };
+    }
+}
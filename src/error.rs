@@ -0,0 +1,53 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::preprocess::PreprocessError;
+
+/// Errors of the game setup and of the assets it builds or loads
+#[derive(Debug)]
+pub enum GameError {
+    /// The elevation map is missing, unreadable or not an equirectangular map
+    Map(PreprocessError),
+    /// The asset server could not provide the elevation map
+    MapLoad { path: String, reason: String },
+    /// The map pixels cannot be sampled as RGBA8
+    MapFormat { path: String },
+    /// An optional asset (texture, scene) could not be loaded; a placeholder stands in for it
+    MissingAsset { path: String, reason: String },
+    /// Rapier rejected the geometry of a collider
+    Collider { what: String, reason: String },
+}
+
+pub type GameResult<T> = Result<T, GameError>;
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Map(e) => write!(f, "{}", e),
+            Self::MapLoad { path, reason } => write!(f, "Failed to load elevation map {}: {}", path, reason),
+            Self::MapFormat { path } => write!(f, "Elevation map {} has an unsupported pixel format", path),
+            Self::MissingAsset { path, reason } => write!(f, "Missing asset {}: {}", path, reason),
+            Self::Collider { what, reason } => write!(f, "Could not build the {} collider: {}", what, reason),
+        }
+    }
+}
+
+impl std::error::Error for GameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Map(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<PreprocessError> for GameError {
+    fn from(e: PreprocessError) -> Self {
+        Self::Map(e)
+    }
+}
+
+/// Error that stopped the game setup (`MapState::Failed`), shown by the error screen
+#[derive(Resource, Debug)]
+pub struct StartupError(pub GameError);
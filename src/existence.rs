@@ -0,0 +1,102 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_object::{ExistenceConditions, ObjectDefinition};
+use crate::vfx::DespawnRequested;
+
+/// Named events and condition flags that `ExistenceConditions` are checked against.
+/// - `OnEvent(name)` holds while the event is raised (until `clear_event`)
+/// - `OnCondition(expr)` evaluates flags combined with `!`, `&&` and `||`, e.g. `"quest_started && !quest_done"`
+#[derive(Resource, Default, Debug)]
+pub struct ExistenceRegistry {
+    events: HashSet<String>,
+    flags: HashMap<String, bool>,
+}
+
+impl ExistenceRegistry {
+    pub fn raise_event(&mut self, name: impl Into<String>) {
+        self.events.insert(name.into());
+    }
+
+    pub fn clear_event(&mut self, name: &str) {
+        self.events.remove(name);
+    }
+
+    pub fn is_raised(&self, name: &str) -> bool {
+        self.events.contains(name)
+    }
+
+    pub fn set_flag(&mut self, name: impl Into<String>, value: bool) {
+        self.flags.insert(name.into(), value);
+    }
+
+    /// Value of a flag, false when it was never set
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.get(name).copied().unwrap_or(false)
+    }
+
+    /// Evaluate a condition expression; `&&` binds tighter than `||`
+    pub fn evaluate(&self, expression: &str) -> bool {
+        expression.split("||").any(|clause| {
+            clause.split("&&").all(|term| {
+                let term = term.trim();
+                match term.strip_prefix('!') {
+                    Some(negated) => !self.flag(negated.trim()),
+                    None => self.flag(term),
+                }
+            })
+        })
+    }
+
+    /// Whether an object with these conditions should currently exist.
+    /// `OnFrame` is handled by `enforce_existence_conditions` and always holds here.
+    pub fn holds(&self, conditions: &ExistenceConditions) -> bool {
+        match conditions {
+            ExistenceConditions::Always | ExistenceConditions::OnFrame => true,
+            ExistenceConditions::OnCondition(expression) => self.evaluate(expression),
+            ExistenceConditions::OnEvent(name) => self.is_raised(name),
+        }
+    }
+}
+
+/// Hide the object (and disable its colliders) instead of despawning it when its
+/// existence condition stops holding, so it can come back (quest-gated props)
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HideWhenAbsent;
+
+/// Despawn or hide objects whose existence condition no longer holds.
+/// `OnFrame` objects are removed on the frame after they were spawned.
+#[allow(clippy::type_complexity)]
+pub fn enforce_existence_conditions(
+    mut commands: Commands,
+    registry: Res<ExistenceRegistry>,
+    mut query: Query<(Entity, Ref<ObjectDefinition>, Option<&HideWhenAbsent>, Option<&mut Visibility>), Without<DespawnRequested>>,
+) {
+    for (entity, definition, hide, visibility) in query.iter_mut() {
+        let Some(conditions) = &definition.existence_conditions else { continue; };
+        let exists = match conditions {
+            ExistenceConditions::OnFrame => definition.is_added(),
+            _ => registry.holds(conditions),
+        };
+
+        match (hide, visibility) {
+            (Some(_), Some(mut visibility)) => {
+                let wanted = if exists { Visibility::Inherited } else { Visibility::Hidden };
+                if *visibility != wanted {
+                    *visibility = wanted;
+                    if exists {
+                        commands.entity(entity).remove::<ColliderDisabled>();
+                    } else {
+                        commands.entity(entity).insert(ColliderDisabled);
+                    }
+                }
+            }
+            _ if !exists => {
+                debug!("{} no longer exists ({:?})", definition.object_type, conditions);
+                crate::vfx::request_despawn(&mut commands, entity);
+            }
+            _ => {}
+        }
+    }
+}
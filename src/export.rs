@@ -0,0 +1,381 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use bevy::render::mesh::{PrimitiveTopology, VertexAttributeValues};
+use serde_json::{json, Map, Value};
+
+use crate::building::PlacedObject;
+use crate::notifications::Notify;
+use crate::preprocess::Manifest;
+use crate::terrain::Tile;
+
+/// Directory of the exports made in game (F11)
+pub const EXPORT_DIR: &str = "exports";
+/// Terrain texture atlas copied next to the exports when the asset manifest lists none
+const DEFAULT_ATLAS_PATH: &str = "assets/textures/texture_atlas.png";
+/// Name of the atlas copy next to an export, referenced by the terrain material
+const ATLAS_FILE: &str = "texture_atlas.png";
+/// Seconds between the first terrain and the command-line export, for object scenes to load
+const STARTUP_EXPORT_DELAY_SECS: f32 = 2.0;
+
+/// File format of an export, from its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Gltf,   // `.gltf` scene with its geometry in a `.bin` file beside it
+    Obj,    // `.obj` with a `.mtl` material library beside it
+}
+
+impl ExportFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gltf" => Some(Self::Gltf),
+            "obj" => Some(Self::Obj),
+            _ => None,
+        }
+    }
+}
+
+/// Write the rendered terrain to `path` (`.gltf` or `.obj`), with the player-built objects
+/// when `objects` is set
+#[derive(Event, Debug, Clone)]
+pub struct ExportTerrain {
+    pub path: PathBuf,
+    pub objects: bool,
+}
+
+/// Export asked for on the command line (`--export`), sent once the first terrain is spawned;
+/// the game quits after it when `exit` is set (`--headless`)
+#[derive(Resource, Debug, Clone)]
+pub struct StartupExport {
+    pub request: ExportTerrain,
+    pub exit: bool,
+}
+
+/// Material of an exported mesh
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportMaterial {
+    TerrainAtlas,        // The terrain texture atlas, through the mesh UVs
+    Color([f32; 4]),     // Linear RGBA base color
+}
+
+/// Triangle mesh baked into world coordinates (x east, y up, z north)
+#[derive(Debug, Clone)]
+pub struct ExportMesh {
+    pub name: String,
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,   // Empty when the mesh has none
+    pub uvs: Vec<[f32; 2]>,       // Empty when the mesh has none
+    pub indices: Vec<u32>,
+    pub material: ExportMaterial,
+}
+
+impl ExportMesh {
+    /// Vertices of `mesh` moved by `transform`; None for meshes without positions or that are
+    /// not triangle lists
+    pub fn from_mesh(name: String, mesh: &Mesh, transform: &GlobalTransform, material: ExportMaterial) -> Option<Self> {
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else { return None; };
+        let matrix = transform.compute_matrix();
+        let normal_matrix = Mat3::from_mat4(matrix).inverse().transpose();
+        let normals = match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+            Some(VertexAttributeValues::Float32x3(normals)) => normals.iter()
+                .map(|&normal| (normal_matrix * Vec3::from_array(normal)).normalize_or_zero().to_array())
+                .collect(),
+            _ => Vec::new(),
+        };
+        let uvs = match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+            Some(VertexAttributeValues::Float32x2(uvs)) => uvs.clone(),
+            _ => Vec::new(),
+        };
+        let indices = match mesh.indices() {
+            Some(indices) => indices.iter().map(|index| index as u32).collect(),
+            None => (0..positions.len() as u32).collect(),
+        };
+        Some(Self {
+            name,
+            positions: positions.iter().map(|&position| matrix.transform_point3(Vec3::from_array(position)).to_array()).collect(),
+            normals,
+            uvs,
+            indices,
+            material,
+        })
+    }
+
+    fn has_normals(&self) -> bool {
+        !self.normals.is_empty() && self.normals.len() == self.positions.len()
+    }
+
+    fn has_uvs(&self) -> bool {
+        !self.uvs.is_empty() && self.uvs.len() == self.positions.len()
+    }
+}
+
+/// Write `meshes` to `path` in the format of its extension, creating its directory, and copy the
+/// texture atlas beside it when the terrain uses it
+pub fn write_export(path: &Path, meshes: &[ExportMesh], atlas: &Path) -> Result<(), String> {
+    let format = ExportFormat::from_path(path)
+        .ok_or_else(|| format!("unknown export format for {} (use .gltf or .obj)", path.display()))?;
+    if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    match format {
+        ExportFormat::Gltf => write_gltf(path, meshes),
+        ExportFormat::Obj => write_obj(path, meshes),
+    }.map_err(|e| e.to_string())?;
+    if meshes.iter().any(|mesh| mesh.material == ExportMaterial::TerrainAtlas)
+        && let Err(e) = std::fs::copy(atlas, path.with_file_name(ATLAS_FILE)) {
+        warn!("Could not copy the texture atlas {} next to the export: {}", atlas.display(), e);
+    }
+    Ok(())
+}
+
+/// Name of a material in the exports
+fn material_name(index: usize, material: ExportMaterial) -> String {
+    match material {
+        ExportMaterial::TerrainAtlas => "terrain".to_string(),
+        ExportMaterial::Color(_) => format!("color_{}", index),
+    }
+}
+
+/// Append `bytes` to the glTF buffer as a new buffer view (4-byte aligned) and return its index
+fn push_buffer_view(buffer: &mut Vec<u8>, views: &mut Vec<Value>, bytes: &[u8], target: u32) -> usize {
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+    views.push(json!({ "buffer": 0, "byteOffset": buffer.len(), "byteLength": bytes.len(), "target": target }));
+    buffer.extend_from_slice(bytes);
+    views.len() - 1
+}
+
+fn float_bytes<const N: usize>(values: &[[f32; N]]) -> Vec<u8> {
+    values.iter().flatten().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+/// Write `meshes` as a glTF 2.0 scene, one node per mesh, with the geometry in a `.bin` file
+/// beside `path`
+pub fn write_gltf(path: &Path, meshes: &[ExportMesh]) -> std::io::Result<()> {
+    const FLOAT: u32 = 5126;
+    const UNSIGNED_INT: u32 = 5125;
+    const ARRAY_BUFFER: u32 = 34962;
+    const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+    const NEAREST: u32 = 9728;
+
+    let bin_path = path.with_extension("bin");
+    let bin_name = bin_path.file_name().map_or_else(|| "terrain.bin".into(), |name| name.to_string_lossy().into_owned());
+    let mut buffer = Vec::new();
+    let (mut views, mut accessors, mut gltf_meshes, mut nodes, mut materials) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut atlas_material = None;
+
+    for (n, mesh) in meshes.iter().enumerate().filter(|(_, mesh)| !mesh.positions.is_empty()) {
+        let (min, max) = mesh.positions.iter().fold((Vec3::MAX, Vec3::MIN), |(min, max), &position| {
+            (min.min(Vec3::from_array(position)), max.max(Vec3::from_array(position)))
+        });
+        let view = push_buffer_view(&mut buffer, &mut views, &float_bytes(&mesh.positions), ARRAY_BUFFER);
+        accessors.push(json!({ "bufferView": view, "componentType": FLOAT, "count": mesh.positions.len(), "type": "VEC3",
+            "min": min.to_array(), "max": max.to_array() }));
+        let mut attributes = Map::new();
+        attributes.insert("POSITION".into(), json!(accessors.len() - 1));
+        if mesh.has_normals() {
+            let view = push_buffer_view(&mut buffer, &mut views, &float_bytes(&mesh.normals), ARRAY_BUFFER);
+            accessors.push(json!({ "bufferView": view, "componentType": FLOAT, "count": mesh.normals.len(), "type": "VEC3" }));
+            attributes.insert("NORMAL".into(), json!(accessors.len() - 1));
+        }
+        if mesh.has_uvs() {
+            let view = push_buffer_view(&mut buffer, &mut views, &float_bytes(&mesh.uvs), ARRAY_BUFFER);
+            accessors.push(json!({ "bufferView": view, "componentType": FLOAT, "count": mesh.uvs.len(), "type": "VEC2" }));
+            attributes.insert("TEXCOORD_0".into(), json!(accessors.len() - 1));
+        }
+        let index_bytes: Vec<u8> = mesh.indices.iter().flat_map(|index| index.to_le_bytes()).collect();
+        let view = push_buffer_view(&mut buffer, &mut views, &index_bytes, ELEMENT_ARRAY_BUFFER);
+        accessors.push(json!({ "bufferView": view, "componentType": UNSIGNED_INT, "count": mesh.indices.len(), "type": "SCALAR" }));
+        let indices = accessors.len() - 1;
+
+        let material = match mesh.material {
+            ExportMaterial::TerrainAtlas => *atlas_material.get_or_insert_with(|| {
+                materials.push(json!({ "name": material_name(n, mesh.material), "doubleSided": true,
+                    "pbrMetallicRoughness": { "baseColorTexture": { "index": 0 }, "metallicFactor": 0.1, "roughnessFactor": 0.8 } }));
+                materials.len() - 1
+            }),
+            ExportMaterial::Color(color) => {
+                materials.push(json!({ "name": material_name(n, mesh.material),
+                    "pbrMetallicRoughness": { "baseColorFactor": color, "metallicFactor": 0.0, "roughnessFactor": 0.9 } }));
+                materials.len() - 1
+            }
+        };
+        gltf_meshes.push(json!({ "name": mesh.name, "primitives": [{ "attributes": attributes, "indices": indices, "material": material }] }));
+        nodes.push(json!({ "name": mesh.name, "mesh": gltf_meshes.len() - 1 }));
+    }
+
+    let mut root = Map::new();
+    root.insert("asset".into(), json!({ "version": "2.0", "generator": "tiles3d" }));
+    root.insert("scene".into(), json!(0));
+    root.insert("scenes".into(), json!([{ "nodes": (0..nodes.len()).collect::<Vec<_>>() }]));
+    // glTF forbids empty arrays: the lists are only written when they have entries
+    for (key, list) in [("nodes", nodes), ("meshes", gltf_meshes), ("accessors", accessors), ("bufferViews", views), ("materials", materials)] {
+        if !list.is_empty() {
+            root.insert(key.into(), Value::Array(list));
+        }
+    }
+    if !buffer.is_empty() {
+        root.insert("buffers".into(), json!([{ "uri": bin_name, "byteLength": buffer.len() }]));
+    }
+    if atlas_material.is_some() {
+        root.insert("samplers".into(), json!([{ "magFilter": NEAREST, "minFilter": NEAREST }]));
+        root.insert("images".into(), json!([{ "uri": ATLAS_FILE }]));
+        root.insert("textures".into(), json!([{ "source": 0, "sampler": 0 }]));
+    }
+
+    std::fs::write(&bin_path, &buffer)?;
+    let json = serde_json::to_string_pretty(&Value::Object(root)).map_err(std::io::Error::other)?;
+    std::fs::write(path, json)
+}
+
+/// Write `meshes` as Wavefront OBJ objects, with their materials in a `.mtl` file beside `path`
+pub fn write_obj(path: &Path, meshes: &[ExportMesh]) -> std::io::Result<()> {
+    let mtl_path = path.with_extension("mtl");
+    let mtl_name = mtl_path.file_name().map_or_else(|| "terrain.mtl".into(), |name| name.to_string_lossy().into_owned());
+    let mut obj = BufWriter::new(File::create(path)?);
+    let mut mtl = BufWriter::new(File::create(&mtl_path)?);
+    writeln!(obj, "# tiles3d terrain export")?;
+    writeln!(obj, "mtllib {}", mtl_name)?;
+
+    let mut written_atlas = false;
+    // OBJ indices are 1-based and global to the file
+    let (mut position_base, mut uv_base, mut normal_base) = (1, 1, 1);
+    for (n, mesh) in meshes.iter().enumerate().filter(|(_, mesh)| !mesh.positions.is_empty()) {
+        let material = material_name(n, mesh.material);
+        match mesh.material {
+            ExportMaterial::TerrainAtlas if !written_atlas => {
+                writeln!(mtl, "newmtl {}\nKd 1 1 1\nmap_Kd {}\n", material, ATLAS_FILE)?;
+                written_atlas = true;
+            }
+            ExportMaterial::TerrainAtlas => {}
+            ExportMaterial::Color([red, green, blue, alpha]) => writeln!(mtl, "newmtl {}\nKd {} {} {}\nd {}\n", material, red, green, blue, alpha)?,
+        }
+
+        writeln!(obj, "o {}", mesh.name)?;
+        writeln!(obj, "usemtl {}", material)?;
+        for [x, y, z] in &mesh.positions {
+            writeln!(obj, "v {} {} {}", x, y, z)?;
+        }
+        let (has_uvs, has_normals) = (mesh.has_uvs(), mesh.has_normals());
+        if has_uvs {
+            for [u, v] in &mesh.uvs {
+                writeln!(obj, "vt {} {}", u, 1.0 - v)?; // OBJ texture rows go up
+            }
+        }
+        if has_normals {
+            for [x, y, z] in &mesh.normals {
+                writeln!(obj, "vn {} {} {}", x, y, z)?;
+            }
+        }
+        let vertex = |index: u32| {
+            let index = index as usize;
+            match (has_uvs, has_normals) {
+                (true, true) => format!("{}/{}/{}", position_base + index, uv_base + index, normal_base + index),
+                (true, false) => format!("{}/{}", position_base + index, uv_base + index),
+                (false, true) => format!("{}//{}", position_base + index, normal_base + index),
+                (false, false) => format!("{}", position_base + index),
+            }
+        };
+        for triangle in mesh.indices.chunks_exact(3) {
+            writeln!(obj, "f {} {} {}", vertex(triangle[0]), vertex(triangle[1]), vertex(triangle[2]))?;
+        }
+
+        position_base += mesh.positions.len();
+        if has_uvs {
+            uv_base += mesh.uvs.len();
+        }
+        if has_normals {
+            normal_base += mesh.normals.len();
+        }
+    }
+    obj.flush()?;
+    mtl.flush()
+}
+
+/// F11 exports the rendered terrain as glTF under `EXPORT_DIR`, Ctrl+F11 with the player-built
+/// objects as well
+pub fn handle_export_keys(keyboard_input: Res<ButtonInput<KeyCode>>, mut requests: EventWriter<ExportTerrain>) {
+    if !keyboard_input.just_pressed(KeyCode::F11) {
+        return;
+    }
+    let objects = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let path = Path::new(EXPORT_DIR).join(format!("terrain-{}.gltf", crate::persistence::unix_time_secs()));
+    requests.write(ExportTerrain { path, objects });
+}
+
+/// Send the command-line export `STARTUP_EXPORT_DELAY_SECS` after the first terrain is spawned,
+/// and quit with it when asked
+pub fn run_startup_export(
+    mut commands: Commands,
+    time: Res<Time>,
+    export: Option<Res<StartupExport>>,
+    tiles: Query<(), With<Tile>>,
+    mut requests: EventWriter<ExportTerrain>,
+    mut exit: EventWriter<AppExit>,
+    mut waited: Local<f32>,
+) {
+    let Some(export) = export else { return; };
+    if tiles.is_empty() {
+        return;
+    }
+    *waited += time.delta_secs();
+    if *waited < STARTUP_EXPORT_DELAY_SECS {
+        return;
+    }
+    requests.write(export.request.clone());
+    if export.exit {
+        exit.write(AppExit::Success);
+    }
+    commands.remove_resource::<StartupExport>();
+}
+
+/// Write the requested exports: the terrain meshes with the atlas material and, when asked, the
+/// meshes of the player-built objects with their base colors
+#[allow(clippy::too_many_arguments)]
+pub fn export_terrain(
+    mut requests: EventReader<ExportTerrain>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    manifest: Option<Res<Manifest>>,
+    tiles: Query<(&Mesh3d, &GlobalTransform), With<Tile>>,
+    placed: Query<(Entity, &PlacedObject)>,
+    children: Query<&Children>,
+    object_meshes: Query<(&Mesh3d, &GlobalTransform, Option<&MeshMaterial3d<StandardMaterial>>)>,
+    mut notify: EventWriter<Notify>,
+) {
+    for request in requests.read() {
+        let mut export: Vec<ExportMesh> = tiles.iter()
+            .filter_map(|(mesh, transform)| {
+                ExportMesh::from_mesh("terrain".to_string(), meshes.get(&mesh.0)?, transform, ExportMaterial::TerrainAtlas)
+            })
+            .collect();
+        if request.objects {
+            for (entity, object) in placed.iter() {
+                for (part, part_entity) in std::iter::once(entity).chain(children.iter_descendants(entity)).enumerate() {
+                    let Ok((mesh, transform, material)) = object_meshes.get(part_entity) else { continue; };
+                    let Some(mesh) = meshes.get(&mesh.0) else { continue; };
+                    let color = material.and_then(|material| materials.get(&material.0))
+                        .map_or(Color::WHITE, |material| material.base_color);
+                    let name = format!("{}_{}_{}", object.template, entity.index(), part);
+                    export.extend(ExportMesh::from_mesh(name, mesh, transform, ExportMaterial::Color(color.to_linear().to_f32_array())));
+                }
+            }
+        }
+
+        let atlas = manifest.as_ref().map_or(DEFAULT_ATLAS_PATH, |manifest| manifest.atlas.path.as_str());
+        match write_export(&request.path, &export, Path::new(atlas)) {
+            Ok(()) => {
+                info!("Exported {} meshes to {}", export.len(), request.path.display());
+                notify.write(Notify::new(format!("Exported to {}", request.path.display())));
+            }
+            Err(e) => {
+                error!("Failed to export the terrain to {}: {}", request.path.display(), e);
+                notify.write(Notify::new("Export failed").with_color(Color::srgb(1.0, 0.3, 0.3)));
+            }
+        }
+    }
+}
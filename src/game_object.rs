@@ -1,13 +1,24 @@
 
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
-use crate::player::Player;
+use serde::Deserialize;
 use crate::planisphere::{self, Planisphere};
 use crate::terrain::{ijk_to_world, TerrainCenter};
+use crate::scene_collider::{SceneColliderMode, PendingSceneCollider};
+use crate::destruction::{Destructible, DestructibleSpec};
+use crate::harvest::{HarvestSpec, Harvestable};
+use crate::vfx::{TemplateEffects, AppearEffect, DisappearEffect};
+use crate::animation::{AnimatedTemplate, AnimatedTemplateFile, TemplateAnimation};
+use crate::occupancy::OccupiesTile;
 
 
-trait IntoWorldPosition{
+pub trait IntoWorldPosition{
     fn into_world_position(&self, planisphere: &planisphere::Planisphere, terrain_center: &crate::terrain::TerrainCenter) -> Vec3;
+    /// Subpixel the position refers to, when known (used to seed per-instance variation)
+    fn tile(&self) -> Option<(usize, usize, usize)> { None }
 }
 
 impl IntoWorldPosition for Vec3 {
@@ -20,6 +31,8 @@ impl IntoWorldPosition for (usize, usize, usize) {
     fn into_world_position(&self, planisphere: &planisphere::Planisphere, terrain_center: &crate::terrain::TerrainCenter) -> Vec3 {
         ijk_to_world(self.0 as i32, self.1 as i32, self.2 as i32, planisphere, terrain_center)
     }
+
+    fn tile(&self) -> Option<(usize, usize, usize)> { Some(*self) }
 }
 
 /// Shared component for entities that use raycast positioning
@@ -58,12 +71,28 @@ pub enum ObjectPosition {
 
 
 /// Shape specification for creating meshes and colliders
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum ObjectShape {
     Cube { size: Vec3 },
     Sphere { radius: f32 },
     Capsule { radius: f32, height: f32 }, 
     Cylinder { radius: f32, height: f32 },
+    Compound(Vec<(Transform, ObjectShape)>), // Parts placed relative to the object origin (part scale is ignored by colliders)
+}
+
+impl ObjectShape {
+    /// This shape uniformly scaled by `factor`, part offsets included
+    pub fn scaled(&self, factor: f32) -> ObjectShape {
+        match self {
+            ObjectShape::Cube { size } => ObjectShape::Cube { size: *size * factor },
+            ObjectShape::Sphere { radius } => ObjectShape::Sphere { radius: radius * factor },
+            ObjectShape::Capsule { radius, height } => ObjectShape::Capsule { radius: radius * factor, height: height * factor },
+            ObjectShape::Cylinder { radius, height } => ObjectShape::Cylinder { radius: radius * factor, height: height * factor },
+            ObjectShape::Compound(parts) => ObjectShape::Compound(parts.iter()
+                .map(|(transform, part)| (transform.with_translation(transform.translation * factor), part.scaled(factor)))
+                .collect()),
+        }
+    }
 }
 
 
@@ -74,14 +103,241 @@ pub struct ObjectTemplate {
     pub y_offset: f32,
     pub scale: Vec3,
     pub rotation_y: f32,  // Rotation around Y-axis in radians
+    pub tags: Vec<String>, // Free-form labels from the template file (e.g. "decor")
+    pub collider: SceneColliderMode, // How the collider is derived from the scene geometry
+    pub variation: SpawnVariation, // Per-instance randomization ranges
+    pub destructible: Option<DestructibleSpec>, // Hit points and loot when the object can be broken
+    pub harvest: Option<HarvestSpec>, // Resource pool when the object can be harvested
+    pub effects: TemplateEffects, // Appear/disappear visual effects
+    pub animations: Option<AnimatedTemplate>, // Named clips from the glb, played through `TemplateAnimation`
+    pub sockets: HashMap<String, Transform>, // Named attachment points, relative to the object origin
+    pub script: Option<String>, // Rhai script of the instances, relative to assets/ (see `scripting::Scripted`)
+    pub icon: Option<String>, // Asset path of the icon shown on the instances' nameplate
     pub object_definition: ObjectDefinition, // Default definition for this template
 }
 
-#[derive(Resource)]
+/// Per-spawn randomization ranges of a template, so repeated objects don't look identical
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct SpawnVariation {
+    #[serde(default)]
+    pub scale_jitter: f32,        // Scale varies by ± this fraction (0.2 = 80%..120%)
+    #[serde(default)]
+    pub random_yaw: bool,         // Random rotation around the Y axis
+    #[serde(default)]
+    pub hue_jitter_degrees: f32,  // Material hue shifts by ± this many degrees
+}
+
+/// Variation actually applied to one spawned instance, kept so template reloads preserve it
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct InstanceVariation {
+    pub scale_factor: f32,
+    pub yaw: f32,         // Radians, added to the template rotation
+    pub hue_shift: f32,   // Degrees
+}
+
+impl Default for InstanceVariation {
+    fn default() -> Self {
+        Self { scale_factor: 1.0, yaw: 0.0, hue_shift: 0.0 }
+    }
+}
+
+impl SpawnVariation {
+    /// Deterministic sample for the object spawned on `tile`; no variation without a tile.
+    /// Values are quantized so that instances can share colliders and materials.
+    pub fn sample(&self, tile: Option<(usize, usize, usize)>) -> InstanceVariation {
+        let Some((i, j, k)) = tile else { return InstanceVariation::default(); };
+        // Independent random draws in [-1, 1) from the same tile
+        let draw = |salt: usize| (crate::terrain::texture::deterministic_random(i, j, k + salt * 1024) * 2.0 - 1.0) as f32;
+
+        let scale_factor = 1.0 + draw(1) * self.scale_jitter;
+        let yaw_degrees = if self.random_yaw { (draw(2) + 1.0) * 180.0 } else { 0.0 };
+        let hue_shift = draw(3) * self.hue_jitter_degrees;
+        InstanceVariation {
+            scale_factor: (scale_factor * 20.0).round() / 20.0,          // 5% steps
+            yaw: ((yaw_degrees / 15.0).round() * 15.0).to_radians(),      // 15° steps
+            hue_shift: (hue_shift / 5.0).round() * 5.0,                    // 5° steps
+        }
+    }
+}
+
+impl InstanceVariation {
+    /// Transform of the scene child for `template` with this variation applied
+    pub fn scene_transform(&self, template: &ObjectTemplate) -> Transform {
+        Transform::from_translation(Vec3::new(0.0, template.y_offset, 0.0))
+            .with_scale(template.scale * self.scale_factor)
+            .with_rotation(Quat::from_rotation_y(template.rotation_y + self.yaw))
+    }
+
+    /// Collider of the template's declared shape, scaled and turned with the scene: the shape
+    /// fits the scene without variation, which scales and turns around the scene origin
+    pub fn shape_collider(&self, template: &ObjectTemplate) -> Collider {
+        let shape = &template.object_definition.shape;
+        if self.scale_factor == 1.0 && self.yaw == 0.0 {
+            return create_collider_from_shape(shape);
+        }
+        let origin = Vec3::new(0.0, template.y_offset, 0.0);
+        let rotation = Quat::from_rotation_y(self.yaw);
+        let part = |transform: &Transform, part: &ObjectShape| (
+            origin + rotation * ((transform.translation - origin) * self.scale_factor),
+            rotation * transform.rotation,
+            create_collider_from_shape(&part.scaled(self.scale_factor)),
+        );
+        match shape {
+            ObjectShape::Compound(parts) => Collider::compound(parts.iter().map(|(transform, shape)| part(transform, shape)).collect()),
+            shape => Collider::compound(vec![part(&Transform::IDENTITY, shape)]),
+        }
+    }
+}
+
+/// Color changes of an object's glTF scene. The scene meshes get tinted copies of their
+/// materials, shared by the instances with the same tint, once the scene is instantiated and
+/// again when the tint changes (`apply_scene_tints`).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+pub struct SceneTint {
+    pub hue_shift: f32,              // Degrees, from the instance variation
+    pub mix: Option<(Color, f32)>,   // Color mixed into the base color, and its weight (seasons)
+    pub glow: Option<(Color, f32)>,  // Replaces the base color, emitting it with this strength (item rarity)
+    applied: bool,                   // The scene meshes have the current tint
+}
+
+impl SceneTint {
+    pub fn hue(hue_shift: f32) -> Self {
+        Self { hue_shift, ..default() }
+    }
+
+    pub fn with_mix(self, mix: Option<(Color, f32)>) -> Self {
+        Self { mix, ..self }
+    }
+
+    pub fn with_glow(self, color: Color, strength: f32) -> Self {
+        Self { glow: Some((color, strength)), ..self }
+    }
+
+    fn is_identity(&self) -> bool {
+        self.hue_shift == 0.0 && self.mix.is_none() && self.glow.is_none()
+    }
+
+    /// Cache key of the materials tinted this way
+    fn key(&self) -> String {
+        format!("{}:{:?}:{:?}", self.hue_shift, self.mix, self.glow)
+    }
+
+    fn tint(&self, original: &StandardMaterial) -> StandardMaterial {
+        if let Some((color, strength)) = self.glow {
+            return StandardMaterial { base_color: color, emissive: color.to_linear() * strength, ..original.clone() };
+        }
+        let color = original.base_color.rotate_hue(self.hue_shift);
+        let base_color = self.mix.map_or(color, |(mix, weight)| color.mix(&mix, weight));
+        StandardMaterial { base_color, ..original.clone() }
+    }
+}
+
+/// Scene mesh whose material was replaced by a tinted copy of `original`
+#[derive(Component, Debug)]
+pub struct TintedMaterial {
+    original: Handle<StandardMaterial>,
+}
+
+/// Tinted copies of scene materials, keyed by original material and tint
+#[derive(Resource, Default)]
+pub struct TintedMaterials {
+    materials: HashMap<(AssetId<StandardMaterial>, String), Handle<StandardMaterial>>,
+}
+
+/// Give the meshes of tinted objects their tinted materials. Objects stay pending until their
+/// scene and its materials are loaded, and while the camera fades them (`occlusion`, which
+/// restores the material it found).
+pub fn apply_scene_tints(
+    mut commands: Commands,
+    mut cache: ResMut<TintedMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut objects: Query<(Entity, &mut SceneTint, Has<crate::occlusion::CameraOccluder>)>,
+    children: Query<&Children>,
+    mut meshes: Query<(&mut MeshMaterial3d<StandardMaterial>, Option<&TintedMaterial>), With<Mesh3d>>,
+) {
+    for (entity, mut tint, faded) in objects.iter_mut() {
+        if tint.applied && !tint.is_changed() {
+            continue;
+        }
+        tint.bypass_change_detection().applied = false;
+        if faded {
+            continue;
+        }
+        let mut ready = true;
+        let mut found_mesh = false;
+        for mesh in children.iter_descendants(entity) {
+            let Ok((mut material, tinted)) = meshes.get_mut(mesh) else { continue; };
+            found_mesh = true;
+            let original = tinted.map_or_else(|| material.0.clone(), |tinted| tinted.original.clone());
+            if tint.is_identity() {
+                material.0 = original;
+                commands.entity(mesh).remove::<TintedMaterial>();
+                continue;
+            }
+            let key = (original.id(), tint.key());
+            let handle = match cache.materials.get(&key) {
+                Some(handle) => handle.clone(),
+                None => {
+                    let Some(copy) = materials.get(&original).map(|source| tint.tint(source)) else {
+                        ready = false; // Material still loading
+                        continue;
+                    };
+                    let handle = materials.add(copy);
+                    cache.materials.insert(key, handle.clone());
+                    handle
+                }
+            };
+            material.0 = handle;
+            if tinted.is_none() {
+                commands.entity(mesh).insert(TintedMaterial { original });
+            }
+        }
+        tint.bypass_change_detection().applied = ready && found_mesh;
+    }
+}
+
+/// Registry of object templates keyed by name (the file stem of their definition,
+/// e.g. "tree", "rock", "robot")
+#[derive(Resource, Default)]
 pub struct ObjectTemplates {
-    pub tree: ObjectTemplate,
-    pub rock: ObjectTemplate,
-    pub robot: ObjectTemplate,
+    templates: HashMap<String, ObjectTemplate>,
+}
+
+/// Error returned when code asks for a template that is not registered
+#[derive(Debug, Clone)]
+pub struct MissingTemplate(pub String);
+
+impl std::fmt::Display for MissingTemplate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no object template named '{}' (see {})", self.0, OBJECT_TEMPLATE_DIR)
+    }
+}
+
+impl std::error::Error for MissingTemplate {}
+
+impl ObjectTemplates {
+    /// Look up a template by name
+    pub fn get(&self, name: &str) -> Result<&ObjectTemplate, MissingTemplate> {
+        self.templates.get(name).ok_or_else(|| MissingTemplate(name.to_string()))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut ObjectTemplate> {
+        self.templates.get_mut(name)
+    }
+
+    /// Register (or replace) a template, returning the previous one if any
+    pub fn insert(&mut self, name: impl Into<String>, template: ObjectTemplate) -> Option<ObjectTemplate> {
+        self.templates.insert(name.into(), template)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.templates.contains_key(name)
+    }
+
+    /// Iterate over all registered (name, template) pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ObjectTemplate)> {
+        self.templates.iter()
+    }
 }
 
 
@@ -95,14 +351,16 @@ pub struct ObjectTemplates {
 
 
 /// Collision behavior for physics bodies
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Deserialize)]
 pub enum CollisionBehavior {
     None,                    // No collision (beacons)
     Static,                  // Fixed collision (landscape elements)
     Dynamic,                 // Can be moved by physics
 }
 
-#[derive(Component, Debug, Clone)]
+/// When an object exists, checked against the `existence::ExistenceRegistry` that scripts
+/// update; e.g. `existence: Some(OnCondition("shrine_used"))` in a template file
+#[derive(Component, Debug, Clone, Deserialize)]
 pub enum ExistenceConditions {
     Always,                 // Always exists
     OnCondition(String),    // Exists based on a specific condition (e.g., player state)
@@ -110,33 +368,108 @@ pub enum ExistenceConditions {
     OnFrame,                // Exists for the current frame only
 }
 
+/// Lifecycle and interaction events for world objects.
+/// Emitted by the spawning, streaming and pickup systems so gameplay logic can live
+/// in small `EventReader<ObjectEvent>` systems instead of inside spawn functions.
+#[derive(Event, Debug, Clone)]
+pub enum ObjectEvent {
+    PickedUp { entity: Entity, by: Entity, item_id: String, count: u32 },
+    Used { entity: Entity, by: Entity },
+    Destroyed { entity: Entity },
+    EnteredRenderedArea { entity: Entity, tile: (usize, usize, usize) },
+    LeftRenderedArea { entity: Entity, tile: (usize, usize, usize) },
+}
+
+/// Log every object event (debugging aid and reference reader)
+pub fn log_object_events(mut object_events: EventReader<ObjectEvent>) {
+    for event in object_events.read() {
+        match event {
+            ObjectEvent::Used { entity, by } => debug!("{:?} used by {:?}", entity, by),
+            ObjectEvent::Destroyed { entity } => debug!("{:?} destroyed", entity),
+            ObjectEvent::EnteredRenderedArea { entity, tile } => debug!("{:?} entered the rendered area at {:?}", entity, tile),
+            ObjectEvent::LeftRenderedArea { entity, tile } => debug!("{:?} left the rendered area at {:?}", entity, tile),
+            ObjectEvent::PickedUp { entity, by, item_id, count } => debug!("{:?} picked up by {:?}: {} x {}", entity, by, count, item_id),
+        }
+    }
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct RaycastTileLocator {
     pub last_tile: Option<(usize, usize, usize)>,
 }
 
 /// Component pour marquer les entités qui doivent avoir un overlay UI
-#[derive(Component)]
+#[derive(Component, Clone, Debug)]
 pub struct EntityInfoOverlay {
     pub show_subpixel: bool,
     pub show_coordinates: bool,
     pub offset: Vec2, // Offset from entity position in pixels
+    pub name: Option<String>,       // Nameplate shown above the readouts
+    pub show_health: bool,          // Health bar filled from the entity's `Destructible`
+    pub hide_at_full_health: bool,  // Only show the overlay once the entity is damaged
+    pub icon: Option<String>,       // Asset path of an icon shown left of the text
 }
 
 impl Default for EntityInfoOverlay {
     fn default() -> Self {
+        Self::debug()
+    }
+}
+
+impl EntityInfoOverlay {
+    /// Tile readout for debug entities (agents, props...)
+    pub fn debug() -> Self {
         Self {
             show_subpixel: true,
             show_coordinates: false,
             offset: Vec2::new(0.0, -50.0), // Au-dessus de l'entité
+            name: None,
+            show_health: false,
+            hide_at_full_health: false,
+            icon: None,
         }
     }
+
+    /// Name only, for agents and interactables
+    pub fn nameplate(name: impl Into<String>) -> Self {
+        Self {
+            show_subpixel: false,
+            name: Some(name.into()),
+            ..Self::debug()
+        }
+    }
+
+    /// Add a health bar, hidden while the entity is undamaged
+    pub fn with_health_bar(mut self) -> Self {
+        self.show_health = true;
+        self.hide_at_full_health = true;
+        self
+    }
+
+    /// Show an icon (asset path) left of the text
+    pub fn with_icon(mut self, path: impl Into<String>) -> Self {
+        self.icon = Some(path.into());
+        self
+    }
 }
 
-/// Component pour identifier les UI text overlays
+/// Component pour identifier les UI text overlays (un slot du pool, réassigné chaque frame)
 #[derive(Component)]
 pub struct EntityUIText {
-    pub target_entity: Entity,
+    pub target_entity: Option<Entity>,
+    pub last_content: String, // Évite de relancer la mise en page du texte quand rien ne change
+    pub last_icon: Option<String>,
+    pub text: Entity,         // Text child
+    pub icon: Entity,         // ImageNode child
+    pub health_bar: Entity,   // Health bar background
+    pub health_fill: Entity,  // Health bar fill, width = hp fraction
+}
+
+/// Pool of overlay UI nodes shared by all entities with `EntityInfoOverlay`.
+/// Only the closest visible entities get a node, up to `config::overlay::MAX_OVERLAYS`.
+#[derive(Resource, Default)]
+pub struct OverlayPool {
+    pub slots: Vec<Entity>,
 }
 
 
@@ -150,37 +483,84 @@ pub struct ObjectDefinition {
     pub existence_conditions: Option<ExistenceConditions>, // Optional conditions for existence
     pub object_type: String,
     pub scale: Vec3,
-    pub y_offset: f32,       // Vertical offset from ground
-    pub mesh: Option<Handle<Mesh>>,                // NEW: Optional mesh handle
-    pub material: Option<Handle<StandardMaterial>>, // NEW: Optional material handle
 }
 
+impl ObjectDefinition {
+    /// Start building a definition for `object_type`; unset fields default to a
+    /// white unit cube without collision that always exists
+    pub fn builder(object_type: impl Into<String>) -> ObjectDefinitionBuilder {
+        ObjectDefinitionBuilder {
+            definition: ObjectDefinition {
+                shape: ObjectShape::Cube { size: Vec3::ONE },
+                color: Color::WHITE,
+                collision: CollisionBehavior::None,
+                existence_conditions: Some(ExistenceConditions::Always),
+                object_type: object_type.into(),
+                scale: Vec3::ONE,
+            },
+        }
+    }
 
+    /// The template's default definition
+    pub fn from_template(template: &ObjectTemplate) -> Self {
+        template.object_definition.clone()
+    }
+}
 
-/// Marker component for mouse tracker objects
-#[derive(Component, Debug, Clone)]
-pub struct MouseTrackerObject {
-   
+/// Builder returned by `ObjectDefinition::builder`
+#[derive(Debug, Clone)]
+pub struct ObjectDefinitionBuilder {
+    definition: ObjectDefinition,
 }
 
-/// Create a mesh handle from an ObjectShape specification
-pub fn create_mesh_from_shape(shape: &ObjectShape, meshes: &mut ResMut<Assets<Mesh>>) -> Handle<Mesh> {
-    match shape {
-        ObjectShape::Cube { size } => {
-            meshes.add(Cuboid::new(size.x, size.y, size.z))
-        }
-        ObjectShape::Sphere { radius } => {
-            meshes.add(Sphere::new(*radius))
-        }
-        ObjectShape::Capsule { radius, height } => {
-            meshes.add(Capsule3d::new(*radius, *height))
-        }
-        ObjectShape::Cylinder { radius, height } => {
-            meshes.add(Cylinder::new(*radius, *height))
-        }
+impl ObjectDefinitionBuilder {
+    /// Start from a template's default definition
+    pub fn from_template(template: &ObjectTemplate) -> Self {
+        Self { definition: ObjectDefinition::from_template(template) }
+    }
+
+    pub fn shape(mut self, shape: ObjectShape) -> Self {
+        self.definition.shape = shape;
+        self
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.definition.color = color;
+        self
+    }
+
+    pub fn collision(mut self, collision: CollisionBehavior) -> Self {
+        self.definition.collision = collision;
+        self
+    }
+
+    /// Shortcut for `collision(CollisionBehavior::Static)`
+    pub fn fixed(self) -> Self {
+        self.collision(CollisionBehavior::Static)
+    }
+
+    /// Shortcut for `collision(CollisionBehavior::Dynamic)`
+    pub fn dynamic(self) -> Self {
+        self.collision(CollisionBehavior::Dynamic)
+    }
+
+    pub fn existence(mut self, conditions: ExistenceConditions) -> Self {
+        self.definition.existence_conditions = Some(conditions);
+        self
+    }
+
+    pub fn scale(mut self, scale: Vec3) -> Self {
+        self.definition.scale = scale;
+        self
+    }
+
+    pub fn build(self) -> ObjectDefinition {
+        self.definition
     }
 }
 
+
+
 /// Create a collider from an ObjectShape specification
 pub fn create_collider_from_shape(shape: &ObjectShape) -> Collider {
     match shape {
@@ -196,6 +576,11 @@ pub fn create_collider_from_shape(shape: &ObjectShape) -> Collider {
         ObjectShape::Cylinder { radius, height } => {
             Collider::cylinder(*height / 2.0, *radius)
         }
+        ObjectShape::Compound(parts) => {
+            Collider::compound(parts.iter()
+                .map(|(transform, part)| (transform.translation, transform.rotation, create_collider_from_shape(part)))
+                .collect())
+        }
     }
 }
 
@@ -204,15 +589,40 @@ pub fn create_collider_from_shape(shape: &ObjectShape) -> Collider {
 
 pub fn setup_entity_overlays(
     mut commands: Commands,
-    new_entities: Query<Entity, (With<EntityInfoOverlay>, Without<EntityUIText>)>,
+    mut pool: ResMut<OverlayPool>,
+    overlay_entities: Query<(), With<EntityInfoOverlay>>,
 ) {
-    for entity in new_entities.iter() {
-        println!("Creating overlay for entity {:?}", entity);
-        
-        // CRITICAL: Mark this entity as having UI created
-        commands.entity(entity).insert(EntityUIText { target_entity: entity });
-        
-        commands.spawn((
+    // Grow the pool lazily, never beyond the cap
+    let wanted = overlay_entities.iter().count().min(crate::config::overlay::MAX_OVERLAYS);
+    while pool.slots.len() < wanted {
+        debug!("Creating overlay slot {}", pool.slots.len());
+        let icon = commands.spawn((
+            ImageNode::default(),
+            Node { width: Val::Px(16.0), height: Val::Px(16.0), display: Display::None, ..default() },
+        )).id();
+        let text = commands.spawn((
+            Text::new(""),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        )).id();
+        let health_fill = commands.spawn((
+            Node { width: Val::Percent(100.0), height: Val::Percent(100.0), ..default() },
+            BackgroundColor(Color::srgb(0.1, 0.8, 0.1)),
+        )).id();
+        let health_bar = commands.spawn((
+            Node { width: Val::Px(60.0), height: Val::Px(6.0), display: Display::None, ..default() },
+            BackgroundColor(Color::srgb(0.4, 0.0, 0.0)),
+        )).add_child(health_fill).id();
+        let column = commands.spawn(Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            ..default()
+        }).add_children(&[text, health_bar]).id();
+
+        let slot = commands.spawn((
             Node {
                 position_type: PositionType::Absolute,
                 left: Val::Px(0.0),
@@ -220,22 +630,24 @@ pub fn setup_entity_overlays(
                 width: Val::Auto,
                 height: Val::Auto,
                 padding: UiRect::all(Val::Px(5.0)),
+                column_gap: Val::Px(4.0),
+                align_items: AlignItems::Center,
                 ..default()
             },
             BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
             BorderRadius::all(Val::Px(5.0)),
             Visibility::Hidden, // Caché par défaut
-            EntityUIText { target_entity: entity },
-        )).with_children(|parent| {
-            parent.spawn((
-                Text::new(""),
-                TextFont {
-                    font_size: 12.0,
-                    ..default()
-                },
-                TextColor(Color::WHITE),
-            ));
-        });
+            EntityUIText {
+                target_entity: None,
+                last_content: String::new(),
+                last_icon: None,
+                text,
+                icon,
+                health_bar,
+                health_fill,
+            },
+        )).add_children(&[icon, column]).id();
+        pool.slots.push(slot);
     }
 }
 
@@ -244,82 +656,106 @@ pub fn setup_entity_overlays(
 
 
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn update_entity_ui_overlays(
     // Entités avec overlay
-    entity_query: Query<(Entity, &Transform, &EntitySubpixelPosition, &EntityInfoOverlay)>,
+    entity_query: Query<(Entity, &Transform, Option<&EntitySubpixelPosition>, &EntityInfoOverlay, Option<&Destructible>)>,
     
     // UI overlays
-    mut ui_query: Query<(&mut Node, &mut Visibility, &EntityUIText, &Children)>,
+    asset_server: Res<AssetServer>,
+    pool: Res<OverlayPool>,
+    mut ui_query: Query<(&mut Node, &mut Visibility, &mut EntityUIText), Without<ImageNode>>,
+    mut part_query: Query<&mut Node, Without<EntityUIText>>,
     mut text_query: Query<&mut Text>,
+    mut image_query: Query<&mut ImageNode>,
     
     // Camera et window pour la projection
     camera_query: Query<(&Camera, &GlobalTransform)>,
     window_query: Query<&Window>,
 ) {
     let Ok((camera, camera_transform)) = camera_query.single() else { return; };
-
-    for (mut style, mut visibility, ui_text, children) in ui_query.iter_mut() {
-        // Trouver l'entité cible
-        if let Ok((entity, transform, subpixel_pos, overlay_config)) = entity_query.get(ui_text.target_entity) {
-            
-            // Projeter la position 3D vers 2D
-            let world_pos = transform.translation;
-            
-            if let Ok(screen_pos) = camera.world_to_viewport(camera_transform, world_pos) {
-                // L'entité est visible à l'écran
-                *visibility = Visibility::Visible;
-                
-                // Positionner l'overlay avec l'offset
-                let final_x = screen_pos.x + overlay_config.offset.x;
-                let final_y = screen_pos.y + overlay_config.offset.y;
-                style.left = Val::Px(final_x);
-                style.top = Val::Px(final_y);
-                
-                // Mettre à jour le texte
-                if let Some(child) = children.first() {
-                    if let Ok(mut text) = text_query.get_mut(*child) {
-                        let mut content = String::new();
-                        
-                        if overlay_config.show_subpixel {
-                            content.push_str(&format!("Tile: ({}, {}, {})", 
-                                subpixel_pos.subpixel.0, 
-                                subpixel_pos.subpixel.1, 
-                                subpixel_pos.subpixel.2
-                            ));
-                        }
-                        
-                        if overlay_config.show_coordinates {
-                            if !content.is_empty() { content.push('\n'); }
-                            content.push_str(&format!("Pos: ({:.1}, {:.1}, {:.1})", 
-                                world_pos.x, world_pos.y, world_pos.z
-                            ));
-                        }
-                        
-                        **text = content;
-                    }
-                }
-            } else {
-                // L'entité n'est pas visible
-                *visibility = Visibility::Hidden;
+    let viewport_size = window_query.single().map(|window| window.size()).unwrap_or(Vec2::splat(f32::MAX));
+    let camera_position = camera_transform.translation();
+
+    // Culling: keep entities within range whose projection lands inside the viewport
+    let mut visible: Vec<(f32, Vec2, Entity)> = entity_query.iter()
+        .filter_map(|(entity, transform, _, overlay_config, destructible)| {
+            if overlay_config.hide_at_full_health && destructible.is_none_or(|d| d.hp >= d.max_hp) {
+                return None;
             }
-        } else {
-            // L'entité cible n'existe plus, cacher l'overlay
+            let distance = transform.translation.distance(camera_position);
+            if distance > crate::config::overlay::MAX_DISTANCE {
+                return None;
+            }
+            let screen_pos = camera.world_to_viewport(camera_transform, transform.translation).ok()?;
+            let on_screen = screen_pos.x >= 0.0 && screen_pos.y >= 0.0 && screen_pos.x <= viewport_size.x && screen_pos.y <= viewport_size.y;
+            on_screen.then_some((distance, screen_pos, entity))
+        })
+        .collect();
+    // Closest entities first, the rest share no slot
+    visible.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    for (slot_index, slot) in pool.slots.iter().enumerate() {
+        let Ok((mut style, mut visibility, mut ui_text)) = ui_query.get_mut(*slot) else { continue; };
+        let Some((_, screen_pos, entity)) = visible.get(slot_index) else {
+            // Slot inutilisé
             *visibility = Visibility::Hidden;
+            ui_text.target_entity = None;
+            continue;
+        };
+        let Ok((_, transform, subpixel_pos, overlay_config, destructible)) = entity_query.get(*entity) else { continue; };
+        ui_text.target_entity = Some(*entity);
+        *visibility = Visibility::Visible;
+
+        // Positionner l'overlay avec l'offset
+        style.left = Val::Px(screen_pos.x + overlay_config.offset.x);
+        style.top = Val::Px(screen_pos.y + overlay_config.offset.y);
+
+        // Mettre à jour le texte
+        let world_pos = transform.translation;
+        let mut content = String::new();
+        if let Some(name) = &overlay_config.name {
+            content.push_str(name);
+        }
+        if let (true, Some(subpixel_pos)) = (overlay_config.show_subpixel, subpixel_pos) {
+            if !content.is_empty() { content.push('\n'); }
+            content.push_str(&format!("Tile: ({}, {}, {})", 
+                subpixel_pos.subpixel.0, 
+                subpixel_pos.subpixel.1, 
+                subpixel_pos.subpixel.2
+            ));
+        }
+        if overlay_config.show_coordinates {
+            if !content.is_empty() { content.push('\n'); }
+            content.push_str(&format!("Pos: ({:.1}, {:.1}, {:.1})", 
+                world_pos.x, world_pos.y, world_pos.z
+            ));
+        }
+        if content != ui_text.last_content {
+            if let Ok(mut text) = text_query.get_mut(ui_text.text) {
+                **text = content.clone();
+            }
+            ui_text.last_content = content;
         }
-    }
-}
 
+        // Icône
+        if overlay_config.icon != ui_text.last_icon {
+            if let Ok(mut icon_node) = part_query.get_mut(ui_text.icon) {
+                icon_node.display = if overlay_config.icon.is_some() { Display::Flex } else { Display::None };
+            }
+            if let (Some(path), Ok(mut image)) = (&overlay_config.icon, image_query.get_mut(ui_text.icon)) {
+                image.image = asset_server.load(path.clone());
+            }
+            ui_text.last_icon = overlay_config.icon.clone();
+        }
 
-/// Système pour nettoyer les overlays des entités supprimées
-pub fn cleanup_orphaned_overlays(
-    mut commands: Commands,
-    ui_query: Query<(Entity, &EntityUIText)>,
-    entity_query: Query<Entity, With<EntityInfoOverlay>>,
-) {
-    for (ui_entity, ui_text) in ui_query.iter() {
-        // Si l'entité cible n'existe plus, supprimer l'overlay
-        if entity_query.get(ui_text.target_entity).is_err() {
-            commands.entity(ui_entity).despawn();
+        // Barre de vie
+        let health = destructible.filter(|_| overlay_config.show_health).map(|d| (d.hp / d.max_hp).clamp(0.0, 1.0));
+        if let Ok(mut bar) = part_query.get_mut(ui_text.health_bar) {
+            bar.display = if health.is_some() { Display::Flex } else { Display::None };
+        }
+        if let (Some(fraction), Ok(mut fill)) = (health, part_query.get_mut(ui_text.health_fill)) {
+            fill.width = Val::Percent(fraction * 100.0);
         }
     }
 }
@@ -336,6 +772,36 @@ pub fn cleanup_orphaned_overlays(
 
 
 
+/// Subpixel of the terrain triangle hit by a ray, if the hit is on a triangle face
+pub fn subpixel_from_ray_hit(ray_intersection: &RayIntersection, terrain_center: &TerrainCenter) -> Option<(usize, usize, usize)> {
+    let feature_info = format!("{:?}", ray_intersection.feature);
+    //eprintln!("RAYCASTING PLAYER Feature: {}", feature_info);
+    if !feature_info.contains("Face") {
+        return None; // Skip non-triangle hits
+    }
+    // Extract the numeric ID from the debug string
+    let triangle_index = feature_info.chars()
+        .filter(|c| c.is_ascii_digit())
+        .collect::<String>()
+        .parse::<u32>()
+        .unwrap_or(0);
+    // Rapier sometimes reports triangle indices that are offset by a multiple of the
+    // mapping size (possibly due to compound-shape indexing). This heuristic strips
+    // the offset so we land inside the valid mapping range. It's a workaround: the
+    // root cause is that Rapier's face index for a trimesh can include a shape-level
+    // prefix when multiple colliders share an ID space.
+    let mapping_size = terrain_center.triangle_mapping.triangle_to_subpixel.len() as u32;
+    if mapping_size == 0 {
+        return None;
+    }
+    let adjusted_triangle_index = if triangle_index >= mapping_size {
+        triangle_index % mapping_size
+    } else {
+        triangle_index
+    };
+    Some(terrain_center.triangle_mapping.triangle_to_subpixel[adjusted_triangle_index as usize])
+}
+
 pub fn raycast_tile_locator_system(
     mut query: Query<(Entity, &Transform, &mut RaycastTileLocator, &mut EntitySubpixelPosition, &mut ObjectDefinition)>,
     rapier_context: ReadRapierContext,
@@ -355,46 +821,8 @@ pub fn raycast_tile_locator_system(
                 //eprint!("Raycast hit tile entity: {:?}", tile_entity);
             if terrain_entities.contains(tile_entity) {
                 //eprintln!("Raycast hit terrain tile entity: {:?}", tile_entity);
-                let feature_info = format!("{:?}", ray_intersection.feature);                  
-                //eprintln!("RAYCASTING PLAYER Feature: {}", feature_info);
-                let triangle_index = match &ray_intersection.feature {      
-
-                    _f if feature_info.contains("Face") => {
-                        // Extract the numeric ID from the debug string
-                        feature_info.chars()
-                            .filter(|c| c.is_ascii_digit())
-                            .collect::<String>()
-                            .parse::<u32>()
-                            .unwrap_or(0)
-                    },
-                    _ => {
-                        continue; // Skip non-triangle hits
-                    }
-                };
-                // Rapier sometimes reports triangle indices that are offset by a multiple of the
-                // mapping size (possibly due to compound-shape indexing). This heuristic strips
-                // the offset so we land inside the valid mapping range. It's a workaround: the
-                // root cause is that Rapier's face index for a trimesh can include a shape-level
-                // prefix when multiple colliders share an ID space.
-                if terrain_center.triangle_mapping.triangle_to_subpixel.len() > 0
-                {
-                let adjusted_triangle_index = if triangle_index >= terrain_center.triangle_mapping.triangle_to_subpixel.len() as u32 {
-                    let mapping_size = terrain_center.triangle_mapping.triangle_to_subpixel.len() as u32;
-                    let potential_offset = (triangle_index / mapping_size) * mapping_size;
-                    let corrected_index = triangle_index - potential_offset;
-
-                    if corrected_index < mapping_size {
-                        corrected_index
-                    } else {
-                        triangle_index
-                    }
-                } else {
-                    triangle_index
-                };
-                let _subpixel_position = terrain_center.triangle_mapping.triangle_to_subpixel[adjusted_triangle_index as usize];
-                subpixel_position.subpixel.0 = _subpixel_position.0;
-                subpixel_position.subpixel.1 = _subpixel_position.1;
-                subpixel_position.subpixel.2 = _subpixel_position.2;
+                if let Some(subpixel) = subpixel_from_ray_hit(&ray_intersection, &terrain_center) {
+                    subpixel_position.subpixel = subpixel;
                 }
                 //eprintln!("Raycast hit tile: {} {} {}", _subpixel_position.0, _subpixel_position.1, _subpixel_position.2);
 
@@ -414,61 +842,9 @@ pub fn raycast_tile_locator_system(
 
 
 
-pub fn spawn_mouse_tracker(
-    commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    planisphere: &crate::planisphere::Planisphere,
-    terrain_center: &crate::terrain::TerrainCenter,
-) -> Entity {
-    let world_pos = Vec3::new(0.0, 10.0, 0.0); // Start above ground
-        let object_definition = ObjectDefinition {
-        shape: ObjectShape::Sphere { radius: 0.3},
-        color: Color::srgb(0.0, 0.3, 0.7), // Red color for player
-        collision: CollisionBehavior::None,
-        existence_conditions: Some(ExistenceConditions::Always),
-        object_type: "MouseTracker".to_string(),
-        scale: Vec3::ONE,
-        y_offset: 5.0, // Start above ground
-        mesh: None,
-        material: None,
-    };
-
-    let position = IntoWorldPosition::into_world_position(&world_pos, planisphere, terrain_center);
-
-    let entity = spawn_unified_object(
-        commands,
-        meshes,
-        materials,
-        planisphere, // No planisphere needed for player
-        terrain_center, // Dummy terrain center
-        position,
-        5.0, // Use player's Y position + offset
-        CollisionBehavior::Static, // No collision for mouse tracker
-        object_definition,
-        (EntitySubpixelPosition::default(), 
-            EntityInfoOverlay::default(), 
-            RaycastTileLocator{last_tile: None}, 
-            MouseTrackerObject{}
-        ), // Pass the bundle and physics components
-    );
-
-    // Attach player-specific components
-    
-    // Add children (sensor, marker) as before if needed
-
-    entity
-}
-
-
-
-
-
-
 // This is a proper Bevy system function that will be scheduled correctly
 pub fn setup_player(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     planisphere: Res<Planisphere>,
     terrain_center: ResMut<TerrainCenter>,
@@ -482,14 +858,6 @@ pub fn setup_player(
         &terrain_center, 
         &object_templates
     );
-
-    spawn_mouse_tracker(
-        &mut commands,
-        &mut meshes,
-        &mut materials,
-        &planisphere,
-        &terrain_center,
-    );
     
 
 }
@@ -541,7 +909,13 @@ pub fn spawn_player(
 
 
 
-    let template = object_templates.robot.clone(); // Use the robot template for player
+    let template = match object_templates.get("robot") { // Use the robot template for player
+        Ok(template) => template.clone(),
+        Err(e) => {
+            warn!("Cannot spawn player: {}", e);
+            return;
+        }
+    };
 
     let entity =spawn_template_scene(
                     commands,
@@ -551,12 +925,12 @@ pub fn spawn_player(
                     &template,
                     Vec3::new(0.0, 150.0, 0.),
                     10.0, // Use player's Y position + offset
-                    CollisionBehavior::Dynamic, // Set collision behavior to dynamic for dropped items
+                    ObjectDefinitionBuilder::from_template(&template).dynamic().build(),
                     (
                         player_bundle,
                         physics_bundle, 
                         crate::game_object::RaycastTileLocator{last_tile: None}, 
-                        crate::game_object::EntityInfoOverlay::default(),
+                        crate::game_object::EntityInfoOverlay::nameplate("Player"),
                     )
                 );
 
@@ -572,98 +946,238 @@ pub fn spawn_player(
 
 
 
-pub fn setup_object_templates(mut commands: Commands, asset_server: Res<AssetServer>)  {
-    let object_templates = ObjectTemplates {
-        tree: ObjectTemplate {
-            name: "Tree".to_string(),
-            scene: asset_server.load("meshes/tree1.glb#Scene0"),
-            y_offset: 0.0,  // No manual offset needed!
-            scale: Vec3::ONE,
-            rotation_y: 0.0,  // No rotation by default
-            object_definition: ObjectDefinition {
-                shape: ObjectShape::Cube { size: Vec3::ONE }, // Default shape
-                color: Color::srgb(0.0, 1.0, 0.0), // Green color for trees
-                collision: CollisionBehavior::Static,
-                existence_conditions: Some(ExistenceConditions::Always),
-                object_type: "Tree".to_string(),
-                scale: Vec3::ONE,
-                y_offset: 0.0,
-                mesh: None, // No specific mesh for tracker
-                material: None, // No specific material for tracker
-            },
-        },
-        rock: ObjectTemplate {
-            name: "Stone".to_string(),
-            scene: asset_server.load("meshes/stone1.glb#Scene0"),
-            y_offset: 0.0,  // No manual offset needed!
-            scale: Vec3::ONE,
-            rotation_y: 0.0,  // No rotation by default
-            object_definition: ObjectDefinition {
-                shape: ObjectShape::Cube { size: Vec3::ONE }, // Default shape
-                color: Color::srgb(0.0, 1.0, 0.0), // Green color for trees
-                collision: CollisionBehavior::Static,
-                existence_conditions: Some(ExistenceConditions::Always),
-                object_type: "Stone".to_string(),
-                scale: Vec3::ONE,
-                y_offset: 0.0,
-                mesh: None, // No specific mesh for tracker
-                material: None, // No specific material for tracker
-            },
-        },
-        robot: ObjectTemplate {
-            name: "Player".to_string(),
-            scene: asset_server.load("meshes/robot1.glb#Scene0"),
-            y_offset: 0.0,  // No manual offset needed!
-            scale: 0.04 * Vec3::ONE,
-            rotation_y: std::f32::consts::PI,  // 180 degrees in radians
-            object_definition: ObjectDefinition {
-                shape: ObjectShape::Cube { size: Vec3::ONE }, // Default shape
-                color: Color::srgb(0.0, 1.0, 0.0), // Green color for trees
-                collision: CollisionBehavior::Dynamic,
-                existence_conditions: Some(ExistenceConditions::Always),
-                object_type: "Player".to_string(),
-                scale: 0.0001*Vec3::ONE,
-                y_offset: 0.0,
-                mesh: None, // No specific mesh for tracker
-                material: None, // No specific material for tracker
-            },
-        },
-    };
-    
-    commands.insert_resource(object_templates);
+/// Directory scanned at startup for object template definitions (`*.ron`)
+pub const OBJECT_TEMPLATE_DIR: &str = "assets/objects";
+
+/// On-disk description of an object template, one per `assets/objects/<id>.ron` file.
+/// The file stem is the template id (e.g. `tree.ron` -> "tree").
+#[derive(Deserialize, Debug, Clone)]
+pub struct ObjectTemplateFile {
+    pub name: String,                 // Object type name given to spawned entities
+    pub scene: String,                // Asset path of the glTF scene, e.g. "meshes/tree1.glb#Scene0"
+    #[serde(default = "default_template_scale")]
+    pub scale: [f32; 3],
+    #[serde(default)]
+    pub rotation_y_degrees: f32,      // Rotation around the Y axis, in degrees
+    #[serde(default)]
+    pub y_offset: f32,
+    #[serde(default = "default_template_collision")]
+    pub collision: CollisionBehavior,
+    #[serde(default = "default_template_color")]
+    pub color: [f32; 3],
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub collider: SceneColliderMode,  // Primitive, Shape, ConvexHull (default), TriMesh or Decomposition
+    #[serde(default)]
+    pub shape: Option<ObjectShape>,   // Collision shape used with `collider: Shape`
+    #[serde(default)]
+    pub variation: SpawnVariation,    // e.g. (scale_jitter: 0.2, random_yaw: true, hue_jitter_degrees: 15.0)
+    #[serde(default)]
+    pub destructible: Option<DestructibleSpec>, // e.g. Some((hp: 3.0, loot_table: Some("tree")))
+    #[serde(default)]
+    pub harvest: Option<HarvestSpec>, // e.g. Some((pool: 4, tool: Some("axe")))
+    #[serde(default)]
+    pub effects: TemplateEffects,     // e.g. (appear: Some(ScalePop(duration: 0.3)))
+    #[serde(default)]
+    pub animations: Option<AnimatedTemplateFile>, // e.g. Some((clips: {"sway": 0}, autoplay: Some("sway")))
+    #[serde(default)]
+    pub sockets: HashMap<String, Transform>, // e.g. {"hand": (translation: (0.3, 1.2, 0.0), rotation: (0.0, 0.0, 0.0, 1.0), scale: (1.0, 1.0, 1.0))}
+    #[serde(default)]
+    pub script: Option<String>,       // e.g. Some("scripts/shrine.rhai"), defining `on_use` and `on_tick` handlers
+    #[serde(default)]
+    pub icon: Option<String>,         // e.g. Some("icons/shrine.png"), shown on the nameplate of destructible and scripted instances
+    #[serde(default)]
+    pub existence: Option<ExistenceConditions>, // e.g. Some(OnEvent("storm")), always exists when omitted
 }
 
+fn default_template_scale() -> [f32; 3] { [1.0, 1.0, 1.0] }
+fn default_template_collision() -> CollisionBehavior { CollisionBehavior::Static }
+fn default_template_color() -> [f32; 3] { [1.0, 1.0, 1.0] }
+
+impl ObjectTemplateFile {
+    /// Build the runtime template, starting the scene load through the asset server
+    pub fn into_template(self, asset_server: &AssetServer) -> ObjectTemplate {
+        let scale = Vec3::from_array(self.scale);
+        ObjectTemplate {
+            name: self.name.clone(),
+            animations: self.animations.map(|animations| animations.into_animated_template(&self.scene, asset_server)),
+            scene: asset_server.load(self.scene),
+            y_offset: self.y_offset,
+            scale,
+            rotation_y: self.rotation_y_degrees.to_radians(),
+            tags: self.tags,
+            collider: self.collider,
+            variation: self.variation,
+            destructible: self.destructible,
+            harvest: self.harvest,
+            effects: self.effects,
+            sockets: self.sockets,
+            script: self.script,
+            icon: self.icon,
+            object_definition: ObjectDefinition::builder(self.name)
+                .shape(self.shape.unwrap_or(ObjectShape::Cube { size: Vec3::ONE })) // Default shape
+                .color(Color::srgb(self.color[0], self.color[1], self.color[2]))
+                .collision(self.collision)
+                .existence(self.existence.unwrap_or(ExistenceConditions::Always))
+                .scale(scale)
+                .build(),
+        }
+    }
+}
 
+/// Read every `*.ron` template definition in `dir`, keyed by file stem.
+/// Files that fail to parse are reported and skipped.
+pub fn load_object_template_files(dir: &str) -> HashMap<String, ObjectTemplateFile> {
+    let mut files = HashMap::new();
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Could not read object template directory {}: {}", dir, e);
+            return files;
+        }
+    };
 
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("ron") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else { continue; };
+        match load_object_template_file(&path) {
+            Ok(file) => { files.insert(id.to_string(), file); }
+            Err(e) => warn!("Skipping object template {}: {}", path.display(), e),
+        }
+    }
+    files
+}
 
+/// Parse a single template definition file
+pub fn load_object_template_file(path: &Path) -> Result<ObjectTemplateFile, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str::<ObjectTemplateFile>(&content).map_err(|e| e.to_string())
+}
 
+/// Modification time of every `*.ron` file in `dir`
+fn template_file_mtimes(dir: &str) -> HashMap<PathBuf, SystemTime> {
+    let Ok(entries) = std::fs::read_dir(dir) else { return HashMap::new(); };
+    entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("ron"))
+        .filter_map(|path| {
+            let modified = std::fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
 
+/// Polls the template directory so edits to `*.ron` files apply without a restart
+#[derive(Resource)]
+pub struct ObjectTemplateWatcher {
+    pub timer: Timer,                            // How often the directory is polled
+    pub modified: HashMap<PathBuf, SystemTime>,  // Last seen modification time per file
+}
 
-pub fn despawn_unified_object_from_name(
-    commands: &mut Commands,
-    object_type: &str,
-    query : Query<(Entity, &mut Transform,  &ObjectDefinition), (Without<Player>, Without<MouseTrackerObject>) >,
-) {
-    for (entity, object_transform, object_definition) in query.iter() {
-        if object_definition.object_type == object_type {
-            commands.entity(entity).despawn();
+impl Default for ObjectTemplateWatcher {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            modified: template_file_mtimes(OBJECT_TEMPLATE_DIR),
         }
     }
 }
- 
-pub fn despawn_unified_objects_from_name(
-    commands: &mut Commands,
-    object_type: &str,
-query : Query<(Entity, &mut Transform,  &ObjectDefinition), (Without<Player>, Without<MouseTrackerObject>) >,
+
+/// Reload template definitions whose file changed on disk and update live instances.
+///
+/// Instances are matched by the template's previous object type name; their scene child
+/// gets the new scene, scale, rotation and y offset in place, so nothing is respawned.
+pub fn hot_reload_object_templates(
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut watcher: ResMut<ObjectTemplateWatcher>,
+    mut templates: ResMut<ObjectTemplates>,
+    mut instances: Query<(&mut ObjectDefinition, &Children, Option<&InstanceVariation>)>,
+    mut scene_parts: Query<(&mut SceneRoot, &mut Transform)>,
 ) {
-    for (entity, object_transform, object_definition) in query.iter() {
-        if object_definition.object_type.contains(object_type) {
-            commands.entity(entity).despawn();
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let current = template_file_mtimes(OBJECT_TEMPLATE_DIR);
+    for (path, modified) in current.iter() {
+        if watcher.modified.get(path) == Some(modified) {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else { continue; };
+        let file = match load_object_template_file(path) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!("Template reload failed for {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let new_template = file.into_template(&asset_server);
+
+        let Some(template) = templates.get_mut(id) else {
+            info!("New object template '{}' registered", id);
+            templates.insert(id, new_template);
+            continue;
+        };
+        let old_name = std::mem::replace(template, new_template.clone()).name;
+
+        let mut updated = 0;
+        for (mut definition, children, variation) in instances.iter_mut() {
+            if definition.object_type != old_name {
+                continue;
+            }
+            let variation = variation.copied().unwrap_or_default();
+            definition.object_type = new_template.name.clone();
+            definition.scale = new_template.scale * variation.scale_factor;
+            for child in children.iter() {
+                if let Ok((mut scene_root, mut transform)) = scene_parts.get_mut(child) {
+                    if scene_root.0.id() != new_template.scene.id() {
+                        scene_root.0 = new_template.scene.clone();
+                    }
+                    *transform = variation.scene_transform(&new_template);
+                }
+            }
+            updated += 1;
         }
+        info!("Reloaded object template '{}' ({} live instances updated)", id, updated);
     }
+    watcher.modified = current;
+}
+
+pub fn setup_object_templates(mut commands: Commands, asset_server: Res<AssetServer>)  {
+    let files = load_object_template_files(OBJECT_TEMPLATE_DIR);
+    info!("Loaded {} object template definitions from {}", files.len(), OBJECT_TEMPLATE_DIR);
+
+    let mut object_templates = ObjectTemplates::default();
+    for (name, file) in files {
+        object_templates.insert(name, file.into_template(&asset_server));
+    }
+
+    // These are used by the spawning code; a missing one only disables what depends on it
+    for name in ["tree", "rock", "robot", "pickup", "beacon", "waypoint", "trail", "marker"] {
+        if !object_templates.contains(name) {
+            warn!("{}", MissingTemplate(name.to_string()));
+        }
+    }
+
+    let mut names: Vec<&str> = object_templates.iter().map(|(name, _)| name.as_str()).collect();
+    names.sort_unstable();
+    debug!("Object templates: {}", names.join(", "));
+
+    commands.insert_resource(object_templates);
+    commands.insert_resource(ObjectTemplateWatcher::default());
 }
 
 
+
+
+
+
+
 pub fn spawn_template_scene<Extra: Bundle, T: IntoWorldPosition>(
     commands: &mut Commands,
     materials: &mut ResMut<Assets<StandardMaterial>>,
@@ -672,126 +1186,221 @@ pub fn spawn_template_scene<Extra: Bundle, T: IntoWorldPosition>(
     template: &ObjectTemplate, // Use your existing template
     position : T,
     y_offset: f32,
-    collision: CollisionBehavior,
+    definition: ObjectDefinition, // Usually built from the template (`ObjectDefinitionBuilder::from_template`)
     extra: Extra, // <-- new parameter for extra components/bundles
 ) -> Entity {
     let world_pos = position.into_world_position(planisphere, terrain_center);
-    
+    let variation = template.variation.sample(position.tile());
+    let material = materials.add(template_material(template));
+    spawn_template_instance(commands, material, template, world_pos + Vec3::new(0.0, y_offset, 0.0), position.tile(), variation, definition, extra)
+}
+
+/// Spawn a template instance with a caller-provided material and no instance variation, for
+/// objects sharing pooled materials (beacons)
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_template_with_material<Extra: Bundle, T: IntoWorldPosition>(
+    commands: &mut Commands,
+    material: Handle<StandardMaterial>,
+    planisphere: &crate::planisphere::Planisphere,
+    terrain_center: &crate::terrain::TerrainCenter,
+    template: &ObjectTemplate,
+    position: T,
+    definition: ObjectDefinition,
+    extra: Extra,
+) -> Entity {
+    let world_pos = position.into_world_position(planisphere, terrain_center);
+    spawn_template_instance(commands, material, template, world_pos + Vec3::new(0.0, template.y_offset, 0.0), position.tile(),
+                            InstanceVariation::default(), definition, extra)
+}
+
+/// Material shared by instances of a template spawned without variation
+pub fn shared_template_material(template: &ObjectTemplate) -> StandardMaterial {
+    template_material(template)
+}
+
+/// Material of a template instance's scene root, shown by the placeholder mesh when the scene
+/// fails to load (the hue shift is applied by `SceneTint`)
+fn template_material(template: &ObjectTemplate) -> StandardMaterial {
+    StandardMaterial {
+        base_color: template.object_definition.color,
+        perceptual_roughness: 0.5,
+        metallic: 0.0,
+        ..default()
+    }
+}
+
+/// Spawn the parent entity and its scene child with an already created material
+#[allow(clippy::too_many_arguments)]
+fn spawn_template_instance<Extra: Bundle>(
+    commands: &mut Commands,
+    material: Handle<StandardMaterial>,
+    template: &ObjectTemplate,
+    translation: Vec3,
+    tile: Option<(usize, usize, usize)>,
+    variation: InstanceVariation,
+    definition: ObjectDefinition,
+    extra: Extra,
+) -> Entity {
+    let wants_scene_collider = template.collider != SceneColliderMode::Primitive && !matches!(definition.collision, CollisionBehavior::None);
+    // Static objects on a tile block it for placement
+    let occupied_tile = tile.filter(|_| matches!(definition.collision, CollisionBehavior::Static));
+
     // Create parent entity
     let parent = commands.spawn((
-        Transform::from_translation(world_pos+Vec3::new(0.0, y_offset, 0.0)),
+        Transform::from_translation(translation),
         Visibility::default(),
-        ObjectDefinition {
-            shape: ObjectShape::Cube { size: Vec3::ONE },
-            color: Color::WHITE,
-            collision,
-            existence_conditions: Some(ExistenceConditions::Always),
-            object_type: template.name.clone(),
-            scale: template.scale,
-            y_offset: 0.0,
-            mesh: None,
-            material: None,
-        },
+        ObjectDefinition { scale: definition.scale * variation.scale_factor, ..definition },
+        variation,
         extra
     )).id();
 
     // Spawn the scene as a child of the parent entity
     let part_entity = commands.spawn((
         SceneRoot(template.scene.clone()),
-        MeshMaterial3d(materials.add(StandardMaterial {
-            base_color: template.object_definition.color,
-            perceptual_roughness: 0.5,
-            metallic: 0.0,
-            ..default()
-        })),
-        Transform::from_translation(Vec3::new(0.0, template.y_offset, 0.0))
-            .with_scale(template.scale)
-            .with_rotation(Quat::from_rotation_y(template.rotation_y)),
+        MeshMaterial3d(material),
+        variation.scene_transform(template),
 
     )).id();
 
     commands.entity(parent).add_child(part_entity);
+
+    if let Some(tile) = occupied_tile {
+        commands.entity(parent).insert(OccupiesTile(tile));
+    }
+
+    if variation.hue_shift != 0.0 {
+        commands.entity(parent).insert(SceneTint::hue(variation.hue_shift));
+    }
+
+    if let Some(effect) = template.effects.appear {
+        commands.entity(parent).insert(AppearEffect(effect));
+    }
+    if let Some(effect) = template.effects.disappear {
+        commands.entity(parent).insert(DisappearEffect(effect));
+    }
+
+    if !template.sockets.is_empty() {
+        let sockets = crate::sockets::spawn_sockets(commands, parent, &template.sockets, variation.scale_factor);
+        commands.entity(parent).insert(sockets);
+    }
+
+    if let Some(animations) = &template.animations {
+        commands.entity(parent).insert(TemplateAnimation::new(animations.clone()));
+    }
+
+    // Destructible objects get a nameplate with a health bar, interactables (scripted) a plain one
+    let mut nameplate = None;
+    if let Some(spec) = &template.destructible {
+        commands.entity(parent).insert(Destructible {
+            hp: spec.hp,
+            max_hp: spec.hp,
+            loot_table: spec.loot_table.clone(),
+            loot_tags: template.tags.clone(),
+        });
+        nameplate = Some(EntityInfoOverlay::nameplate(template.name.clone()).with_health_bar());
+    }
+
+    if let Some(script) = &template.script {
+        commands.entity(parent).insert(crate::scripting::Scripted::new(script, template.name.clone(), tile));
+        nameplate.get_or_insert_with(|| EntityInfoOverlay::nameplate(template.name.clone()));
+    }
+
+    if let Some(mut nameplate) = nameplate {
+        if let Some(icon) = &template.icon {
+            nameplate = nameplate.with_icon(icon.clone());
+        }
+        commands.entity(parent).insert(nameplate);
+    }
+
+    if let Some(spec) = &template.harvest {
+        let destructible_loot = template.destructible.as_ref().and_then(|spec| spec.loot_table.as_ref());
+        commands.entity(parent).insert(Harvestable::new(spec, destructible_loot, &template.tags));
+    }
+
+    // Use the template's declared shape, or build a collider matching the scene geometry once it is loaded
+    if wants_scene_collider && template.collider == SceneColliderMode::Shape {
+        commands.entity(parent).insert(variation.shape_collider(template));
+    } else if wants_scene_collider {
+        commands.entity(parent).insert(PendingSceneCollider {
+            mode: template.collider,
+            cache_key: format!("{}:{:?}:{}:{}", template.name, template.scale * variation.scale_factor,
+                               template.rotation_y + variation.yaw, template.y_offset),
+        });
+    }
     // Spawn the scene from the template
     parent
 }
 
-
-/// Spawn a unified object based on an ObjectDefinition
-pub fn spawn_unified_object<Extra: Bundle, T: IntoWorldPosition>(
+/// Spawn many template objects on tiles in one pass.
+/// Materials are created once per template and shared by all instances, instead of one per
+/// entity as with `spawn_template_scene`. Each object uses its template's collision behavior.
+pub fn spawn_objects_batch<'a, T: IntoWorldPosition>(
     commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     planisphere: &crate::planisphere::Planisphere,
     terrain_center: &crate::terrain::TerrainCenter,
-    position : T,
-    y_offset: f32,
-    collision: CollisionBehavior,
-    definition: ObjectDefinition,
-    extra: Extra, // <-- new parameter for extra components/bundles
+    objects: impl IntoIterator<Item = (&'a ObjectTemplate, T)>,
+) -> Vec<Entity> {
+    let mut shared_materials: HashMap<String, Handle<StandardMaterial>> = HashMap::new();
+    let mut entities = Vec::new();
+
+    for (template, position) in objects {
+        let world_pos = position.into_world_position(planisphere, terrain_center);
+        let variation = template.variation.sample(position.tile());
+        let material = shared_materials
+            .entry(template.name.clone())
+            .or_insert_with(|| materials.add(template_material(template)))
+            .clone();
+        entities.push(spawn_template_instance(
+            commands,
+            material,
+            template,
+            world_pos,
+            position.tile(),
+            variation,
+            ObjectDefinition::from_template(template),
+            (),
+        ));
+    }
+    debug!("Batch spawned {} objects with {} shared materials", entities.len(), shared_materials.len());
+    entities
+}
 
-) -> Entity {
-    // Determine world position
-    //eprintln!("Spawning object of type: {}", definition.object_type);
-    let world_pos =  position.into_world_position(planisphere, terrain_center);
-
-    //eprintln!("mean_tile_size: {}", planisphere.map_or(1.0, |p| p.mean_tile_size as f32));
-    //eprintln!(  "World position for object {}: {:?}", definition.object_type, world_pos);
-    // Apply scale and y_offset
-    let final_position = Vec3::new(
-        world_pos.x,
-        world_pos.y + definition.y_offset,
-        world_pos.z
-    );
-    let mesh_handle = if let Some(mesh) = &definition.mesh {
-        mesh.clone()
-    } else {
-        create_mesh_from_shape(&definition.shape, meshes)
-    };
-    let material_handle = if let Some(material) = &definition.material {
-        material.clone()
-    } else {
-        materials.add(StandardMaterial {
-            base_color: definition.color,
-            perceptual_roughness: 0.5,
-            metallic: 0.0,
-            ..default()
-        })
-    };
-    
-    // Create transform with scale
-    let transform = Transform {
-        translation: final_position,
-        scale: definition.scale,
-        ..default()
-    };
-    
-    // Build entity components
-    let mut entity_commands = commands.spawn((
-        Mesh3d(mesh_handle),
-        MeshMaterial3d(material_handle),
-        transform,
-        definition.clone(),
-        extra, // Insert extra components/bundles here
-    ));
-    
-    // Add physics components based on collision behavior
-    match definition.collision {
-        CollisionBehavior::None => {
-            // No physics components
-        }
-        CollisionBehavior::Static => {
-            entity_commands.insert((
-                RigidBody::Fixed,
-                create_collider_from_shape(&definition.shape),
-            ));
-        }
-        CollisionBehavior::Dynamic => {
-            entity_commands.insert((
-                RigidBody::Dynamic,
-                create_collider_from_shape(&definition.shape),
-            ));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(name: &str) -> ObjectTemplate {
+        ObjectTemplate {
+            name: name.to_string(),
+            scene: Handle::default(),
+            y_offset: 0.0,
+            scale: Vec3::ONE,
+            rotation_y: 0.0,
+            tags: Vec::new(),
+            collider: SceneColliderMode::default(),
+            variation: SpawnVariation::default(),
+            destructible: None,
+            harvest: None,
+            effects: TemplateEffects::default(),
+            animations: None,
+            sockets: HashMap::new(),
+            script: None,
+            icon: None,
+            object_definition: ObjectDefinition::builder(name).build(),
         }
     }
-    entity_commands.id()
-    //println!("Spawned {} object at {:?}", definition.object_type, final_position);
-}
\ No newline at end of file
+
+    #[test]
+    fn templates_iterate_over_every_registered_name() {
+        let mut templates = ObjectTemplates::default();
+        templates.insert("rock", template("Rock"));
+        templates.insert("tree", template("Tree"));
+        templates.insert("rock", template("Boulder")); // Replaces the first rock
+
+        let mut entries: Vec<(&str, &str)> = templates.iter().map(|(id, template)| (id.as_str(), template.name.as_str())).collect();
+        entries.sort_unstable();
+        assert_eq!(entries, [("rock", "Boulder"), ("tree", "Tree")]);
+        assert!(templates.get("robot").is_err_and(|e| e.to_string().contains("'robot'")));
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::beacons::{spawn_beacon, BeaconAnchor, BeaconKind, BeaconPool};
+use crate::game_object::{EntityInfoOverlay, ObjectTemplates};
+use crate::grid_overlay::surface_point;
+use crate::planisphere::Planisphere;
+use crate::terrain::{TerrainCenter, TerrainReady};
+use crate::trail::{polyline_tiles, PathTrail};
+use crate::TerrainConfig;
+
+/// GeoJSON layers imported when the game starts
+pub const GEOJSON_CONFIG_PATH: &str = "assets/geojson/layers.ron";
+
+/// GeoJSON file and how its features show: points as beacons of `point_template`, lines as
+/// trails and polygons as zone overlays tinting the tiles they cover
+#[derive(Debug, Clone, Deserialize)]
+pub struct GeoJsonLayer {
+    pub file: String,                      // Relative to the working directory
+    #[serde(default = "default_point_template")]
+    pub point_template: String,            // Key in ObjectTemplates
+    #[serde(default)]
+    pub label_property: Option<String>,    // Feature property shown on the point nameplates
+    #[serde(default = "default_line_color")]
+    pub line_color: (f32, f32, f32),
+    #[serde(default = "default_zone_color")]
+    pub zone_color: (f32, f32, f32, f32),  // The alpha is the overlay opacity
+}
+
+fn default_point_template() -> String { crate::beacons::MARKER_TEMPLATE.to_string() }
+fn default_line_color() -> (f32, f32, f32) { (0.2, 0.8, 1.0) }
+fn default_zone_color() -> (f32, f32, f32, f32) { (1.0, 0.6, 0.1, 0.35) }
+
+/// Layers to import, loaded from `GEOJSON_CONFIG_PATH`
+#[derive(Resource, Debug, Clone, Default, Deserialize)]
+pub struct GeoJsonConfig {
+    pub layers: Vec<GeoJsonLayer>,
+}
+
+impl GeoJsonConfig {
+    /// Read the layer list; a missing or broken file is reported and nothing is imported
+    pub fn load(path: &str) -> Self {
+        match load_geojson_config_file(Path::new(path)) {
+            Ok(config) => {
+                info!("Loaded {} GeoJSON layers from {}", config.layers.len(), path);
+                config
+            }
+            Err(e) => {
+                warn!("Could not load GeoJSON layers {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Parse a GeoJSON layer list file
+pub fn load_geojson_config_file(path: &Path) -> Result<GeoJsonConfig, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str::<GeoJsonConfig>(&content).map_err(|e| e.to_string())
+}
+
+/// Geometry of a feature, in (longitude, latitude) degrees
+#[derive(Debug, Clone, PartialEq)]
+pub enum GeoShape {
+    Point((f64, f64)),
+    Line(Vec<(f64, f64)>),
+    Polygon(Vec<Vec<(f64, f64)>>),   // Outer ring, then the holes
+}
+
+/// Imported feature with the label read from its properties
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoFeature {
+    pub shape: GeoShape,
+    pub label: Option<String>,
+}
+
+/// Parse a GeoJSON document: a FeatureCollection, a Feature or a bare geometry. Multi-geometries
+/// and geometry collections are split into their parts; features without geometry are skipped.
+pub fn parse_geojson(text: &str, label_property: Option<&str>) -> Result<Vec<GeoFeature>, String> {
+    let document: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    let mut features = Vec::new();
+    match document.get("type").and_then(Value::as_str) {
+        Some("FeatureCollection") => {
+            let list = document.get("features").and_then(Value::as_array).ok_or("FeatureCollection without features")?;
+            for feature in list {
+                parse_feature(feature, label_property, &mut features)?;
+            }
+        }
+        Some("Feature") => parse_feature(&document, label_property, &mut features)?,
+        Some(_) => parse_geometry(&document, None, &mut features)?,
+        None => return Err("not a GeoJSON object (no type)".to_string()),
+    }
+    Ok(features)
+}
+
+fn parse_feature(feature: &Value, label_property: Option<&str>, features: &mut Vec<GeoFeature>) -> Result<(), String> {
+    let label = label_property
+        .and_then(|property| feature.get("properties")?.get(property))
+        .map(|value| value.as_str().map_or_else(|| value.to_string(), str::to_string));
+    match feature.get("geometry") {
+        Some(geometry) if !geometry.is_null() => parse_geometry(geometry, label, features),
+        _ => Ok(()),
+    }
+}
+
+fn parse_geometry(geometry: &Value, label: Option<String>, features: &mut Vec<GeoFeature>) -> Result<(), String> {
+    let kind = geometry.get("type").and_then(Value::as_str).ok_or("geometry without type")?;
+    if kind == "GeometryCollection" {
+        for part in geometry.get("geometries").and_then(Value::as_array).ok_or("GeometryCollection without geometries")? {
+            parse_geometry(part, label.clone(), features)?;
+        }
+        return Ok(());
+    }
+    let coordinates = geometry.get("coordinates").ok_or_else(|| format!("{} without coordinates", kind))?;
+    let mut push = |shape| features.push(GeoFeature { shape, label: label.clone() });
+    match kind {
+        "Point" => push(GeoShape::Point(position(coordinates)?)),
+        "MultiPoint" => for point in list(coordinates)? { push(GeoShape::Point(position(point)?)) },
+        "LineString" => push(GeoShape::Line(positions(coordinates)?)),
+        "MultiLineString" => for line in list(coordinates)? { push(GeoShape::Line(positions(line)?)) },
+        "Polygon" => push(GeoShape::Polygon(rings(coordinates)?)),
+        "MultiPolygon" => for polygon in list(coordinates)? { push(GeoShape::Polygon(rings(polygon)?)) },
+        _ => return Err(format!("unknown geometry type {}", kind)),
+    }
+    Ok(())
+}
+
+fn list(value: &Value) -> Result<&Vec<Value>, String> {
+    value.as_array().ok_or_else(|| format!("expected an array, got {}", value))
+}
+
+/// GeoJSON position `[longitude, latitude, altitude?]`
+fn position(value: &Value) -> Result<(f64, f64), String> {
+    match list(value)?.as_slice() {
+        [lon, lat, ..] => match (lon.as_f64(), lat.as_f64()) {
+            (Some(lon), Some(lat)) if (-180.0..=180.0).contains(&lon) && (-90.0..=90.0).contains(&lat) => Ok((lon, lat)),
+            _ => Err(format!("invalid position {}", value)),
+        },
+        _ => Err(format!("invalid position {}", value)),
+    }
+}
+
+fn positions(value: &Value) -> Result<Vec<(f64, f64)>, String> {
+    list(value)?.iter().map(position).collect()
+}
+
+fn rings(value: &Value) -> Result<Vec<Vec<(f64, f64)>>, String> {
+    list(value)?.iter().map(positions).collect()
+}
+
+/// Whether a position is inside a polygon (even-odd rule over all rings, so holes are excluded)
+pub fn polygon_contains(rings: &[Vec<(f64, f64)>], (x, y): (f64, f64)) -> bool {
+    let mut inside = false;
+    for ring in rings {
+        for (n, &(x1, y1)) in ring.iter().enumerate() {
+            let (x2, y2) = ring[(n + 1) % ring.len()];
+            if (y1 > y) != (y2 > y) && x < x1 + (y - y1) * (x2 - x1) / (y2 - y1) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Imported point features; their beacons are spawned while they are in the rendered area
+#[derive(Resource, Debug, Default)]
+pub struct ImportedPoints {
+    pub points: Vec<ImportedPoint>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedPoint {
+    pub geo: (f64, f64),
+    pub tile: (usize, usize, usize),
+    pub label: Option<String>,
+    pub template: String,
+}
+
+/// Beacon of the imported point at this index of `ImportedPoints`
+#[derive(Component, Debug, Clone, Copy)]
+pub struct ImportedPointBeacon(pub usize);
+
+/// Polygon feature drawn as a translucent overlay (its material) over the rendered tiles it covers
+#[derive(Component, Debug, Clone)]
+pub struct ZoneOverlay {
+    pub rings: Vec<Vec<(f64, f64)>>,
+    bounds: (f64, f64, f64, f64),   // Longitude and latitude ranges of the outer ring
+}
+
+impl ZoneOverlay {
+    pub fn new(rings: Vec<Vec<(f64, f64)>>) -> Self {
+        let bounds = rings.first().into_iter().flatten().fold(
+            (f64::MAX, f64::MIN, f64::MAX, f64::MIN),
+            |(lon_min, lon_max, lat_min, lat_max), &(lon, lat)| (lon_min.min(lon), lon_max.max(lon), lat_min.min(lat), lat_max.max(lat)),
+        );
+        Self { rings, bounds }
+    }
+
+    pub fn contains(&self, (lon, lat): (f64, f64)) -> bool {
+        let (lon_min, lon_max, lat_min, lat_max) = self.bounds;
+        (lon_min..=lon_max).contains(&lon) && (lat_min..=lat_max).contains(&lat) && polygon_contains(&self.rings, (lon, lat))
+    }
+}
+
+/// Read the configured GeoJSON layers: points wait for the terrain in `ImportedPoints`, lines
+/// become `PathTrail` entities and polygons `ZoneOverlay` entities. Broken files are reported
+/// and skipped.
+pub fn import_geojson_layers(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    config: Res<GeoJsonConfig>,
+    planisphere: Res<Planisphere>,
+    mut imported: ResMut<ImportedPoints>,
+) {
+    for layer in config.layers.iter() {
+        let features = match std::fs::read_to_string(&layer.file).map_err(|e| e.to_string())
+            .and_then(|text| parse_geojson(&text, layer.label_property.as_deref())) {
+            Ok(features) => features,
+            Err(e) => {
+                warn!("Could not import GeoJSON {}: {}", layer.file, e);
+                continue;
+            }
+        };
+        let (red, green, blue) = layer.line_color;
+        let (zone_red, zone_green, zone_blue, zone_alpha) = layer.zone_color;
+        let zone_material = materials.add(StandardMaterial {
+            base_color: Color::srgba(zone_red, zone_green, zone_blue, zone_alpha),
+            unlit: true,
+            alpha_mode: AlphaMode::Blend,
+            cull_mode: None,
+            ..default()
+        });
+        for feature in features.iter() {
+            match &feature.shape {
+                GeoShape::Point(geo) => imported.points.push(ImportedPoint {
+                    geo: *geo,
+                    tile: planisphere.geo_to_subpixel(geo.0, geo.1),
+                    label: feature.label.clone(),
+                    template: layer.point_template.clone(),
+                }),
+                GeoShape::Line(line) => {
+                    commands.spawn(PathTrail::new(polyline_tiles(&planisphere, line), Color::srgb(red, green, blue)));
+                }
+                GeoShape::Polygon(rings) => {
+                    commands.spawn((
+                        ZoneOverlay::new(rings.clone()),
+                        MeshMaterial3d(zone_material.clone()),
+                        Transform::default(),
+                        Visibility::default(),
+                    ));
+                }
+            }
+        }
+        info!("Imported {} GeoJSON features from {}", features.len(), layer.file);
+    }
+}
+
+/// Spawn the beacons of the imported points in the rendered area once a terrain is ready; the
+/// beacon pool culls them when the area moves away
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_imported_points(
+    mut commands: Commands,
+    mut ready_events: EventReader<TerrainReady>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut beacon_pool: ResMut<BeaconPool>,
+    imported: Res<ImportedPoints>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    object_templates: Res<ObjectTemplates>,
+    beacons: Query<&ImportedPointBeacon>,
+) {
+    if ready_events.read().last().is_none() || imported.points.is_empty() {
+        return;
+    }
+    let rendered: HashSet<(usize, usize, usize)> = terrain_center.rendered_subpixels.subpixels.iter()
+        .map(|(i, j, k, _)| (*i, *j, *k))
+        .collect();
+    let live: HashSet<usize> = beacons.iter().map(|beacon| beacon.0).collect();
+    for (index, point) in imported.points.iter().enumerate() {
+        if live.contains(&index) || !rendered.contains(&point.tile) {
+            continue;
+        }
+        match spawn_beacon(&mut commands, &mut beacon_pool, &mut materials, &planisphere, &terrain_center, &object_templates,
+                           &point.template, BeaconAnchor::Geo(point.geo.0, point.geo.1), BeaconKind::Marker) {
+            Ok(entity) => {
+                commands.entity(entity).insert(ImportedPointBeacon(index));
+                if let Some(label) = &point.label {
+                    commands.entity(entity).insert(EntityInfoOverlay::nameplate(label.clone()));
+                }
+            }
+            Err(e) => {
+                warn!("Cannot spawn imported point: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Rebuild the zone overlay meshes over the rendered tiles whose center is inside each polygon,
+/// once a terrain is ready
+pub fn rebuild_zone_overlays(
+    mut commands: Commands,
+    mut ready_events: EventReader<TerrainReady>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    terrain_config: Res<TerrainConfig>,
+    zones: Query<(Entity, &ZoneOverlay, Option<&Mesh3d>)>,
+) {
+    if ready_events.read().last().is_none() {
+        return;
+    }
+    for (entity, zone, old_mesh) in zones.iter() {
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+        for &(i, j, k, corners) in terrain_center.rendered_subpixels.subpixels.iter() {
+            if !zone.contains(planisphere.subpixel_to_geo(i, j, k)) {
+                continue;
+            }
+            let heights = terrain_config.relief.corner_heights(&planisphere, i, j, k);
+            let first = positions.len() as u32;
+            for (geo, height) in corners.iter().zip(heights.iter()) {
+                positions.push(surface_point(&planisphere, &terrain_center, *geo, *height).to_array());
+            }
+            indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+        if let Some(old_mesh) = old_mesh {
+            meshes.remove(&old_mesh.0);
+        }
+        if indices.is_empty() {
+            commands.entity(entity).remove::<Mesh3d>();
+            continue;
+        }
+        let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+        let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+            .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+            .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+            .with_inserted_indices(Indices::U32(indices));
+        commands.entity(entity).insert(Mesh3d(meshes.add(mesh)));
+    }
+}
@@ -0,0 +1,263 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+use crate::game_object::EntitySubpixelPosition;
+use crate::message_log::{LogCategory, LogMessage};
+use crate::minimap::MinimapMarker;
+use crate::notifications::Notify;
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::terrain::{geo_to_world, TerrainCenter, TerrainReady};
+use crate::trail::{polyline_tiles, PathTrail};
+use crate::waypoints::Waypoint;
+
+/// Directory scanned for `.gpx` files at startup
+pub const GPX_DIR: &str = "assets/tracks";
+/// Trail, minimap and compass color of GPX tracks
+pub const TRACK_COLOR: Color = Color::srgb(1.0, 0.45, 0.1);
+
+/// Track or route of a GPX file, its segments joined in order
+#[derive(Debug, Clone)]
+pub struct GpxTrack {
+    pub name: String,
+    pub points: Vec<(f64, f64)>,   // (longitude, latitude) in degrees
+}
+
+/// Progress along the track being navigated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackNavigation {
+    pub track: usize,
+    pub next: Option<usize>,   // Point the player heads to, None until picked (the nearest one)
+}
+
+/// Tracks loaded from `GPX_DIR` and the one the player follows, if any
+#[derive(Resource, Debug, Default)]
+pub struct GpxTracks {
+    pub tracks: Vec<GpxTrack>,
+    pub navigation: Option<TrackNavigation>,
+}
+
+impl GpxTracks {
+    /// Read every `.gpx` file of `dir` in name order; broken files are reported and skipped
+    pub fn load(dir: &str) -> Self {
+        let Ok(entries) = std::fs::read_dir(dir) else { return Self::default(); };
+        let mut files: Vec<_> = entries.filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("gpx")))
+            .collect();
+        files.sort();
+
+        let mut tracks = Vec::new();
+        for file in files {
+            match load_gpx_file(&file) {
+                Ok(file_tracks) => tracks.extend(file_tracks),
+                Err(e) => warn!("Could not load GPX file {}: {}", file.display(), e),
+            }
+        }
+        info!("Loaded {} GPX tracks from {}", tracks.len(), dir);
+        Self { tracks, navigation: None }
+    }
+
+    /// Start following `track` from its nearest point, or stop when already following it
+    pub fn toggle_navigation(&mut self, track: usize) {
+        self.navigation = match self.navigation {
+            Some(navigation) if navigation.track == track => None,
+            _ => Some(TrackNavigation { track, next: None }),
+        };
+    }
+}
+
+/// Parse a GPX file, naming unnamed tracks after the file
+pub fn load_gpx_file(path: &Path) -> Result<Vec<GpxTrack>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut tracks = parse_gpx(&content)?;
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+    let count = tracks.len();
+    for (n, track) in tracks.iter_mut().enumerate() {
+        if track.name.is_empty() {
+            track.name = if count == 1 { stem.clone() } else { format!("{} {}", stem, n + 1) };
+        }
+    }
+    Ok(tracks)
+}
+
+/// Tracks (`<trk>`, all `<trkseg>` joined) and routes (`<rte>`) of a GPX document, without
+/// empty ones. Only tags and their `lat`/`lon` attributes are read: elevations, times and
+/// extensions are ignored.
+pub fn parse_gpx(text: &str) -> Result<Vec<GpxTrack>, String> {
+    let mut tracks = Vec::new();
+    let mut current: Option<GpxTrack> = None;
+    let mut in_point = false;   // Inside a point, whose `<name>` is not the track's
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        if let Some(comment) = rest.strip_prefix("!--") {
+            let end = comment.find("-->").ok_or("unterminated comment")?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+        let end = rest.find('>').ok_or("unterminated tag")?;
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        let closing = tag.starts_with('/');
+        let name = tag.trim_start_matches('/').split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or("");
+        let name = name.rsplit(':').next().unwrap_or(name); // Drop namespace prefixes
+
+        match (name, closing) {
+            ("trk" | "rte", false) => current = Some(GpxTrack { name: String::new(), points: Vec::new() }),
+            ("trk" | "rte", true) => {
+                if let Some(track) = current.take().filter(|track| !track.points.is_empty()) {
+                    tracks.push(track);
+                }
+            }
+            ("trkpt" | "rtept", false) => {
+                let coordinate = |key: &str| -> Result<f64, String> {
+                    attribute(tag, key).ok_or(format!("point without {}", key))?
+                        .trim().parse::<f64>().map_err(|e| format!("bad {}: {}", key, e))
+                };
+                let (lat, lon) = (coordinate("lat")?, coordinate("lon")?);
+                if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+                    return Err(format!("point out of range: lat {}, lon {}", lat, lon));
+                }
+                if let Some(track) = current.as_mut() {
+                    track.points.push((lon, lat));
+                }
+                in_point = !tag.ends_with('/');
+            }
+            ("trkpt" | "rtept", true) => in_point = false,
+            ("name", false) if !in_point => {
+                if let Some(track) = current.as_mut().filter(|track| track.name.is_empty()) {
+                    track.name = element_text(rest);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(tracks)
+}
+
+/// Value of the `key` attribute in the inside of a tag
+fn attribute<'a>(tag: &'a str, key: &str) -> Option<&'a str> {
+    let mut search = tag;
+    while let Some(at) = search.find(key) {
+        let starts_word = at > 0 && search.as_bytes()[at - 1].is_ascii_whitespace();
+        let after = &search[at + key.len()..];
+        if starts_word
+            && let Some(value) = after.trim_start().strip_prefix('=') {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                let value = &value[1..];
+                return value.find(quote).map(|end| &value[..end]);
+            }
+        }
+        search = after;
+    }
+    None
+}
+
+/// Text content at the start of `rest`, up to the closing tag, unescaped
+fn element_text(rest: &str) -> String {
+    if let Some(data) = rest.trim_start().strip_prefix("<![CDATA[") {
+        return data.split("]]>").next().unwrap_or("").trim().to_string();
+    }
+    let text = rest.split('<').next().unwrap_or("").trim();
+    text.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&")
+}
+
+/// Trail drawn along a loaded track
+#[derive(Component, Debug, Clone, Copy)]
+pub struct GpxTrail;
+
+/// Invisible target at the next point of the navigated track, showing on the compass, the
+/// minimap and as a screen-edge indicator
+#[derive(Component)]
+pub struct TrackGuide;
+
+/// Draw every loaded track as a trail on the terrain
+pub fn spawn_gpx_trails(mut commands: Commands, tracks: Res<GpxTracks>, planisphere: Res<Planisphere>) {
+    for track in tracks.tracks.iter() {
+        commands.spawn((GpxTrail, PathTrail::new(polyline_tiles(&planisphere, &track.points), TRACK_COLOR)));
+    }
+}
+
+/// Respawn the track markers after the terrain is rebuilt, so the tracks stay marked wherever
+/// the player goes (trail markers only cover the rendered area)
+pub fn refresh_gpx_trails(mut ready_events: EventReader<TerrainReady>, mut trails: Query<&mut PathTrail, With<GpxTrail>>) {
+    if ready_events.read().count() == 0 {
+        return;
+    }
+    for mut trail in trails.iter_mut() {
+        trail.set_changed();
+    }
+}
+
+/// Guide the player along the navigated track: the guide stands on the next point and moves on
+/// once the player comes within `config::waypoints::TRACK_ARRIVAL_TILES`. Navigation stops at the
+/// last point.
+#[allow(clippy::too_many_arguments)]
+pub fn update_track_navigation(
+    mut commands: Commands,
+    mut tracks: ResMut<GpxTracks>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    player_query: Query<&EntitySubpixelPosition, With<Player>>,
+    mut guides: Query<(Entity, &mut Transform, &mut Waypoint), With<TrackGuide>>,
+    mut notify: EventWriter<Notify>,
+    mut log: EventWriter<LogMessage>,
+) {
+    let Some(navigation) = tracks.navigation else {
+        for (guide, ..) in guides.iter() {
+            commands.entity(guide).despawn();
+        }
+        return;
+    };
+    let Ok(position) = player_query.single() else { return; };
+    let Some(track) = tracks.tracks.get(navigation.track) else {
+        tracks.navigation = None;
+        return;
+    };
+    let player = position.geo_coords;
+    let distance = |point: (f64, f64)| planisphere.geodesic_distance(player, point);
+
+    let mut next = navigation.next.unwrap_or_else(|| {
+        (0..track.points.len()).min_by(|&a, &b| distance(track.points[a]).total_cmp(&distance(track.points[b]))).unwrap_or(0)
+    });
+    let arrival = crate::config::waypoints::TRACK_ARRIVAL_TILES * planisphere.mean_tile_size;
+    while next < track.points.len() && distance(track.points[next]) < arrival {
+        next += 1;
+    }
+    if next >= track.points.len() {
+        let message = format!("Reached the end of {}", track.name);
+        notify.write(Notify::new(message.clone()).with_color(TRACK_COLOR));
+        log.write(LogMessage::new(LogCategory::Waypoint, message));
+        tracks.navigation = None;
+        return; // The guide goes on the next update
+    }
+
+    let name = format!("{} {}/{}", track.name, next + 1, track.points.len());
+    let (lon, lat) = track.points[next];
+    let target = geo_to_world(lon, lat, &planisphere, &terrain_center);
+    if navigation.next != Some(next) {
+        tracks.navigation = Some(TrackNavigation { next: Some(next), ..navigation });
+    }
+    match guides.single_mut() {
+        Ok((_, mut transform, mut waypoint)) => {
+            transform.translation = target;
+            if waypoint.name != name {
+                waypoint.name = name;
+            }
+        }
+        Err(_) => {
+            commands.spawn((
+                TrackGuide,
+                Waypoint { name, active: true },
+                MinimapMarker(TRACK_COLOR),
+                Transform::from_translation(target),
+            ));
+        }
+    }
+}
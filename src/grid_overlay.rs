@@ -0,0 +1,91 @@
+use std::collections::HashSet;
+use bevy::prelude::*;
+
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::terrain::{ijk_to_world, TerrainCenter};
+use crate::TerrainConfig;
+
+/// Lift of the lines above the terrain surface, against z-fighting
+const LINE_LIFT: f32 = 0.05;
+const SUBPIXEL_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.35);
+const PIXEL_COLOR: Color = Color::srgb(1.0, 0.5, 0.1);
+
+/// Pixel and subpixel boundaries drawn around the player, toggled with G
+#[derive(Resource, Debug, Default)]
+pub struct GridOverlay {
+    pub enabled: bool,
+}
+
+/// Toggle the grid overlay with G
+pub fn toggle_grid_overlay(keyboard_input: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<GridOverlay>) {
+    if keyboard_input.just_pressed(KeyCode::KeyG) {
+        overlay.enabled = !overlay.enabled;
+        info!("Grid overlay {}", if overlay.enabled { "shown" } else { "hidden" });
+    }
+}
+
+/// Point of the terrain surface at a geographic position and world height, in the frame of the
+/// terrain mesh (same projection as `terrain::mesh::terrain_mesh`), lifted against z-fighting
+pub fn surface_point(planisphere: &Planisphere, terrain_center: &TerrainCenter, (lon, lat): (f64, f64), height: f32) -> Vec3 {
+    let (x, z) = planisphere.geo_to_gnomonic(lon, lat, terrain_center.longitude, terrain_center.latitude);
+    Vec3::new(x as f32, height + LINE_LIFT, z as f32)
+}
+
+/// Outline the rendered subpixels within `TerrainConfig::grid_radius` tiles of the player, and the
+/// pixels they belong to, following the terrain altitude
+pub fn draw_grid_overlay(
+    mut gizmos: Gizmos,
+    overlay: Res<GridOverlay>,
+    terrain_config: Res<TerrainConfig>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+    let Ok(player_transform) = player_query.single() else { return; };
+    let range = terrain_config.grid_radius as f32 * planisphere.mean_tile_size as f32;
+
+    let mut pixels = HashSet::new();
+    for &(i, j, k, _) in terrain_center.rendered_subpixels.subpixels.iter() {
+        let center = ijk_to_world(i as i32, j as i32, k as i32, &planisphere, &terrain_center);
+        if center.xz().distance(player_transform.translation.xz()) > range {
+            continue;
+        }
+        pixels.insert((i, j));
+        let corners = planisphere.get_subpixel_corners(i, j, k);
+        let heights = terrain_config.relief.corner_heights(&planisphere, i, j, k);
+        let points: Vec<Vec3> = corners.iter().zip(heights.iter())
+            .map(|(geo, height)| surface_point(&planisphere, &terrain_center, *geo, *height))
+            .collect();
+        gizmos.linestrip(points.iter().copied().chain(std::iter::once(points[0])), SUBPIXEL_COLOR);
+    }
+
+    // Pixel edges span several subpixels, so the altitude is sampled along them
+    let steps = planisphere.subpixel_divisions.max(1);
+    for (i, j) in pixels {
+        let (left, right, top, bottom) = planisphere.get_pixel_boundaries(i, j);
+        let (fi, fj) = (i as f64, j as f64);
+        // (pixel-grid position, geographic position) of the corners, "top" being the south edge
+        let corners = [
+            ((fi, fj), (left, top)),
+            ((fi + 1.0, fj), (right, top)),
+            ((fi + 1.0, fj + 1.0), (right, bottom)),
+            ((fi, fj + 1.0), (left, bottom)),
+        ];
+        let mut points = Vec::with_capacity(4 * steps + 1);
+        for edge in 0..4 {
+            let ((grid_a, geo_a), (grid_b, geo_b)) = (corners[edge], corners[(edge + 1) % 4]);
+            for step in 0..steps {
+                let t = step as f64 / steps as f64;
+                let lerp = |a: f64, b: f64| a + (b - a) * t;
+                let height = terrain_config.relief.height_scale * planisphere.alti_at_pixel_coords(lerp(grid_a.0, grid_b.0), lerp(grid_a.1, grid_b.1));
+                points.push(surface_point(&planisphere, &terrain_center, (lerp(geo_a.0, geo_b.0), lerp(geo_a.1, geo_b.1)), height));
+            }
+        }
+        points.push(points[0]);
+        gizmos.linestrip(points, PIXEL_COLOR);
+    }
+}
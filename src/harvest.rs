@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::destruction::Destructible;
+use crate::game_object::{ObjectDefinition, ObjectEvent};
+use crate::items::{Hotbar, ItemDefinitions};
+use crate::loot::{LootContext, LootDropEvent};
+use crate::message_log::{LogCategory, LogMessage};
+use crate::player::{CursorTarget, Player, PlayerInventory};
+
+/// Seconds a harvested object shakes after a hit
+const SHAKE_SECS: f32 = 0.35;
+/// Tilt of the shake in radians
+const SHAKE_ANGLE: f32 = 0.06;
+
+/// Template-file description of a harvestable object, e.g.
+/// `(pool: 4, loot_table: Some("tree"), tool: Some("axe"))`
+#[derive(Debug, Clone, Deserialize)]
+pub struct HarvestSpec {
+    pub pool: u32,                    // Harvests before the object is used up
+    #[serde(default)]
+    pub loot_table: Option<String>,   // Dropped per harvest, defaults to the destructible loot table
+    #[serde(default)]
+    pub tool: Option<String>,         // Item tag harvesting `config::objects::TOOL_HARVEST_UNITS` at once
+}
+
+/// Object the player can harvest with E. Each harvest drops `loot_table` and takes one unit from
+/// the pool (more with the right tool); the object is removed once the pool is empty.
+#[derive(Component, Debug, Clone)]
+pub struct Harvestable {
+    pub remaining: u32,
+    pub pool: u32,
+    pub loot_table: Option<String>,
+    pub tool: Option<String>,
+    pub loot_tags: Vec<String>,   // Passed to loot conditions (template tags)
+}
+
+impl Harvestable {
+    pub fn new(spec: &HarvestSpec, destructible_loot: Option<&String>, tags: &[String]) -> Self {
+        Self {
+            remaining: spec.pool.max(1),
+            pool: spec.pool.max(1),
+            loot_table: spec.loot_table.clone().or_else(|| destructible_loot.cloned()),
+            tool: spec.tool.clone(),
+            loot_tags: tags.to_vec(),
+        }
+    }
+}
+
+/// Short wobble played on a harvested object, around its rotation before the hit
+#[derive(Component, Debug)]
+pub struct HarvestShake {
+    timer: Timer,
+    base_rotation: Quat,
+}
+
+/// Harvest the object under the cursor with E when it is within `config::objects::HARVEST_REACH`
+/// of the player. The selected hotbar item counts as the right tool when it carries the
+/// object's `tool` tag. Destructible objects lose hit points along with their pool and break
+/// through `destruction::destroy_broken_objects` on the last harvest (which drops their loot);
+/// others are removed here.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn handle_harvest_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    cursor: Res<CursorTarget>,
+    item_definitions: Res<ItemDefinitions>,
+    player_query: Query<(&Transform, &PlayerInventory, Option<&Hotbar>), With<Player>>,
+    mut targets: Query<(&Transform, &mut Harvestable, Option<&mut Destructible>, Option<&ObjectDefinition>, Option<&mut HarvestShake>), Without<Player>>,
+    mut loot_events: EventWriter<LootDropEvent>,
+    mut object_events: EventWriter<ObjectEvent>,
+    mut log: EventWriter<LogMessage>,
+) {
+    use crate::config::objects::{HARVEST_REACH, TOOL_HARVEST_UNITS};
+
+    if !keyboard_input.just_pressed(KeyCode::KeyE) {
+        return;
+    }
+    let Some(entity) = cursor.entity else { return; };
+    let Ok((player_transform, inventory, hotbar)) = player_query.single() else { return; };
+    let Ok((transform, mut harvestable, destructible, definition, shake)) = targets.get_mut(entity) else { return; };
+    if transform.translation.distance(player_transform.translation) > HARVEST_REACH {
+        log.write(LogMessage::new(LogCategory::Building, "Too far away to harvest"));
+        return;
+    }
+
+    let selected = hotbar.and_then(|hotbar| inventory.stacks.get(hotbar.selected));
+    let with_tool = harvestable.tool.as_ref().is_some_and(|tool| {
+        selected.and_then(|stack| item_definitions.get(&stack.item_id)).is_some_and(|item| item.tags.contains(tool))
+    });
+    let units = (if with_tool { TOOL_HARVEST_UNITS } else { 1 }).min(harvestable.remaining);
+    harvestable.remaining -= units;
+    let depleted = harvestable.remaining == 0;
+
+    // The destructible loot is dropped by the destruction of the object, not once more here
+    let drops = if depleted && destructible.is_some() { units - 1 } else { units };
+    if let Some(table_id) = &harvestable.loot_table {
+        for _ in 0..drops {
+            loot_events.write(LootDropEvent {
+                table_id: table_id.clone(),
+                position: transform.translation,
+                context: LootContext { tags: harvestable.loot_tags.clone() },
+            });
+        }
+    }
+    let name = definition.map_or("Object", |definition| definition.object_type.as_str());
+    log.write(LogMessage::new(LogCategory::Building, format!("Harvested {} ({} left)", name, harvestable.remaining)));
+
+    match destructible {
+        Some(mut destructible) => {
+            destructible.hp = destructible.max_hp * harvestable.remaining as f32 / harvestable.pool as f32;
+        }
+        None if depleted => {
+            object_events.write(ObjectEvent::Destroyed { entity });
+            crate::vfx::request_despawn(&mut commands, entity);
+            return;
+        }
+        None => {}
+    }
+    match shake {
+        Some(mut shake) => shake.timer.reset(),
+        None => {
+            commands.entity(entity).insert(HarvestShake {
+                timer: Timer::from_seconds(SHAKE_SECS, TimerMode::Once),
+                base_rotation: transform.rotation,
+            });
+        }
+    }
+}
+
+/// Wobble harvested objects, settling back to their rotation when the shake ends
+pub fn animate_harvest_shake(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut HarvestShake, &mut Transform)>,
+) {
+    for (entity, mut shake, mut transform) in query.iter_mut() {
+        shake.timer.tick(time.delta());
+        if shake.timer.finished() {
+            transform.rotation = shake.base_rotation;
+            commands.entity(entity).remove::<HarvestShake>();
+            continue;
+        }
+        let fade = shake.timer.fraction_remaining();
+        let angle = SHAKE_ANGLE * fade * (shake.timer.elapsed_secs() * 45.0).sin();
+        transform.rotation = shake.base_rotation * Quat::from_rotation_z(angle);
+    }
+}
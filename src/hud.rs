@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+
+use crate::items::{Hotbar, ItemDefinitions};
+use crate::player::{Player, PlayerInventory};
+use crate::player_stats::{Breath, Health, Stamina, StatFraction};
+
+const BAR_WIDTH_PX: f32 = 200.0;
+const BAR_HEIGHT_PX: f32 = 10.0;
+/// Bars below this fill level flash
+const LOW_FRACTION: f32 = 0.25;
+/// How fast the displayed fill catches up with the stat (higher is snappier)
+const FILL_SMOOTHING: f32 = 6.0;
+
+/// Player stat shown by a HUD bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HudStat {
+    Health,
+    Stamina,
+    Breath,
+}
+
+impl HudStat {
+    fn color(self) -> Color {
+        match self {
+            HudStat::Health => Color::srgb(0.85, 0.2, 0.2),
+            HudStat::Stamina => Color::srgb(0.95, 0.75, 0.2),
+            HudStat::Breath => Color::srgb(0.3, 0.65, 0.95),
+        }
+    }
+}
+
+/// Row of a HUD bar (label and frame), hidden for breath while it is full
+#[derive(Component)]
+pub struct HudBarRow(pub HudStat);
+
+/// Filled part of a HUD bar; `displayed` eases toward the stat value
+#[derive(Component)]
+pub struct HudBarFill {
+    pub stat: HudStat,
+    pub displayed: f32,
+}
+
+/// Spawn the stat bars at the bottom center of the screen, leaving room below for the hotbar
+pub fn setup_hud(mut commands: Commands) {
+    commands.spawn(Node {
+        position_type: PositionType::Absolute,
+        left: Val::Percent(50.0),
+        bottom: Val::Px(crate::config::hud::BARS_BOTTOM_PX),
+        margin: UiRect::left(Val::Px(-BAR_WIDTH_PX / 2.0 - 30.0)),
+        flex_direction: FlexDirection::Column,
+        row_gap: Val::Px(3.0),
+        ..default()
+    }).with_children(|column| {
+        for (stat, label) in [(HudStat::Health, "HP"), (HudStat::Stamina, "ST"), (HudStat::Breath, "O2")] {
+            column.spawn((
+                Node { align_items: AlignItems::Center, column_gap: Val::Px(6.0), ..default() },
+                HudBarRow(stat),
+            )).with_children(|row| {
+                row.spawn((
+                    Node { width: Val::Px(24.0), ..default() },
+                    Text::new(label),
+                    TextFont { font_size: 11.0, ..default() },
+                    TextColor(Color::WHITE),
+                ));
+                row.spawn((
+                    Node { width: Val::Px(BAR_WIDTH_PX), height: Val::Px(BAR_HEIGHT_PX), padding: UiRect::all(Val::Px(1.0)), ..default() },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                )).with_children(|frame| {
+                    frame.spawn((
+                        Node { width: Val::Percent(100.0), height: Val::Percent(100.0), ..default() },
+                        BackgroundColor(stat.color()),
+                        HudBarFill { stat, displayed: 1.0 },
+                    ));
+                });
+            });
+        }
+    });
+}
+
+/// Ease the bars toward the player's stats, flash them when low and hide the breath bar while
+/// breath is full
+pub fn update_hud(
+    time: Res<Time<Real>>,
+    player_query: Query<(&Health, &Stamina, &Breath), With<Player>>,
+    mut rows: Query<(&HudBarRow, &mut Visibility)>,
+    mut fills: Query<(&mut HudBarFill, &mut Node, &mut BackgroundColor)>,
+) {
+    let Ok((health, stamina, breath)) = player_query.single() else { return; };
+    let fraction = |stat: HudStat| match stat {
+        HudStat::Health => health.fraction(),
+        HudStat::Stamina => stamina.fraction(),
+        HudStat::Breath => breath.fraction(),
+    };
+
+    for (row, mut visibility) in rows.iter_mut() {
+        let target = if row.0 == HudStat::Breath && fraction(HudStat::Breath) >= 1.0 { Visibility::Hidden } else { Visibility::Inherited };
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+
+    let blend = 1.0 - (-FILL_SMOOTHING * time.delta_secs()).exp();
+    let flash = 0.5 + 0.5 * (time.elapsed_secs() * 8.0).sin();
+    for (mut fill, mut node, mut background) in fills.iter_mut() {
+        let target = fraction(fill.stat);
+        fill.displayed += (target - fill.displayed) * blend;
+        node.width = Val::Percent(fill.displayed * 100.0);
+        let color = fill.stat.color();
+        background.0 = if target < LOW_FRACTION { color.mix(&Color::WHITE, flash * 0.6) } else { color };
+    }
+}
+
+/// Number keys selecting the hotbar slots, in slot order
+const SLOT_KEYS: [KeyCode; 9] = [
+    KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5,
+    KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+];
+
+/// Hotbar slot frame, highlighted when selected
+#[derive(Component)]
+pub struct HotbarSlot(pub usize);
+
+/// Item icon of a slot, shown when the item definition has one
+#[derive(Component)]
+pub struct HotbarIcon(pub usize);
+
+/// Short item name, shown in place of a missing icon
+#[derive(Component)]
+pub struct HotbarLabel(pub usize);
+
+#[derive(Component)]
+pub struct HotbarCount(pub usize);
+
+/// Spawn the hotbar slots centered at the bottom of the screen, below the stat bars
+pub fn setup_hotbar(mut commands: Commands) {
+    let slot_px = crate::config::hud::HOTBAR_SLOT_PX;
+    commands.spawn(Node {
+        position_type: PositionType::Absolute,
+        bottom: Val::Px(10.0),
+        width: Val::Percent(100.0),
+        justify_content: JustifyContent::Center,
+        column_gap: Val::Px(4.0),
+        ..default()
+    }).with_children(|bar| {
+        for index in 0..crate::config::hud::HOTBAR_SLOTS {
+            bar.spawn((
+                Node {
+                    width: Val::Px(slot_px),
+                    height: Val::Px(slot_px),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                BorderColor(Color::srgba(0.4, 0.4, 0.4, 0.8)),
+                HotbarSlot(index),
+            )).with_children(|slot| {
+                slot.spawn((
+                    Node { position_type: PositionType::Absolute, left: Val::Px(3.0), top: Val::Px(1.0), ..default() },
+                    Text::new((index + 1).to_string()),
+                    TextFont { font_size: 9.0, ..default() },
+                    TextColor(Color::srgb(0.6, 0.6, 0.6)),
+                ));
+                slot.spawn((
+                    Node { width: Val::Px(slot_px - 16.0), height: Val::Px(slot_px - 16.0), display: Display::None, ..default() },
+                    ImageNode::default(),
+                    HotbarIcon(index),
+                ));
+                slot.spawn((Text::new(""), TextFont { font_size: 12.0, ..default() }, TextColor(Color::WHITE), HotbarLabel(index)));
+                slot.spawn((
+                    Node { position_type: PositionType::Absolute, right: Val::Px(3.0), bottom: Val::Px(1.0), ..default() },
+                    Text::new(""),
+                    TextFont { font_size: 11.0, ..default() },
+                    TextColor(Color::WHITE),
+                    HotbarCount(index),
+                ));
+            });
+        }
+    });
+}
+
+/// Select a hotbar slot with the number keys or Ctrl + mouse wheel
+pub fn handle_hotbar_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut scroll_events: EventReader<MouseWheel>,
+    mut hotbar_query: Query<&mut Hotbar, With<Player>>,
+) {
+    let Ok(mut hotbar) = hotbar_query.single_mut() else { return; };
+    if let Some(index) = SLOT_KEYS.iter().take(crate::config::hud::HOTBAR_SLOTS).position(|key| keyboard_input.just_pressed(*key)) {
+        hotbar.selected = index;
+    }
+    if !(keyboard_input.pressed(KeyCode::ControlLeft) || keyboard_input.pressed(KeyCode::ControlRight)) {
+        scroll_events.clear();
+        return;
+    }
+    for event in scroll_events.read() {
+        let steps = match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 40.0,
+        };
+        if steps != 0.0 {
+            // Scrolling down moves right
+            hotbar.cycle(-steps.signum() as i32);
+        }
+    }
+}
+
+/// Refresh the hotbar slots from the player's inventory when it or the selection changes
+pub fn update_hotbar(
+    asset_server: Res<AssetServer>,
+    item_definitions: Res<ItemDefinitions>,
+    player_query: Query<(Ref<PlayerInventory>, Ref<Hotbar>), With<Player>>,
+    mut slots: Query<(&HotbarSlot, &mut BorderColor)>,
+    mut icons: Query<(&HotbarIcon, &mut ImageNode, &mut Node)>,
+    mut labels: Query<(&HotbarLabel, &mut Text), Without<HotbarCount>>,
+    mut counts: Query<(&HotbarCount, &mut Text), Without<HotbarLabel>>,
+) {
+    let Ok((inventory, hotbar)) = player_query.single() else { return; };
+    if !inventory.is_changed() && !hotbar.is_changed() {
+        return;
+    }
+
+    for (slot, mut border) in slots.iter_mut() {
+        border.0 = if slot.0 == hotbar.selected { Color::srgb(1.0, 0.85, 0.2) } else { Color::srgba(0.4, 0.4, 0.4, 0.8) };
+    }
+    for (icon, mut image, mut node) in icons.iter_mut() {
+        let path = inventory.stacks.get(icon.0)
+            .and_then(|stack| item_definitions.get(&stack.item_id))
+            .and_then(|definition| definition.icon.as_ref());
+        match path {
+            Some(path) => {
+                image.image = asset_server.load(path);
+                node.display = Display::Flex;
+            }
+            None => node.display = Display::None,
+        }
+    }
+    for (label, mut text) in labels.iter_mut() {
+        **text = match inventory.stacks.get(label.0) {
+            Some(stack) if item_definitions.get(&stack.item_id).is_none_or(|definition| definition.icon.is_none()) => {
+                item_definitions.display_name(&stack.item_id).chars().take(4).collect()
+            }
+            _ => String::new(),
+        };
+    }
+    for (count, mut text) in counts.iter_mut() {
+        **text = match inventory.stacks.get(count.0) {
+            Some(stack) if stack.count > 1 => stack.count.to_string(),
+            _ => String::new(),
+        };
+    }
+}
@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::path::Path;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Directory scanned at startup for item definitions (one `*.ron` file per item)
+pub const ITEM_DEFINITION_DIR: &str = "assets/items";
+
+/// Static description of an item kind, loaded from `assets/items/<id>.ron`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ItemDefinition {
+    #[serde(default)]
+    pub id: String,                   // Registry key, filled from the file stem when omitted
+    pub display_name: String,         // Name shown to the player
+    #[serde(default)]
+    pub icon: Option<String>,         // Asset path of the inventory icon, e.g. "icons/coin.png"
+    #[serde(default = "default_stack_size")]
+    pub stack_size: u32,              // Maximum count held in one inventory stack
+    #[serde(default)]
+    pub weight: f32,                  // Weight of a single unit
+    #[serde(default)]
+    pub tags: Vec<String>,            // Free-form labels (e.g. "currency", "material")
+    #[serde(default)]
+    pub rarity: Rarity,               // Spawn weight and visual treatment in the world
+}
+
+fn default_stack_size() -> u32 { 1 }
+
+/// Rarity tier of an item: rarer items are scattered less often, glow in their tier color and
+/// from `Rare` on show a light beam where they lie
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+}
+
+impl Rarity {
+    pub const ALL: [Rarity; 4] = [Rarity::Common, Rarity::Uncommon, Rarity::Rare, Rarity::Epic];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Rarity::Common => "Common",
+            Rarity::Uncommon => "Uncommon",
+            Rarity::Rare => "Rare",
+            Rarity::Epic => "Epic",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Rarity::Common => Color::srgb(0.9, 0.9, 0.9),
+            Rarity::Uncommon => Color::srgb(0.3, 0.9, 0.3),
+            Rarity::Rare => Color::srgb(0.3, 0.55, 1.0),
+            Rarity::Epic => Color::srgb(0.75, 0.35, 1.0),
+        }
+    }
+
+    /// Relative weight when choosing which item to scatter on a tile
+    pub fn spawn_weight(self) -> u32 {
+        match self {
+            Rarity::Common => 60,
+            Rarity::Uncommon => 25,
+            Rarity::Rare => 10,
+            Rarity::Epic => 3,
+        }
+    }
+
+    /// Emissive strength of items lying in the world, relative to the tier color
+    pub fn glow(self) -> f32 {
+        match self {
+            Rarity::Common => 0.0,
+            Rarity::Uncommon => 0.5,
+            Rarity::Rare => 1.5,
+            Rarity::Epic => 3.0,
+        }
+    }
+
+    /// Whether items of this tier are marked by a light beam
+    pub fn has_beam(self) -> bool {
+        self >= Rarity::Rare
+    }
+}
+
+/// Registry of item definitions keyed by id
+#[derive(Resource, Default)]
+pub struct ItemDefinitions {
+    definitions: HashMap<String, ItemDefinition>,
+}
+
+impl ItemDefinitions {
+    /// Read every `*.ron` definition in `dir`; files that fail to parse are reported and skipped
+    pub fn load(dir: &str) -> Self {
+        let mut registry = Self::default();
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Could not read item definition directory {}: {}", dir, e);
+                return registry;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("ron") {
+                continue;
+            }
+            let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else { continue; };
+            match load_item_definition_file(&path) {
+                Ok(mut definition) => {
+                    if definition.id.is_empty() {
+                        definition.id = stem.to_string();
+                    }
+                    registry.definitions.insert(definition.id.clone(), definition);
+                }
+                Err(e) => warn!("Skipping item definition {}: {}", path.display(), e),
+            }
+        }
+        info!("Loaded {} item definitions from {}", registry.definitions.len(), dir);
+        registry
+    }
+
+    pub fn get(&self, id: &str) -> Option<&ItemDefinition> {
+        self.definitions.get(id)
+    }
+
+    /// Stack size of an item, 1 for unknown ids so they still fit in an inventory
+    pub fn stack_size(&self, id: &str) -> u32 {
+        self.get(id).map_or(1, |definition| definition.stack_size.max(1))
+    }
+
+    /// Rarity of an item, `Common` for unknown ids
+    pub fn rarity(&self, id: &str) -> Rarity {
+        self.get(id).map_or(Rarity::Common, |definition| definition.rarity)
+    }
+
+    /// Display name of an item, falling back to its id
+    pub fn display_name<'a>(&'a self, id: &'a str) -> &'a str {
+        self.get(id).map_or(id, |definition| definition.display_name.as_str())
+    }
+}
+
+/// Parse a single item definition file
+pub fn load_item_definition_file(path: &Path) -> Result<ItemDefinition, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str::<ItemDefinition>(&content).map_err(|e| e.to_string())
+}
+
+/// One inventory slot: `count` units of the same item
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemStack {
+    pub item_id: String,
+    pub count: u32,
+}
+
+/// Stacked item storage shared by the player and any other holder
+#[derive(Component, Default, Debug, Clone)]
+pub struct Inventory {
+    pub stacks: Vec<ItemStack>,
+}
+
+impl Inventory {
+    /// Add `count` units, topping up existing stacks before opening new ones
+    pub fn add(&mut self, definitions: &ItemDefinitions, item_id: &str, count: u32) {
+        let stack_size = definitions.stack_size(item_id);
+        let mut remaining = count;
+        for stack in self.stacks.iter_mut().filter(|stack| stack.item_id == item_id) {
+            if remaining == 0 {
+                break;
+            }
+            let added = remaining.min(stack_size.saturating_sub(stack.count));
+            stack.count += added;
+            remaining -= added;
+        }
+        while remaining > 0 {
+            let added = remaining.min(stack_size);
+            self.stacks.push(ItemStack { item_id: item_id.to_string(), count: added });
+            remaining -= added;
+        }
+    }
+
+    /// Remove up to `count` units, emptying the last stacks first. Returns how many were removed.
+    pub fn remove(&mut self, item_id: &str, count: u32) -> u32 {
+        let mut remaining = count;
+        for stack in self.stacks.iter_mut().rev().filter(|stack| stack.item_id == item_id) {
+            let removed = remaining.min(stack.count);
+            stack.count -= removed;
+            remaining -= removed;
+            if remaining == 0 {
+                break;
+            }
+        }
+        self.stacks.retain(|stack| stack.count > 0);
+        count - remaining
+    }
+
+    /// Total units of an item across all stacks
+    pub fn count(&self, item_id: &str) -> u32 {
+        self.stacks.iter().filter(|stack| stack.item_id == item_id).map(|stack| stack.count).sum()
+    }
+
+    /// Total carried weight
+    pub fn weight(&self, definitions: &ItemDefinitions) -> f32 {
+        self.stacks.iter()
+            .map(|stack| definitions.get(&stack.item_id).map_or(0.0, |definition| definition.weight) * stack.count as f32)
+            .sum()
+    }
+}
+
+/// Quick-access bar over the first `config::hud::HOTBAR_SLOTS` stacks of an inventory
+#[derive(Component, Default, Debug, Clone)]
+pub struct Hotbar {
+    pub selected: usize,   // Selected slot index
+}
+
+impl Hotbar {
+    /// Move the selection by `steps` slots, wrapping around
+    pub fn cycle(&mut self, steps: i32) {
+        let slots = crate::config::hud::HOTBAR_SLOTS as i32;
+        self.selected = (self.selected as i32 + steps).rem_euclid(slots) as usize;
+    }
+}
@@ -0,0 +1,13 @@
+//! Bevy-free core of tiles3d: the planisphere (elevation map, geographic projections and
+//! subpixel tiling) and the terrain tiling math (texture selection, mesh geometry and UVs).
+//! It builds without Bevy with `default-features = false`, for tests, benchmarks and external
+//! tools; the `game` feature (on by default) adds the Bevy integration used by the game binary.
+
+pub mod config;      // config.rs - centralized constants for terrain, player, camera, etc.
+pub mod profiling;   // profiling.rs - tracing spans (Tracy) and feature-gated stage timers
+pub mod planisphere; // planisphere/ - geographic coordinate conversion, projections and map sampling
+pub mod tiling;      // tiling/ - atlas texture selection and terrain mesh geometry
+#[cfg(feature = "net")]
+pub mod net;         // net.rs - client/server protocol and message stream
+#[cfg(feature = "preprocess")]
+pub mod preprocess;  // preprocess.rs - map validation, atlas, planisphere cache and asset manifest
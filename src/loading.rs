@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::error::StartupError;
+use crate::terrain::TerrainBuild;
+
+const BAR_WIDTH_PX: f32 = 320.0;
+
+/// Full-screen overlay shown while a blocking terrain build runs
+#[derive(Component)]
+pub struct LoadingScreen;
+
+/// Filled part of the progress bar
+#[derive(Component)]
+pub struct LoadingBar;
+
+#[derive(Component)]
+pub struct LoadingText;
+
+/// Spawn the loading screen, visible at startup since the first terrain build is blocking
+pub fn setup_loading_screen(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(12.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.05, 0.06, 0.08)),
+        GlobalZIndex(10),
+        LoadingScreen,
+    )).with_children(|screen| {
+        screen.spawn((
+            Text::new("Generating terrain..."),
+            TextFont { font_size: 20.0, ..default() },
+            TextColor(Color::WHITE),
+            LoadingText,
+        ));
+        screen.spawn((
+            Node {
+                width: Val::Px(BAR_WIDTH_PX),
+                height: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+        )).with_children(|bar| {
+            bar.spawn((
+                Node { width: Val::Px(0.0), height: Val::Percent(100.0), ..default() },
+                BackgroundColor(Color::srgb(0.3, 0.7, 0.3)),
+                LoadingBar,
+            ));
+        });
+    });
+}
+
+/// Show the loading screen with the build progress while a blocking terrain build runs, and
+/// pause physics meanwhile so nothing falls through the missing terrain
+pub fn update_loading_screen(
+    build: Res<TerrainBuild>,
+    mut screen_query: Query<&mut Visibility, With<LoadingScreen>>,
+    mut bar_query: Query<&mut Node, With<LoadingBar>>,
+    mut text_query: Query<&mut Text, With<LoadingText>>,
+    mut rapier_config: Query<&mut RapierConfiguration>,
+) {
+    let loading = build.is_running() && build.blocking;
+    if let Ok(mut visibility) = screen_query.single_mut() {
+        let target = if loading { Visibility::Inherited } else { Visibility::Hidden };
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+    for mut config in rapier_config.iter_mut() {
+        if config.physics_pipeline_active == loading {
+            config.physics_pipeline_active = !loading;
+        }
+    }
+    if !loading {
+        return;
+    }
+
+    let progress = build.progress();
+    if let Ok(mut node) = bar_query.single_mut() {
+        node.width = Val::Px(progress * BAR_WIDTH_PX);
+    }
+    if let Ok(mut text) = text_query.single_mut() {
+        **text = format!("Generating terrain... {:.0}%", progress * 100.0);
+    }
+}
+
+/// Full-screen message shown instead of the game when the setup failed (`MapState::Failed`)
+#[derive(Component)]
+pub struct ErrorScreen;
+
+/// Spawn the error screen, with its own camera since the game camera is only spawned once the
+/// map is loaded
+pub fn setup_error_screen(mut commands: Commands, error: Res<StartupError>) {
+    commands.spawn((Camera2d, ErrorScreen));
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(12.0),
+            padding: UiRect::all(Val::Px(40.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.05, 0.06, 0.08)),
+        GlobalZIndex(10),
+        ErrorScreen,
+    )).with_children(|screen| {
+        screen.spawn((
+            Text::new("tiles3d cannot start"),
+            TextFont { font_size: 24.0, ..default() },
+            TextColor(Color::srgb(1.0, 0.3, 0.3)),
+        ));
+        screen.spawn((
+            Text::new(error.0.to_string()),
+            TextFont { font_size: 16.0, ..default() },
+            TextColor(Color::WHITE),
+            TextLayout::new_with_justify(JustifyText::Center),
+        ));
+        screen.spawn((
+            Text::new("Check the map path (config/tiles3d.toml or --map), then restart. Press Escape to quit."),
+            TextFont { font_size: 14.0, ..default() },
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+        ));
+    });
+}
+
+/// Escape closes the game from the error screen
+pub fn quit_from_error_screen(keys: Res<ButtonInput<KeyCode>>, mut exit: EventWriter<AppExit>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        exit.write(AppExit::error());
+    }
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::path::Path;
+use bevy::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::game_object::{EntitySubpixelPosition, ObjectTemplates};
+use crate::items::ItemStack;
+use crate::message_log::{LogCategory, LogMessage};
+use crate::persistence::DespawnPolicy;
+use crate::planisphere::Planisphere;
+use crate::props::{spawn_prop, PICKUP_TEMPLATE};
+use crate::terrain::{world_to_geo, TerrainCenter};
+
+/// Directory scanned at startup for loot tables (one `*.ron` file per table, keyed by file stem)
+pub const LOOT_TABLE_DIR: &str = "assets/loot";
+
+/// Extra requirement for a loot entry to be considered in a roll
+#[derive(Debug, Clone, Deserialize)]
+pub enum LootCondition {
+    Chance(f32),        // Entry only drops with this probability (0..1)
+    HasTag(String),     // Source must carry this tag (e.g. template tag "vegetation")
+    LacksTag(String),   // Source must not carry this tag
+}
+
+/// One weighted candidate of a loot table
+#[derive(Debug, Clone, Deserialize)]
+pub struct LootEntry {
+    pub item: String,                 // Item id in the ItemDefinitions registry
+    #[serde(default = "default_weight")]
+    pub weight: u32,                  // Relative weight among the eligible entries
+    #[serde(default = "default_count")]
+    pub count: (u32, u32),            // Inclusive (min, max) units dropped
+    #[serde(default)]
+    pub conditions: Vec<LootCondition>,
+}
+
+fn default_weight() -> u32 { 1 }
+fn default_count() -> (u32, u32) { (1, 1) }
+
+/// A weighted loot table rolled `rolls` times
+#[derive(Debug, Clone, Deserialize)]
+pub struct LootTable {
+    #[serde(default = "default_rolls")]
+    pub rolls: u32,
+    pub entries: Vec<LootEntry>,
+}
+
+fn default_rolls() -> u32 { 1 }
+
+/// Information about what is being looted, checked by entry conditions
+#[derive(Debug, Clone, Default)]
+pub struct LootContext {
+    pub tags: Vec<String>,
+}
+
+/// Registry of loot tables keyed by id
+#[derive(Resource, Default)]
+pub struct LootTables {
+    tables: HashMap<String, LootTable>,
+}
+
+impl LootTables {
+    /// Read every `*.ron` table in `dir`; files that fail to parse are reported and skipped
+    pub fn load(dir: &str) -> Self {
+        let mut registry = Self::default();
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                warn!("Could not read loot table directory {}: {}", dir, e);
+                return registry;
+            }
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("ron") {
+                continue;
+            }
+            let Some(id) = path.file_stem().and_then(|stem| stem.to_str()) else { continue; };
+            match load_loot_table_file(&path) {
+                Ok(table) => { registry.tables.insert(id.to_string(), table); }
+                Err(e) => warn!("Skipping loot table {}: {}", path.display(), e),
+            }
+        }
+        info!("Loaded {} loot tables from {}", registry.tables.len(), dir);
+        registry
+    }
+
+    pub fn get(&self, table_id: &str) -> Option<&LootTable> {
+        self.tables.get(table_id)
+    }
+
+    /// Roll a table and return the dropped stacks (empty for unknown tables)
+    pub fn roll_loot(&self, table_id: &str, context: &LootContext, rng: &mut impl Rng) -> Vec<ItemStack> {
+        let Some(table) = self.get(table_id) else {
+            warn!("Unknown loot table '{}'", table_id);
+            return Vec::new();
+        };
+        let mut drops: Vec<ItemStack> = Vec::new();
+        for _ in 0..table.rolls {
+            let eligible: Vec<&LootEntry> = table.entries.iter()
+                .filter(|entry| entry.weight > 0 && entry.conditions.iter().all(|condition| condition_holds(condition, context, rng)))
+                .collect();
+            let total_weight: u32 = eligible.iter().map(|entry| entry.weight).sum();
+            if total_weight == 0 {
+                continue;
+            }
+            let mut pick = rng.gen_range(0..total_weight);
+            let Some(entry) = eligible.iter().find(|entry| {
+                if pick < entry.weight { true } else { pick -= entry.weight; false }
+            }) else { continue; };
+
+            let (min, max) = (entry.count.0.min(entry.count.1), entry.count.0.max(entry.count.1));
+            let count = rng.gen_range(min..=max);
+            if count == 0 {
+                continue;
+            }
+            match drops.iter_mut().find(|stack| stack.item_id == entry.item) {
+                Some(stack) => stack.count += count,
+                None => drops.push(ItemStack { item_id: entry.item.clone(), count }),
+            }
+        }
+        drops
+    }
+}
+
+fn condition_holds(condition: &LootCondition, context: &LootContext, rng: &mut impl Rng) -> bool {
+    match condition {
+        LootCondition::Chance(probability) => rng.r#gen::<f32>() < *probability,
+        LootCondition::HasTag(tag) => context.tags.iter().any(|t| t == tag),
+        LootCondition::LacksTag(tag) => !context.tags.iter().any(|t| t == tag),
+    }
+}
+
+/// Parse a single loot table file
+pub fn load_loot_table_file(path: &Path) -> Result<LootTable, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str::<LootTable>(&content).map_err(|e| e.to_string())
+}
+
+/// Request to roll a loot table and scatter the result as pickable items.
+/// Sent by destroyed objects, harvested trees, killed agents...
+#[derive(Event, Debug, Clone)]
+pub struct LootDropEvent {
+    pub table_id: String,
+    pub position: Vec3,          // World position the items are scattered around
+    pub context: LootContext,
+}
+
+/// Roll the requested tables and spawn one pickup prop per dropped stack. Drops left behind
+/// go away once their subpixel is no longer rendered.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_loot_drops(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut loot_events: EventReader<LootDropEvent>,
+    loot_tables: Res<LootTables>,
+    object_templates: Res<ObjectTemplates>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    mut log: EventWriter<LogMessage>,
+) {
+    let mut rng = rand::thread_rng();
+    for event in loot_events.read() {
+        let drops = loot_tables.roll_loot(&event.table_id, &event.context, &mut rng);
+        if drops.is_empty() {
+            continue;
+        }
+
+        let names: Vec<String> = drops.iter().map(|stack| format!("{}× {}", stack.count, stack.item_id)).collect();
+        log.write(LogMessage::new(LogCategory::Pickup, format!("Loot dropped: {}", names.join(", "))));
+        for stack in drops {
+            // Scatter drops around the source so they don't overlap
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let offset = Vec3::new(angle.cos(), 0.0, angle.sin()) * rng.gen_range(0.5..1.5);
+            let position = event.position + offset;
+            match spawn_prop(&mut commands, &mut materials, &planisphere, &terrain_center, &object_templates,
+                             PICKUP_TEMPLATE, position, Some(stack)) {
+                Ok(entity) => {
+                    let geo_coords = world_to_geo(position, &planisphere, &terrain_center);
+                    let subpixel = planisphere.geo_to_subpixel(geo_coords.0, geo_coords.1);
+                    commands.entity(entity).insert((
+                        EntitySubpixelPosition { subpixel, geo_coords, world_pos: position, previous_subpixel: subpixel, ..default() },
+                        DespawnPolicy::outside_rendered_area(),
+                    ));
+                }
+                Err(e) => {
+                    log.write(LogMessage::new(LogCategory::Error, format!("Cannot drop loot: {}", e)));
+                }
+            }
+        }
+    }
+}
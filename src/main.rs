@@ -1,38 +1,87 @@
 // Import statements - bring in code from external crates and our own modules
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+// Bevy-free core from the library (lib.rs): constants, planisphere and tiling math
+use tiles3d::{config, planisphere, preprocess};
 // Module declarations - tell Rust about our other source files
-mod config;      // config.rs - centralized constants for terrain, player, camera, etc.
 mod terrain;     // terrain.rs - handles pure terrain mesh generation
-mod landscape;   // landscape.rs - handles trees, rocks, items, and decorative elements
+mod landscape;   // landscape.rs - collectible Item component
 mod camera;      // camera.rs - handles camera controls (zoom, rotation)
+mod daylight;    // daylight.rs - game time of day, sun and moon lights following the player's latitude, ambient light
+mod sky;         // sky.rs - procedural sky: gradient dome, star field turning with latitude, sun and moon discs
 mod player;      // player.rs - handles the player character
-mod planisphere; // planisphere.rs - handles geographic coordinate conversion and projections
 mod ui;          // ui.rs - handles user interface elements (like text, buttons, etc.)
 mod game_object; // game_object.rs - handles object definitions and spawning logic
+mod persistence; // persistence.rs - saves player-placed objects and removed landscape elements, respawning objects with the terrain
+mod scene_collider; // scene_collider.rs - builds object colliders from loaded glTF scene geometry
+mod items;       // items.rs - item definitions registry and stacked inventories
+mod loot;        // loot.rs - weighted loot tables and item drops
+mod building;    // building.rs - placing, undoing and dismantling player-built objects
+mod physics_lod; // physics_lod.rs - freezes distant dynamic bodies and parks distant landscape colliders to keep the physics step cheap
+mod destruction; // destruction.rs - hit points, projectile damage, debris and loot on destruction
+mod harvest;     // harvest.rs - E harvesting of trees and rocks into resource drops
+mod vfx;         // vfx.rs - appear/disappear effects (scale pops, particle bursts)
+mod animation;   // animation.rs - glTF animation clips declared by object templates
+mod sockets;     // sockets.rs - named attachment points on templates (torch in a hand, sign on a post)
+mod existence;   // existence.rs - despawns or hides objects whose ExistenceConditions no longer hold
+mod occupancy;   // occupancy.rs - which static object stands on each tile, for placement checks
+mod populate;    // populate.rs - scatters biome-weighted objects over the rendered terrain, skipping removed ones until they regrow
+mod props;       // props.rs - template-based world props whose tags drive pickups and visibility
+mod settings;    // settings.rs - user settings file (graphics, controls, camera tuning and presets)
+mod settings_menu; // settings_menu.rs - Escape menu editing the user settings
+mod notifications; // notifications.rs - Notify event and stacked toast messages
+mod loading;      // loading.rs - loading screen shown while the terrain is generated, error screen when the map is unusable
+mod seasons;      // seasons.rs - season state, seasonal terrain re-texturing and vegetation tint
+mod wind;         // wind.rs - global wind resource and vegetation sway
+mod weather;      // weather.rs - per-region weather state machine driving fog, rain, wind, wet terrain and rain sounds
+mod beacons;      // beacons.rs - pooled beacon spawning, culling and per-kind animations (pulse, spin, bob)
+mod grid_overlay; // grid_overlay.rs - G overlay outlining pixels and subpixels around the player
+mod trail;        // trail.rs - PathTrail component drawing ordered tiles as marker beacons and a ribbon
+mod measure;      // measure.rs - M tool measuring the great-circle distance between two tiles
+mod waypoints;    // waypoints.rs - persisted waypoints, their beacons, list panel and screen-edge indicators
+mod message_log;  // message_log.rs - scrollable history of gameplay events with category filters
+mod player_stats; // player_stats.rs - health, stamina and breath components
+mod hud;          // hud.rs - stat bars and hotbar at the bottom of the screen
+mod dev_panel;    // dev_panel.rs - F1 panel for live tuning of terrain, camera and spawn values
+mod occlusion;   // occlusion.rs - fades objects blocking the camera's view of the player
+mod minimap;     // minimap.rs - corner map drawn from planisphere data with item and marker dots
+mod compass;     // compass.rs - heading strip with cardinal directions and marker icons
+mod perf_overlay; // perf_overlay.rs - F2 diagnostics: FPS, frame time graph, entity/body/terrain counters
+mod cli;          // cli.rs - command-line options overriding the startup config
+mod startup_config; // startup_config.rs - config/tiles3d.toml startup parameters (map, spawn, terrain tuning)
+mod plugins;      // plugins.rs - game plugins (planisphere, terrain, objects, player, camera, UI) and the Tiles3dPlugins group
+mod world_save;   // world_save.rs - versioned world saves (player, terrain center), F10 slots and autosave on exit
+mod net_client;   // net_client.rs - connection to a tiles3d-server: input, remote players and replicated objects
+mod remote;       // remote.rs - --remote: HTTP/JSON API for game state queries and commands (teleport, spawn, weather)
+mod storage;      // storage.rs - settings and save files: the file system, or localStorage in the browser
+mod error;        // error.rs - GameError of the setup and assets, and the StartupError shown when the map is unusable
+mod placeholders; // placeholders.rs - checkerboard texture and cube standing in for optional assets that fail to load
+mod scripting;    // scripting.rs - rhai scripts of templates: use and tick handlers with a sandboxed game API
+mod export;       // export.rs - F11 and --export: rendered terrain and placed objects to glTF or OBJ
+mod geojson;      // geojson.rs - GeoJSON layers imported as beacons (points), trails (lines) and zone overlays (polygons)
+mod gpx;          // gpx.rs - GPX tracks drawn as trails, with navigation along a track
+mod music;        // music.rs - adaptive music cross-fading playlist tracks by context (exploring, danger, underwater, night), ducking
+mod time_control; // time_control.rs - simulation speed: pause, slow motion and fast-forward of the virtual clock
 
 
 
-// Import the specific functions we need from our modules
-// 'use' statements make functions available in this file without the module prefix
-use terrain::{create_terrain_gnomonic_rectangular, RenderedSubpixels, TriangleSubpixelMapping, TerrainCenter}; // Pure terrain mesh generation
-use camera::{setup_third_person_camera, update_third_person_camera, update_camera_light, handle_camera_zoom, handle_camera_height}; // Camera-related functions
-use player::{move_player, check_player_sensors, check_player_ground_sensors, terrain_recreation_system}; // Player-related functions
-use ui::{setup_ui, update_coordinate_display, handle_method_buttons, update_method_button_colors};
-use game_object::{setup_object_templates, cleanup_orphaned_overlays, setup_entity_overlays, 
-    update_entity_ui_overlays, setup_player}; // Game object spawning and management
-use crate::planisphere::Planisphere;
+use crate::terrain::TerrainCenter;
 
-/// Configuration for terrain generation and management
-#[derive(Resource)]
+/// Configuration for terrain generation and management, from the `[terrain]` table of the
+/// startup config (startup_config.rs)
+#[derive(Resource, Debug, Clone)]
 pub struct TerrainConfig {
     pub terrain_radius: usize,           // How far from center to generate terrain (in tiles)
     pub recreation_threshold: usize,     // Distance from center before recreating (auto-calculated as 1/4 radius)
     pub recreation_cooldown: f32,        // Minimum seconds between terrain recreations
     pub landscape_radius: usize,         // Radius for landscape elements (trees, rocks)
     pub item_radius: usize,              // Radius for collectible items
-    pub beacon_radius: usize,            // Radius for debug beacons
+    pub grid_radius: usize,              // Radius of the pixel/subpixel grid overlay (G), in tiles
     pub agent_search_radius: usize,      // Maximum search radius for agent respawning
+    pub relief: terrain::Relief,         // Vertex heights from the elevation map
+    pub lod: terrain::TerrainLod,        // Merged rings of distant terrain beyond the render distance
+    pub ocean: terrain::Ocean,           // Water surface over the sea pixels
+    pub distance_method: planisphere::DistanceMethod, // Shape of the rendered area: square, circle or diamond
 }
 
 /// Asset tracking for proper cleanup during terrain recreation
@@ -53,8 +102,12 @@ impl Default for TerrainConfig {
             recreation_cooldown: config::terrain::RECREATION_COOLDOWN_SECS,
             landscape_radius: config::terrain::LANDSCAPE_RADIUS,
             item_radius: 10,
-            beacon_radius: 5,
+            grid_radius: 5,
             agent_search_radius: 5,
+            relief: terrain::Relief::default(),
+            lod: terrain::TerrainLod::default(),
+            ocean: terrain::Ocean::default(),
+            distance_method: planisphere::DistanceMethod::default(),
         }
     }
 }
@@ -62,10 +115,6 @@ impl Default for TerrainConfig {
 
 
 
-impl Resource for Planisphere {
-    // This allows Planisphere to be used as a Bevy resource
-    // Resources are global data that can be accessed by systems
-}
 
 impl TerrainAssetTracker {
     /// Clean up old asset handles before creating new terrain
@@ -99,7 +148,7 @@ impl TerrainAssetTracker {
         
         // Note: We keep the texture atlas handle as it's reusable
         
-        println!("ASSET CLEANUP: Removed {} meshes and {} materials from asset system", 
+        debug!("Asset cleanup: removed {} meshes and {} materials from the asset system",
                  total_meshes_before, total_materials_before);
     }
 }
@@ -109,91 +158,81 @@ impl TerrainAssetTracker {
 /// Main function - the entry point of our Rust program
 /// This is where the program starts running when you execute it
 fn main() {
-    let sub_k = crate::config::terrain::SUB_K; // Number of subpixels in the vertical direction
-    let image_path = "assets/maps/sphere_texture.png";
-
-
-    // Initialize the Planisphere with the specified size and detail level
-    let mut planisphere = Planisphere::from_elevation_map(image_path, sub_k)
-        .expect("Failed to load elevation map");
+    // Command-line options override the startup config file
+    let cli = match cli::CliArgs::parse(std::env::args().skip(1)) {
+        Ok(cli) => cli,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if cli.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    // Create and configure the Bevy App (the main game engine instance)
+    let mut app = App::new();
+    if cli.headless {
+        // No window and no GPU: the schedule runs in a plain loop at 60 Hz
+        app.add_plugins(DefaultPlugins
+                .set(cli.log_plugin())
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: bevy::window::ExitCondition::DontExit,
+                    ..default()
+                })
+                .set(bevy::render::RenderPlugin {
+                    render_creation: bevy::render::settings::WgpuSettings { backends: None, ..default() }.into(),
+                    ..default()
+                })
+                .disable::<bevy::winit::WinitPlugin>())
+            .add_plugins(bevy::app::ScheduleRunnerPlugin::run_loop(std::time::Duration::from_secs_f64(1.0 / 60.0)));
+    } else {
+        // Add core Bevy plugins that provide essential functionality
+        app.add_plugins(DefaultPlugins.set(cli.log_plugin())) // Graphics, audio, input, windowing, logging, etc.
+            // Physics debug visualization (collision shapes, etc.), toggled from the developer panel
+            .add_plugins(RapierDebugRenderPlugin::default().disabled());
+    }
+    // Loaded once the logger is set up (DefaultPlugins) so that its messages show
+    let mut config = startup_config::StartupConfig::load(startup_config::STARTUP_CONFIG_PATH);
+    cli.apply(&mut config);
 
-    // Set the radius before making planisphere immutable
-    let planet_radius = config::terrain::PLANET_RADIUS as f64;
-    //let circumference = planet_radius/(planisphere_width * sub_k);
-    let radius = planet_radius;//circumference as f64 / (2.0 * std::f64::consts::PI);
-    planisphere.set_radius(radius);
+    let physics = &config.physics;
+    app
+        // Fixed-timestep simulation: gameplay systems in FixedUpdate and one physics step per run,
+        // tuned by the [physics] section of the startup config
+        .insert_resource(physics.fixed_time())
+        .insert_resource(physics.timestep_mode())
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default() // 3D physics with no custom user data
+            .with_custom_initialization(physics.context_initialization())
+            .in_fixed_schedule())
+        .add_plugins((
+            bevy::diagnostic::FrameTimeDiagnosticsPlugin::default(),    // FPS and frame time history
+            bevy::diagnostic::EntityCountDiagnosticsPlugin,             // Entity count
+        ))
 
-    // Compute initial subpixel from desired geographic coordinates
-    let initial_lon = crate::config::player::INITIAL_LON as f64;
-    let initial_lat = crate::config::player::INITIAL_LAT as f64;
-    let (iplayer, jplayer, kplayer) = planisphere.geo_to_subpixel(initial_lon, initial_lat);
-    let max_subpixel_distance = config::terrain::RADIUS;
+        // The game itself: planisphere, terrain, objects, player, camera and UI (plugins.rs)
+        .add_plugins(plugins::Tiles3dPlugins { config });
 
-    // Create and configure the Bevy App (the main game engine instance)
-    App::new()
-        // Add core Bevy plugins that provide essential functionality
-        .add_plugins(DefaultPlugins)              // Graphics, audio, input, windowing, etc.
-        
-        // Add physics simulation
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default()) // 3D physics with no custom user data
-        
+    // Terrain export once the first terrain is rendered, for external tools and documentation renders
+    if let Some(path) = cli.export {
+        let request = export::ExportTerrain { path: path.into(), objects: cli.export_objects };
+        app.insert_resource(export::StartupExport { request, exit: cli.headless });
+    }
 
-        // Uncomment the next line to see physics debug visualization (collision shapes, etc.)
-        // .add_plugins(RapierDebugRenderPlugin::default()) // Debug disabled for cleaner visuals
-        .insert_resource(planisphere)
-        .insert_resource(TerrainConfig::default()) // Terrain configuration settings
-        .insert_resource(TerrainAssetTracker::default()) // Asset tracking for cleanup
-        // Add shared resources for player tracking and terrain management
-         // Initialize Planisphere with size and detail
+    // Multiplayer: the server moves the player and replicates the others (src/bin/server.rs)
+    if let Some(address) = cli.connect {
+        let name = cli.name.or_else(|| std::env::var("USER").ok()).unwrap_or_else(|| "player".to_string());
+        app.add_plugins(plugins::NetClientPlugin { address, name });
+    }
 
-        //.init_resource::<PlayerSubpixelPosition>()
-        .insert_resource(terrain::TerrainCenter {
-            longitude: initial_lon,
-            latitude: initial_lat,
-            subpixel: (iplayer, jplayer, kplayer),
-            max_subpixel_distance,
-            last_recreation_time: -10.0,
-            distance_method: crate::planisphere::DistanceMethod::default(),
-            force_recreation: false,
-            rendered_subpixels: RenderedSubpixels::new(),                //Vec<(usize, usize, usize, [(f64, f64); 4])>,
-            triangle_mapping: TriangleSubpixelMapping::new(),
-        })
-        .insert_resource(RenderedSubpixels::new())
-        .insert_resource(TriangleSubpixelMapping::default())
-        
-        
-        // Systems that run once at startup (world setup)
-        .add_systems(Startup, setup_third_person_camera) // Setup camera, physics world, and UI
-        .add_systems(Startup, (setup_physics, setup_ui))
-        .add_systems(Startup, (setup_object_templates, setup_player).chain())
-        // Systems that run every frame (game loop) - split into groups to avoid tuple size limit
-        .add_systems(Update, terrain_recreation_system)     // Handle terrain recreation with asset cleanup and coordinate sync
-        .add_systems(Update, update_coordinate_display)
-        .add_systems(Update, (handle_method_buttons, update_method_button_colors))
-        .add_systems(Update, (
-            move_player,                    // Handle player movement with keyboard
-            check_player_sensors,           // Handle player item pickup detection
-            check_player_ground_sensors,    // Handle player ground collision detection
-            setup_entity_overlays,          // Setup UI overlays for entities
-            cleanup_orphaned_overlays,      // Clean up old UI overlays
-            update_entity_ui_overlays,
-        ))
-        .add_systems(Update, (
-            player::cast_ray_from_camera,
-            player::detect_mouse_clicks,
-            //track_entities_subpixel_position_raycast,
-            game_object::raycast_tile_locator_system,
-        ))
-        
-        .add_systems(Update, (
-            update_third_person_camera,     // Update camera to follow player
-            handle_camera_zoom,             // Handle mouse wheel zoom
-            handle_camera_height,           // Handle keyboard arrow keys for height
-            update_camera_light,            // Update light to follow camera
-        ))
+    // Remote control: game state queries and commands over HTTP for dashboards, bots and tests
+    if let Some(port) = cli.remote {
+        app.add_plugins(plugins::RemoteApiPlugin { port });
+    }
 
-        // Start the game loop - this runs until the window is closed
-        .run();
+    // Start the game loop - this runs until the window is closed
+    app.run();
 }
 
 /// Setup function for physics world and game objects
@@ -204,14 +243,11 @@ fn main() {
 /// - meshes: Storage for 3D shapes (meshes)
 /// - materials: Storage for surface materials (colors, textures, etc.)
 fn setup_physics(
-    mut commands: Commands,                              // Entity spawning and management
-    mut meshes: ResMut<Assets<Mesh>>,                   // 3D mesh asset storage
-    mut materials: ResMut<Assets<StandardMaterial>>,    // Material asset storage
     mut terrain_center: ResMut<TerrainCenter>,          // Terrain center resource
-    asset_server: Res<AssetServer>,                     // Asset server resource
-    planisphere: Res<planisphere::Planisphere>,
-    mut asset_tracker: ResMut<TerrainAssetTracker>,     // Asset tracker for cleanup
-    time: Res<Time>,                                    // Time resource for profiling
+    shared_planisphere: Res<terrain::SharedPlanisphere>,
+    mut terrain_build: ResMut<terrain::TerrainBuild>,  // Async terrain build
+    settings: Res<settings::Settings>,                 // Render distance of the first terrain build
+    terrain_config: Res<TerrainConfig>,                // Relief, LOD rings and distance method of the terrain
 ) {
     // Create a small planisphere for gnomonic projection terrain
 
@@ -220,26 +256,16 @@ fn setup_physics(
     //terrain_center.latitude = 0.0;  // 45° North
     //terrain_center.max_subpixel_distance = terrain_config.recreation_threshold; // Sync with TerrainConfig
     terrain_center.last_recreation_time = -10.0; // Allow immediate recreation if needed
+    terrain_center.max_subpixel_distance = settings.graphics.render_distance.max(1);
+    terrain_center.distance_method = terrain_config.distance_method;
     
     // setup_object_templates is now handled by Startup systems
 
-    create_terrain_gnomonic_rectangular(
-        &mut commands, 
-        &mut meshes, 
-        &mut materials,
-        &asset_server,            // Center latitude
-        &planisphere,    
-        &mut terrain_center,                    // Planisphere reference (mutable)
-        Some(&mut asset_tracker),               // Pass asset tracker for cleanup
-        &time                                   // Pass time resource for profiling
-    );
+    // The first terrain is built in the background behind the loading screen (loading.rs)
+    // and spawned by terrain::finish_terrain_build
+    terrain_build.start(&shared_planisphere, &terrain_center, terrain_config.relief, terrain_config.lod, None, true);
 
-    // Create the terrain center beacon at the gnomonic projection center
-    //create_terrain_center_beacon(
-    //    &mut commands,
-    //    &mut meshes,
-    //    &mut materials,
-    //);
+    // The terrain center beacon is spawned as a debug prop by populate::populate_rendered_subpixels
     
     // Create the agents (autonomous entities that move around the terrain)
     // This spawns 5 agents in a grid pattern on the terrain
@@ -250,18 +276,8 @@ fn setup_physics(
 
     //spawn_dynamic_object_with_raycast_with_ui(&mut commands, &mut meshes, &mut materials, Some(&planisphere), &terrain_center, object_definition);
 
-    // Create collectible items in the world
-    // Currently creates a single "Magic Stone" that agents can pick up
-    //create_items(&mut commands, &mut meshes, &mut materials, &planisphere, terrain_center.center_lon, terrain_center.center_lat, &terrain_config, &triangle_mapping);
-    
-    // Create landscape elements (decorative objects like stones, trees, rocks)
-    //create_landscape_elements(&mut commands, &mut meshes, &mut materials, &planisphere, terrain_center.center_lon, terrain_center.center_lat, &terrain_config, &triangle_mapping, Some(&mut asset_tracker));
-    
-    // Create debug beacons to visualize tile structure and player position
-    //create_debug_beacons(&mut commands, &mut meshes, &mut materials, &planisphere, terrain_center.center_lon, terrain_center.center_lat, &terrain_config);
-    
-    // Create the player tile beacon that follows the player's current tile
-    //create_player_tile_beacon(&mut commands, &mut meshes, &mut materials);
+    // Collectible items, landscape elements and debug beacons are props (props.rs), scattered
+    // over the rendered terrain by populate::populate_rendered_subpixels
 }
 
 // Additional explanation for beginners:
@@ -277,6 +293,7 @@ fn setup_physics(
 // SYSTEM SCHEDULING:
 // - Startup systems run once when the game starts
 // - Update systems run every frame (usually 60 times per second)
+// - FixedUpdate systems run at a fixed rate (physics, movement, stats), zero or more times a frame
 // - Systems can read and modify components and resources
 //
 // PHYSICS INTEGRATION:
@@ -0,0 +1,149 @@
+use std::collections::HashSet;
+use bevy::prelude::*;
+
+use crate::notifications::Notify;
+use crate::planisphere::Planisphere;
+use crate::player::CursorTarget;
+use crate::trail::{great_circle_tiles, PathTrail};
+use crate::waypoints::format_distance;
+
+/// Color of the measured path
+const TRAIL_COLOR: Color = Color::srgb(1.0, 0.9, 0.2);
+
+/// Tile picked by the measuring tool
+#[derive(Debug, Clone, Copy)]
+pub struct MeasurePoint {
+    pub tile: (usize, usize, usize),
+    pub geo: (f64, f64),   // (longitude, latitude) of the tile in degrees, see `subpixel_to_geo`
+}
+
+/// Great-circle distance between the two picked tiles and the number of tiles it crosses
+#[derive(Debug, Clone, Copy)]
+pub struct Measurement {
+    pub distance: f64,
+    pub tiles: usize,
+}
+
+/// Measuring mode (M): left clicks pick two tiles instead of dropping stones
+#[derive(Resource, Debug, Default)]
+pub struct MeasureTool {
+    pub active: bool,
+    pub start: Option<MeasurePoint>,
+    pub end: Option<MeasurePoint>,
+    pub result: Option<Measurement>,
+}
+
+/// Entity carrying the `PathTrail` of the current measurement
+#[derive(Component)]
+pub struct MeasurePath;
+
+/// Text showing the measuring state and result
+#[derive(Component)]
+pub struct MeasureReadout;
+
+/// Measure the great circle between two positions; tiles are counted by sampling the path a few
+/// times per tile and collecting the distinct subpixels
+pub fn measure_path(planisphere: &Planisphere, from: (f64, f64), to: (f64, f64)) -> Measurement {
+    let distance = planisphere.geodesic_distance(from, to);
+    let samples = (distance / planisphere.mean_tile_size * crate::config::measure::SAMPLES_PER_TILE).ceil() as usize;
+    let samples = samples.clamp(2, crate::config::measure::MAX_SAMPLES);
+    let tiles: HashSet<(usize, usize, usize)> = planisphere.great_circle_points(from, to, samples).into_iter()
+        .map(|(lon, lat)| planisphere.geo_to_subpixel(lon, lat))
+        .collect();
+    Measurement { distance, tiles: tiles.len() }
+}
+
+/// Toggle measuring with M; while measuring, the first click picks the start tile and the second
+/// one the end tile, drawing the trail and reporting the distance. A third click starts over.
+#[allow(clippy::too_many_arguments)]
+pub fn handle_measure_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    mut tool: ResMut<MeasureTool>,
+    cursor: Res<CursorTarget>,
+    planisphere: Res<Planisphere>,
+    path_query: Query<Entity, With<MeasurePath>>,
+    mut notify: EventWriter<Notify>,
+) {
+    let clear_path = |commands: &mut Commands| {
+        for entity in path_query.iter() {
+            commands.entity(entity).despawn();
+        }
+    };
+
+    if keyboard_input.just_pressed(KeyCode::KeyM) {
+        *tool = MeasureTool { active: !tool.active, ..default() };
+        clear_path(&mut commands);
+        notify.write(Notify::new(if tool.active { "Measuring: click two tiles" } else { "Measuring off" }));
+        return;
+    }
+    if !tool.active || !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some((i, j, k)) = cursor.tile else { return; };
+    let point = MeasurePoint { tile: (i, j, k), geo: planisphere.subpixel_to_geo(i, j, k) };
+
+    clear_path(&mut commands);
+    match tool.start {
+        Some(start) if tool.end.is_none() => {
+            let measurement = measure_path(&planisphere, start.geo, point.geo);
+            let markers = measurement.tiles.clamp(2, crate::config::measure::MAX_TRAIL_MARKERS);
+            let tiles = great_circle_tiles(&planisphere, start.geo, point.geo, markers);
+            commands.spawn((MeasurePath, PathTrail::new(tiles, TRAIL_COLOR)));
+            info!("Measured {:?} -> {:?}: {:.1} m, {} tiles", start.tile, point.tile, measurement.distance, measurement.tiles);
+            tool.end = Some(point);
+            tool.result = Some(measurement);
+        }
+        _ => {
+            commands.spawn((MeasurePath, PathTrail::new(vec![point.tile], TRAIL_COLOR)));
+            *tool = MeasureTool { active: true, start: Some(point), ..default() };
+        }
+    }
+}
+
+/// Spawn the (hidden) measuring readout below the compass
+pub fn setup_measure_readout(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(60.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Visibility::Hidden,
+        MeasureReadout,
+    )).with_children(|row| {
+        row.spawn((
+            Node { padding: UiRect::axes(Val::Px(8.0), Val::Px(3.0)), ..default() },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            Text::new(""),
+            TextFont { font_size: 14.0, ..default() },
+            TextColor(Color::srgb(1.0, 0.9, 0.2)),
+        ));
+    });
+}
+
+/// Show the measuring state while the tool is active
+pub fn update_measure_readout(
+    tool: Res<MeasureTool>,
+    mut readout: Query<(&mut Visibility, &Children), With<MeasureReadout>>,
+    mut texts: Query<&mut Text>,
+) {
+    if !tool.is_changed() {
+        return;
+    }
+    let Ok((mut visibility, children)) = readout.single_mut() else { return; };
+    *visibility = if tool.active { Visibility::Inherited } else { Visibility::Hidden };
+    let message = match (tool.start, tool.result) {
+        (_, Some(result)) => format!("Distance: {} ({} tiles) - click to measure again, M to exit", format_distance(result.distance), result.tiles),
+        (Some(_), None) => "Measuring: click the second tile".to_string(),
+        (None, None) => "Measuring: click the first tile".to_string(),
+    };
+    for child in children.iter() {
+        if let Ok(mut text) = texts.get_mut(child) {
+            **text = message.clone();
+        }
+    }
+}
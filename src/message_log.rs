@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+use bevy::prelude::*;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+
+/// Kind of gameplay event recorded in the message log, used for filtering
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogCategory {
+    Pickup,
+    Combat,
+    Building,
+    Terrain,
+    Waypoint,
+    Error,
+}
+
+impl LogCategory {
+    pub const ALL: [LogCategory; 6] = [LogCategory::Pickup, LogCategory::Combat, LogCategory::Building, LogCategory::Terrain, LogCategory::Waypoint, LogCategory::Error];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LogCategory::Pickup => "Pickups",
+            LogCategory::Combat => "Combat",
+            LogCategory::Building => "Building",
+            LogCategory::Terrain => "Terrain",
+            LogCategory::Waypoint => "Waypoints",
+            LogCategory::Error => "Errors",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            LogCategory::Pickup => Color::srgb(1.0, 0.85, 0.1),
+            LogCategory::Combat => Color::srgb(0.95, 0.45, 0.35),
+            LogCategory::Building => Color::srgb(0.5, 0.8, 1.0),
+            LogCategory::Terrain => Color::srgb(0.5, 0.9, 0.5),
+            LogCategory::Waypoint => Color::srgb(0.2, 0.9, 0.9),
+            LogCategory::Error => Color::srgb(1.0, 0.3, 0.3),
+        }
+    }
+}
+
+/// Record a gameplay event in the message log
+#[derive(Event, Debug, Clone)]
+pub struct LogMessage {
+    pub category: LogCategory,
+    pub message: String,
+}
+
+impl LogMessage {
+    pub fn new(category: LogCategory, message: impl Into<String>) -> Self {
+        Self { category, message: message.into() }
+    }
+}
+
+/// One line of the message log
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub time_secs: f32,   // Time since startup
+    pub category: LogCategory,
+    pub message: String,
+}
+
+/// Event history collected from `LogMessage`, oldest first, capped at
+/// `config::message_log::MAX_ENTRIES`
+#[derive(Resource, Debug, Default)]
+pub struct MessageLog {
+    pub entries: VecDeque<LogEntry>,
+    pub hidden: Vec<LogCategory>,   // Categories filtered out of the panel
+}
+
+impl MessageLog {
+    pub fn is_shown(&self, category: LogCategory) -> bool {
+        !self.hidden.contains(&category)
+    }
+
+    pub fn toggle(&mut self, category: LogCategory) {
+        if let Some(index) = self.hidden.iter().position(|hidden| *hidden == category) {
+            self.hidden.remove(index);
+        } else {
+            self.hidden.push(category);
+        }
+    }
+}
+
+/// Root node of the message log panel, toggled with F4
+#[derive(Component)]
+pub struct MessageLogPanel;
+
+/// Scrollable list holding one text per shown entry
+#[derive(Component)]
+pub struct MessageLogList;
+
+/// Category filter button of the panel
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LogFilterButton(pub LogCategory);
+
+/// Store `LogMessage` events in the message log. Errors are still echoed to stderr.
+pub fn collect_log_messages(
+    time: Res<Time>,
+    mut events: EventReader<LogMessage>,
+    mut log: ResMut<MessageLog>,
+) {
+    for event in events.read() {
+        if event.category == LogCategory::Error {
+            error!("{}", event.message);
+        }
+        log.entries.push_back(LogEntry {
+            time_secs: time.elapsed_secs(),
+            category: event.category,
+            message: event.message.clone(),
+        });
+        while log.entries.len() > crate::config::message_log::MAX_ENTRIES {
+            log.entries.pop_front();
+        }
+    }
+}
+
+/// Spawn the (hidden) message log panel on the right, above the minimap
+pub fn setup_message_log(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(crate::config::minimap::SIZE_PX + 20.0),
+            width: Val::Px(crate::config::message_log::WIDTH_PX),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Visibility::Hidden,
+        MessageLogPanel,
+    )).with_children(|panel| {
+        panel.spawn(Node { column_gap: Val::Px(4.0), flex_wrap: FlexWrap::Wrap, ..default() }).with_children(|filters| {
+            for category in LogCategory::ALL {
+                filters.spawn((
+                    Button,
+                    Node { padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)), ..default() },
+                    BackgroundColor(Color::srgba(0.25, 0.25, 0.25, 0.9)),
+                    LogFilterButton(category),
+                )).with_children(|button| {
+                    button.spawn((Text::new(category.label()), TextFont { font_size: 11.0, ..default() }, TextColor(category.color())));
+                });
+            }
+        });
+        panel.spawn((
+            Node {
+                height: Val::Px(crate::config::message_log::HEIGHT_PX),
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::scroll_y(),
+                ..default()
+            },
+            ScrollPosition::default(),
+            Interaction::default(),
+            MessageLogList,
+        ));
+    });
+}
+
+/// Toggle the message log with F4
+pub fn toggle_message_log(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<&mut Visibility, With<MessageLogPanel>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F4) {
+        return;
+    }
+    if let Ok(mut visibility) = panel_query.single_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Apply filter clicks, scroll the list with the mouse wheel while hovered, and rebuild the
+/// list when the log, the filters or the panel visibility change (scrolled to the newest entry)
+pub fn update_message_log(
+    mut commands: Commands,
+    mut log: ResMut<MessageLog>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    panel_query: Query<Ref<Visibility>, With<MessageLogPanel>>,
+    mut list_query: Query<(Entity, &Interaction, &mut ScrollPosition), With<MessageLogList>>,
+    mut filter_buttons: Query<(&Interaction, &LogFilterButton, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    let Ok(visibility) = panel_query.single() else { return; };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+    let Ok((list, list_interaction, mut scroll)) = list_query.single_mut() else { return; };
+
+    for (interaction, button, mut background) in filter_buttons.iter_mut() {
+        if *interaction == Interaction::Pressed {
+            log.toggle(button.0);
+        }
+        background.0 = if log.is_shown(button.0) { Color::srgba(0.25, 0.25, 0.25, 0.9) } else { Color::srgba(0.08, 0.08, 0.08, 0.9) };
+    }
+
+    if *list_interaction == Interaction::Hovered {
+        for event in mouse_wheel.read() {
+            let lines = match event.unit {
+                MouseScrollUnit::Line => event.y * 16.0,
+                MouseScrollUnit::Pixel => event.y,
+            };
+            scroll.offset_y = (scroll.offset_y - lines).max(0.0);
+        }
+    }
+
+    if !log.is_changed() && !visibility.is_changed() {
+        return;
+    }
+    commands.entity(list).despawn_related::<Children>();
+    commands.entity(list).with_children(|list| {
+        for entry in log.entries.iter().filter(|entry| log.is_shown(entry.category)) {
+            let seconds = entry.time_secs as u32;
+            list.spawn((
+                Text::new(format!("[{:02}:{:02}] {}", seconds / 60, seconds % 60, entry.message)),
+                TextFont { font_size: 12.0, ..default() },
+                TextColor(entry.category.color()),
+            ));
+        }
+    });
+    // Clamped to the content height by the UI layout
+    scroll.offset_y = f32::MAX;
+}
@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::landscape::Item;
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::terrain::{select_texture_from_rgba, world_to_geo, TerrainCenter};
+
+/// Minimap color of each terrain texture index (see `select_texture_from_rgba`)
+const TEXTURE_COLORS: [[u8; 3]; 19] = [
+    [30, 60, 140],   // Deep water
+    [110, 85, 60],   // Dirt
+    [150, 150, 80],  // Dry grass
+    [90, 140, 60],   // East grass
+    [70, 130, 50],   // Grass
+    [90, 120, 90],   // Green stone
+    [210, 230, 240], // Ice
+    [200, 70, 30],   // Lava
+    [70, 50, 45],    // Lava stone
+    [60, 100, 60],   // Moss
+    [85, 110, 85],   // Mossy stone
+    [80, 135, 75],   // North grass
+    [150, 145, 135], // Paved stone
+    [120, 115, 110], // Raw stone
+    [220, 205, 150], // Sand
+    [240, 245, 250], // Snow
+    [120, 150, 55],  // South grass
+    [50, 100, 170],  // Water
+    [85, 145, 70],   // West grass
+];
+
+/// Shows an entity on the minimap and the compass (agents, waypoints...). Collectible items are
+/// shown on the minimap without it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct MinimapMarker(pub Color);
+
+/// Image the minimap is drawn into
+#[derive(Resource)]
+pub struct Minimap {
+    pub image: Handle<Image>,
+    pub timer: Timer,   // How often the terrain and markers are redrawn
+}
+
+/// UI node holding the minimap image; markers are spawned as its children
+#[derive(Component)]
+pub struct MinimapPanel;
+
+/// Marker dot currently drawn on the minimap
+#[derive(Component)]
+pub struct MinimapDot;
+
+/// Create the minimap image and its panel (bottom-right corner)
+pub fn setup_minimap(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let resolution = crate::config::minimap::RESOLUTION;
+    let image = images.add(Image::new_fill(
+        Extent3d { width: resolution, height: resolution, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        bevy::render::render_asset::RenderAssetUsages::default(),
+    ));
+
+    let size = crate::config::minimap::SIZE_PX;
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            width: Val::Px(size),
+            height: Val::Px(size),
+            border: UiRect::all(Val::Px(2.0)),
+            overflow: Overflow::clip(),
+            ..default()
+        },
+        BorderColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        BorderRadius::MAX, // Circular
+        ImageNode::new(image.clone()),
+        MinimapPanel,
+    )).with_children(|panel| {
+        // The player always sits in the center, facing up
+        panel.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(size / 2.0 - 5.0),
+                top: Val::Px(size / 2.0 - 5.0),
+                width: Val::Px(10.0),
+                height: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::WHITE),
+            BorderRadius::MAX,
+        ));
+    });
+
+    commands.insert_resource(Minimap {
+        image,
+        timer: Timer::from_seconds(crate::config::minimap::UPDATE_SECS, TimerMode::Repeating),
+    });
+}
+
+/// World axes of the minimap: screen right and screen up, rotating with the player
+fn minimap_axes(facing_angle: f32) -> (Vec3, Vec3) {
+    let rotation = Quat::from_rotation_y(facing_angle);
+    (rotation * Vec3::X, rotation * Vec3::NEG_Z)
+}
+
+/// Redraw the terrain around the player from planisphere data and place markers for items and
+/// `MinimapMarker` entities within range
+#[allow(clippy::too_many_arguments)]
+pub fn update_minimap(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut minimap: ResMut<Minimap>,
+    mut images: ResMut<Assets<Image>>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    player_query: Query<(&Transform, &Player)>,
+    panel_query: Query<Entity, With<MinimapPanel>>,
+    dots: Query<Entity, With<MinimapDot>>,
+    items: Query<&GlobalTransform, With<Item>>,
+    markers: Query<(&GlobalTransform, &MinimapMarker)>,
+) {
+    if !minimap.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok((player_transform, player)) = player_query.single() else { return; };
+    let Ok(panel) = panel_query.single() else { return; };
+    let Some(image) = images.get_mut(&minimap.image) else { return; };
+    let Some(data) = image.data.as_mut() else { return; };
+
+    let resolution = crate::config::minimap::RESOLUTION;
+    let range = crate::config::minimap::RANGE;
+    let (right, up) = minimap_axes(player.facing_angle);
+    let player_pos = player_transform.translation;
+
+    for y in 0..resolution {
+        for x in 0..resolution {
+            // Pixel -> offset from the player in world units
+            let u = (x as f32 + 0.5) / resolution as f32 * 2.0 - 1.0;
+            let v = 1.0 - (y as f32 + 0.5) / resolution as f32 * 2.0;
+            let world = player_pos + right * (u * range) + up * (v * range);
+
+            let (lon, lat) = world_to_geo(world, &planisphere, &terrain_center);
+            let color = if lon.is_nan() || lat.is_nan() {
+                [0, 0, 0]
+            } else {
+                let (i, j, k) = planisphere.geo_to_subpixel(lon, lat);
+                let (red, green, blue, alpha) = planisphere.get_rgba_at_subpixel(i as i32, j as i32, k);
+                TEXTURE_COLORS[select_texture_from_rgba(red, green, blue, alpha).min(TEXTURE_COLORS.len() - 1)]
+            };
+
+            let offset = ((y * resolution + x) * 4) as usize;
+            data[offset..offset + 3].copy_from_slice(&color);
+            data[offset + 3] = 255;
+        }
+    }
+
+    for dot in dots.iter() {
+        commands.entity(dot).despawn();
+    }
+
+    let size = crate::config::minimap::SIZE_PX;
+    let item_color = Color::srgb(1.0, 0.85, 0.1);
+    let marked = items.iter().map(|transform| (transform.translation(), item_color))
+        .chain(markers.iter().map(|(transform, marker)| (transform.translation(), marker.0)));
+    for (position, color) in marked {
+        let offset = position - player_pos;
+        let (u, v) = (offset.dot(right) / range, offset.dot(up) / range);
+        if u * u + v * v > 1.0 {
+            continue; // Outside the circular map
+        }
+        let dot = commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px((u + 1.0) / 2.0 * size - 3.0),
+                top: Val::Px((1.0 - v) / 2.0 * size - 3.0),
+                width: Val::Px(6.0),
+                height: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(color),
+            BorderRadius::MAX,
+            MinimapDot,
+        )).id();
+        commands.entity(panel).add_child(dot);
+    }
+}
@@ -0,0 +1,232 @@
+use std::path::Path;
+
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::config::music::*;
+use crate::daylight::SunPosition;
+use crate::game_object::EntitySubpixelPosition;
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::props::{tags, Prop};
+use crate::settings::Settings;
+
+/// Music playlist, listing the tracks of each context
+pub const PLAYLIST_PATH: &str = "assets/music/playlist.ron";
+
+/// Daylight under which the night tracks play
+const NIGHT_DAYLIGHT: f32 = 0.3;
+
+/// What the music plays for, by priority: underwater, then danger, then night, then exploring
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum MusicContext {
+    Exploring,
+    Night,
+    Danger,       // Hostile props within `config::music::DANGER_RADIUS`
+    Underwater,   // The player is on a sea tile
+}
+
+/// Track of the playlist, e.g. `(path: "music/night_1.ogg", contexts: [Night], volume: 0.8)`
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlaylistTrack {
+    pub path: String,                 // Asset path, under `assets/`
+    pub contexts: Vec<MusicContext>,
+    #[serde(default = "default_track_volume")]
+    pub volume: f32,                  // Mix level of the track, 0.0 to 1.0
+}
+
+fn default_track_volume() -> f32 { 1.0 }
+fn default_crossfade_secs() -> f32 { 4.0 }
+fn default_duck_volume() -> f32 { 0.3 }
+
+/// Music tracks and mixing, loaded from `PLAYLIST_PATH`
+#[derive(Resource, Debug, Clone, Deserialize)]
+pub struct MusicPlaylist {
+    pub tracks: Vec<PlaylistTrack>,
+    #[serde(default = "default_crossfade_secs")]
+    pub crossfade_secs: f32,          // Duration of the cross-fade between two tracks
+    #[serde(default = "default_duck_volume")]
+    pub duck_volume: f32,             // Music level while ducked, 0.0 to 1.0
+}
+
+impl Default for MusicPlaylist {
+    fn default() -> Self {
+        Self { tracks: Vec::new(), crossfade_secs: default_crossfade_secs(), duck_volume: default_duck_volume() }
+    }
+}
+
+impl MusicPlaylist {
+    /// Read the playlist; a missing or broken file is reported and leaves the game silent.
+    /// Tracks whose file is missing are skipped (the game ships without music).
+    pub fn load(path: &str) -> Self {
+        let mut playlist = match load_playlist_file(Path::new(path)) {
+            Ok(playlist) => playlist,
+            Err(e) => {
+                warn!("Could not load music playlist {}: {}", path, e);
+                return Self::default();
+            }
+        };
+        playlist.tracks.retain(|track| {
+            let present = Path::new("assets").join(&track.path).exists();
+            if !present {
+                info!("Skipping music track {}: no file under assets/", track.path);
+            }
+            present
+        });
+        info!("Loaded {} music tracks from {}", playlist.tracks.len(), path);
+        playlist
+    }
+
+    /// Indices of the tracks of `context`, the exploring ones when it has none
+    fn tracks_for(&self, context: MusicContext) -> Vec<usize> {
+        let of = |context: MusicContext| -> Vec<usize> {
+            (0..self.tracks.len()).filter(|&n| self.tracks[n].contexts.contains(&context)).collect()
+        };
+        let tracks = of(context);
+        if tracks.is_empty() { of(MusicContext::Exploring) } else { tracks }
+    }
+}
+
+/// Parse a playlist file
+pub fn load_playlist_file(path: &Path) -> Result<MusicPlaylist, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str::<MusicPlaylist>(&content).map_err(|e| e.to_string())
+}
+
+/// Lower the music to the playlist's `duck_volume` for `secs`, for dialogue lines and
+/// anything else speaking over it
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DuckMusic {
+    pub secs: f32,
+}
+
+/// Context the music follows and ducking state
+#[derive(Resource, Debug, Default)]
+pub struct MusicState {
+    pub context: Option<MusicContext>,       // None until the first track starts
+    pending: Option<(MusicContext, f32)>,    // New context and how long it has held
+    played: usize,                           // Tracks started so far, to cycle through a context's tracks
+    duck_secs: f32,                          // Ducking time left
+    ducking: f32,                            // 0.0 (full volume) to 1.0 (fully ducked)
+}
+
+/// Playing music track: fades in while it is the current one, then out and despawns
+#[derive(Component, Debug)]
+pub struct MusicTrack {
+    volume: f32,    // Track mix level from the playlist
+    level: f32,     // Fade level, 0.0 to 1.0
+    current: bool,
+}
+
+/// Context for the player's surroundings
+fn current_context(
+    tile: (usize, usize, usize),
+    position: Vec3,
+    planisphere: &Planisphere,
+    sun: &SunPosition,
+    props: &Query<(&Prop, &GlobalTransform)>,
+) -> MusicContext {
+    if planisphere.is_sea(tile.0, tile.1) {
+        return MusicContext::Underwater;
+    }
+    let threatened = props.iter().any(|(prop, transform)| {
+        prop.has_tag(tags::HOSTILE) && transform.translation().distance(position) < DANGER_RADIUS
+    });
+    if threatened {
+        MusicContext::Danger
+    } else if sun.daylight < NIGHT_DAYLIGHT {
+        MusicContext::Night
+    } else {
+        MusicContext::Exploring
+    }
+}
+
+/// Follow the player's context once it has held for `CONTEXT_HOLD_SECS`, cross-fading to one
+/// of its tracks (in turn)
+#[allow(clippy::too_many_arguments)]
+pub fn update_music_context(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    asset_server: Res<AssetServer>,
+    playlist: Res<MusicPlaylist>,
+    planisphere: Res<Planisphere>,
+    sun: Res<SunPosition>,
+    mut state: ResMut<MusicState>,
+    player_query: Query<(&Transform, &EntitySubpixelPosition), With<Player>>,
+    props: Query<(&Prop, &GlobalTransform)>,
+    mut tracks: Query<&mut MusicTrack>,
+) {
+    if playlist.tracks.is_empty() {
+        return;
+    }
+    let Ok((transform, position)) = player_query.single() else { return; };
+    let context = current_context(position.subpixel, transform.translation, &planisphere, &sun, &props);
+    if state.context == Some(context) {
+        state.pending = None;
+        return;
+    }
+    let held = match state.pending {
+        Some((pending, secs)) if pending == context => secs + time.delta_secs(),
+        _ => 0.0,
+    };
+    if state.context.is_some() && held < CONTEXT_HOLD_SECS {
+        state.pending = Some((context, held));
+        return;
+    }
+
+    state.context = Some(context);
+    state.pending = None;
+    let candidates = playlist.tracks_for(context);
+    if candidates.is_empty() {
+        return; // No exploring tracks to fall back on: keep the current music
+    }
+    let track = &playlist.tracks[candidates[state.played % candidates.len()]];
+    state.played += 1;
+    debug!("Music for {:?}: {}", context, track.path);
+    for mut playing in tracks.iter_mut() {
+        playing.current = false;
+    }
+    commands.spawn((
+        AudioPlayer::new(asset_server.load(track.path.clone())),
+        PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+        MusicTrack { volume: track.volume, level: 0.0, current: true },
+    ));
+}
+
+/// Cross-fade the tracks, duck them while `DuckMusic` lasts and apply the music volume setting
+pub fn mix_music(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    settings: Res<Settings>,
+    playlist: Res<MusicPlaylist>,
+    mut state: ResMut<MusicState>,
+    mut duck_events: EventReader<DuckMusic>,
+    mut tracks: Query<(Entity, &mut MusicTrack, Option<&mut AudioSink>)>,
+) {
+    let dt = time.delta_secs();
+    for event in duck_events.read() {
+        state.duck_secs = state.duck_secs.max(event.secs);
+    }
+    state.duck_secs = (state.duck_secs - dt).max(0.0);
+    let duck_target = if state.duck_secs > 0.0 { 1.0 } else { 0.0 };
+    let duck_step = dt / DUCK_FADE_SECS;
+    state.ducking = if state.ducking < duck_target {
+        (state.ducking + duck_step).min(duck_target)
+    } else {
+        (state.ducking - duck_step).max(duck_target)
+    };
+    let duck = 1.0 - (1.0 - playlist.duck_volume) * state.ducking;
+
+    let fade_step = dt / playlist.crossfade_secs.max(0.01);
+    for (entity, mut track, sink) in tracks.iter_mut() {
+        track.level = (track.level + if track.current { fade_step } else { -fade_step }).clamp(0.0, 1.0);
+        if !track.current && track.level <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(mut sink) = sink {
+            sink.set_volume(Volume::Linear(track.level * track.volume * settings.audio.music_volume * duck));
+        }
+    }
+}
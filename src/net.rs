@@ -0,0 +1,149 @@
+//! Client/server protocol: newline-delimited JSON messages over TCP, shared by the game (client)
+//! and the dedicated server (`src/bin/server.rs`).
+//!
+//! The server is authoritative for player positions: clients send the ground velocity they want
+//! and the server integrates it on the planisphere, then sends snapshots of the players around
+//! each client's terrain center. Placed objects are sent to the clients whose area they are in.
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpStream;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::net::{MAX_MESSAGE_BYTES, MAX_SEND_BACKLOG_BYTES};
+
+/// Bumped when messages change; clients with another version are rejected
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Message from a client to the server
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    /// First message of a connection
+    Hello { version: u32, name: String },
+    /// Desired ground velocity (east, north) in world units per second, and facing angle in radians
+    Input { velocity: (f32, f32), facing: f32 },
+    /// Object placed by the player; `local_ref` comes back in `ServerMessage::ObjectPlaced`
+    PlaceObject { local_ref: u64, object: NetObject },
+    RemoveObject { id: u64 },
+}
+
+/// Message from the server to a client
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    /// Reply to `Hello`: the world to run (same seed and map give the same world) and the spawn point
+    Welcome { version: u32, client_id: u32, seed: u64, spawn: (f64, f64) },
+    Rejected { reason: String },
+    /// Authoritative state of the players around the client's terrain center, the client included
+    Snapshot { tick: u64, terrain_center: (usize, usize, usize), players: Vec<PlayerState> },
+    /// Server id of an object the client placed
+    ObjectPlaced { local_ref: u64, id: u64 },
+    /// Object placed by another client entered the client's area
+    ObjectSpawned { object: NetObject },
+    /// Object removed, or out of the client's area
+    ObjectRemoved { id: u64 },
+}
+
+/// Replicated player
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PlayerState {
+    pub id: u32,
+    pub name: String,
+    pub geo: (f64, f64),   // (longitude, latitude) in degrees
+    pub facing: f32,
+}
+
+/// Replicated placed object; `id` is 0 until the server assigns one
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NetObject {
+    pub id: u64,
+    pub template: String,
+    pub tile: (usize, usize, usize),
+    pub rotation: [f32; 4],   // Orientation quaternion (x, y, z, w)
+}
+
+/// Non-blocking message stream over a TCP connection. Outgoing messages are buffered until the
+/// socket accepts them (`flush`), incoming bytes until a full line arrives (`receive`). Both
+/// buffers are bounded: a message line over `MAX_MESSAGE_BYTES`, or a peer leaving more than
+/// `MAX_SEND_BACKLOG_BYTES` unread, is an error and the connection should be dropped.
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> std::io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self { stream, incoming: Vec::new(), outgoing: Vec::new() })
+    }
+
+    pub fn peer(&self) -> String {
+        self.stream.peer_addr().map_or_else(|_| "unknown peer".to_string(), |address| address.to_string())
+    }
+
+    /// Queue a message and write as much of the queue as the socket takes
+    pub fn send<T: Serialize>(&mut self, message: &T) -> std::io::Result<()> {
+        serde_json::to_writer(&mut self.outgoing, message).map_err(std::io::Error::other)?;
+        self.outgoing.push(b'\n');
+        self.flush()?;
+        if self.outgoing.len() > MAX_SEND_BACKLOG_BYTES {
+            return Err(std::io::Error::other(format!("peer left over {} bytes unread", MAX_SEND_BACKLOG_BYTES)));
+        }
+        Ok(())
+    }
+
+    /// Write queued bytes until the socket would block
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => { self.outgoing.drain(..written); }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Messages received since the last call; an error means the connection is closed or broken
+    pub fn receive<T: DeserializeOwned>(&mut self) -> std::io::Result<Vec<T>> {
+        let mut buffer = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+            if self.incoming.len() > MAX_MESSAGE_BYTES {
+                break;   // The rest stays in the socket until the next call
+            }
+        }
+        let mut messages = Vec::new();
+        while let Some(end) = self.incoming.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.incoming.drain(..=end).collect();
+            let message = serde_json::from_slice(&line[..end]).map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
+            messages.push(message);
+        }
+        if self.incoming.len() > MAX_MESSAGE_BYTES {
+            return Err(std::io::Error::new(ErrorKind::InvalidData, format!("message over {} bytes", MAX_MESSAGE_BYTES)));
+        }
+        Ok(messages)
+    }
+}
+
+/// Geographic position reached from `geo` after moving at `velocity` (east, north, world units
+/// per second) for `dt` seconds on a planet of `radius` world units
+pub fn step_geo(geo: (f64, f64), velocity: (f64, f64), dt: f64, radius: f64) -> (f64, f64) {
+    let units_per_degree = radius * std::f64::consts::PI / 180.0;
+    let latitude = (geo.1 + velocity.1 * dt / units_per_degree).clamp(-89.9, 89.9);
+    let east_scale = units_per_degree * latitude.to_radians().cos().max(1e-3);
+    let longitude = (geo.0 + velocity.0 * dt / east_scale + 540.0).rem_euclid(360.0) - 180.0;
+    (longitude, latitude)
+}
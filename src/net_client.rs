@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use tiles3d::net::{ClientMessage, Connection, NetObject, PlayerState, ServerMessage, PROTOCOL_VERSION};
+
+use crate::building::BuildEvent;
+use crate::game_object::{spawn_template_scene, create_collider_from_shape, EntitySubpixelPosition, ObjectDefinitionBuilder,
+                         ObjectShape, ObjectTemplates};
+use crate::notifications::Notify;
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::terrain::{geo_to_world, TerrainCenter, TerrainReady};
+use crate::terrain::texture::WorldSeed;
+
+/// Seconds to wait for the server when connecting
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+
+/// Connection to a `tiles3d-server`, with the replicated players and objects
+#[derive(Resource)]
+pub struct NetClient {
+    connection: Option<Connection>,             // None once disconnected
+    pub client_id: Option<u32>,                 // Assigned by the server's `Welcome`
+    input_timer: Timer,
+    placed: HashMap<Entity, u64>,               // Local placed object -> server id
+    pending: HashMap<u64, Entity>,              // `local_ref` sent with `PlaceObject` -> local entity
+    remote_players: HashMap<u32, Entity>,
+    remote_objects: HashMap<u64, (NetObject, Option<Entity>)>, // Server id -> object and its entity
+}
+
+impl NetClient {
+    /// Connect to `address` (`host:port`) and introduce the player as `name`
+    pub fn connect(address: &str, name: &str) -> std::io::Result<Self> {
+        let socket_address = address.to_socket_addrs()?.next()
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "no address"))?;
+        let stream = TcpStream::connect_timeout(&socket_address, Duration::from_secs(CONNECT_TIMEOUT_SECS))?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&ClientMessage::Hello { version: PROTOCOL_VERSION, name: name.to_string() })?;
+        Ok(Self {
+            connection: Some(connection),
+            client_id: None,
+            input_timer: Timer::from_seconds((1.0 / crate::config::net::TICK_HZ) as f32, TimerMode::Repeating),
+            placed: HashMap::new(),
+            pending: HashMap::new(),
+            remote_players: HashMap::new(),
+            remote_objects: HashMap::new(),
+        })
+    }
+
+    /// Send a message, closing the connection when it fails
+    fn send(&mut self, message: &ClientMessage, notify: &mut EventWriter<Notify>) {
+        let Some(connection) = self.connection.as_mut() else { return; };
+        if let Err(e) = connection.send(message) {
+            self.disconnect(&e.to_string(), notify);
+        }
+    }
+
+    fn disconnect(&mut self, reason: &str, notify: &mut EventWriter<Notify>) {
+        if self.connection.take().is_some() {
+            warn!("Disconnected from the server: {}", reason);
+            notify.write(Notify::new("Disconnected from the server").with_color(Color::srgb(1.0, 0.3, 0.3)));
+        }
+    }
+}
+
+/// Other player, positioned from the server snapshots
+#[derive(Component, Debug)]
+pub struct RemotePlayer;
+
+/// Object placed by another player, replicated by the server
+#[derive(Component, Debug)]
+pub struct RemoteObject;
+
+/// Apply the server messages: remote players and objects, and the authoritative position of the
+/// local player, which snaps back when it drifts more than `config::net::CORRECTION_DISTANCE_TILES`
+#[allow(clippy::too_many_arguments)]
+pub fn receive_server_messages(
+    mut commands: Commands,
+    mut client: ResMut<NetClient>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    world_seed: Res<WorldSeed>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    object_templates: Res<ObjectTemplates>,
+    mut players: Query<(&mut Transform, &EntitySubpixelPosition), With<Player>>,
+    mut remote_transforms: Query<&mut Transform, (With<RemotePlayer>, Without<Player>)>,
+    mut notify: EventWriter<Notify>,
+) {
+    let client = client.as_mut();
+    let Some(connection) = client.connection.as_mut() else { return; };
+    let messages = match connection.receive::<ServerMessage>() {
+        Ok(messages) => messages,
+        Err(e) => {
+            client.disconnect(&e.to_string(), &mut notify);
+            return;
+        }
+    };
+    for message in messages {
+        match message {
+            ServerMessage::Welcome { client_id, seed, .. } => {
+                info!("Joined the server as client {}", client_id);
+                if seed != world_seed.0 {
+                    warn!("The server runs world seed {}, this game {}: start with --seed {} for the same world", seed, world_seed.0, seed);
+                }
+                client.client_id = Some(client_id);
+                notify.write(Notify::new("Connected to the server"));
+            }
+            ServerMessage::Rejected { reason } => client.disconnect(&format!("rejected, {}", reason), &mut notify),
+            ServerMessage::Snapshot { players: states, .. } => {
+                apply_snapshot(&mut commands, client, &states, &mut meshes, &mut materials, &planisphere, &terrain_center,
+                               &mut players, &mut remote_transforms);
+            }
+            ServerMessage::ObjectPlaced { local_ref, id } => {
+                if let Some(entity) = client.pending.remove(&local_ref) {
+                    client.placed.insert(entity, id);
+                }
+            }
+            ServerMessage::ObjectSpawned { object } => {
+                let entity = spawn_remote_object(&mut commands, &mut materials, &planisphere, &terrain_center, &object_templates, &object);
+                client.remote_objects.insert(object.id, (object, entity));
+            }
+            ServerMessage::ObjectRemoved { id } => {
+                if let Some((_, Some(entity))) = client.remote_objects.remove(&id) {
+                    crate::vfx::request_despawn(&mut commands, entity);
+                }
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_snapshot(
+    commands: &mut Commands,
+    client: &mut NetClient,
+    states: &[PlayerState],
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    planisphere: &Planisphere,
+    terrain_center: &TerrainCenter,
+    players: &mut Query<(&mut Transform, &EntitySubpixelPosition), With<Player>>,
+    remote_transforms: &mut Query<&mut Transform, (With<RemotePlayer>, Without<Player>)>,
+) {
+    let correction = crate::config::net::CORRECTION_DISTANCE_TILES * planisphere.mean_tile_size;
+    for state in states {
+        let position = geo_to_world(state.geo.0, state.geo.1, planisphere, terrain_center);
+        if Some(state.id) == client.client_id {
+            let Ok((mut transform, subpixel_position)) = players.single_mut() else { continue; };
+            if planisphere.geodesic_distance(subpixel_position.geo_coords, state.geo) > correction {
+                transform.translation.x = position.x;
+                transform.translation.z = position.z;
+            }
+            continue;
+        }
+        match client.remote_players.get(&state.id).and_then(|entity| remote_transforms.get_mut(*entity).ok()) {
+            Some(mut transform) => {
+                transform.translation.x = position.x;
+                transform.translation.z = position.z;
+                transform.rotation = Quat::from_rotation_y(state.facing);
+            }
+            None => {
+                let entity = commands.spawn((
+                    Mesh3d(meshes.add(Capsule3d::new(0.3, 0.8))),
+                    MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 1.0))),
+                    Transform::from_translation(position + Vec3::Y).with_rotation(Quat::from_rotation_y(state.facing)),
+                    RemotePlayer,
+                    Name::new(format!("Remote player {}", state.name)),
+                )).id();
+                client.remote_players.insert(state.id, entity);
+            }
+        }
+    }
+    // Players missing from the snapshot left or are out of range
+    client.remote_players.retain(|id, entity| {
+        let present = states.iter().any(|state| state.id == *id);
+        if !present {
+            commands.entity(*entity).despawn();
+        }
+        present
+    });
+}
+
+fn spawn_remote_object(
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    planisphere: &Planisphere,
+    terrain_center: &TerrainCenter,
+    object_templates: &ObjectTemplates,
+    object: &NetObject,
+) -> Option<Entity> {
+    let Ok(template) = object_templates.get(&object.template) else {
+        warn!("Cannot spawn remote object {}: unknown template '{}'", object.id, object.template);
+        return None;
+    };
+    let entity = spawn_template_scene(
+        commands,
+        materials,
+        planisphere,
+        terrain_center,
+        template,
+        object.tile,
+        template.y_offset,
+        ObjectDefinitionBuilder::from_template(template).fixed().build(),
+        (
+            RigidBody::Fixed,
+            create_collider_from_shape(&ObjectShape::Cube { size: Vec3::ONE }),
+            EntitySubpixelPosition { subpixel: object.tile, previous_subpixel: object.tile, ..default() },
+            RemoteObject,
+        ),
+    );
+    let rotation = Quat::from_array(object.rotation);
+    commands.entity(entity).entry::<Transform>().and_modify(move |mut transform| {
+        transform.rotation = rotation;
+    });
+    Some(entity)
+}
+
+/// Respawn the remote objects at their new world position once the terrain is recentred
+pub fn reposition_remote_objects(
+    mut commands: Commands,
+    mut ready_events: EventReader<TerrainReady>,
+    mut client: ResMut<NetClient>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    object_templates: Res<ObjectTemplates>,
+) {
+    if ready_events.read().last().is_none() {
+        return;
+    }
+    for (object, entity) in client.remote_objects.values_mut() {
+        if let Some(entity) = entity.take() {
+            commands.entity(entity).despawn();
+        }
+        *entity = spawn_remote_object(&mut commands, &mut materials, &planisphere, &terrain_center, &object_templates, object);
+    }
+}
+
+/// Send the local player's ground velocity and facing to the server at `config::net::TICK_HZ`
+pub fn send_player_input(
+    time: Res<Time>,
+    mut client: ResMut<NetClient>,
+    players: Query<(&Velocity, &Player)>,
+    mut notify: EventWriter<Notify>,
+) {
+    if !client.input_timer.tick(time.delta()).just_finished() || client.client_id.is_none() {
+        return;
+    }
+    let Ok((velocity, player)) = players.single() else { return; };
+    // World X is east and Z north around the terrain center (gnomonic projection)
+    let input = ClientMessage::Input { velocity: (velocity.linvel.x, velocity.linvel.z), facing: player.facing_angle };
+    client.send(&input, &mut notify);
+}
+
+/// Forward the player's placements and removals to the server
+pub fn send_build_events(
+    mut client: ResMut<NetClient>,
+    mut build_events: EventReader<BuildEvent>,
+    mut notify: EventWriter<Notify>,
+) {
+    for event in build_events.read() {
+        match event {
+            BuildEvent::Placed { entity, template, tile } => {
+                let local_ref = entity.to_bits();
+                client.pending.insert(local_ref, *entity);
+                let object = NetObject { id: 0, template: template.clone(), tile: *tile, rotation: Quat::IDENTITY.to_array() };
+                client.send(&ClientMessage::PlaceObject { local_ref, object }, &mut notify);
+            }
+            BuildEvent::Removed { entity, .. } => {
+                if let Some(id) = client.placed.remove(entity) {
+                    client.send(&ClientMessage::RemoveObject { id }, &mut notify);
+                }
+            }
+        }
+    }
+}
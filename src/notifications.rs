@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+
+/// Show a short message to the player as a toast in the top-right corner
+#[derive(Event, Debug, Clone)]
+pub struct Notify {
+    pub message: String,
+    pub color: Color,   // Text color, white by default
+}
+
+impl Notify {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into(), color: Color::WHITE }
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Column the toasts are stacked in, newest at the bottom
+#[derive(Component)]
+pub struct ToastStack;
+
+/// One toast; fades out during the last `config::notifications::FADE_SECS` of its lifetime
+#[derive(Component, Debug)]
+pub struct Toast {
+    pub timer: Timer,
+}
+
+pub fn setup_notifications(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            top: Val::Px(10.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::FlexEnd,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        ToastStack,
+    ));
+}
+
+/// Turn `Notify` events into toasts, dropping the oldest beyond `config::notifications::MAX_TOASTS`
+pub fn spawn_toasts(
+    mut commands: Commands,
+    mut events: EventReader<Notify>,
+    stack_query: Query<Entity, With<ToastStack>>,
+    toasts: Query<(Entity, &Toast)>,
+) {
+    let Ok(stack) = stack_query.single() else { return; };
+    let mut live: Vec<(Entity, f32)> = toasts.iter().map(|(entity, toast)| (entity, toast.timer.elapsed_secs())).collect();
+    for Notify { message, color } in events.read() {
+        info!("{}", message);
+        let toast = commands.spawn((
+            Node { padding: UiRect::axes(Val::Px(10.0), Val::Px(5.0)), ..default() },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            Toast { timer: Timer::from_seconds(crate::config::notifications::TOAST_SECS, TimerMode::Once) },
+        )).with_children(|toast| {
+            toast.spawn((Text::new(message.clone()), TextFont { font_size: 14.0, ..default() }, TextColor(*color)));
+        }).id();
+        commands.entity(stack).add_child(toast);
+        live.push((toast, 0.0));
+    }
+
+    if live.len() > crate::config::notifications::MAX_TOASTS {
+        // Oldest first
+        live.sort_by(|a, b| b.1.total_cmp(&a.1));
+        for (entity, _) in live.iter().take(live.len() - crate::config::notifications::MAX_TOASTS) {
+            commands.entity(*entity).despawn();
+        }
+    }
+}
+
+/// Age toasts, fade them out at the end of their lifetime and remove expired ones
+pub fn update_toasts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut toasts: Query<(Entity, &mut Toast, &mut BackgroundColor, &Children)>,
+    mut texts: Query<&mut TextColor>,
+) {
+    for (entity, mut toast, mut background, children) in toasts.iter_mut() {
+        toast.timer.tick(time.delta());
+        if toast.timer.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let alpha = (toast.timer.remaining_secs() / crate::config::notifications::FADE_SECS).min(1.0);
+        background.0.set_alpha(0.7 * alpha);
+        for child in children.iter() {
+            if let Ok(mut color) = texts.get_mut(child) {
+                color.0.set_alpha(alpha);
+            }
+        }
+    }
+}
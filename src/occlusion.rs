@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use bevy::time::Real;
+use bevy_rapier3d::prelude::*;
+use bevy_rapier3d::plugin::context::systemparams::ReadRapierContext;
+
+use crate::camera::ThirdPersonCamera;
+use crate::game_object::ObjectDefinition;
+use crate::player::Player;
+
+/// Object standing between the camera and the player, faded while it blocks the view
+#[derive(Component, Debug)]
+pub struct CameraOccluder {
+    pub alpha: f32,      // Current opacity of its meshes
+    pub blocking: bool,  // Still on the camera -> player line this frame
+}
+
+/// Translucent copy of a mesh material, swapped in while its object occludes the player
+#[derive(Component, Debug)]
+pub struct FadedMaterial {
+    original: Handle<StandardMaterial>,
+    faded: Handle<StandardMaterial>,
+}
+
+/// Fade trees and structures between the camera and the player, and restore them once the line
+/// of sight clears. Materials are shared between instances, so occluding meshes get their own
+/// translucent copy for as long as they are faded.
+#[allow(clippy::too_many_arguments)]
+pub fn fade_camera_occluders(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    rapier_context: ReadRapierContext,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    camera_query: Query<&GlobalTransform, With<ThirdPersonCamera>>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    objects: Query<(), (With<ObjectDefinition>, Without<Player>)>,
+    mut occluders: Query<(Entity, &mut CameraOccluder)>,
+    mut meshes: Query<(&mut MeshMaterial3d<StandardMaterial>, Option<&FadedMaterial>)>,
+) {
+    let Ok(camera_transform) = camera_query.single() else { return; };
+    let Ok((player_entity, player_transform)) = player_query.single() else { return; };
+    let Ok(ctx) = rapier_context.single() else { return; };
+
+    for (_, mut occluder) in occluders.iter_mut() {
+        occluder.blocking = false;
+    }
+
+    // Every object hit between the camera and the player's head
+    let camera_pos = camera_transform.translation();
+    let to_player = player_transform.translation + Vec3::new(0.0, 1.0, 0.0) - camera_pos;
+    let mut blocking = Vec::new();
+    let filter = QueryFilter::new().exclude_rigid_body(player_entity).exclude_sensors();
+    ctx.intersections_with_ray(camera_pos, to_player.normalize_or_zero(), to_player.length(), true, filter, |hit, _| {
+        // Scene colliders sit on children, fade the whole object
+        let object = std::iter::once(hit).chain(parents.iter_ancestors(hit)).find(|entity| objects.contains(*entity));
+        if let Some(object) = object {
+            blocking.push(object);
+        }
+        true // Keep looking for further occluders
+    });
+
+    for object in blocking {
+        match occluders.get_mut(object) {
+            Ok((_, mut occluder)) => occluder.blocking = true,
+            Err(_) => {
+                commands.entity(object).insert(CameraOccluder { alpha: 1.0, blocking: true });
+            }
+        }
+    }
+
+    let step = crate::config::camera::OCCLUDER_FADE_SPEED * time.delta_secs();
+    for (entity, mut occluder) in occluders.iter_mut() {
+        let target = if occluder.blocking { crate::config::camera::OCCLUDER_ALPHA } else { 1.0 };
+        occluder.alpha = if occluder.alpha < target {
+            (occluder.alpha + step).min(target)
+        } else {
+            (occluder.alpha - step).max(target)
+        };
+        let restore = !occluder.blocking && occluder.alpha >= 1.0;
+
+        for mesh in children.iter_descendants(entity) {
+            let Ok((mut material, faded)) = meshes.get_mut(mesh) else { continue; };
+            match (faded, restore) {
+                (Some(faded), true) => {
+                    materials.remove(&faded.faded);
+                    material.0 = faded.original.clone();
+                    commands.entity(mesh).remove::<FadedMaterial>();
+                }
+                (Some(faded), false) => {
+                    if let Some(faded) = materials.get_mut(&faded.faded) {
+                        faded.base_color.set_alpha(occluder.alpha);
+                    }
+                }
+                (None, false) => {
+                    let Some(original) = materials.get(&material.0) else { continue; };
+                    let mut copy = original.clone();
+                    copy.alpha_mode = AlphaMode::Blend;
+                    copy.base_color.set_alpha(occluder.alpha);
+                    let faded = materials.add(copy);
+                    commands.entity(mesh).insert(FadedMaterial { original: material.0.clone(), faded: faded.clone() });
+                    material.0 = faded;
+                }
+                (None, true) => {}
+            }
+        }
+
+        if restore {
+            commands.entity(entity).remove::<CameraOccluder>();
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+
+/// Static object standing on a subpixel; keeps other objects from being placed there
+#[derive(Component, Debug, Clone, Copy)]
+pub struct OccupiesTile(pub (usize, usize, usize));
+
+/// Which entity occupies each subpixel, kept in sync with `OccupiesTile` components
+#[derive(Resource, Default, Debug)]
+pub struct TileOccupancy {
+    tiles: HashMap<(usize, usize, usize), Entity>,
+    entity_tiles: HashMap<Entity, (usize, usize, usize)>,
+}
+
+impl TileOccupancy {
+    pub fn is_tile_free(&self, tile: (usize, usize, usize)) -> bool {
+        !self.tiles.contains_key(&tile)
+    }
+
+    pub fn occupant(&self, tile: (usize, usize, usize)) -> Option<Entity> {
+        self.tiles.get(&tile).copied()
+    }
+
+    pub fn occupy(&mut self, tile: (usize, usize, usize), entity: Entity) {
+        self.release(entity);
+        self.tiles.insert(tile, entity);
+        self.entity_tiles.insert(entity, tile);
+    }
+
+    pub fn release(&mut self, entity: Entity) {
+        if let Some(tile) = self.entity_tiles.remove(&entity) {
+            // Only free the tile if this entity still holds it
+            if self.tiles.get(&tile) == Some(&entity) {
+                self.tiles.remove(&tile);
+            }
+        }
+    }
+}
+
+/// Register newly spawned tile occupants and free the tiles of removed ones
+pub fn update_tile_occupancy(
+    mut occupancy: ResMut<TileOccupancy>,
+    added_query: Query<(Entity, &OccupiesTile), Changed<OccupiesTile>>,
+    mut removed: RemovedComponents<OccupiesTile>,
+) {
+    for entity in removed.read() {
+        occupancy.release(entity);
+    }
+    for (entity, occupied) in added_query.iter() {
+        if let Some(other) = occupancy.occupant(occupied.0).filter(|other| *other != entity) {
+            warn!("tile {:?} already occupied by {:?}, now also by {:?}", occupied.0, other, entity);
+        }
+        occupancy.occupy(occupied.0, entity);
+    }
+}
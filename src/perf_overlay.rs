@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use bevy::diagnostic::{DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin};
+use bevy_rapier3d::prelude::*;
+
+use crate::terrain::TerrainCenter;
+
+/// Number of bars in the frame time graph (most recent frames)
+const GRAPH_BARS: usize = 60;
+/// Frame time (ms) drawn as a full-height bar
+const GRAPH_MAX_MS: f64 = 50.0;
+const GRAPH_HEIGHT_PX: f32 = 40.0;
+
+/// Root node of the diagnostics overlay, toggled with F2
+#[derive(Component)]
+pub struct PerfOverlay;
+
+#[derive(Component)]
+pub struct PerfOverlayText;
+
+/// Bar of the frame time graph; index 0 is the oldest frame
+#[derive(Component)]
+pub struct FrameTimeBar(pub usize);
+
+/// Spawn the (hidden) overlay in the bottom-left corner
+pub fn setup_perf_overlay(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(6.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        Visibility::Hidden,
+        PerfOverlay,
+    )).with_children(|panel| {
+        panel.spawn((
+            Text::new(""),
+            TextFont { font_size: 12.0, ..default() },
+            TextColor(Color::WHITE),
+            PerfOverlayText,
+        ));
+        panel.spawn(Node {
+            height: Val::Px(GRAPH_HEIGHT_PX),
+            align_items: AlignItems::FlexEnd,
+            column_gap: Val::Px(1.0),
+            ..default()
+        }).with_children(|graph| {
+            for index in 0..GRAPH_BARS {
+                graph.spawn((
+                    Node { width: Val::Px(3.0), height: Val::Px(0.0), ..default() },
+                    BackgroundColor(Color::srgb(0.3, 0.9, 0.3)),
+                    FrameTimeBar(index),
+                ));
+            }
+        });
+    });
+}
+
+/// Toggle the diagnostics overlay with F2
+pub fn toggle_perf_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay_query: Query<&mut Visibility, With<PerfOverlay>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F2) {
+        return;
+    }
+    if let Ok(mut visibility) = overlay_query.single_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+/// Refresh the counters and the frame time graph while the overlay is shown
+pub fn update_perf_overlay(
+    diagnostics: Res<DiagnosticsStore>,
+    terrain_center: Res<TerrainCenter>,
+    overlay_query: Query<&Visibility, With<PerfOverlay>>,
+    bodies: Query<&RigidBody>,
+    mut text_query: Query<&mut Text, With<PerfOverlayText>>,
+    mut bars: Query<(&FrameTimeBar, &mut Node, &mut BackgroundColor)>,
+) {
+    if overlay_query.single().map_or(true, |visibility| *visibility == Visibility::Hidden) {
+        return;
+    }
+    let Ok(mut text) = text_query.single_mut() else { return; };
+
+    let fps = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS).and_then(|fps| fps.smoothed()).unwrap_or(0.0);
+    let frame_time = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME);
+    let frame_ms = frame_time.and_then(|frame_time| frame_time.smoothed()).unwrap_or(0.0);
+    let entities = diagnostics.get(&EntityCountDiagnosticsPlugin::ENTITY_COUNT).and_then(|count| count.value()).unwrap_or(0.0);
+    let (dynamic_bodies, fixed_bodies) = bodies.iter().fold((0, 0), |(dynamic, fixed), body| match body {
+        RigidBody::Fixed => (dynamic, fixed + 1),
+        _ => (dynamic + 1, fixed),
+    });
+    let stats = &terrain_center.stats;
+
+    **text = format!(
+        "FPS: {fps:.0} ({frame_ms:.2} ms)\nEntities: {entities:.0}\nBodies: {dynamic_bodies} dynamic, {fixed_bodies} fixed\n\
+         Terrain: {} subpixels + {} LOD quads, {} vertices, {} triangles\nTerrain build: {:.1} ms (#{})",
+        stats.subpixel_count, stats.merged_quad_count, stats.vertex_count, stats.triangle_count, stats.generation_ms, stats.generations
+    );
+
+    // Newest frame on the right
+    let history: Vec<f64> = frame_time.map(|frame_time| frame_time.values().copied().collect()).unwrap_or_default();
+    let start = history.len().saturating_sub(GRAPH_BARS);
+    let recent = &history[start..];
+    let offset = GRAPH_BARS - recent.len();
+    for (bar, mut node, mut color) in bars.iter_mut() {
+        let ms = bar.0.checked_sub(offset).and_then(|index| recent.get(index)).copied().unwrap_or(0.0);
+        let fraction = (ms / GRAPH_MAX_MS).min(1.0) as f32;
+        node.height = Val::Px(fraction * GRAPH_HEIGHT_PX);
+        // Green under 60 fps, yellow under 30 fps, red beyond
+        color.0 = if ms < 16.7 {
+            Color::srgb(0.3, 0.9, 0.3)
+        } else if ms < 33.3 {
+            Color::srgb(0.9, 0.8, 0.2)
+        } else {
+            Color::srgb(0.9, 0.3, 0.2)
+        };
+    }
+}
@@ -0,0 +1,414 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::building::PlacedObject;
+use crate::game_object::{spawn_template_scene, create_collider_from_shape, ObjectDefinitionBuilder, ObjectShape,
+                         ObjectTemplates, EntitySubpixelPosition, RaycastTileLocator, ObjectEvent, IntoWorldPosition};
+use crate::planisphere::Planisphere;
+use crate::populate::LandscapeKey;
+use crate::terrain::TerrainCenter;
+
+/// File in the save directory where placed/dropped objects and removed landscape elements are
+/// stored between sessions
+pub const WORLD_OBJECTS_SAVE_FILE: &str = "world_objects.json";
+
+/// Height above the tile centre at which dynamic objects are respawned (they fall onto the terrain)
+const RESPAWN_DROP_HEIGHT: f32 = 5.0;
+
+/// Marks a live entity whose existence is backed by a geographic record.
+/// Spawning code only needs to attach this (plus `EntitySubpixelPosition`);
+/// `sync_persistent_objects` registers it on the next frame.
+#[derive(Component, Debug, Clone)]
+pub struct PersistentObject {
+    pub template: String,   // Template registry name used to respawn the object
+    pub dynamic: bool,      // Respawn with a dynamic rigid body instead of a fixed one
+}
+
+/// Serialized description of one persistent object
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ObjectRecord {
+    pub id: u64,
+    pub template: String,
+    pub tile: (usize, usize, usize),  // (i, j, k) subpixel the object rests on
+    pub rotation: [f32; 4],           // Orientation quaternion (x, y, z, w)
+    pub dynamic: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placed: Option<PlacedObject>, // Built by the player: restored so it can be undone, dismantled and exported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub despawn: Option<DespawnPolicy>, // Policy left when the object was parked or streamed out, restored on respawn
+}
+
+/// Landscape element removed by the player (harvested, destroyed)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LandscapeRemoval {
+    pub key: LandscapeKey,
+    pub removed_at: u64,   // Unix time in seconds, for regrowth
+}
+
+/// On-disk layout of the world objects file
+#[derive(Serialize, Deserialize, Default)]
+struct WorldObjectsFile {
+    next_id: u64,
+    objects: Vec<ObjectRecord>,
+    #[serde(default)]
+    removed_landscape: Vec<LandscapeRemoval>,
+}
+
+/// All persistent objects, whether or not they are currently spawned
+#[derive(Resource, Default)]
+pub struct PersistedObjects {
+    pub records: HashMap<u64, ObjectRecord>,
+    path: String,                            // File the records were loaded from and are saved to
+    removed_landscape: HashMap<LandscapeKey, u64>, // Removed element -> unix time of the removal
+    next_id: u64,
+    live: HashMap<Entity, u64>,              // Spawned entity -> record id
+    synced_area: Option<((usize, usize, usize), usize)>, // (terrain center, rendered count) the live set was computed for
+    dirty: bool,                             // Records changed since the last save
+    autosave_timer: Timer,
+}
+
+impl PersistedObjects {
+    /// Load records from `path`, starting empty if the file is missing or unreadable
+    pub fn load(path: &str) -> Self {
+        let mut persisted = Self {
+            path: path.to_string(),
+            autosave_timer: Timer::from_seconds(10.0, TimerMode::Repeating),
+            ..default()
+        };
+        let Ok(content) = crate::storage::read_to_string(path) else { return persisted; };
+        match serde_json::from_str::<WorldObjectsFile>(&content) {
+            Ok(file) => {
+                persisted.next_id = file.next_id;
+                persisted.records = file.objects.into_iter().map(|record| (record.id, record)).collect();
+                persisted.removed_landscape = file.removed_landscape.into_iter().map(|removal| (removal.key, removal.removed_at)).collect();
+                info!("Loaded {} persistent objects and {} landscape removals from {}",
+                         persisted.records.len(), persisted.removed_landscape.len(), path);
+            }
+            Err(e) => warn!("Ignoring unreadable world objects file {}: {}", path, e),
+        }
+        persisted
+    }
+
+    /// Write all records to `path`, creating the parent directory if needed
+    pub fn save(&mut self, path: &str) -> std::io::Result<()> {
+        let mut objects: Vec<ObjectRecord> = self.records.values().cloned().collect();
+        objects.sort_by_key(|record| record.id);
+        let mut removed_landscape: Vec<LandscapeRemoval> = self.removed_landscape.iter()
+            .map(|(key, removed_at)| LandscapeRemoval { key: key.clone(), removed_at: *removed_at })
+            .collect();
+        removed_landscape.sort_by_key(|removal| (removal.key.tile, removal.key.dart));
+        let file = WorldObjectsFile { next_id: self.next_id, objects, removed_landscape };
+        let json = serde_json::to_string_pretty(&file).map_err(std::io::Error::other)?;
+        crate::storage::write(path, &json)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Id of the record backing a live entity, once `sync_persistent_objects` registered it.
+    /// Ids grow with each new record, across sessions.
+    pub fn record_id(&self, entity: Entity) -> Option<u64> {
+        self.live.get(&entity).copied()
+    }
+
+    /// Forget the live entity backing a record without deleting the record, saving the policy
+    /// that fired (with what is left of a lifetime). The record is respawned the next time the
+    /// terrain is recentred over its tile, unless the policy still holds (see `DespawnPolicy::keeps_parked`).
+    pub fn park(&mut self, entity: Entity, policy: &DespawnPolicy) -> bool {
+        let Some(id) = self.live.remove(&entity) else { return false; };
+        if let Some(record) = self.records.get_mut(&id) {
+            record.despawn = Some(policy.clone());
+            self.dirty = true;
+        }
+        true
+    }
+
+    /// Whether a scattered landscape element was removed and has not regrown yet
+    pub fn is_landscape_removed(&self, key: &LandscapeKey) -> bool {
+        self.removed_landscape.contains_key(key)
+    }
+
+    /// Remember that a scattered landscape element was removed, so it is not scattered again
+    pub fn remove_landscape(&mut self, key: LandscapeKey) {
+        self.removed_landscape.insert(key, unix_time_secs());
+        self.dirty = true;
+    }
+
+    /// Forget the removals older than `regrowth_secs`, returning how many elements grow back
+    pub fn regrow_landscape(&mut self, regrowth_secs: u64) -> usize {
+        let now = unix_time_secs();
+        let before = self.removed_landscape.len();
+        self.removed_landscape.retain(|_, removed_at| now.saturating_sub(*removed_at) < regrowth_secs);
+        let regrown = before - self.removed_landscape.len();
+        if regrown > 0 {
+            self.dirty = true;
+        }
+        regrown
+    }
+
+    fn allocate_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+/// Seconds since the Unix epoch, for timestamps in save files
+pub fn unix_time_secs() -> u64 {
+    #[cfg(target_arch = "wasm32")]
+    return (js_sys::Date::now() / 1000.0) as u64; // No system clock in the browser
+    #[cfg(not(target_arch = "wasm32"))]
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// When a dynamic object should stop existing as a live entity
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum DespawnRule {
+    DistanceFromPlayer(f32),  // Horizontal distance in world units
+    Lifetime(f32),            // Seconds left before the object despawns
+    OutsideRenderedArea,      // Despawn when its subpixel is no longer rendered
+}
+
+/// Despawn rule attached to dynamic objects (thrown stones, dropped items) so they do not
+/// accumulate forever. With `persist`, a `PersistentObject` is parked as a record instead
+/// of being forgotten; the policy is saved with the record.
+#[derive(Component, Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DespawnPolicy {
+    pub rule: DespawnRule,
+    pub persist: bool,
+}
+
+impl DespawnPolicy {
+    pub fn distance(max_distance: f32) -> Self {
+        Self { rule: DespawnRule::DistanceFromPlayer(max_distance), persist: false }
+    }
+
+    pub fn lifetime(seconds: f32) -> Self {
+        Self { rule: DespawnRule::Lifetime(seconds), persist: false }
+    }
+
+    pub fn outside_rendered_area() -> Self {
+        Self { rule: DespawnRule::OutsideRenderedArea, persist: false }
+    }
+
+    /// Keep a persisted record when the rule fires (requires `PersistentObject`)
+    pub fn persisted(mut self) -> Self {
+        self.persist = true;
+        self
+    }
+
+    /// Whether a record parked under this policy must stay parked: its lifetime ran out, or it
+    /// lies at `position` still beyond the distance limit from the player. A record parked
+    /// outside the rendered area comes back as soon as its tile is rendered again.
+    pub fn keeps_parked(&self, position: Vec3, player_position: Option<Vec3>) -> bool {
+        match self.rule {
+            DespawnRule::DistanceFromPlayer(max_distance) => player_position
+                .is_some_and(|player| horizontal_distance(position, player) > max_distance),
+            DespawnRule::Lifetime(remaining) => remaining <= 0.0,
+            DespawnRule::OutsideRenderedArea => false,
+        }
+    }
+}
+
+fn horizontal_distance(a: Vec3, b: Vec3) -> f32 {
+    Vec2::new(a.x - b.x, a.z - b.z).length()
+}
+
+/// Enforce `DespawnPolicy` on every entity carrying one.
+/// Must run after `sync_persistent_objects` so parked entities are not re-registered.
+#[allow(clippy::type_complexity)]
+pub fn enforce_despawn_policies(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut persisted: ResMut<PersistedObjects>,
+    terrain_center: Res<TerrainCenter>,
+    player_query: Query<&Transform, With<crate::player::Player>>,
+    mut objects: Query<(Entity, &mut DespawnPolicy, &Transform, Option<&EntitySubpixelPosition>, Option<&PersistentObject>)>,
+    mut object_events: EventWriter<ObjectEvent>,
+) {
+    let player_position = player_query.single().ok().map(|transform| transform.translation);
+    let mut rendered: Option<HashSet<(usize, usize, usize)>> = None;
+
+    for (entity, mut policy, transform, subpixel_position, persistent) in objects.iter_mut() {
+        let expired = match &mut policy.rule {
+            DespawnRule::DistanceFromPlayer(max_distance) => player_position
+                .is_some_and(|player| horizontal_distance(transform.translation, player) > *max_distance),
+            DespawnRule::Lifetime(remaining) => {
+                *remaining -= time.delta_secs();
+                *remaining <= 0.0
+            }
+            DespawnRule::OutsideRenderedArea => subpixel_position.is_some_and(|position| {
+                let rendered = rendered.get_or_insert_with(|| terrain_center.rendered_subpixels.subpixels.iter()
+                    .map(|(i, j, k, _)| (*i, *j, *k))
+                    .collect());
+                !rendered.is_empty() && !rendered.contains(&position.subpixel)
+            }),
+        };
+        if !expired {
+            continue;
+        }
+        if policy.persist && persistent.is_some() && persisted.park(entity, &policy) {
+            debug!("Parked {:?} as a persisted record", entity);
+        } else {
+            object_events.write(ObjectEvent::Destroyed { entity });
+        }
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Keep records in sync with live entities and stream objects in and out with the rendered area.
+///
+/// - newly tagged entities get a record,
+/// - live entities update their record's tile and orientation,
+/// - entities despawned by gameplay drop their record,
+/// - when the terrain is recentred, objects outside the rendered subpixels are despawned
+///   (record kept) and recorded objects whose tile is now rendered are respawned, unless a
+///   despawn policy parked them and still holds.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn sync_persistent_objects(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut persisted: ResMut<PersistedObjects>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    object_templates: Res<ObjectTemplates>,
+    player_query: Query<&Transform, With<crate::player::Player>>,
+    objects: Query<(Entity, &PersistentObject, &Transform, Option<&EntitySubpixelPosition>, Option<&PlacedObject>, Option<&DespawnPolicy>)>,
+    mut object_events: EventWriter<ObjectEvent>,
+) {
+    let persisted = persisted.as_mut();
+
+    // Register new entities and refresh the records of live ones
+    for (entity, persistent, transform, subpixel_position, placed, despawn) in objects.iter() {
+        let tile = subpixel_position.map(|position| position.subpixel);
+        let rotation = transform.rotation.to_array();
+        match persisted.live.get(&entity) {
+            Some(id) => {
+                if let Some(record) = persisted.records.get_mut(id) {
+                    let tile = tile.unwrap_or(record.tile);
+                    if record.tile != tile || record.rotation != rotation {
+                        record.tile = tile;
+                        record.rotation = rotation;
+                        persisted.dirty = true;
+                    }
+                }
+            }
+            None => {
+                let Some(tile) = tile else { continue; };
+                let id = persisted.allocate_id();
+                persisted.records.insert(id, ObjectRecord {
+                    id,
+                    template: persistent.template.clone(),
+                    tile,
+                    rotation,
+                    dynamic: persistent.dynamic,
+                    placed: placed.cloned(),
+                    despawn: despawn.cloned(),
+                });
+                persisted.live.insert(entity, id);
+                persisted.dirty = true;
+            }
+        }
+    }
+
+    // Entities that disappeared without us despawning them were destroyed or picked up
+    let gone: Vec<Entity> = persisted.live.keys().copied().filter(|entity| objects.get(*entity).is_err()).collect();
+    for entity in gone {
+        if let Some(id) = persisted.live.remove(&entity) {
+            persisted.records.remove(&id);
+            persisted.dirty = true;
+        }
+    }
+
+    // Stream objects in/out only when the rendered area has moved
+    let area = (terrain_center.subpixel, terrain_center.rendered_subpixels.subpixels.len());
+    if persisted.synced_area == Some(area) || area.1 == 0 {
+        return;
+    }
+    persisted.synced_area = Some(area);
+
+    let rendered: HashSet<(usize, usize, usize)> = terrain_center.rendered_subpixels.subpixels.iter()
+        .map(|(i, j, k, _)| (*i, *j, *k))
+        .collect();
+
+    // Despawn live objects that left the rendered area, keeping their records
+    let leaving: Vec<(Entity, (usize, usize, usize))> = persisted.live.iter()
+        .filter_map(|(entity, id)| persisted.records.get(id).map(|record| (*entity, record.tile)))
+        .filter(|(_, tile)| !rendered.contains(tile))
+        .collect();
+    for (entity, tile) in leaving {
+        // Keep what is left of a lifetime for when the object comes back
+        if let (Some(id), Ok((.., Some(policy)))) = (persisted.live.remove(&entity), objects.get(entity))
+            && let Some(record) = persisted.records.get_mut(&id) {
+            record.despawn = Some(policy.clone());
+        }
+        // No longer persistent while it fades out, or it would be registered again as a new record
+        commands.entity(entity).remove::<PersistentObject>();
+        crate::vfx::request_despawn(&mut commands, entity);
+        object_events.write(ObjectEvent::LeftRenderedArea { entity, tile });
+    }
+
+    // Respawn recorded objects whose tile is rendered but that have no live entity, except
+    // those parked by a despawn policy that still holds
+    let player_position = player_query.single().ok().map(|transform| transform.translation);
+    let live_ids: HashSet<u64> = persisted.live.values().copied().collect();
+    let entering: Vec<ObjectRecord> = persisted.records.values()
+        .filter(|record| !live_ids.contains(&record.id) && rendered.contains(&record.tile))
+        .filter(|record| !record.despawn.as_ref().is_some_and(|policy| policy.persist
+            && policy.keeps_parked(record.tile.into_world_position(&planisphere, &terrain_center), player_position)))
+        .cloned()
+        .collect();
+    for record in entering {
+        let Ok(template) = object_templates.get(&record.template) else {
+            warn!("Cannot respawn persistent object {}: unknown template '{}'", record.id, record.template);
+            continue;
+        };
+        let definition = ObjectDefinitionBuilder::from_template(template);
+        let (definition, body, y_offset) = if record.dynamic {
+            (definition.dynamic(), RigidBody::Dynamic, template.y_offset + RESPAWN_DROP_HEIGHT)
+        } else {
+            (definition.fixed(), RigidBody::Fixed, template.y_offset)
+        };
+        let entity = spawn_template_scene(
+            &mut commands,
+            &mut materials,
+            &planisphere,
+            &terrain_center,
+            template,
+            record.tile,
+            y_offset,
+            definition.build(),
+            (
+                body,
+                create_collider_from_shape(&ObjectShape::Cube { size: Vec3::ONE }),
+                PersistentObject { template: record.template.clone(), dynamic: record.dynamic },
+                EntitySubpixelPosition { subpixel: record.tile, previous_subpixel: record.tile, ..default() },
+                RaycastTileLocator { last_tile: Some(record.tile) },
+            ),
+        );
+        commands.entity(entity).entry::<Transform>().and_modify(move |mut transform| {
+            transform.rotation = Quat::from_array(record.rotation);
+        });
+        if let Some(placed) = record.placed {
+            commands.entity(entity).insert(placed);
+        }
+        if let Some(despawn) = record.despawn {
+            commands.entity(entity).insert(despawn);
+        }
+        persisted.live.insert(entity, record.id);
+        object_events.write(ObjectEvent::EnteredRenderedArea { entity, tile: record.tile });
+    }
+}
+
+/// Periodically write the records to disk when something changed
+pub fn autosave_persistent_objects(time: Res<Time>, mut persisted: ResMut<PersistedObjects>) {
+    if !persisted.autosave_timer.tick(time.delta()).just_finished() || !persisted.dirty {
+        return;
+    }
+    let path = persisted.path.clone();
+    if let Err(e) = persisted.save(&path) {
+        error!("Failed to save world objects: {}", e);
+    }
+}
+
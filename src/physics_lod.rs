@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::config::objects::{LANDSCAPE_COLLIDER_DROP_DISTANCE, LANDSCAPE_COLLIDER_RADIUS, PHYSICS_FREEZE_DISTANCE, PHYSICS_FREEZE_MAX_SPEED, PHYSICS_WAKE_DISTANCE};
+use crate::player::Player;
+use crate::populate::PopulatedObject;
+
+/// Marks a dynamic body that was switched to `RigidBody::Fixed` because it is far from the player
+#[derive(Component, Debug)]
+pub struct FrozenByDistance;
+
+/// Freeze dynamic objects beyond `PHYSICS_FREEZE_DISTANCE` from the player and reactivate
+/// them once they are back within `PHYSICS_WAKE_DISTANCE`. The gap between both distances
+/// keeps objects on the boundary from toggling every frame. Only bodies at rest (sleeping, or
+/// slower than `PHYSICS_FREEZE_MAX_SPEED`) are frozen, so distant ones still finish falling.
+#[allow(clippy::type_complexity)]
+pub fn update_physics_lod(
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    dynamic_query: Query<(Entity, &Transform, &RigidBody, Option<&FrozenByDistance>, Option<&Sleeping>, Option<&Velocity>), Without<Player>>,
+) {
+    let Ok(player_transform) = player_query.single() else { return; };
+    let player_position = player_transform.translation;
+
+    for (entity, transform, body, frozen, sleeping, velocity) in dynamic_query.iter() {
+        let distance = transform.translation.distance(player_position);
+        let at_rest = sleeping.is_some_and(|sleeping| sleeping.sleeping)
+            || velocity.is_some_and(|velocity| velocity.linvel.length() < PHYSICS_FREEZE_MAX_SPEED
+                && velocity.angvel.length() < PHYSICS_FREEZE_MAX_SPEED);
+        match (body, frozen) {
+            (RigidBody::Dynamic, None) if distance > PHYSICS_FREEZE_DISTANCE && at_rest => {
+                commands.entity(entity).insert((RigidBody::Fixed, FrozenByDistance));
+            }
+            (_, Some(_)) if distance < PHYSICS_WAKE_DISTANCE => {
+                commands.entity(entity)
+                    .insert(RigidBody::Dynamic)
+                    .remove::<FrozenByDistance>();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Collider taken off a landscape element far from the player, put back when it comes close
+#[derive(Component, Debug)]
+pub struct ParkedCollider(pub Collider);
+
+/// Keep colliders on landscape elements (`PopulatedObject`) only within
+/// `LANDSCAPE_COLLIDER_RADIUS` of the player, so a dense forest does not register thousands of
+/// static bodies. Colliders built later from the scene (`PendingSceneCollider`) are parked on
+/// the next pass. Distant elements can no longer be hit by the cursor ray either.
+pub fn update_landscape_colliders(
+    mut commands: Commands,
+    player_query: Query<&Transform, With<Player>>,
+    active_query: Query<(Entity, &Transform, &Collider), With<PopulatedObject>>,
+    parked_query: Query<(Entity, &Transform, &ParkedCollider), With<PopulatedObject>>,
+) {
+    let Ok(player_transform) = player_query.single() else { return; };
+    let player_position = player_transform.translation;
+
+    for (entity, transform, collider) in active_query.iter() {
+        if transform.translation.distance(player_position) > LANDSCAPE_COLLIDER_DROP_DISTANCE {
+            commands.entity(entity)
+                .remove::<Collider>()
+                .insert(ParkedCollider(collider.clone()));
+        }
+    }
+    for (entity, transform, parked) in parked_query.iter() {
+        if transform.translation.distance(player_position) < LANDSCAPE_COLLIDER_RADIUS {
+            commands.entity(entity)
+                .insert(parked.0.clone())
+                .remove::<ParkedCollider>();
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+use bevy::asset::{AssetLoadFailedEvent, LoadState, RenderAssetUsages};
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::scene::SceneInstance;
+
+use crate::error::GameError;
+use crate::notifications::Notify;
+
+/// Side of the placeholder texture, in pixels
+const TEXTURE_SIZE: u32 = 8;
+/// Colours of the placeholder texture squares: the usual "missing texture" checkerboard
+const TEXTURE_COLORS: [[u8; 4]; 2] = [[255, 0, 255, 255], [20, 20, 20, 255]];
+
+/// Stand-ins for optional assets that failed to load (missing or unreadable files), so the game
+/// keeps running and shows what is missing instead of invisible objects or black terrain
+#[derive(Resource)]
+pub struct PlaceholderAssets {
+    pub mesh: Handle<Mesh>, // Unit cube drawn instead of a template scene
+}
+
+impl FromWorld for PlaceholderAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Cuboid::new(1.0, 1.0, 1.0));
+        Self { mesh }
+    }
+}
+
+/// Checkerboard drawn instead of a texture that failed to load
+pub fn placeholder_texture() -> Image {
+    let data = (0..TEXTURE_SIZE * TEXTURE_SIZE)
+        .flat_map(|n| TEXTURE_COLORS[((n % TEXTURE_SIZE + n / TEXTURE_SIZE) % 2) as usize])
+        .collect();
+    Image::new(
+        Extent3d { width: TEXTURE_SIZE, height: TEXTURE_SIZE, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Put the placeholder texture in place of images that failed to load (terrain atlas, icons):
+/// materials and UI nodes holding their handle show it
+pub fn replace_failed_images(
+    mut failed: EventReader<AssetLoadFailedEvent<Image>>,
+    mut images: ResMut<Assets<Image>>,
+    mut notify: EventWriter<Notify>,
+) {
+    for event in failed.read() {
+        images.insert(event.id, placeholder_texture());
+        report(GameError::MissingAsset { path: event.path.to_string(), reason: event.error.to_string() }, &mut notify);
+    }
+}
+
+/// Give template parts whose scene failed to load the placeholder cube; they keep their
+/// material, and a pending scene collider is then built from the cube
+pub fn replace_failed_scenes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    placeholders: Res<PlaceholderAssets>,
+    parts: Query<(Entity, &SceneRoot), Without<SceneInstance>>,
+    mut reported: Local<HashSet<AssetId<Scene>>>,
+    mut notify: EventWriter<Notify>,
+) {
+    for (entity, scene_root) in parts.iter() {
+        let Some(reason) = scene_load_error(&asset_server, &scene_root.0) else { continue; };
+        if reported.insert(scene_root.0.id()) {
+            let path = scene_root.0.path().map_or_else(|| "scene".to_string(), |path| path.to_string());
+            report(GameError::MissingAsset { path, reason }, &mut notify);
+        }
+        commands.entity(entity).remove::<SceneRoot>().insert(Mesh3d(placeholders.mesh.clone()));
+    }
+}
+
+/// Why a scene failed to load. Scenes are labelled assets of a glTF file ("tree.glb#Scene0"):
+/// when the file itself is missing only the file's load fails.
+fn scene_load_error(asset_server: &AssetServer, scene: &Handle<Scene>) -> Option<String> {
+    if let Some(LoadState::Failed(e)) = asset_server.get_load_state(scene) {
+        return Some(e.to_string());
+    }
+    let file = scene.path()?.without_label();
+    asset_server.get_path_ids(file).into_iter().find_map(|id| match asset_server.get_load_state(id) {
+        Some(LoadState::Failed(e)) => Some(e.to_string()),
+        _ => None,
+    })
+}
+
+/// Log the missing asset and show it in a toast (assets may fail before the message log is set up)
+fn report(error: GameError, notify: &mut EventWriter<Notify>) {
+    warn!("{}, using a placeholder", error);
+    notify.write(Notify::new(error.to_string()).with_color(Color::srgb(1.0, 0.3, 0.3)));
+}
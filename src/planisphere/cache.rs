@@ -0,0 +1,82 @@
+use std::io::{Error, ErrorKind, Read, Write};
+
+use super::{Planisphere, PixelField};
+use ndarray::Array2;
+
+/// First bytes of a planisphere cache file
+const CACHE_MAGIC: &[u8; 8] = b"T3DPLAN\0";
+/// Layout version of the cache; older or newer caches are rejected
+pub const CACHE_VERSION: u32 = 1;
+
+impl Planisphere {
+    /// Writes the sampled map (elevation, sea mask and RGBA channels) in the binary cache format,
+    /// so that later runs skip decoding and sampling the image (`read_cache`)
+    ///
+    /// # Parameters
+    /// * `writer` - Destination of the cache, buffered by the caller
+    pub fn write_cache<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(CACHE_MAGIC)?;
+        writer.write_all(&CACHE_VERSION.to_le_bytes())?;
+        writer.write_all(&(self.width_pixels as u64).to_le_bytes())?;
+        writer.write_all(&(self.height_pixels as u64).to_le_bytes())?;
+        for field in [&self.elevation_grid, &self.red_channel, &self.green_channel, &self.blue_channel, &self.alpha_channel] {
+            for value in field.data.iter() {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+        }
+        let mask: Vec<u8> = self.sea_mask.iter().map(|sea| *sea as u8).collect();
+        writer.write_all(&mask)
+    }
+
+    /// Reads a planisphere written by `write_cache`. The source image is not kept, the radius is
+    /// left to the caller (`set_radius`) like for `from_elevation_map`.
+    ///
+    /// # Parameters
+    /// * `reader` - Cache contents, buffered by the caller
+    /// * `subpixel_divisions` - Number of subdivisions within each grid cell
+    pub fn read_cache<R: Read>(reader: &mut R, subpixel_divisions: usize) -> std::io::Result<Self> {
+        crate::profile_span!("planisphere_read_cache");
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != CACHE_MAGIC {
+            return Err(Error::new(ErrorKind::InvalidData, "not a planisphere cache"));
+        }
+        let version = u32::from_le_bytes(read_bytes(reader)?);
+        if version != CACHE_VERSION {
+            return Err(Error::new(ErrorKind::InvalidData, format!("cache version {} instead of {}", version, CACHE_VERSION)));
+        }
+        let width = u64::from_le_bytes(read_bytes(reader)?) as usize;
+        let height = u64::from_le_bytes(read_bytes(reader)?) as usize;
+        if width == 0 || height == 0 || width.checked_mul(height).is_none_or(|cells| cells > u32::MAX as usize) {
+            return Err(Error::new(ErrorKind::InvalidData, format!("invalid cached map size {}x{}", width, height)));
+        }
+
+        let mut planisphere = Self::new(width, height, subpixel_divisions);
+        for field in [&mut planisphere.elevation_grid, &mut planisphere.red_channel, &mut planisphere.green_channel,
+                      &mut planisphere.blue_channel, &mut planisphere.alpha_channel] {
+            *field = read_field(reader, width, height)?;
+        }
+        let mut mask = vec![0u8; width * height];
+        reader.read_exact(&mut mask)?;
+        planisphere.sea_mask = Array2::from_shape_vec((width, height), mask.into_iter().map(|sea| sea != 0).collect())
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        Ok(planisphere)
+    }
+}
+
+fn read_bytes<const N: usize, R: Read>(reader: &mut R) -> std::io::Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// One `width` x `height` field, in the row-major order `write_cache` iterates it
+fn read_field<R: Read>(reader: &mut R, width: usize, height: usize) -> std::io::Result<PixelField> {
+    let mut bytes = vec![0u8; width * height * 8];
+    reader.read_exact(&mut bytes)?;
+    let values = bytes.chunks_exact(8)
+        .map(|chunk| f64::from_le_bytes(chunk.try_into().expect("chunks of 8 bytes")))
+        .collect();
+    let data = Array2::from_shape_vec((width, height), values).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    Ok(PixelField { data })
+}
@@ -49,20 +49,19 @@ impl Planisphere {
     /// # Returns
     /// A tuple of (i, j, k) representing (horizontal_pixel, vertical_pixel, subpixel_index)
     pub fn geo_to_subpixel(&self, longitude: f64, latitude: f64) -> (usize, usize, usize) {
-        // Normalize geographic coordinates to [0.0, 1.0) range
-        let norm_long = (longitude + 180.0) / 360.0;
-        let norm_lat = (latitude + 90.0) / 180.0;
+        // Continuous pixel-grid position
+        let grid_i = (longitude + 180.0).rem_euclid(360.0) / 360.0 * self.width_pixels as f64;
+        let grid_j = ((latitude + 90.0) / 180.0).clamp(0.0, 1.0) * self.height_pixels as f64;
 
-        // Calculate grid cell indices
-        let i = (norm_long * self.width_pixels as f64) as usize % self.width_pixels;
-        let j = (norm_lat * self.height_pixels as f64) as usize % self.height_pixels;
+        // Calculate grid cell indices (nudged so cell corners land in their own cell)
+        let (cell_i, cell_j) = ((grid_i + 1e-9) as usize, ((grid_j + 1e-9) as usize).min(self.height_pixels - 1));
+        let (i, j) = (cell_i % self.width_pixels, cell_j);
 
-        // Calculate subpixel count for longitude, accounting for latitude (cosine effect)
-        let lon_subdivisions = (self.subpixel_divisions as f64 * latitude.to_radians().cos()).max(1.0) as usize;
-
-        // Calculate subpixel position
-        let sub_i = i % lon_subdivisions;
-        let sub_j = j % self.subpixel_divisions;
+        // Subpixel position within the cell, with the same latitude-dependent longitude
+        // subdivisions as `subpixel_to_geo` (whose corners map back to their own subpixel)
+        let lon_subdivisions = self.get_pixel_lon_subdivisions(i, j);
+        let sub_i = (((grid_i - cell_i as f64) * lon_subdivisions as f64 + 1e-9) as usize).min(lon_subdivisions - 1);
+        let sub_j = (((grid_j - cell_j as f64) * self.subpixel_divisions as f64 + 1e-9) as usize).min(self.subpixel_divisions - 1);
 
         // Combine into final subpixel index
         let k = sub_i * self.subpixel_divisions + sub_j;
@@ -104,4 +103,39 @@ impl Planisphere {
         // The rendering scale can be adjusted by changing the planet_radius parameter
         (x, y)
     }
+
+    /// Great-circle distance between two geographic positions (longitude, latitude in degrees),
+    /// in the same units as the planet radius
+    pub fn geodesic_distance(&self, (lon1, lat1): (f64, f64), (lon2, lat2): (f64, f64)) -> f64 {
+        let (lat1_rad, lat2_rad) = (lat1.to_radians(), lat2.to_radians());
+        let d_lat = lat2_rad - lat1_rad;
+        let d_lon = (lon2 - lon1).to_radians();
+        // Haversine formula, stable for short distances
+        let a = (d_lat / 2.0).sin().powi(2) + lat1_rad.cos() * lat2_rad.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * self.radius * a.sqrt().min(1.0).asin()
+    }
+
+    /// `count` evenly spaced positions (longitude, latitude in degrees) along the great circle
+    /// from `from` to `to`, both ends included
+    pub fn great_circle_points(&self, from: (f64, f64), to: (f64, f64), count: usize) -> Vec<(f64, f64)> {
+        let to_unit = |(lon, lat): (f64, f64)| {
+            let (lon, lat) = (lon.to_radians(), lat.to_radians());
+            [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+        };
+        let (a, b) = (to_unit(from), to_unit(to));
+        let omega = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2]).clamp(-1.0, 1.0).acos();
+        let count = count.max(2);
+        (0..count).map(|n| {
+            let t = n as f64 / (count - 1) as f64;
+            // Spherical linear interpolation, linear when the ends (nearly) coincide
+            let (wa, wb) = if omega < 1e-9 {
+                (1.0 - t, t)
+            } else {
+                (((1.0 - t) * omega).sin() / omega.sin(), (t * omega).sin() / omega.sin())
+            };
+            let p = [wa * a[0] + wb * b[0], wa * a[1] + wb * b[1], wa * a[2] + wb * b[2]];
+            let lat = p[2].atan2((p[0] * p[0] + p[1] * p[1]).sqrt());
+            (p[1].atan2(p[0]).to_degrees(), lat.to_degrees())
+        }).collect()
+    }
 }
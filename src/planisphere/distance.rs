@@ -12,6 +12,39 @@ pub enum DistanceMethod {
     Chebyshev,
 }
 
+impl DistanceMethod {
+    pub const ALL: [DistanceMethod; 3] = [DistanceMethod::Chebyshev, DistanceMethod::Euclidean, DistanceMethod::Manhattan];
+
+    /// e.g. "Euclidean (circle)"
+    pub fn label(self) -> &'static str {
+        match self {
+            DistanceMethod::Chebyshev => "Chebyshev (square)",
+            DistanceMethod::Euclidean => "Euclidean (circle)",
+            DistanceMethod::Manhattan => "Manhattan (diamond)",
+        }
+    }
+
+    /// Name in the startup config, e.g. "euclidean"
+    pub fn name(self) -> &'static str {
+        match self {
+            DistanceMethod::Chebyshev => "chebyshev",
+            DistanceMethod::Euclidean => "euclidean",
+            DistanceMethod::Manhattan => "manhattan",
+        }
+    }
+
+    /// Method of a startup config name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|method| method.name().eq_ignore_ascii_case(name))
+    }
+
+    /// Method after this one in `ALL`, wrapping around
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|method| *method == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
 impl Planisphere {
     /// Returns all subpixels whose distance from `(center_i, center_j, center_k)` is at most
     /// `max_subpixel_distance`, using the chosen `DistanceMethod`:
@@ -27,6 +60,7 @@ impl Planisphere {
         max_subpixel_distance: usize,
         method: DistanceMethod,
     ) -> Vec<(usize, usize, usize, [(f64, f64); 4])> {
+        crate::profile_span!("get_subpixels_by_distance_method", max_subpixel_distance, method = ?method);
         // --- bounding search rectangle (same for all methods) ---
         let pixel_radius = (max_subpixel_distance / self.subpixel_divisions) + 2;
         let min_i = center_i.saturating_sub(pixel_radius);
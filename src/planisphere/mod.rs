@@ -1,6 +1,7 @@
 use ndarray::Array2;
 use image::{DynamicImage, GenericImageView};
 
+pub mod cache;
 pub mod coordinates;
 pub mod distance;
 pub mod field;
@@ -40,6 +41,10 @@ pub struct Planisphere {
     pub(crate) alpha_channel: PixelField,
 }
 
+/// Used as a Bevy resource by the game
+#[cfg(feature = "game")]
+impl bevy::prelude::Resource for Planisphere {}
+
 impl Planisphere {
     /// Creates a new Planisphere with specified dimensions
     ///
@@ -88,16 +93,24 @@ impl Planisphere {
     /// # Returns
     /// * `Result<Self, image::ImageError>` - A new Planisphere with dimensions matching the image, or an error
     pub fn from_elevation_map(filename: &str, subpixel_divisions: usize) -> Result<Self> {
-        let img = image::open(filename)?;
+        Ok(Self::from_image(image::open(filename)?, subpixel_divisions))
+    }
+
+    /// Creates a new Planisphere from an elevation map already in memory (decoded by the caller,
+    /// e.g. from the game's asset server where files cannot be read directly)
+    ///
+    /// # Parameters
+    /// * `img` - Elevation map image
+    /// * `subpixel_divisions` - Number of subdivisions within each grid cell
+    pub fn from_image(img: DynamicImage, subpixel_divisions: usize) -> Self {
+        crate::profile_span!("planisphere_from_image");
         let (width_pixels, height_pixels) = img.dimensions();
-        println!("Loaded elevation map: {}x{}", width_pixels, height_pixels);
         let mut planisphere = Self::new(width_pixels as usize, height_pixels as usize, subpixel_divisions);
         planisphere.elevation_map = Some(img);
 
         // Initialize elevation grid and sea mask based on the image
         planisphere.process_elevation_data();
-        println!("Processed elevation data for Planisphere ({}x{})", planisphere.width_pixels, planisphere.height_pixels);
-        Ok(planisphere)
+        planisphere
     }
 
     pub fn compute_mean_tile_size(&mut self) {
@@ -147,6 +160,11 @@ impl Planisphere {
         &self.alpha_channel
     }
 
+    /// Whether pixel (i, j) is sea according to the elevation map
+    pub fn is_sea(&self, i: usize, j: usize) -> bool {
+        self.sea_mask.get([i, j]).copied().unwrap_or(false)
+    }
+
 
 
     /// Gets the coordinates of a neighboring grid point with appropriate wrapping at map edges
@@ -334,6 +352,7 @@ impl Planisphere {
     /// representing the four corners of each subpixel in the order: top-left, top-right, bottom-left, bottom-right
     pub fn get_subpixels_in_rectangle(&self, min_i: usize, max_i: usize, min_j: usize, max_j: usize)
         -> Vec<(usize, usize, usize, [(f64, f64); 4])> {
+        crate::profile_span!("get_subpixels_in_rectangle");
         let mut result = Vec::new();
 
         // Pre-allocate with approximate capacity
@@ -216,7 +216,7 @@ impl Planisphere {
     }
 
     /// Bilinear interpolation of altitude at a continuous pixel-grid position `(fi, fj)`.
-    fn alti_at_pixel_coords(&self, fi: f64, fj: f64) -> f32 {
+    pub fn alti_at_pixel_coords(&self, fi: f64, fj: f64) -> f32 {
         let i0 = fi.floor() as i32;
         let j0 = fj.floor() as i32;
         let tx = fi - fi.floor();
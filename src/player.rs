@@ -9,13 +9,13 @@ use bevy_rapier3d::plugin::context::systemparams::ReadRapierContext;
 use bevy::input::mouse::{MouseMotion, MouseButton}; 
 
 // Mouse movement events
-use crate::terrain::{RenderedSubpixels, Tile, TerrainCenter, entities_in_rendered_subpixels, ijk_to_world}; // Import Tile component and resources from terrain module
+use crate::terrain::{Tile, TerrainCenter, ijk_to_world}; // Import Tile component and resources from terrain module
 use crate::landscape::Item; // Import Item from landscape module
 // use crate::TerrainConfig;
 use crate::planisphere::{self}; // Import planisphere for coordinate conversion
-use crate::game_object::{ObjectTemplate, CollisionBehavior, 
-                        spawn_template_scene, ObjectDefinition, 
-                        ObjectTemplates, MouseTrackerObject, EntitySubpixelPosition}; // Import game object definitions
+use crate::game_object::{ObjectTemplate, 
+                        spawn_template_scene, ObjectDefinition, ObjectDefinitionBuilder, 
+                        ObjectTemplates, EntitySubpixelPosition, subpixel_from_ray_hit}; // Import game object definitions
 // Note: Terrain configuration is now accessed via TerrainConfig resource instead of constants
 // use crate::agent::Agent; // Import Agent component for shared positioning
 
@@ -34,7 +34,11 @@ pub struct Player {
 pub struct PlayerBundle {
     pub player: Player,
     pub player_inventory: PlayerInventory,
+    pub hotbar: crate::items::Hotbar,
     pub entity_position: EntitySubpixelPosition, // NEW: Shared positioning component
+    pub health: crate::player_stats::Health,
+    pub stamina: crate::player_stats::Stamina,
+    pub breath: crate::player_stats::Breath,
 }
 
 impl Default for PlayerBundle {
@@ -48,7 +52,11 @@ impl Default for PlayerBundle {
                 move_speed: crate::config::player::MOVE_SPEED,
             },
             player_inventory: PlayerInventory::default(),
+            hotbar: crate::items::Hotbar::default(),
             entity_position: EntitySubpixelPosition::default(), // NEW: Initialize shared positioning
+            health: default(),
+            stamina: default(),
+            breath: default(),
         }
     }
 }
@@ -59,10 +67,16 @@ pub struct PlayerSensor {
     pub parent_entity: Entity,    // Reference to the player that owns this sensor
 }
 
-/// PlayerInventory Component - Stores items the player has collected
-#[derive(Component, Default, Debug)]
-pub struct PlayerInventory {
-    pub items: Vec<String>,       // Vec<String> is a dynamic array of text strings
+/// PlayerInventory Component - Stores items the player has collected, as (item_id, count) stacks
+pub type PlayerInventory = crate::items::Inventory;
+
+/// What the mouse cursor points at, updated every frame by `cast_ray_from_camera`
+#[derive(Resource, Debug, Clone, Default)]
+pub struct CursorTarget {
+    pub hit_point: Option<Vec3>,                 // World position under the cursor, None when the ray hits nothing
+    pub normal: Vec3,                            // Surface normal at the hit point
+    pub entity: Option<Entity>,                  // Collider entity under the cursor (terrain tile or object)
+    pub tile: Option<(usize, usize, usize)>,     // Subpixel under the cursor
 }
 
 /// Marker component for the ray intersection visualization sphere
@@ -80,32 +94,35 @@ pub fn detect_mouse_clicks(
     mut commands: Commands,
     materials: ResMut<Assets<StandardMaterial>>,
     object_templates: Res<ObjectTemplates>,
-    mousetracker_query: Query<(Entity, &Transform, &EntitySubpixelPosition),
-        With<MouseTrackerObject>>,
+    cursor: Res<CursorTarget>,
     player_query: Query<(Entity, &Transform, &EntitySubpixelPosition), With<Player>>,
     planisphere: Res<planisphere::Planisphere>,
     terrain_center: Res<TerrainCenter>,
     // Add mouse button input resource to detect clicks
     mouse_button_input: Res<ButtonInput<MouseButton>>,
+    measure_tool: Res<crate::measure::MeasureTool>,
 ) {
-    // Check for left mouse button press
-    if mouse_button_input.just_pressed(MouseButton::Left) {
-        println!("Left mouse button was clicked!");
-        drop_stone(
-            commands, 
-            materials, 
-            &object_templates.rock, // Use rock template for stone
-            mousetracker_query, 
-            player_query,
-            planisphere, 
-            terrain_center
-        );
+    // Check for left mouse button press (picks tiles instead while measuring)
+    if mouse_button_input.just_pressed(MouseButton::Left) && !measure_tool.active {
+        debug!("Left mouse button was clicked!");
+        match object_templates.get("rock") { // Use rock template for stone
+            Ok(template) => drop_stone(
+                commands, 
+                materials, 
+                template,
+                &cursor, 
+                player_query,
+                planisphere, 
+                terrain_center
+            ),
+            Err(e) => warn!("Cannot drop stone: {}", e),
+        }
         // Your left click action code here
     }
     
     // Check for right mouse button press
     if mouse_button_input.just_pressed(MouseButton::Right) {
-        println!("Right mouse button was clicked!");
+        debug!("Right mouse button was clicked!");
         // Your right click action code here
     }
     
@@ -119,29 +136,34 @@ pub fn detect_mouse_clicks(
 
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn cast_ray_from_camera(
     //commands: &mut Commands,
     windows: Query<&Window, With<PrimaryWindow>>,
     cameras: Query<(&Camera, &GlobalTransform)>,
     rapier_context: ReadRapierContext,
-    mut mouse_tracker_query: Query<(Entity, &mut Transform), With<MouseTrackerObject>>,
+    terrain_center: Res<TerrainCenter>,
+    terrain_entities: Query<(), With<Tile>>,
+    object_positions: Query<&EntitySubpixelPosition>,
+    player_query: Query<Entity, With<Player>>,
+    mut cursor: ResMut<CursorTarget>,
 ){
     let Ok(window) = windows.single() else { return ; };
     let Ok((camera, camera_transform)) = cameras.single() else { return ; };
-    let mut hit_point = Vec3::ZERO; // Default hit point if no intersection occurs
+    let Ok(ctx) = rapier_context.single() else { return ; };
+    let mut target = CursorTarget::default(); // Nothing under the cursor unless the ray hits
     if let Some(cursor_position) = window.cursor_position() {
         // Create a ray from the camera to the cursor position
         if let Ok(ray) = camera.viewport_to_world(camera_transform, cursor_position) {
-            // Get the rapier context
-            let Ok(ctx) = rapier_context.single() else { return ; };
-            
             // Perform physics raycast
-            let max_distance = 100.0;
+            let max_distance = 200.0; // Reaches the ground from the overhead camera
             let solid = true;
-            //let filter = // This is more efficient as it doesn't pre-collect entities
-            let filter = QueryFilter::default();
+            // Never pick the player itself
+            let mut filter = QueryFilter::default();
+            if let Ok(player_entity) = player_query.single() {
+                filter = filter.exclude_rigid_body(player_entity);
+            }
 
-            
             if let Some((entity, ray_intersection)) = ctx.cast_ray_and_get_normal(
                 ray.origin,
                 *ray.direction,
@@ -150,35 +172,55 @@ pub fn cast_ray_from_camera(
                 filter,
             ) {
                 // Calculate hit point
-                hit_point = ray.origin + *ray.direction * ray_intersection.time_of_impact;
+                target.hit_point = Some(ray.origin + *ray.direction * ray_intersection.time_of_impact);
+                target.normal = ray_intersection.normal;
+                target.entity = Some(entity);
+                // Terrain hits map to the triangle's subpixel, objects report the tile they stand on
+                target.tile = if terrain_entities.contains(entity) {
+                    subpixel_from_ray_hit(&ray_intersection, &terrain_center)
+                } else {
+                    object_positions.get(entity).ok().map(|position| position.subpixel)
+                };
             }
         }
     }
-    for (marker_entity, mut transform) in mouse_tracker_query.iter_mut() {
-        // Reset the mouse tracker position to the raycast hit point
-        transform.translation = hit_point;
-    }
+    *cursor = target;
+}
+
+/// Mark the tile under the cursor
+pub fn highlight_cursor_tile(
+    cursor: Res<CursorTarget>,
+    planisphere: Res<planisphere::Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    mut gizmos: Gizmos,
+) {
+    let Some(tile) = cursor.tile else { return; };
+    let center = ijk_to_world(tile.0 as i32, tile.1 as i32, tile.2 as i32, &planisphere, &terrain_center);
+    let up = if cursor.normal == Vec3::ZERO { Vec3::Y } else { cursor.normal };
+    gizmos.circle(
+        Isometry3d::new(center + up * 0.05, Quat::from_rotation_arc(Vec3::Z, up)),
+        0.4,
+        Color::srgb(0.0, 0.3, 0.7), // Blue marker
+    );
 }
 
 pub fn drop_stone(
     mut commands: Commands,
     mut materials: ResMut<Assets<StandardMaterial>>,
         template: &ObjectTemplate,
-        mousetracker_query: Query<(Entity, &Transform, &EntitySubpixelPosition), With<MouseTrackerObject>>,
+        cursor: &CursorTarget,
         player_query: Query<(Entity, &Transform, &EntitySubpixelPosition), With<Player>>,
         planisphere: Res<planisphere::Planisphere>,
         terrain_center: Res<TerrainCenter>,
     )
-    {   for (player_entity, player_transform, player_ijkpos) in player_query.iter() {
-            for (mousetracker_entity, mousetracker_transform, mousetracker_ijkpos) in mousetracker_query.iter() {
-                // Get the subpixel coordinates from the mouse tracker
-                let mousetracker_subpixel = mousetracker_ijkpos.subpixel;
+    {   let Some(target_subpixel) = cursor.tile else { return; };
+        for (player_entity, player_transform, player_ijkpos) in player_query.iter() {
                 let player_subpixel = player_ijkpos.subpixel;
                 // Calculate the world position of the subpixel center
-                let mousetracker_world_pos = ijk_to_world(
-                    mousetracker_subpixel.0 as i32, 
-                    mousetracker_subpixel.1 as i32, 
-                    mousetracker_subpixel.2 as i32, 
+                let target_world_pos = ijk_to_world(
+                    target_subpixel.0 as i32, 
+                    target_subpixel.1 as i32, 
+                    target_subpixel.2 as i32, 
                     &planisphere, 
                     &terrain_center
                 );
@@ -190,9 +232,9 @@ pub fn drop_stone(
                     &terrain_center
                 );
                 let player_to_target = Vec3::new(
-                    mousetracker_world_pos.x - player_world_pos.x,
+                    target_world_pos.x - player_world_pos.x,
                     0.0, // Keep Y at 0 for ground level
-                    mousetracker_world_pos.z - player_world_pos.z,
+                    target_world_pos.z - player_world_pos.z,
                 );
                 let distance = player_to_target.length();
                 let force = 13.0;
@@ -213,24 +255,27 @@ pub fn drop_stone(
                     ActiveEvents::COLLISION_EVENTS,
                     ActiveCollisionTypes::all(),
                     );
-                // Spawn a stone at the mouse tracker position
+                // Spawn a stone at the player, thrown toward the cursor tile
                 spawn_template_scene(
                     &mut commands,
                     &mut materials,
                     &planisphere,
                     &terrain_center,
                     template,
-                    //player_transform.translation + player_to_target * 0.5, // Position it halfway between player and mouse tracker
-                    player_ijkpos.subpixel, // Position it halfway between player and mouse tracker
+                    //player_transform.translation + player_to_target * 0.5, // Position it halfway between player and cursor
+                    player_ijkpos.subpixel, // Position it halfway between player and cursor
                     player_transform.translation.y + template.y_offset, // Use player's Y position + offset
-                    CollisionBehavior::Dynamic, // Set collision behavior to dynamic for dropped items
+                    ObjectDefinitionBuilder::from_template(template).dynamic().build(), // Dynamic collision for dropped items
                     (physics_bundle, 
-                        //crate::game_object::RaycastTileLocator{last_tile: None}, 
+                        crate::game_object::RaycastTileLocator{last_tile: None}, 
                         //crate::game_object::EntityInfoOverlay::default(),
-                        //EntitySubpixelPosition::default(),
+                        EntitySubpixelPosition { subpixel: player_ijkpos.subpixel, previous_subpixel: player_ijkpos.subpixel, ..default() },
+                        // Record the stone so it survives recentring and restarts
+                        crate::persistence::PersistentObject { template: "rock".to_string(), dynamic: true },
+                        crate::persistence::DespawnPolicy::distance(crate::config::objects::STONE_DESPAWN_DISTANCE).persisted(),
+                        crate::destruction::Projectile { damage: 1.0 },
                     )
                 );
-            }
         }
 }
 
@@ -245,36 +290,60 @@ pub fn drop_stone(
 
 
 
-/// Function to handle player movement with keyboard and mouse input
-pub fn move_player(
-    time: Res<Time>,                                    // Bevy's time resource
-    keyboard_input: Res<ButtonInput<KeyCode>>,         // Keyboard input state
+/// Function to turn the player with the mouse, every frame so the camera follows smoothly
+pub fn turn_player(
     mut mouse_motion: EventReader<MouseMotion>,        // Mouse movement events
-    mut query: Query<(&mut ExternalImpulse, &mut Transform, &mut Player, &mut Velocity)>,
+    mut query: Query<(&mut Transform, &mut Player)>,
+    camera_query: Query<&crate::camera::ThirdPersonCamera>, // Mouse look is disabled while the camera orbits
 ) {
-    // Removed map_boundary - player can move freely
-    let current_time = time.elapsed_secs();            // How many seconds since the game started
-    
-    // Process the player entity
-    for (_impulse, mut transform, mut player, mut velocity) in query.iter_mut() {
-        
+    let orbiting = camera_query.single().is_ok_and(|camera| camera.orbiting);
+    // The overhead camera takes the mouse for panning
+    let overhead = camera_query.single().is_ok_and(|camera| camera.overhead);
+
+    for (mut transform, mut player) in query.iter_mut() {
         // MOUSE LOOK - Update facing direction based on mouse movement
         for motion in mouse_motion.read() {
+            if orbiting || overhead {
+                continue; // The orbit and overhead cameras use the mouse
+            }
             // Update facing angle based on horizontal mouse movement
             player.facing_angle -= motion.delta.x * player.mouse_sensitivity;
         }
-        
+
         // Always update the visual rotation to match the facing angle
         transform.rotation = Quat::from_rotation_y(player.facing_angle);
-        
+    }
+}
+
+/// Function to handle player movement and jumps with keyboard input. Runs in `FixedUpdate`, so
+/// the jump cooldown and the sprint stamina cost follow the fixed simulation clock.
+pub fn move_player(
+    time: Res<Time>,                                    // Fixed simulation clock
+    keyboard_input: Res<ButtonInput<KeyCode>>,         // Keyboard input state
+    mut query: Query<(&Transform, &mut Player, &mut Velocity, &mut crate::player_stats::Stamina)>,
+    camera_query: Query<&crate::camera::ThirdPersonCamera>,
+    settings: Res<crate::settings::Settings>,           // Key bindings
+) {
+    let keys = &settings.controls.key_bindings;
+    // The overhead camera takes WASD for panning; the player stands still
+    let overhead = camera_query.single().is_ok_and(|camera| camera.overhead);
+    // Removed map_boundary - player can move freely
+    let current_time = time.elapsed_secs();            // How many seconds since the game started
+
+    // Process the player entity
+    for (transform, mut player, mut velocity, mut stamina) in query.iter_mut() {
         // JUMPING BEHAVIOR
-        if keyboard_input.pressed(KeyCode::Space) && player.is_grounded && current_time >= player.next_jump_time {
+        if keyboard_input.pressed(keys.jump) && player.is_grounded && current_time >= player.next_jump_time
+            && stamina.try_spend(crate::config::player::JUMP_STAMINA) {
             velocity.linvel.y = crate::config::player::JUMP_FORCE;
             player.next_jump_time = current_time + crate::config::player::JUMP_COOLDOWN_SECS;
             player.is_grounded = false;
         }
         
-        if player.is_grounded {
+        if player.is_grounded && overhead {
+            velocity.linvel.x = 0.0;
+            velocity.linvel.z = 0.0;
+        } else if player.is_grounded {
             // Calculate movement directions relative to CURRENT facing angle
 
             let forward_dir = transform.forward();
@@ -282,22 +351,27 @@ pub fn move_player(
             let mut movement = Vec3::ZERO;
             
             // FORWARD/BACKWARD MOVEMENT
-            if keyboard_input.pressed(KeyCode::KeyW) || keyboard_input.pressed(KeyCode::ArrowUp) {
+            if keyboard_input.pressed(keys.forward) || keyboard_input.pressed(KeyCode::ArrowUp) {
                 movement += forward_dir * player.move_speed;  // Forward
             }
-            if keyboard_input.pressed(KeyCode::KeyS) || keyboard_input.pressed(KeyCode::ArrowDown) {
+            if keyboard_input.pressed(keys.back) || keyboard_input.pressed(KeyCode::ArrowDown) {
                 movement -= forward_dir * player.move_speed * 0.5;  // Backward (slower)
             }
             
             // STRAFE LEFT/RIGHT MOVEMENT
-            if keyboard_input.pressed(KeyCode::KeyA) {
+            if keyboard_input.pressed(keys.left) {
                 //println!("Strafe left pressed!");
                 movement -= right_dir * player.move_speed;  // Strafe left
             }
-            if keyboard_input.pressed(KeyCode::KeyD) {
+            if keyboard_input.pressed(keys.right) {
                 //println!("Strafe right pressed!");
                 movement += right_dir * player.move_speed;  // Strafe right
             }
+            // SPRINTING - costs stamina while moving
+            if keyboard_input.pressed(keys.sprint) && movement != Vec3::ZERO
+                && stamina.try_spend(crate::config::player::SPRINT_STAMINA_PER_SEC * time.delta_secs()) {
+                movement *= crate::config::player::SPRINT_MULTIPLIER;
+            }
             velocity.linvel.x = movement.x;
             velocity.linvel.z = movement.z;
            
@@ -306,12 +380,17 @@ pub fn move_player(
 }
 
 /// Function to handle item pickup when player touches items
+#[allow(clippy::too_many_arguments)]
 pub fn check_player_sensors(
     mut commands: Commands,                    // To despawn picked-up items
     mut collision_events: EventReader<CollisionEvent>, // Physics collision events
     sensor_query: Query<&PlayerSensor>,       // Find all player sensor entities
     mut inventory_query: Query<&mut PlayerInventory>, // Find all player inventory components
     item_query: Query<(Entity, &Item)>,       // Find all item entities
+    item_definitions: Res<crate::items::ItemDefinitions>, // Stack sizes and display names
+    mut object_events: EventWriter<crate::game_object::ObjectEvent>, // Announce pickups to gameplay systems
+    mut notify: EventWriter<crate::notifications::Notify>, // Pickup toasts
+    mut log: EventWriter<crate::message_log::LogMessage>,  // Pickup history
 ) {
     // Process each collision event that happened this frame
     for collision_event in collision_events.read() {
@@ -333,36 +412,73 @@ pub fn check_player_sensors(
 
             // Try to add the item to the player's inventory
             if let Ok(mut inventory) = inventory_query.get_mut(parent_entity) {
-                println!("Player picked up item: {}", item.item_type);
-                inventory.items.push(item.item_type.clone());
-                println!("Player inventory: {:?}", inventory);
-                commands.entity(item_entity).despawn();  // Remove the item from the world
+                let rarity = item_definitions.rarity(&item.item_id);
+                let mut message = format!("Picked up {}× {}", item.count, item_definitions.display_name(&item.item_id));
+                if rarity != crate::items::Rarity::Common {
+                    message.push_str(&format!(" ({})", rarity.label()));
+                }
+                inventory.add(&item_definitions, &item.item_id, item.count);
+                let carried = format!("{} (carrying {:.1} kg)", message, inventory.weight(&item_definitions));
+                log.write(crate::message_log::LogMessage::new(crate::message_log::LogCategory::Pickup, carried));
+                notify.write(crate::notifications::Notify::new(message).with_color(rarity.color()));
+                debug!("Player inventory: {:?}", inventory);
+                crate::vfx::request_despawn(&mut commands, item_entity);  // Remove the item from the world (with its pickup effect)
+                object_events.write(crate::game_object::ObjectEvent::PickedUp {
+                    entity: item_entity,
+                    by: parent_entity,
+                    item_id: item.item_id.clone(),
+                    count: item.count,
+                });
             }
         }
     }
 }
 
+/// Shake the camera and apply fall damage when the player lands hard
+fn shake_on_landing(
+    velocity: Option<&Velocity>,
+    player: &Player,
+    health: Option<Mut<crate::player_stats::Health>>,
+    shake_events: &mut EventWriter<crate::camera::CameraShakeEvent>,
+) {
+    let Some(velocity) = velocity else { return; };
+    let fall_speed = -velocity.linvel.y;
+    if !player.is_grounded && fall_speed > crate::config::camera::LANDING_SHAKE_SPEED {
+        let excess = fall_speed - crate::config::camera::LANDING_SHAKE_SPEED;
+        shake_events.write(crate::camera::CameraShakeEvent { trauma: (excess / crate::config::camera::LANDING_SHAKE_SPEED).min(0.8) });
+        if let Some(mut health) = health {
+            health.damage(excess * crate::config::player::FALL_DAMAGE_PER_SPEED);
+        }
+    }
+}
+
 /// Function to detect when player touches or leaves the ground
 pub fn check_player_ground_sensors(
     mut collision_events: EventReader<CollisionEvent>, // Physics collision events
-    mut player_query: Query<&mut Player>,              // Find all player entities
+    mut player_query: Query<(&mut Player, Option<&mut crate::player_stats::Health>)>, // Find all player entities
     tile_query: Query<Entity, With<Tile>>,            // Find all terrain tile entities
-    landscape_query: Query<Entity, With<crate::landscape::LandscapeElement>>, // Find all landscape elements
+    prop_query: Query<&crate::props::Prop>,           // Props the player can stand on
+    velocity_query: Query<&Velocity>,                 // Landing speed, for camera shake
+    mut shake_events: EventWriter<crate::camera::CameraShakeEvent>,
 ) {
+    // Decor props count as ground, collectibles are only pickup sensors
+    let is_ground_prop = |entity: Entity| prop_query.get(entity).is_ok_and(|prop| prop.has_tag(crate::props::tags::DECOR));
     // Process each collision event
     for collision_event in collision_events.read() {
         match collision_event {
             // Collision just started - player might have landed
             CollisionEvent::Started(entity1, entity2, _) => {
-                // Check if entity1 is a player and entity2 is ground (tile or landscape element)
-                if let Ok(mut player) = player_query.get_mut(*entity1) {
-                    if tile_query.get(*entity2).is_ok() || landscape_query.get(*entity2).is_ok() {
+                // Check if entity1 is a player and entity2 is ground (tile or decor prop)
+                if let Ok((mut player, health)) = player_query.get_mut(*entity1) {
+                    if tile_query.get(*entity2).is_ok() || is_ground_prop(*entity2) {
+                        shake_on_landing(velocity_query.get(*entity1).ok(), &player, health, &mut shake_events);
                         player.is_grounded = true;
                         //println!("Player became grounded!");
                     }
-                } else if let Ok(mut player) = player_query.get_mut(*entity2) {
+                } else if let Ok((mut player, health)) = player_query.get_mut(*entity2) {
                     // Check the opposite order: entity2 is player, entity1 is ground
-                    if tile_query.get(*entity1).is_ok() || landscape_query.get(*entity1).is_ok() {
+                    if tile_query.get(*entity1).is_ok() || is_ground_prop(*entity1) {
+                        shake_on_landing(velocity_query.get(*entity2).ok(), &player, health, &mut shake_events);
                         player.is_grounded = true;
                         //println!("Player became grounded!");
                     }
@@ -370,16 +486,15 @@ pub fn check_player_ground_sensors(
             },
             // Collision just ended - player might have become airborne
             CollisionEvent::Stopped(entity1, entity2, _) => {
-                if let Ok(mut player) = player_query.get_mut(*entity1) {
-                    if tile_query.get(*entity2).is_ok() || landscape_query.get(*entity2).is_ok() {
-                        player.is_grounded = false;
-                        //println!("Player became airborne!");
-                    }
-                } else if let Ok(mut player) = player_query.get_mut(*entity2) {
-                    if tile_query.get(*entity1).is_ok() || landscape_query.get(*entity1).is_ok() {
+                if let Ok((mut player, _)) = player_query.get_mut(*entity1) {
+                    if tile_query.get(*entity2).is_ok() || is_ground_prop(*entity2) {
                         player.is_grounded = false;
                         //println!("Player became airborne!");
                     }
+                } else if let Ok((mut player, _)) = player_query.get_mut(*entity2)
+                    && (tile_query.get(*entity1).is_ok() || is_ground_prop(*entity1)) {
+                    player.is_grounded = false;
+                    //println!("Player became airborne!");
                 }
             }
         }
@@ -387,38 +502,11 @@ pub fn check_player_ground_sensors(
 }
 
 
-
-
-
-
-
-
-
-
-
-
-
-pub fn entity_replacement_system(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    rendered_subpixels: ResMut<RenderedSubpixels>,
-    object_query : Query<(Entity, &mut Transform,  &ObjectDefinition), (Without<Player>, Without<MouseTrackerObject>)>,
-
-    terrain_center: ResMut<TerrainCenter>,
-    planisphere: Res<planisphere::Planisphere>,
-    object_templates: Res<ObjectTemplates>,
-) {
-        //despawn_unified_objects_from_name(&mut commands, "LandCubes", object_query);
-        entities_in_rendered_subpixels(&mut commands, &mut meshes, &mut materials, rendered_subpixels, planisphere, terrain_center, object_templates, object_query);
-}
-
-
-
 fn check_terrain_need_recreation(
     player_query: &mut Query<(Entity, &mut Transform, &EntitySubpixelPosition , &Player)>,
     planisphere: &Res<planisphere::Planisphere>,
     terrain_center: &ResMut<TerrainCenter>,
+    recreation_threshold: usize,
 ) -> (bool,(usize, usize, usize)) {
     let mut needs_recreation = false;
     let mut next_terrain_center_tile = (0,0,0);
@@ -426,11 +514,11 @@ fn check_terrain_need_recreation(
         let player_world_pos = player_transform.translation;
         let center_world_pos = Vec3::new(0.0,  player_transform.translation.y, 0.0);// eprintln!("Player entity: {:?}, Position: ({:.2}, {:.2}, {:.2})", player_entity, player_transform.translation.x, player_transform.translation.y, player_transform.translation.z);
         let distance_tiles = (player_world_pos - center_world_pos).length()/planisphere.mean_tile_size as f32;
-        if distance_tiles > 5.0 {  //0.5 * terrain_center.max_subpixel_distance as f32 {
-            println!("Player is too far from terrain center! Distance: {:.2} tiles, max allowed: {}", distance_tiles, terrain_center.max_subpixel_distance);
+        if distance_tiles > recreation_threshold as f32 {  //0.5 * terrain_center.max_subpixel_distance as f32 {
+            debug!("Player is too far from terrain center! Distance: {:.2} tiles, max allowed: {}", distance_tiles, terrain_center.max_subpixel_distance);
             needs_recreation = true; // Set flag to recreate terrain
             next_terrain_center_tile = player_subpixel_position.subpixel; // Use player's subpixel as new center
-            println!("next center at {} {} {}", next_terrain_center_tile.0, next_terrain_center_tile.1, next_terrain_center_tile.2)
+            debug!("next center at {} {} {}", next_terrain_center_tile.0, next_terrain_center_tile.1, next_terrain_center_tile.2)
     }
     // Placeholder for actual logic to determine if terrain needs recreation
     // This could be based on player position, time since last recreation, etc.
@@ -441,7 +529,7 @@ fn check_terrain_need_recreation(
 
 pub fn reinitialize_positions(
     mut player_query: Query<(Entity, &mut Transform, &EntitySubpixelPosition , &Player)>,
-    mut object_query: Query<(Entity, &mut Transform,  &ObjectDefinition), (Without<Player>, Without<MouseTrackerObject>)>,
+    mut object_query: Query<(Entity, &mut Transform,  &ObjectDefinition), Without<Player>>,
 ) {
         // Store player positions and calculate the offset needed to move them to origin
         let player_offset = if let Some((_, player_transform, _, _)) = player_query.iter().next() {
@@ -472,84 +560,41 @@ pub fn reinitialize_positions(
     }
 
 
-/// Terrain recreation system - handles asset cleanup and terrain generation
+/// Terrain recreation system - starts a terrain build when the player leaves the center tile or
+/// a rebuild is forced. The new terrain is swapped in by `terrain::finish_terrain_build`.
 pub fn terrain_recreation_system(
     time: Res<Time>,
     mut terrain_center: ResMut<TerrainCenter>,
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    asset_server: Res<AssetServer>,
     mut player_query: Query<(Entity, &mut Transform, &EntitySubpixelPosition , &Player)>,
-    terrain_query: Query<Entity, With<crate::terrain::Tile>>,
-    landscape_query: Query<Entity, With<crate::landscape::LandscapeElement>>,
-    mut object_query: Query<(Entity, &mut Transform, &ObjectDefinition),(Without<Player>, Without<MouseTrackerObject>)>,
     planisphere: Res<planisphere::Planisphere>,
-    mut rendered_subpixels: ResMut<RenderedSubpixels>,
-    mut triangle_mapping: ResMut<crate::terrain::TriangleSubpixelMapping>,
-    mut asset_tracker: ResMut<crate::TerrainAssetTracker>,
-    object_templates: Res<ObjectTemplates>,
+    shared_planisphere: Res<crate::terrain::SharedPlanisphere>,
+    mut terrain_build: ResMut<crate::terrain::TerrainBuild>,
+    terrain_config: Res<crate::TerrainConfig>,
 ) {
+    if terrain_build.is_running() {
+        return;
+    }
     let current_time = time.elapsed_secs();
     let time_since_last_recreation = current_time - terrain_center.last_recreation_time;
     // Calculate distance from terrain center
     let (needs_recreation, next_terrain_center_tile) = check_terrain_need_recreation(
         &mut player_query,
         &planisphere,
-        &terrain_center
+        &terrain_center,
+        terrain_config.recreation_threshold,
     );
 
 
     if needs_recreation || terrain_center.force_recreation {
+        let forced = terrain_center.force_recreation;
         terrain_center.force_recreation = false;
-        println!("Recreating terrain... (last recreation: {:.1}s ago, method: {:?})", time_since_last_recreation, terrain_center.distance_method);
- 
-
+        debug!("Recreating terrain... (last recreation: {:.1}s ago, method: {:?})", time_since_last_recreation, terrain_center.distance_method);
 
         // Only relocate the terrain center when the player moved too far.
         // A forced recreation (e.g. method change) keeps the existing center.
-        if needs_recreation {
-            terrain_center.set_ijk(
-                next_terrain_center_tile.0,
-                next_terrain_center_tile.1,
-                next_terrain_center_tile.2,
-                &planisphere
-            );
-            reinitialize_positions(player_query, object_query);
-        }
-
-
-
-        // Clear old triangle mapping
-        terrain_center.triangle_mapping.triangle_to_subpixel.clear();
-        // CRITICAL: Clean up old asset handles from Bevy's asset system to prevent memory leaks
-        asset_tracker.cleanup_assets(&mut meshes, &mut materials);
-        
-        // Remove existing terrain and landscape entities
-        for terrain_entity in terrain_query.iter() {
-            commands.entity(terrain_entity).despawn(); // Use despawn() instead of despawn_recursive()
-        }
-        for landscape_entity in landscape_query.iter() {
-            commands.entity(landscape_entity).despawn();
-        }
-        
-        // Create new terrain
-        crate::terrain::create_terrain_gnomonic_rectangular(
-            &mut commands,
-            &mut meshes,
-            &mut materials,
-            &asset_server,
-            &planisphere,
-            &mut terrain_center,
-            Some(&mut asset_tracker),
-            &time
-        );
-
-
-       
-        println!("Terrain recreation completed successfully at {} {} {} ", terrain_center.subpixel.0, terrain_center.subpixel.1, terrain_center.subpixel.2);
-        // Note: cannot print triangle mapping details or rendered subpixels because they were moved into the terrain creation function
-        // entity_replacement_system(commands, meshes, materials, rendered_subpixels, object_query, terrain_center, planisphere, object_templates);
+        // Forced rebuilds (render distance, method) can be large: show the loading screen.
+        let recenter = needs_recreation.then_some(next_terrain_center_tile);
+        terrain_build.start(&shared_planisphere, &terrain_center, terrain_config.relief, terrain_config.lod, recenter, forced);
     }
 }
 
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::game_object::EntitySubpixelPosition;
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+
+/// Player hit points, lost on hard landings and while out of breath
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+/// Spent by sprinting and jumping, regenerates after a short rest
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+    pub rest_secs: f32,   // Time since stamina was last spent
+}
+
+/// Seconds of air left; drains while the player is on a sea tile and refills on land
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Breath {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self { current: crate::config::player::MAX_HEALTH, max: crate::config::player::MAX_HEALTH }
+    }
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Self { current: crate::config::player::MAX_STAMINA, max: crate::config::player::MAX_STAMINA, rest_secs: 0.0 }
+    }
+}
+
+impl Default for Breath {
+    fn default() -> Self {
+        Self { current: crate::config::player::BREATH_SECS, max: crate::config::player::BREATH_SECS }
+    }
+}
+
+impl Health {
+    pub fn damage(&mut self, amount: f32) {
+        self.current = (self.current - amount).max(0.0);
+    }
+}
+
+impl Stamina {
+    /// Spend `amount` if available, returns whether it was
+    pub fn try_spend(&mut self, amount: f32) -> bool {
+        if self.current < amount {
+            return false;
+        }
+        self.current -= amount;
+        self.rest_secs = 0.0;
+        true
+    }
+}
+
+/// Fill level of a stat, 0.0 to 1.0, shown by the HUD bars
+pub trait StatFraction {
+    fn fraction(&self) -> f32;
+}
+
+impl StatFraction for Health {
+    fn fraction(&self) -> f32 { (self.current / self.max).clamp(0.0, 1.0) }
+}
+
+impl StatFraction for Stamina {
+    fn fraction(&self) -> f32 { (self.current / self.max).clamp(0.0, 1.0) }
+}
+
+impl StatFraction for Breath {
+    fn fraction(&self) -> f32 { (self.current / self.max).clamp(0.0, 1.0) }
+}
+
+/// Regenerate stamina once the player rested for `config::player::STAMINA_REGEN_DELAY_SECS`
+pub fn regenerate_stamina(time: Res<Time>, mut query: Query<&mut Stamina, With<Player>>) {
+    for mut stamina in query.iter_mut() {
+        stamina.rest_secs += time.delta_secs();
+        if stamina.rest_secs >= crate::config::player::STAMINA_REGEN_DELAY_SECS && stamina.current < stamina.max {
+            stamina.current = (stamina.current + crate::config::player::STAMINA_REGEN_PER_SEC * time.delta_secs()).min(stamina.max);
+        }
+    }
+}
+
+/// Drain breath on sea tiles and refill it on land; without breath the player loses health
+pub fn update_breath(
+    time: Res<Time>,
+    planisphere: Res<Planisphere>,
+    mut query: Query<(&EntitySubpixelPosition, &mut Breath, &mut Health), With<Player>>,
+) {
+    let dt = time.delta_secs();
+    for (position, mut breath, mut health) in query.iter_mut() {
+        let (i, j, _) = position.subpixel;
+        if planisphere.is_sea(i, j) {
+            breath.current = (breath.current - dt).max(0.0);
+            if breath.current <= 0.0 {
+                health.damage(crate::config::player::DROWNING_DAMAGE_PER_SEC * dt);
+            }
+        } else if breath.current < breath.max {
+            breath.current = (breath.current + crate::config::player::BREATH_REGEN_PER_SEC * dt).min(breath.max);
+        }
+    }
+}
@@ -0,0 +1,601 @@
+use bevy::app::PluginGroupBuilder;
+use bevy::prelude::*;
+
+use crate::error::{GameError, GameResult, StartupError};
+use crate::planisphere::Planisphere;
+use crate::preprocess;
+use crate::startup_config::{self, StartupConfig};
+use crate::terrain::{RenderedSubpixels, TriangleSubpixelMapping};
+use crate::terrain::texture::WorldSeed;
+use crate::{animation, beacons, building, camera, compass, daylight, destruction, dev_panel, existence, export, game_object,
+            geojson, gpx, grid_overlay, harvest, hud, items, loading, loot, measure, message_log, minimap, music,
+            notifications, occlusion, occupancy, perf_overlay, persistence, physics_lod, placeholders, player, player_stats,
+            populate, props, remote, scene_collider, scripting, seasons, sky, net_client, settings, settings_menu, sockets, terrain,
+            time_control, trail, ui, vfx, waypoints, weather, wind, world_save};
+
+/// Every game plugin, in dependency order (the planisphere first, the terrain needs it),
+/// configured from the startup parameters. Engine plugins (`DefaultPlugins`, Rapier) are added
+/// by `main`.
+pub struct Tiles3dPlugins {
+    pub config: StartupConfig,
+}
+
+impl Default for Tiles3dPlugins {
+    /// Plugins configured from `startup_config::STARTUP_CONFIG_PATH`
+    fn default() -> Self {
+        Self { config: StartupConfig::load(startup_config::STARTUP_CONFIG_PATH) }
+    }
+}
+
+impl PluginGroup for Tiles3dPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        PluginGroupBuilder::start::<Self>()
+            .add(PlanispherePlugin { elevation_map: self.config.map_path.clone(), sub_k: self.config.sub_k })
+            .add(TerrainPlugin { config: self.config })
+            .add(ObjectsPlugin)
+            .add(PlayerPlugin)
+            .add(CameraPlugin)
+            .add(UiPlugin)
+    }
+}
+
+/// Systems of `TerrainPlugin`: terrain builds, seasons, weather, exports
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TerrainSystems;
+
+/// Systems of `ObjectsPlugin`: templates, props, persistence, physics LOD, effects
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ObjectSystems;
+
+/// Systems of `PlayerPlugin`: movement, sensors, cursor, stats
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlayerSystems;
+
+/// Systems of `CameraPlugin`: follow, orbit, zoom, presets, occlusion, day/night lighting, sky
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CameraSystems;
+
+/// Systems of `UiPlugin`: panels, overlays, navigation tools, settings, music
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UiSystems;
+
+/// Phases of a frame, run in this order by every plugin's update systems: device input, then
+/// the world reacting to it, then the terrain catching up with the player (builds, population,
+/// tile lookups), then the camera and UI reflecting the result. Gameplay that must not depend on
+/// the frame rate (movement, jumps, stats, script ticks) runs in `GameSet::Simulation` of
+/// `FixedUpdate`, next to the physics steps.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameSet {
+    Input,                // Keys, mouse, cursor ray, settings reloads
+    Simulation,           // Movement, objects, building and harvesting, effects, network
+    TerrainMaintenance,   // Terrain builds and recentring, landscape population, tile locations
+    UiSync,               // Camera follow, overlays, HUD, panels
+}
+
+/// Whether the elevation map is loaded. Nothing in the game runs before: the startup systems of
+/// the game plugins run on entering `Ready`, their update systems only in `Ready`.
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MapState {
+    #[default]
+    Loading,
+    Ready,
+    Failed, // The map cannot be used: the error screen shows the `StartupError` instead of the game
+}
+
+/// Loads the elevation map into the `Planisphere` resource (and its shared copy for
+/// background terrain builds). Native builds first look for the planisphere cache written by
+/// `tiles3d-preprocess` (the asset manifest is kept as a resource). Otherwise maps under
+/// `assets/` go through the asset server, which also fetches them in the browser; other paths
+/// are read from disk (native builds only). An unusable map leads to `MapState::Failed`.
+pub struct PlanispherePlugin {
+    pub elevation_map: String,
+    pub sub_k: usize,   // Subpixel divisions per pixel row
+}
+
+impl Default for PlanispherePlugin {
+    fn default() -> Self {
+        let config = StartupConfig::default();
+        Self { elevation_map: config.map_path, sub_k: config.sub_k }
+    }
+}
+
+/// Elevation map requested from the asset server
+#[derive(Resource)]
+struct MapLoad {
+    path: String,
+    image: Handle<Image>,
+    sub_k: usize,
+}
+
+impl Plugin for PlanispherePlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(Update, (TerrainSystems, ObjectSystems, PlayerSystems, CameraSystems, UiSystems)
+            .run_if(in_state(MapState::Ready)))
+            .configure_sets(Update, (GameSet::Input, GameSet::Simulation, GameSet::TerrainMaintenance, GameSet::UiSync)
+                .chain().run_if(in_state(MapState::Ready)))
+            .configure_sets(FixedUpdate, (ObjectSystems, PlayerSystems).run_if(in_state(MapState::Ready)))
+            .configure_sets(FixedUpdate, GameSet::Simulation.run_if(in_state(MapState::Ready)))
+            .add_systems(OnEnter(MapState::Failed), report_startup_error);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let manifest = match preprocess::Manifest::load(preprocess::CACHE_DIR) {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    warn!("Ignoring {}", e);
+                    None
+                }
+            };
+            let cached = manifest.as_ref()
+                .and_then(|manifest| preprocess::cached_planisphere(manifest, &self.elevation_map, self.sub_k));
+            if let Some(manifest) = manifest {
+                app.insert_resource(manifest);
+            }
+            if let Some(planisphere) = cached {
+                insert_planisphere(app.world_mut(), planisphere);
+                app.insert_state(MapState::Ready);
+                return;
+            }
+        }
+
+        if let Some(asset_path) = self.elevation_map.strip_prefix("assets/") {
+            let image = app.world().resource::<AssetServer>().load::<Image>(asset_path.to_string());
+            app.init_state::<MapState>()
+                .insert_resource(MapLoad { path: self.elevation_map.clone(), image, sub_k: self.sub_k })
+                .add_systems(PreUpdate, finish_map_load.run_if(in_state(MapState::Loading)));
+            return;
+        }
+        #[cfg(target_arch = "wasm32")]
+        let map = Err(GameError::MapLoad {
+            path: self.elevation_map.clone(),
+            reason: "the web build loads its map from the assets directory".to_string(),
+        });
+        #[cfg(not(target_arch = "wasm32"))]
+        let map = preprocess::validate_map(&self.elevation_map)
+            .map(|image| Planisphere::from_image(image, self.sub_k))
+            .map_err(GameError::from);
+        match map {
+            Ok(planisphere) => {
+                insert_planisphere(app.world_mut(), planisphere);
+                app.insert_state(MapState::Ready);
+            }
+            Err(e) => {
+                app.insert_resource(StartupError(e)).insert_state(MapState::Failed);
+            }
+        }
+    }
+}
+
+/// Print the error that stopped the setup. Without a window (`--headless`) nobody sees the error
+/// screen, so the app exits with an error status instead.
+fn report_startup_error(error: Res<StartupError>, windows: Query<(), With<bevy::window::PrimaryWindow>>, mut exit: EventWriter<AppExit>) {
+    error!("{}", error.0);
+    if windows.is_empty() {
+        exit.write(AppExit::error());
+    }
+}
+
+/// Build the planisphere once the asset server has the map image, then enter `MapState::Ready`
+/// (or `MapState::Failed` when the map is unusable)
+fn finish_map_load(world: &mut World) {
+    let Some(load) = world.get_resource::<MapLoad>() else { return; };
+    let (path, handle, sub_k) = (load.path.clone(), load.image.clone(), load.sub_k);
+    let state = match planisphere_from_asset(world, &path, &handle, sub_k) {
+        Ok(None) => return, // Still loading
+        Ok(Some(planisphere)) => {
+            insert_planisphere(world, planisphere);
+            MapState::Ready
+        }
+        Err(e) => {
+            world.insert_resource(StartupError(e));
+            MapState::Failed
+        }
+    };
+    world.remove_resource::<MapLoad>();
+    world.resource_mut::<Assets<Image>>().remove(&handle);
+    world.resource_mut::<NextState<MapState>>().set(state);
+}
+
+/// Planisphere sampled from the map image of the asset server, None while it loads
+fn planisphere_from_asset(world: &World, path: &str, handle: &Handle<Image>, sub_k: usize) -> GameResult<Option<Planisphere>> {
+    if let Some(bevy::asset::LoadState::Failed(e)) = world.resource::<AssetServer>().get_load_state(handle) {
+        return Err(GameError::MapLoad { path: path.to_string(), reason: e.to_string() });
+    }
+    let Some(image) = world.resource::<Assets<Image>>().get(handle) else { return Ok(None); };
+    preprocess::check_map_dimensions(path, image.width(), image.height())?;
+    // The asset server decodes the map into a texture; the planisphere samples it as RGBA8
+    let rgba = image.convert(bevy::render::render_resource::TextureFormat::Rgba8UnormSrgb)
+        .and_then(|image| image::RgbaImage::from_raw(image.width(), image.height(), image.data?))
+        .ok_or_else(|| GameError::MapFormat { path: path.to_string() })?;
+    Ok(Some(Planisphere::from_image(image::DynamicImage::ImageRgba8(rgba), sub_k)))
+}
+
+fn insert_planisphere(world: &mut World, mut planisphere: Planisphere) {
+    info!("Loaded elevation map {}x{} ({} subpixel divisions)", planisphere.width_pixels, planisphere.height_pixels, planisphere.subpixel_divisions);
+    planisphere.set_radius(crate::config::terrain::PLANET_RADIUS as f64);
+    world.insert_resource(terrain::SharedPlanisphere(std::sync::Arc::new(planisphere.clone())));
+    world.insert_resource(planisphere);
+}
+
+/// Terrain around the player: centre, background builds, seasonal re-texturing, weather, glTF
+/// and OBJ exports, and the startup parameters (spawn point, hot-reloaded `TerrainConfig`).
+/// Its startup systems need the `Planisphere` resource and run once `PlanispherePlugin` has
+/// loaded the map (`MapState::Ready`).
+#[derive(Default)]
+pub struct TerrainPlugin {
+    pub config: StartupConfig,
+}
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        let initial_lon = self.config.initial_lon;
+        let initial_lat = self.config.initial_lat;
+        let world_seed = WorldSeed(self.config.seed);
+        world_seed.install();
+        info!("World seed {}", world_seed.0);
+        match terrain::BiomeTable::load(terrain::biome::BIOMES_PATH) {
+            Ok(table) => {
+                info!("Loaded {} biomes from {}", table.biomes.len(), terrain::biome::BIOMES_PATH);
+                table.install();
+            }
+            Err(e) => warn!("Could not load biomes {}: {}, textures follow the elevation", terrain::biome::BIOMES_PATH, e),
+        }
+        let texture_atlas = preprocessed_atlas(app);
+
+        app.init_resource::<terrain::TerrainBuild>()
+            .insert_resource(self.config.clone())
+            .insert_resource(world_seed)
+            .init_resource::<startup_config::StartupConfigWatcher>()
+            .insert_resource(self.config.terrain.clone())
+            .insert_resource(crate::TerrainAssetTracker { texture_atlas, ..default() })
+            .insert_resource(terrain::TerrainCenter {
+                longitude: initial_lon,
+                latitude: initial_lat,
+                subpixel: (0, 0, 0),      // Located on the map by `terrain::locate_terrain_center`
+                max_subpixel_distance: 1, // From the render distance setting at startup
+                last_recreation_time: -10.0,
+                distance_method: crate::planisphere::DistanceMethod::default(),
+                force_recreation: false,
+                rendered_subpixels: RenderedSubpixels::new(),
+                lod_quads: Vec::new(),
+                skirts: Vec::new(),
+                triangle_mapping: TriangleSubpixelMapping::new(),
+                stats: terrain::TerrainStats::default(),
+            })
+            .insert_resource(RenderedSubpixels::new())
+            .insert_resource(TriangleSubpixelMapping::default())
+            .init_resource::<seasons::SeasonState>()
+            .init_resource::<weather::Weather>()
+            .add_event::<terrain::TerrainReady>()
+            .add_event::<export::ExportTerrain>()
+            .add_systems(OnEnter(MapState::Ready), (terrain::locate_terrain_center, crate::setup_physics).chain())
+            .add_systems(OnEnter(MapState::Ready), (weather::setup_rain, weather::setup_weather_audio, terrain::setup_ocean_material))
+            .add_systems(Update, (startup_config::hot_reload_startup_config, export::handle_export_keys, terrain::handle_terrain_view_keys)
+                .in_set(TerrainSystems).in_set(GameSet::Input))
+            .add_systems(Update, (seasons::advance_season, weather::update_weather).in_set(TerrainSystems).in_set(GameSet::Simulation))
+            .add_systems(Update, (
+                (terrain::apply_terrain_view, player::terrain_recreation_system, terrain::finish_terrain_build).chain(), // Start terrain builds, swap in finished ones
+                seasons::retexture_terrain.after(terrain::finish_terrain_build),
+                terrain::rebuild_ocean.after(terrain::finish_terrain_build),
+            ).in_set(TerrainSystems).in_set(GameSet::TerrainMaintenance))
+            .add_systems(Update, (
+                weather::apply_weather_fog,
+                weather::update_rain.after(camera::update_third_person_camera),
+                weather::apply_wet_surfaces,
+                terrain::animate_ocean,
+                weather::update_weather_audio,
+                (export::run_startup_export, export::export_terrain).chain(),
+            ).in_set(TerrainSystems).in_set(GameSet::UiSync));
+    }
+}
+
+/// Texture atlas generated by `tiles3d-preprocess`, when the asset manifest lists one the asset
+/// server can load (under `assets/`); the terrain falls back to `textures/texture_atlas.png`
+fn preprocessed_atlas(app: &App) -> Option<Handle<Image>> {
+    let manifest = app.world().get_resource::<preprocess::Manifest>()?;
+    let Some(asset_path) = manifest.atlas.path.strip_prefix("assets/") else {
+        warn!("Ignoring texture atlas {}: it is not in the assets directory", manifest.atlas.path);
+        return None;
+    };
+    Some(app.world().resource::<AssetServer>().load(asset_path.to_string()))
+}
+
+/// Save file path in the save directory of the startup config (`TerrainPlugin`), or the default one
+fn save_path(app: &App, file: &str) -> String {
+    match app.world().get_resource::<StartupConfig>() {
+        Some(config) => config.save_path(file),
+        None => StartupConfig::default().save_path(file),
+    }
+}
+
+/// Template objects and everything living on the terrain: props and their population,
+/// persistence, items and loot, building, destruction and harvesting, beacons, effects, and the
+/// features of the GeoJSON layers
+pub struct ObjectsPlugin;
+
+impl Plugin for ObjectsPlugin {
+    fn build(&self, app: &mut App) {
+        let world_objects_path = save_path(app, persistence::WORLD_OBJECTS_SAVE_FILE);
+        let save_dir = app.world().get_resource::<StartupConfig>()
+            .map_or_else(|| StartupConfig::default().save_dir, |config| config.save_dir.clone());
+        app.insert_resource(persistence::PersistedObjects::load(&world_objects_path))
+            .insert_resource(world_save::WorldSave::load(&save_dir))
+            .init_resource::<scene_collider::SceneColliderCache>()
+            .init_resource::<game_object::TintedMaterials>()
+            .insert_resource(items::ItemDefinitions::load(items::ITEM_DEFINITION_DIR))
+            .insert_resource(loot::LootTables::load(loot::LOOT_TABLE_DIR))
+            .init_resource::<vfx::VfxAssets>()
+            .init_resource::<placeholders::PlaceholderAssets>()
+            .init_resource::<game_object::OverlayPool>()
+            .init_resource::<existence::ExistenceRegistry>()
+            .init_resource::<occupancy::TileOccupancy>()
+            .init_resource::<populate::PopulatedArea>()
+            .init_resource::<props::PropSettings>()
+            .init_resource::<props::RarityBeams>()
+            .init_resource::<wind::Wind>()
+            .init_resource::<beacons::BeaconPool>()
+            .insert_resource(populate::SpawnConfig::load(populate::SPAWN_CONFIG_PATH))
+            .insert_resource(geojson::GeoJsonConfig::load(geojson::GEOJSON_CONFIG_PATH))
+            .init_resource::<geojson::ImportedPoints>()
+            .add_event::<game_object::ObjectEvent>()
+            .add_event::<loot::LootDropEvent>()
+            .add_event::<building::BuildEvent>()
+            .add_event::<beacons::SpawnMarker>()
+            .add_event::<beacons::ClearMarkers>()
+            .add_event::<world_save::SaveWorld>()
+            .add_systems(OnEnter(MapState::Ready),
+                (game_object::setup_object_templates, scripting::setup_scripting, geojson::import_geojson_layers))
+            .add_systems(OnEnter(MapState::Ready), world_save::restore_terrain_center
+                .after(terrain::locate_terrain_center).before(crate::setup_physics).before(game_object::setup_player))
+            .add_systems(Update, (
+                props::toggle_debug_props,
+                world_save::handle_save_keys,
+                scripting::use_scripted_objects,
+            ).in_set(ObjectSystems).in_set(GameSet::Input))
+            .add_systems(Update, (
+                (harvest::handle_harvest_input.before(destruction::destroy_broken_objects), harvest::animate_harvest_shake),
+                game_object::hot_reload_object_templates,
+                persistence::autosave_persistent_objects,
+                game_object::log_object_events,
+                scene_collider::build_scene_colliders.after(placeholders::replace_failed_scenes),
+                placeholders::replace_failed_scenes,
+                loot::spawn_loot_drops,
+                building::handle_building_input.after(occupancy::update_tile_occupancy),
+                occupancy::update_tile_occupancy,
+                physics_lod::update_physics_lod,
+                physics_lod::update_landscape_colliders,
+                destruction::apply_projectile_hits,
+                destruction::destroy_broken_objects.after(destruction::apply_projectile_hits),
+                world_save::restore_player_state,
+            ).in_set(ObjectSystems).in_set(GameSet::Simulation))
+            .add_systems(FixedUpdate, (scripting::run_script_handlers, scripting::apply_script_actions).chain()
+                .in_set(ObjectSystems).in_set(GameSet::Simulation))
+            .add_systems(Update, (
+                vfx::start_appear_effects,
+                vfx::process_despawn_requests,
+                vfx::animate_scale,
+                vfx::update_particles,
+                beacons::animate_beacons,
+                beacons::handle_marker_requests,
+                beacons::expire_markers,
+                animation::bind_template_animations,
+                animation::apply_animation_commands.after(animation::bind_template_animations),
+                existence::enforce_existence_conditions,
+                props::apply_prop_visibility,
+                props::highlight_item_rarity,
+                seasons::tint_vegetation,
+                game_object::apply_scene_tints.after(props::highlight_item_rarity).after(seasons::tint_vegetation)
+                    .after(placeholders::replace_failed_scenes),
+                wind::attach_wind_sway,
+                wind::apply_wind_sway,
+            ).in_set(ObjectSystems).in_set(GameSet::Simulation))
+            // Everything tied to the rendered area runs once the terrain build of the frame is swapped in
+            .add_systems(Update, (
+                (populate::record_landscape_removals, populate::regrow_landscape).before(populate::populate_rendered_subpixels),
+                populate::populate_rendered_subpixels.after(terrain::finish_terrain_build),
+                populate::cull_landscape_by_terrain.after(populate::populate_rendered_subpixels),
+                //track_entities_subpixel_position_raycast,
+                game_object::raycast_tile_locator_system.after(terrain::finish_terrain_build),
+                persistence::sync_persistent_objects.after(terrain::finish_terrain_build),
+                persistence::enforce_despawn_policies.after(persistence::sync_persistent_objects),
+                beacons::reanchor_beacons.after(terrain::finish_terrain_build),
+                geojson::spawn_imported_points.after(terrain::finish_terrain_build),
+                geojson::rebuild_zone_overlays.after(terrain::finish_terrain_build),
+            ).in_set(ObjectSystems).in_set(GameSet::TerrainMaintenance))
+            .add_systems(Update, (
+                game_object::setup_entity_overlays,          // Grow the pooled UI overlay nodes
+                game_object::update_entity_ui_overlays,
+            ).in_set(ObjectSystems).in_set(GameSet::UiSync))
+            // Not gated on the map: the terrain atlas and icons may fail to load while it loads
+            .add_systems(Update, placeholders::replace_failed_images)
+            .add_systems(Last, world_save::save_world.run_if(in_state(MapState::Ready)))
+            .add_systems(PostUpdate, beacons::cull_beacons.run_if(in_state(MapState::Ready)));
+    }
+}
+
+/// The player character: spawn, movement, sensors, cursor picking, stats and hotbar input
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<player::CursorTarget>()
+            .add_systems(OnEnter(MapState::Ready), game_object::setup_player.after(game_object::setup_object_templates))
+            .add_systems(Update, (
+                player::turn_player,                  // Mouse look
+                player::cast_ray_from_camera,
+                player::detect_mouse_clicks.after(player::cast_ray_from_camera),
+                hud::handle_hotbar_input,
+            ).in_set(PlayerSystems).in_set(GameSet::Input))
+            .add_systems(Update, (
+                player::check_player_sensors,        // Handle player item pickup detection
+                player::check_player_ground_sensors, // Handle player ground collision detection
+            ).in_set(PlayerSystems).in_set(GameSet::Simulation))
+            .add_systems(FixedUpdate, (
+                player::move_player,                 // Handle player movement and jumps with keyboard
+                (player_stats::regenerate_stamina, player_stats::update_breath).chain(),
+            ).in_set(PlayerSystems).in_set(GameSet::Simulation))
+            .add_systems(Update, (
+                player::highlight_cursor_tile,
+                sockets::hold_selected_item,         // Selected hotbar item in the player's hand
+                hud::update_hud,
+                hud::update_hotbar,
+            ).in_set(PlayerSystems).in_set(GameSet::UiSync));
+    }
+}
+
+/// Third-person and overhead cameras, shake, camera presets and occluder fading, and the
+/// day/night cycle lighting the scene under the procedural sky
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<camera::CameraShake>()
+            .add_event::<camera::CameraShakeEvent>()
+            .init_resource::<daylight::SunPosition>()
+            .add_systems(OnEnter(MapState::Ready), (camera::setup_third_person_camera, daylight::setup_daylight, sky::setup_sky))
+            .add_systems(Update, daylight::advance_game_time.in_set(CameraSystems).in_set(GameSet::Simulation))
+            .add_systems(Update, (
+                camera::handle_camera_orbit,         // Free orbit while middle mouse / Left Alt is held
+                camera::handle_camera_zoom,          // Handle mouse wheel zoom
+                camera::handle_shoulder_swap,        // Q swaps the over-the-shoulder side
+                camera::handle_overhead_camera,      // T toggles the overhead camera, WASD / edges pan it
+                settings::handle_camera_preset_keys, // F5..F8 camera presets
+                camera::apply_camera_settings.after(settings::hot_reload_settings), // Live camera tuning
+                camera::handle_camera_height,        // Handle keyboard arrow keys for height
+            ).in_set(CameraSystems).in_set(GameSet::Input))
+            .add_systems(Update, (
+                camera::update_third_person_camera,  // Update camera to follow player
+                camera::smooth_camera_zoom,          // Ease distance/FOV toward the zoom target
+                occlusion::fade_camera_occluders.after(camera::update_third_person_camera), // Fade objects hiding the player
+                daylight::update_daylight,           // Sun, moon and ambient light for the time of day
+                sky::update_sky.after(camera::update_third_person_camera).after(daylight::update_daylight), // Sky around the camera
+            ).in_set(CameraSystems).in_set(GameSet::UiSync));
+    }
+}
+
+/// User interface and user settings: HUD panels, overlays, menus, notifications, message log,
+/// the waypoint and measuring tools, GPX tracks, the simulation speed, and the adaptive music
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        let waypoints_path = save_path(app, waypoints::WAYPOINTS_SAVE_FILE);
+        let mut user_settings = settings::Settings::load(settings::SETTINGS_PATH);
+        if let Some(config) = app.world().get_resource::<StartupConfig>() {
+            if let Some(distance) = config.render_distance {
+                user_settings.graphics.render_distance = distance;
+            } else if !std::path::Path::new(settings::SETTINGS_PATH).exists() {
+                // First run: the render distance starts from the startup config terrain radius
+                user_settings.graphics.render_distance = config.terrain.terrain_radius;
+            }
+        }
+        app.insert_resource(user_settings)
+            .init_resource::<settings::SettingsWatcher>()
+            .init_resource::<settings_menu::SettingsMenuState>()
+            .init_resource::<ui::DebugPanel>()
+            .init_resource::<message_log::MessageLog>()
+            .insert_resource(waypoints::Waypoints::load(&waypoints_path))
+            .insert_resource(gpx::GpxTracks::load(gpx::GPX_DIR))
+            .init_resource::<measure::MeasureTool>()
+            .init_resource::<grid_overlay::GridOverlay>()
+            .init_resource::<trail::TrailMarkers>()
+            .insert_resource(music::MusicPlaylist::load(music::PLAYLIST_PATH))
+            .init_resource::<music::MusicState>()
+            .init_resource::<time_control::TimeScale>()
+            .add_event::<notifications::Notify>()
+            .add_event::<music::DuckMusic>()
+            .add_event::<message_log::LogMessage>()
+            .add_systems(OnEnter(MapState::Ready), (ui::setup_ui, minimap::setup_minimap, compass::setup_compass, perf_overlay::setup_perf_overlay,
+                settings_menu::setup_settings_menu, notifications::setup_notifications, ui::setup_crosshair, loading::setup_loading_screen,
+                message_log::setup_message_log, hud::setup_hud, hud::setup_hotbar, dev_panel::setup_dev_panel,
+                waypoints::setup_waypoint_panel, measure::setup_measure_readout, gpx::spawn_gpx_trails))
+            .add_systems(OnEnter(MapState::Failed), loading::setup_error_screen)
+            .add_systems(Update, loading::quit_from_error_screen.run_if(in_state(MapState::Failed)))
+            .add_systems(Update, (
+                settings::hot_reload_settings,  // Pick up edits to the settings file
+                settings::apply_settings.after(settings::hot_reload_settings), // Window, shadows, render distance, controls
+                (time_control::handle_time_keys, time_control::apply_time_scale).chain(), // Pause, slow motion, fast-forward
+            ).in_set(UiSystems).in_set(GameSet::Input))
+            .add_systems(Update, (
+                loading::update_loading_screen,
+                (ui::update_coordinate_display, ui::update_debug_panel).chain(),
+                ui::update_hover_info,
+                (dev_panel::toggle_dev_panel, dev_panel::update_dev_panel).chain(),
+                (grid_overlay::toggle_grid_overlay, grid_overlay::draw_grid_overlay).chain(),
+                (message_log::collect_log_messages, message_log::toggle_message_log, message_log::update_message_log).chain(),
+                (music::update_music_context, music::mix_music).chain(),
+            ).in_set(UiSystems).in_set(GameSet::UiSync))
+            .add_systems(Update, (ui::handle_method_buttons, ui::update_method_button_colors, minimap::update_minimap, compass::update_compass,
+                perf_overlay::toggle_perf_overlay, perf_overlay::update_perf_overlay,
+                settings_menu::toggle_settings_menu, settings_menu::handle_settings_menu, settings_menu::update_settings_menu,
+                notifications::spawn_toasts, notifications::update_toasts, waypoints::update_waypoint_indicators)
+                .in_set(UiSystems).in_set(GameSet::UiSync))
+            .add_systems(Update, (
+                (waypoints::place_waypoint, waypoints::toggle_waypoint_panel, waypoints::update_waypoint_panel,
+                    waypoints::sync_waypoint_beacons, waypoints::update_waypoint_route, gpx::update_track_navigation,
+                    waypoints::save_waypoints).chain(),
+                (measure::handle_measure_input, measure::update_measure_readout).chain(),
+                (gpx::refresh_gpx_trails, trail::sync_path_trails, trail::draw_path_trails).chain()
+                    .after(measure::handle_measure_input).after(waypoints::update_waypoint_route),
+            ).in_set(UiSystems).in_set(GameSet::UiSync));
+    }
+}
+
+/// Systems of `NetClientPlugin`: server messages, input and build replication
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NetSystems;
+
+/// Plays on a `tiles3d-server` (`--connect`): the server moves the player from its input and
+/// replicates the other players and their placed objects. Without a server the game stays local.
+pub struct NetClientPlugin {
+    pub address: String,   // host:port
+    pub name: String,      // Player name shown to the others
+}
+
+impl Plugin for NetClientPlugin {
+    fn build(&self, app: &mut App) {
+        let client = match net_client::NetClient::connect(&self.address, &self.name) {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Could not connect to {}: {}, playing offline", self.address, e);
+                return;
+            }
+        };
+        info!("Connected to {}", self.address);
+        app.insert_resource(client)
+            .add_systems(Update, (
+                net_client::receive_server_messages,
+                net_client::send_player_input,
+                net_client::send_build_events.after(building::handle_building_input),
+            ).in_set(NetSystems).in_set(GameSet::Simulation))
+            .add_systems(Update, net_client::reposition_remote_objects.after(terrain::finish_terrain_build)
+                .in_set(NetSystems).in_set(GameSet::TerrainMaintenance));
+    }
+}
+
+/// Systems of `RemoteApiPlugin`
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RemoteSystems;
+
+/// Remote control API (`--remote <port>`): external tools query the game state and send commands
+/// over HTTP, see `remote::RemoteApi`. The game runs on without it when the port is taken.
+pub struct RemoteApiPlugin {
+    pub port: u16,
+}
+
+impl Plugin for RemoteApiPlugin {
+    fn build(&self, app: &mut App) {
+        let api = match remote::RemoteApi::bind(self.port) {
+            Ok(api) => api,
+            Err(e) => {
+                warn!("Could not serve the remote API on port {}: {}", self.port, e);
+                return;
+            }
+        };
+        info!("Remote API listening on http://127.0.0.1:{}", self.port);
+        app.insert_resource(api)
+            .add_systems(Update, remote::serve_remote_api.in_set(RemoteSystems).in_set(GameSet::Input))
+            .add_systems(Update, remote::release_teleport_holds.after(terrain::finish_terrain_build)
+                .in_set(RemoteSystems).in_set(GameSet::TerrainMaintenance));
+    }
+}
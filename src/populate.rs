@@ -0,0 +1,359 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::beacons::{spawn_beacon, BeaconAnchor, BeaconKind, BeaconPool};
+use crate::game_object::{spawn_objects_batch, EntitySubpixelPosition, IntoWorldPosition, ObjectEvent, ObjectTemplates};
+use crate::items::ItemDefinitions;
+use crate::persistence::PersistedObjects;
+use crate::props::{scatter_item_props, tags, Prop, BEACON_TEMPLATE};
+use crate::planisphere::Planisphere;
+use crate::seasons::{Season, SeasonState};
+use crate::settings::Settings;
+use crate::terrain::{geo_to_world, select_texture_from_rgba, TerrainCenter};
+use crate::terrain::texture::WorldSeed;
+
+/// Biome spawn rules scattered on the rendered terrain
+pub const SPAWN_CONFIG_PATH: &str = "assets/spawn/biomes.ron";
+
+/// Template candidate of a biome with its relative weight
+#[derive(Debug, Clone, Deserialize)]
+pub struct WeightedTemplate {
+    pub template: String,     // Key in ObjectTemplates (e.g. "tree")
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default)]
+    pub vegetation: bool,     // Scaled by the vegetation density (green channel) and clustered in groves
+    #[serde(default)]
+    pub seasons: Vec<Season>, // Seasons the template spawns in, all when empty
+    #[serde(default)]
+    pub spacing: f64,         // Minimum distance to other scattered objects in tiles, 0 for none
+}
+
+fn default_weight() -> u32 { 1 }
+
+/// Objects spawned on the terrain textures of one biome
+#[derive(Debug, Clone, Deserialize)]
+pub struct BiomeSpawn {
+    pub name: String,
+    pub textures: Vec<usize>,           // Texture atlas indices (see `select_texture_from_rgba`)
+    pub density: f64,                   // Probability that a scatter sample (about one per subpixel) gets an object, before vegetation scaling
+    pub objects: Vec<WeightedTemplate>,
+}
+
+/// Spawn rules for every biome, loaded from `SPAWN_CONFIG_PATH`
+#[derive(Resource, Debug, Clone, Default, Deserialize)]
+pub struct SpawnConfig {
+    pub biomes: Vec<BiomeSpawn>,
+}
+
+impl SpawnConfig {
+    /// Read the spawn rules; a missing or broken file is reported and spawns nothing
+    pub fn load(path: &str) -> Self {
+        match load_spawn_config_file(Path::new(path)) {
+            Ok(config) => {
+                info!("Loaded {} biome spawn rules from {}", config.biomes.len(), path);
+                config
+            }
+            Err(e) => {
+                warn!("Could not load spawn config {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Template to spawn at a scatter sample of pixel `(i, j)` with the given texture, if any.
+    /// `draw` is the sample's random value in [0, 1), deterministic so the same objects come
+    /// back when the terrain is rebuilt.
+    ///
+    /// `vegetation` is the vegetation density (0.0–1.0) at the sample, from the planisphere green
+    /// channel. It scales the probability of vegetation templates, which also cluster in groves:
+    /// whole pixels drawn as groves (more likely where greener) get most of the vegetation.
+    /// The season scales vegetation further and filters out-of-season templates; groves depend
+    /// on the world seed.
+    pub fn pick(&self, texture_index: usize, (i, j): (usize, usize), draw: f64, vegetation: f64, season: Season, seed: WorldSeed) -> Option<&WeightedTemplate> {
+        use crate::config::vegetation::{CLEARING_FACTOR, GROVE_BOOST, MAX_FACTOR};
+
+        let biome = self.biomes.iter().find(|biome| biome.textures.contains(&texture_index))?;
+        let total_weight: u32 = biome.objects.iter().map(|object| object.weight).sum();
+        if total_weight == 0 {
+            return None;
+        }
+        let vegetation = vegetation.clamp(0.0, 1.0);
+        let grove = seed.random(i, j, GROVE_SALT) < vegetation;
+        let vegetation_factor = MAX_FACTOR * vegetation * if grove { GROVE_BOOST } else { CLEARING_FACTOR } * season.vegetation_density();
+
+        // Walk the cumulative probabilities of the templates with the single draw
+        let mut draw = draw;
+        biome.objects.iter().find(|object| {
+            if !object.seasons.is_empty() && !object.seasons.contains(&season) {
+                return false;
+            }
+            let mut probability = biome.density * object.weight as f64 / total_weight as f64;
+            if object.vegetation {
+                probability *= vegetation_factor;
+            }
+            if draw < probability { true } else { draw -= probability; false }
+        })
+    }
+}
+
+// Salts of the per-pixel random draws (the third `WorldSeed::random` coordinate), far above
+// subpixel indices
+const GROVE_SALT: usize = 1 << 20;
+const DART_SALT: usize = 1 << 21;
+
+/// Point picked by the scatter inside a pixel, with the subpixel it falls in
+#[derive(Debug, Clone, Copy)]
+pub struct ScatterPoint {
+    pub tile: (usize, usize, usize),
+    pub geo: (f64, f64),   // (longitude, latitude) in degrees
+    pub dart: usize,       // Index of the dart in its pixel, identifies the object with the pixel
+}
+
+impl IntoWorldPosition for ScatterPoint {
+    fn into_world_position(&self, planisphere: &Planisphere, terrain_center: &TerrainCenter) -> Vec3 {
+        geo_to_world(self.geo.0, self.geo.1, planisphere, terrain_center)
+    }
+
+    fn tile(&self) -> Option<(usize, usize, usize)> {
+        Some(self.tile)
+    }
+}
+
+/// Blue-noise scatter of biome objects over pixel `(i, j)`: darts are thrown at random points
+/// (`config::scatter::DARTS_PER_SUBPIXEL` per subpixel), each picks a template for the subpixel
+/// it lands in, and picks closer than their `spacing` to an accepted object are rejected
+/// (Poisson-disk sampling). Objects keep half their spacing from the pixel edges, so spacing
+/// also holds across pixels. Darts are resolved for the whole pixel and only those landing on
+/// `rendered` subpixels are returned, so the result does not depend on the rendered area.
+/// Darts are drawn from `seed`: the same seed and map give the same objects.
+pub fn scatter_pixel<'a>(
+    spawn_config: &'a SpawnConfig,
+    planisphere: &Planisphere,
+    (i, j): (usize, usize),
+    rendered: &HashSet<(usize, usize, usize)>,
+    season: Season,
+    seed: WorldSeed,
+) -> Vec<(&'a str, ScatterPoint)> {
+    let subpixels = planisphere.get_pixel_lon_subdivisions(i, j) * planisphere.get_subpixel_divisions();
+    let darts = (subpixels as f64 * crate::config::scatter::DARTS_PER_SUBPIXEL).ceil() as usize;
+    let pixel_width = 360.0 / planisphere.width_pixels as f64;
+    let pixel_height = 180.0 / planisphere.height_pixels as f64;
+    // Pixel extent in world units, for distances between darts
+    let units_per_degree = planisphere.radius * std::f64::consts::PI / 180.0;
+    let latitude = -90.0 + (j as f64 + 0.5) * pixel_height;
+    let size = (pixel_width * units_per_degree * latitude.to_radians().cos(), pixel_height * units_per_degree);
+
+    let mut accepted: Vec<((f64, f64), f64)> = Vec::new();   // (position in the pixel, spacing) in world units
+    let mut picks = Vec::new();
+    for dart in 0..darts {
+        let salt = DART_SALT + dart * 4;
+        let (u, v) = (seed.random(i, j, salt), seed.random(i, j, salt + 1));
+        let geo = (-180.0 + (i as f64 + u) * pixel_width, -90.0 + (j as f64 + v) * pixel_height);
+        let tile = planisphere.geo_to_subpixel(geo.0, geo.1);
+        let (red, green, blue, alpha) = planisphere.get_rgba_at_subpixel(tile.0 as i32, tile.1 as i32, tile.2);
+        let texture = select_texture_from_rgba(red, green, blue, alpha);
+        let Some(object) = spawn_config.pick(texture, (i, j), seed.random(i, j, salt + 2), green, season, seed) else { continue; };
+
+        let spacing = object.spacing * planisphere.mean_tile_size;
+        let position = (u * size.0, v * size.1);
+        let margin = spacing / 2.0;
+        if position.0 < margin || position.0 > size.0 - margin || position.1 < margin || position.1 > size.1 - margin {
+            continue;
+        }
+        let crowded = accepted.iter().any(|&(other, other_spacing)| {
+            (position.0 - other.0).hypot(position.1 - other.1) < spacing.max(other_spacing)
+        });
+        if crowded {
+            continue;
+        }
+        accepted.push((position, spacing));
+        if rendered.contains(&tile) {
+            picks.push((object.template.as_str(), ScatterPoint { tile, geo, dart }));
+        }
+    }
+    picks
+}
+
+/// Parse the spawn rules file
+pub fn load_spawn_config_file(path: &Path) -> Result<SpawnConfig, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str::<SpawnConfig>(&content).map_err(|e| e.to_string())
+}
+
+/// Marks objects scattered by `populate_rendered_subpixels`
+#[derive(Component)]
+pub struct PopulatedObject;
+
+/// Rendered area and season the scattered objects were spawned for
+#[derive(Resource, Default)]
+pub struct PopulatedArea {
+    synced_area: Option<((usize, usize, usize), usize, Season)>,
+    spawned: HashMap<Entity, LandscapeKey>,   // Scattered biome objects of the current area
+}
+
+/// Identity of a scattered landscape element, stable across terrain rebuilds and sessions: the
+/// scatter is deterministic, so a pixel's dart always lands on the same tile. The dart tells
+/// apart objects sharing a tile, the template those a dart picks in different seasons.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LandscapeKey {
+    pub tile: (usize, usize, usize),
+    pub dart: usize,
+    pub template: String,
+}
+
+/// Scatter biome objects and collectible items over the rendered subpixels, plus a debug beacon
+/// on the terrain center, whenever the rendered area or the season changes. World positions
+/// depend on the terrain center, so the previous objects are replaced.
+#[allow(clippy::too_many_arguments)]
+pub fn populate_rendered_subpixels(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut populated: ResMut<PopulatedArea>,
+    persisted: Res<PersistedObjects>,
+    mut beacon_pool: ResMut<BeaconPool>,
+    spawn_config: Res<SpawnConfig>,
+    season: Res<SeasonState>,
+    object_templates: Res<ObjectTemplates>,
+    item_definitions: Res<ItemDefinitions>,
+    world_seed: Res<WorldSeed>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    previous_objects: Query<Entity, With<PopulatedObject>>,
+) {
+    let area = (terrain_center.subpixel, terrain_center.rendered_subpixels.subpixels.len(), season.season);
+    if populated.synced_area == Some(area) || area.1 == 0 {
+        return;
+    }
+    populated.synced_area = Some(area);
+
+    for entity in previous_objects.iter() {
+        commands.entity(entity).despawn();
+    }
+    populated.spawned.clear();
+
+    let rendered: HashSet<(usize, usize, usize)> = terrain_center.rendered_subpixels.subpixels.iter()
+        .map(|(i, j, k, _)| (*i, *j, *k))
+        .collect();
+    let pixels: HashSet<(usize, usize)> = rendered.iter().map(|(i, j, _)| (*i, *j)).collect();
+
+    let mut missing: HashMap<&str, usize> = HashMap::new();
+    let objects: Vec<_> = pixels.iter()
+        .flat_map(|pixel| scatter_pixel(&spawn_config, &planisphere, *pixel, &rendered, season.season, *world_seed))
+        .map(|(name, point)| (LandscapeKey { tile: point.tile, dart: point.dart, template: name.to_string() }, name, point))
+        .filter(|(key, _, _)| !persisted.is_landscape_removed(key))
+        .filter_map(|(key, name, point)| match object_templates.get(name) {
+            Ok(template) => Some((key, template, point)),
+            Err(_) => {
+                *missing.entry(name).or_default() += 1;
+                None
+            }
+        })
+        .collect();
+    for (name, count) in missing {
+        warn!("Skipping {} spawns of unknown template '{}'", count, name);
+    }
+
+    // Templates are spawned in one batch (`spawn_template_scene` with shared materials); the
+    // subpixel position lets tile-based systems (occupancy, overlays, despawn rules) treat them
+    // like any other template object
+    let batch = objects.iter().map(|(_, template, point)| (*template, *point));
+    let entities = spawn_objects_batch(&mut commands, &mut materials, &planisphere, &terrain_center, batch);
+    for (entity, (key, template, point)) in entities.into_iter().zip(objects) {
+        let position = EntitySubpixelPosition {
+            subpixel: point.tile,
+            geo_coords: point.geo,
+            world_pos: point.into_world_position(&planisphere, &terrain_center),
+            previous_subpixel: point.tile,
+            ..default()
+        };
+        commands.entity(entity).insert((Prop::new(template), position, PopulatedObject));
+        populated.spawned.insert(entity, key);
+    }
+
+    let mut extras = scatter_item_props(&mut commands, &mut materials, &planisphere, &terrain_center, &object_templates, &item_definitions, *world_seed);
+    match spawn_beacon(&mut commands, &mut beacon_pool, &mut materials, &planisphere, &terrain_center, &object_templates,
+                       BEACON_TEMPLATE, BeaconAnchor::Tile(terrain_center.subpixel), BeaconKind::TerrainCenter) {
+        Ok(beacon) => extras.push(beacon),
+        Err(e) => warn!("Cannot spawn terrain center beacon: {}", e),
+    }
+    for entity in extras {
+        commands.entity(entity).insert(PopulatedObject);
+    }
+}
+
+/// Rendered tiles and pixels the landscape visibility was last computed for
+#[derive(Default)]
+pub struct LandscapeCullArea {
+    area: Option<((usize, usize, usize), usize)>,   // (terrain center, rendered count)
+    tiles: HashSet<(usize, usize, usize)>,
+    pixels: HashSet<(usize, usize)>,
+}
+
+/// Show landscape elements standing on a rendered subpixel and hide those whose pixel is no
+/// longer rendered at all. In between (rendered pixel, subpixel outside the rendered area) the
+/// visibility is kept, so elements along the edge of the terrain do not flicker as it is
+/// rebuilt. Debug props are left to `props::apply_prop_visibility`.
+pub fn cull_landscape_by_terrain(
+    terrain_center: Res<TerrainCenter>,
+    mut cull_area: Local<LandscapeCullArea>,
+    mut query: Query<(&Prop, Ref<EntitySubpixelPosition>, &mut Visibility), With<PopulatedObject>>,
+) {
+    let area = (terrain_center.subpixel, terrain_center.rendered_subpixels.subpixels.len());
+    let area_changed = cull_area.area != Some(area);
+    if area_changed {
+        cull_area.tiles = terrain_center.rendered_subpixels.subpixels.iter().map(|(i, j, k, _)| (*i, *j, *k)).collect();
+        cull_area.pixels = cull_area.tiles.iter().map(|(i, j, _)| (*i, *j)).collect();
+        cull_area.area = Some(area);
+    }
+
+    for (prop, position, mut visibility) in query.iter_mut() {
+        if !(area_changed || position.is_changed()) || prop.has_tag(tags::DEBUG) {
+            continue;
+        }
+        let (i, j, k) = position.subpixel;
+        if cull_area.tiles.contains(&(i, j, k)) {
+            visibility.set_if_neq(Visibility::Inherited);
+        } else if !cull_area.pixels.contains(&(i, j)) {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
+
+/// Remember the scattered landscape elements that get destroyed (harvested, broken) in the world
+/// save, so they are not scattered again
+pub fn record_landscape_removals(
+    mut events: EventReader<ObjectEvent>,
+    mut populated: ResMut<PopulatedArea>,
+    mut persisted: ResMut<PersistedObjects>,
+) {
+    for event in events.read() {
+        if let ObjectEvent::Destroyed { entity } = event
+            && let Some(key) = populated.spawned.remove(entity) {
+            persisted.remove_landscape(key);
+        }
+    }
+}
+
+/// With `WorldSettings::landscape_regrowth_secs` set, bring back the removed landscape elements
+/// once their regrowth time has passed, repopulating the rendered area when some do
+pub fn regrow_landscape(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut check_timer: Local<Option<Timer>>,
+    mut persisted: ResMut<PersistedObjects>,
+    mut populated: ResMut<PopulatedArea>,
+) {
+    let Some(regrowth_secs) = settings.world.landscape_regrowth_secs else { return; };
+    let timer = check_timer.get_or_insert_with(|| Timer::from_seconds(crate::config::objects::REGROWTH_CHECK_SECS, TimerMode::Repeating));
+    if !timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let regrown = persisted.regrow_landscape(regrowth_secs);
+    if regrown > 0 {
+        debug!("{} landscape elements grew back", regrown);
+        populated.synced_area = None;
+    }
+}
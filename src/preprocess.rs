@@ -0,0 +1,351 @@
+//! Asset preprocessing, run by `tiles3d-preprocess` before the game: validates the elevation
+//! map, builds the terrain texture atlas from `assets/textures/img`, writes the sampled
+//! planisphere as a binary cache with a mip pyramid of the map, and a manifest listing them.
+//!
+//! The game reads the manifest at startup: when it was made from the same map file it loads the
+//! planisphere cache instead of decoding the map, and uses the generated atlas.
+
+use std::fmt;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgb, RgbImage};
+use serde::{Deserialize, Serialize};
+
+use crate::planisphere::Planisphere;
+
+/// Directory the preprocessed files and the manifest are written to
+pub const CACHE_DIR: &str = "assets/cache";
+/// Manifest file in `CACHE_DIR`
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// Version written to the manifest; manifests of another version are ignored
+pub const MANIFEST_VERSION: u32 = 1;
+/// Source textures of the atlas, one image per terrain type, in file name order
+pub const TEXTURES_DIR: &str = "assets/textures/img";
+/// Elevation map preprocessed when none is given
+pub const DEFAULT_MAP: &str = "assets/maps/sphere_texture.png";
+
+/// Side of an atlas tile in pixels, a 1 pixel grey border included
+const ATLAS_TILE_SIZE: u32 = 16;
+/// Border colour of the atlas tiles
+const ATLAS_BORDER: Rgb<u8> = Rgb([128, 128, 128]);
+/// Fill of the atlas tiles without a texture
+const ATLAS_EMPTY: Rgb<u8> = Rgb([200, 200, 200]);
+/// Image formats read from `TEXTURES_DIR`
+const TEXTURE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "bmp", "gif", "tiff"];
+
+/// Why preprocessing (or loading its output) failed
+#[derive(Debug)]
+pub enum PreprocessError {
+    Io { path: String, source: std::io::Error },
+    Image { path: String, source: image::ImageError },
+    /// The map cannot be used as a planisphere
+    InvalidMap { path: String, reason: String },
+    NoTextures { dir: String },
+    /// Unreadable or incompatible manifest
+    Manifest { path: String, reason: String },
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "{}: {}", path, source),
+            Self::Image { path, source } => write!(f, "{} is not a readable image: {}", path, source),
+            Self::InvalidMap { path, reason } => write!(f, "Elevation map {} is not usable: {}", path, reason),
+            Self::NoTextures { dir } => write!(f, "No texture images ({}) in {}", TEXTURE_EXTENSIONS.join(", "), dir),
+            Self::Manifest { path, reason } => write!(f, "Asset manifest {}: {}", path, reason),
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Image { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+fn io_error(path: impl AsRef<Path>) -> impl FnOnce(std::io::Error) -> PreprocessError {
+    let path = path.as_ref().display().to_string();
+    move |source| PreprocessError::Io { path, source }
+}
+
+/// Preprocessed files of a map, written to `CACHE_DIR/MANIFEST_FILE`. Paths are relative to the
+/// working directory, like the map path of the startup config.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Manifest {
+    pub version: u32,
+    pub map: MapInfo,
+    pub planisphere_cache: String,   // `Planisphere::write_cache` of the map
+    pub mips: Vec<MipLevel>,         // Map halved at each level, the full size map excluded
+    pub atlas: AtlasInfo,
+}
+
+/// Elevation map the files were made from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MapInfo {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    pub hash: String,   // FNV-1a of the file contents, to tell whether the cache is stale
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MipLevel {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Terrain texture atlas, `config::atlas::SIZE` tiles per side
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AtlasInfo {
+    pub path: String,
+    pub textures: Vec<String>,   // Source texture of each tile index, in order
+}
+
+/// Read by the game at startup (`PlanispherePlugin`)
+#[cfg(feature = "game")]
+impl bevy::prelude::Resource for Manifest {}
+
+impl Manifest {
+    /// Read the manifest in `dir`; `None` when there is none
+    pub fn load(dir: &str) -> Result<Option<Self>, PreprocessError> {
+        let path = Path::new(dir).join(MANIFEST_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(io_error(&path)(e)),
+        };
+        let manifest_error = |reason: String| PreprocessError::Manifest { path: path.display().to_string(), reason };
+        let manifest: Self = serde_json::from_str(&content).map_err(|e| manifest_error(e.to_string()))?;
+        if manifest.version != MANIFEST_VERSION {
+            return Err(manifest_error(format!("version {} instead of {}, run tiles3d-preprocess again", manifest.version, MANIFEST_VERSION)));
+        }
+        Ok(Some(manifest))
+    }
+
+    /// Whether the files were made from the current contents of the map at `map_path`
+    pub fn is_for_map(&self, map_path: &str) -> Result<bool, PreprocessError> {
+        let bytes = std::fs::read(map_path).map_err(io_error(map_path))?;
+        Ok(self.map.hash == content_hash(&bytes))
+    }
+
+    /// Load the planisphere cache, with `subpixel_divisions` subdivisions per pixel row
+    pub fn load_planisphere(&self, subpixel_divisions: usize) -> Result<Planisphere, PreprocessError> {
+        let file = std::fs::File::open(&self.planisphere_cache).map_err(io_error(&self.planisphere_cache))?;
+        let planisphere = Planisphere::read_cache(&mut BufReader::new(file), subpixel_divisions)
+            .map_err(io_error(&self.planisphere_cache))?;
+        if (planisphere.width_pixels, planisphere.height_pixels) != (self.map.width as usize, self.map.height as usize) {
+            return Err(PreprocessError::Manifest {
+                path: self.planisphere_cache.clone(),
+                reason: format!("cached map is {}x{}, the manifest says {}x{}", planisphere.width_pixels,
+                                planisphere.height_pixels, self.map.width, self.map.height),
+            });
+        }
+        Ok(planisphere)
+    }
+}
+
+/// Open the elevation map at `path` and check it can be used as a planisphere
+pub fn validate_map(path: &str) -> Result<DynamicImage, PreprocessError> {
+    if !Path::new(path).is_file() {
+        return Err(PreprocessError::InvalidMap { path: path.to_string(), reason: "no such file".to_string() });
+    }
+    let image = image::open(path).map_err(|source| PreprocessError::Image { path: path.to_string(), source })?;
+    check_map_dimensions(path, image.width(), image.height())?;
+    Ok(image)
+}
+
+/// The planisphere is an equirectangular map: 360 degrees of longitude across, 180 of latitude
+/// down, so twice as wide as high
+pub fn check_map_dimensions(path: &str, width: u32, height: u32) -> Result<(), PreprocessError> {
+    let invalid = |reason: String| Err(PreprocessError::InvalidMap { path: path.to_string(), reason });
+    if height < 2 {
+        return invalid(format!("{}x{} is too small, it needs at least 2 pixel rows", width, height));
+    }
+    if width != 2 * height {
+        return invalid(format!("{}x{} is not an equirectangular map, the width must be twice the height ({}x{})",
+                               width, height, 2 * height, height));
+    }
+    Ok(())
+}
+
+/// FNV-1a hash of `bytes`, as 16 hexadecimal digits
+fn content_hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| (hash ^ *byte as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+/// Map halved until it is a single pixel row, the full size map excluded
+pub fn build_mip_pyramid(map: &DynamicImage) -> Vec<DynamicImage> {
+    let mut levels = Vec::new();
+    let mut level = map.clone();
+    while level.height() > 1 {
+        level = level.resize_exact((level.width() / 2).max(1), level.height() / 2, FilterType::Triangle);
+        levels.push(level.clone());
+    }
+    levels
+}
+
+/// Terrain texture atlas made from the images in `dir`, in file name order (the order of
+/// `tiling::texture::TEXTURE_NAMES`), with the names of the textures used
+pub fn build_atlas(dir: &str) -> Result<(RgbImage, Vec<String>), PreprocessError> {
+    let mut files: Vec<_> = std::fs::read_dir(dir).map_err(io_error(dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str())
+            .is_some_and(|ext| TEXTURE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str())))
+        .collect();
+    files.sort();
+    if files.is_empty() {
+        return Err(PreprocessError::NoTextures { dir: dir.to_string() });
+    }
+
+    let tiles_per_side = crate::config::atlas::SIZE as u32;
+    let capacity = (tiles_per_side * tiles_per_side) as usize;
+    if files.len() > capacity {
+        tracing::warn!("{} textures in {}, the atlas holds {}: ignoring the last {}", files.len(), dir, capacity, files.len() - capacity);
+        files.truncate(capacity);
+    }
+    let mut atlas = RgbImage::from_pixel(tiles_per_side * ATLAS_TILE_SIZE, tiles_per_side * ATLAS_TILE_SIZE, ATLAS_BORDER);
+    for index in 0..capacity as u32 {
+        let tile = match files.get(index as usize) {
+            Some(path) => {
+                let image = image::open(path)
+                    .map_err(|source| PreprocessError::Image { path: path.display().to_string(), source })?;
+                atlas_tile(&image)
+            }
+            None => RgbImage::from_pixel(ATLAS_TILE_SIZE - 2, ATLAS_TILE_SIZE - 2, ATLAS_EMPTY),
+        };
+        let (x, y) = ((index % tiles_per_side) * ATLAS_TILE_SIZE, (index / tiles_per_side) * ATLAS_TILE_SIZE);
+        image::imageops::replace(&mut atlas, &tile, (x + 1) as i64, (y + 1) as i64);
+    }
+    let names = files.iter()
+        .map(|path| path.file_stem().map_or_else(String::new, |stem| stem.to_string_lossy().into_owned()))
+        .collect();
+    Ok((atlas, names))
+}
+
+/// Texture shrunk to the inside of a tile, with more contrast, saturation and brightness, then
+/// reduced to 16-bit colours (5 bits red, 6 green, 5 blue) for the game's flat pixel look
+fn atlas_tile(texture: &DynamicImage) -> RgbImage {
+    let size = ATLAS_TILE_SIZE - 2;
+    let mut tile = texture.resize_exact(size, size, FilterType::Lanczos3).to_rgb8();
+    let luma = |pixel: &Rgb<u8>| 0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32;
+    let mean_luma = tile.pixels().map(luma).sum::<f32>() / (size * size) as f32;
+    for pixel in tile.pixels_mut() {
+        let contrasted = pixel.0.map(|channel| mean_luma + 1.3 * (channel as f32 - mean_luma));
+        let gray = 0.299 * contrasted[0] + 0.587 * contrasted[1] + 0.114 * contrasted[2];
+        let [r, g, b] = contrasted.map(|channel| {
+            let saturated = (gray + 1.4 * (channel - gray)).clamp(0.0, 255.0);
+            let brightened = (saturated * 1.1).clamp(0.0, 255.0);
+            ((brightened / 255.0).powf(0.8) * 255.0).min(255.0) as u8
+        });
+        *pixel = Rgb([r & 0xf8, g & 0xfc, b & 0xf8]);
+    }
+    tile
+}
+
+/// What to preprocess and where to
+#[derive(Debug, Clone)]
+pub struct PreprocessOptions {
+    pub map: String,
+    pub textures_dir: String,
+    pub out_dir: String,
+}
+
+impl Default for PreprocessOptions {
+    fn default() -> Self {
+        Self { map: DEFAULT_MAP.to_string(), textures_dir: TEXTURES_DIR.to_string(), out_dir: CACHE_DIR.to_string() }
+    }
+}
+
+/// Validate the map, write the atlas, planisphere cache and mip pyramid to `options.out_dir`,
+/// then the manifest listing them
+pub fn run(options: &PreprocessOptions) -> Result<Manifest, PreprocessError> {
+    let map = validate_map(&options.map)?;
+    let map_bytes = std::fs::read(&options.map).map_err(io_error(&options.map))?;
+    let out_dir = Path::new(&options.out_dir);
+    std::fs::create_dir_all(out_dir).map_err(io_error(out_dir))?;
+    let out_path = |file: &str| out_dir.join(file).to_string_lossy().into_owned();
+
+    let (atlas, textures) = build_atlas(&options.textures_dir)?;
+    let atlas_path = out_path("texture_atlas.png");
+    atlas.save(&atlas_path).map_err(|source| PreprocessError::Image { path: atlas_path.clone(), source })?;
+    tracing::info!("Texture atlas with {} textures: {}", textures.len(), atlas_path);
+
+    let planisphere = Planisphere::from_image(map.clone(), crate::config::terrain::SUB_K);
+    let planisphere_cache = out_path("planisphere.bin");
+    let file = std::fs::File::create(&planisphere_cache).map_err(io_error(&planisphere_cache))?;
+    let mut writer = BufWriter::new(file);
+    planisphere.write_cache(&mut writer)
+        .and_then(|()| std::io::Write::flush(&mut writer))
+        .map_err(io_error(&planisphere_cache))?;
+    tracing::info!("Planisphere cache: {}", planisphere_cache);
+
+    let mut mips = Vec::new();
+    for (level, image) in build_mip_pyramid(&map).iter().enumerate() {
+        let path = out_path(&format!("map_mip{}.png", level + 1));
+        image.save(&path).map_err(|source| PreprocessError::Image { path: path.clone(), source })?;
+        mips.push(MipLevel { path, width: image.width(), height: image.height() });
+    }
+    tracing::info!("Mip pyramid: {} levels", mips.len());
+
+    let manifest = Manifest {
+        version: MANIFEST_VERSION,
+        map: MapInfo { path: options.map.clone(), width: map.width(), height: map.height(), hash: content_hash(&map_bytes) },
+        planisphere_cache,
+        mips,
+        atlas: AtlasInfo { path: atlas_path, textures },
+    };
+    let manifest_path = out_path(MANIFEST_FILE);
+    let json = serde_json::to_string_pretty(&manifest).map_err(|e| io_error(&manifest_path)(e.into()))?;
+    std::fs::write(&manifest_path, json).map_err(io_error(&manifest_path))?;
+    tracing::info!("Manifest: {}", manifest_path);
+    Ok(manifest)
+}
+
+/// Planisphere from the cache of `manifest` when it was made from the map at `map_path`; `None`
+/// (with the reason logged) when the map must be decoded instead
+pub fn cached_planisphere(manifest: &Manifest, map_path: &str, subpixel_divisions: usize) -> Option<Planisphere> {
+    match manifest.is_for_map(map_path) {
+        Ok(true) => {}
+        Ok(false) => {
+            tracing::info!("Planisphere cache is for {}, not {}: run tiles3d-preprocess to update it", manifest.map.path, map_path);
+            return None;
+        }
+        Err(e) => {
+            tracing::warn!("Ignoring planisphere cache: {}", e);
+            return None;
+        }
+    }
+    match manifest.load_planisphere(subpixel_divisions) {
+        Ok(planisphere) => {
+            tracing::info!("Loaded planisphere cache {} ({}x{})", manifest.planisphere_cache, planisphere.width_pixels, planisphere.height_pixels);
+            Some(planisphere)
+        }
+        Err(e) => {
+            tracing::warn!("Ignoring planisphere cache: {}", e);
+            None
+        }
+    }
+}
+
+/// Planisphere of the map at `map_path`: from the preprocessed cache in `CACHE_DIR` when it is
+/// up to date, otherwise decoded from the validated map
+pub fn load_planisphere(map_path: &str, subpixel_divisions: usize) -> Result<Planisphere, PreprocessError> {
+    match Manifest::load(CACHE_DIR) {
+        Ok(Some(manifest)) => {
+            if let Some(planisphere) = cached_planisphere(&manifest, map_path, subpixel_divisions) {
+                return Ok(planisphere);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Ignoring {}", e),
+    }
+    Ok(Planisphere::from_image(validate_map(map_path)?, subpixel_divisions))
+}
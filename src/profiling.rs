@@ -0,0 +1,59 @@
+//! Profiling instrumentation shared by the library and the game. `profile_span!` opens a
+//! `tracing` span with the `profiling` feature (on with `game`); spans show up in Tracy with the
+//! `trace_tracy` feature, or in any other `tracing` subscriber. `StageTimer` logs the time spent
+//! in each stage of a long operation with the `detailed_timers` feature. Both compile to nothing
+//! when their feature is off.
+
+#[cfg(feature = "profiling")]
+#[doc(hidden)]
+pub use tracing;
+
+/// Enter an info-level span until the end of the enclosing block:
+/// `profile_span!("terrain_mesh")`, or with fields `profile_span!("terrain_mesh", subpixels = n)`
+#[macro_export]
+macro_rules! profile_span {
+    ($($args:tt)*) => {
+        #[cfg(feature = "profiling")]
+        let _profile_span = $crate::profiling::tracing::info_span!($($args)*).entered();
+    };
+}
+
+/// Times the stages of an operation; with the `detailed_timers` feature each `stage` logs the
+/// time since the previous one and `finish` the total
+pub struct StageTimer {
+    #[cfg(feature = "detailed_timers")]
+    name: &'static str,
+    #[cfg(feature = "detailed_timers")]
+    start: std::time::Instant,
+    #[cfg(feature = "detailed_timers")]
+    last: std::time::Instant,
+}
+
+impl StageTimer {
+    pub fn start(_name: &'static str) -> Self {
+        Self {
+            #[cfg(feature = "detailed_timers")]
+            name: _name,
+            #[cfg(feature = "detailed_timers")]
+            start: std::time::Instant::now(),
+            #[cfg(feature = "detailed_timers")]
+            last: std::time::Instant::now(),
+        }
+    }
+
+    /// End of a stage
+    pub fn stage(&mut self, _stage: &str) {
+        #[cfg(feature = "detailed_timers")]
+        {
+            let now = std::time::Instant::now();
+            tracing::info!("[{}] {}: {:.3} ms", self.name, _stage, (now - self.last).as_secs_f64() * 1000.0);
+            self.last = now;
+        }
+    }
+
+    /// End of the operation
+    pub fn finish(self) {
+        #[cfg(feature = "detailed_timers")]
+        tracing::info!("[{}] total: {:.3} ms", self.name, self.start.elapsed().as_secs_f64() * 1000.0);
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_object::{spawn_template_scene, CollisionBehavior, EntitySubpixelPosition, IntoWorldPosition, ObjectDefinitionBuilder, ObjectTemplate, ObjectTemplates, SceneTint};
+use crate::items::{ItemDefinitions, ItemStack, Rarity};
+use crate::landscape::Item;
+use crate::planisphere::Planisphere;
+use crate::terrain::TerrainCenter;
+use crate::terrain::texture::WorldSeed;
+use crate::vfx::DisappearEffect;
+
+/// Template used for every collectible item lying in the world
+pub const PICKUP_TEMPLATE: &str = "pickup";
+/// Template used for debug beacons
+pub const BEACON_TEMPLATE: &str = "beacon";
+/// Pickup sensor radius of collectible props
+const PICKUP_RADIUS: f32 = 0.5;
+/// Effect played when the player picks up an item
+const ITEM_PICKUP_EFFECT: crate::vfx::Effect = crate::vfx::Effect::Particles { count: 8, duration: 0.6 };
+/// Size of the light beam above rare items
+const BEAM_RADIUS: f32 = 0.06;
+const BEAM_HEIGHT: f32 = 12.0;
+
+/// Template tags with a meaning for props
+pub mod tags {
+    /// Picked up on contact: sensor collider, `Item` component, no physics
+    pub const COLLECTIBLE: &str = "collectible";
+    /// Static scenery the player can stand on
+    pub const DECOR: &str = "decor";
+    /// Only visible while debug props are shown (F3), never collides
+    pub const DEBUG: &str = "debug";
+    /// Sways in the wind (see wind.rs)
+    pub const VEGETATION: &str = "vegetation";
+    /// A threat nearby switches the music to its danger tracks (see music.rs)
+    pub const HOSTILE: &str = "hostile";
+}
+
+/// World object spawned from a template through the prop subsystem; behavior follows its tags
+#[derive(Component, Debug, Clone)]
+pub struct Prop {
+    pub tags: Vec<String>,
+}
+
+impl Prop {
+    pub fn new(object_template: &ObjectTemplate) -> Self {
+        Self { tags: object_template.tags.clone() }
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+}
+
+/// Visibility rules for tagged props
+#[derive(Resource, Debug, Default)]
+pub struct PropSettings {
+    pub show_debug: bool,
+}
+
+/// Spawn a prop from the template registry. Collectible props carry `item` (defaulting to one
+/// unit of an item named like the template).
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_prop<T: IntoWorldPosition>(
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    planisphere: &Planisphere,
+    terrain_center: &TerrainCenter,
+    object_templates: &ObjectTemplates,
+    template_name: &str,
+    position: T,
+    item: Option<ItemStack>,
+) -> Result<Entity, crate::game_object::MissingTemplate> {
+    let template = object_templates.get(template_name)?;
+    let prop = Prop::new(template);
+    let collectible = prop.has_tag(tags::COLLECTIBLE);
+    let mut definition = ObjectDefinitionBuilder::from_template(template);
+    if collectible || prop.has_tag(tags::DEBUG) {
+        definition = definition.collision(CollisionBehavior::None); // Pickup sensors are added below, debug props never collide
+    }
+
+    let entity = spawn_template_scene(
+        commands,
+        materials,
+        planisphere,
+        terrain_center,
+        template,
+        position,
+        template.y_offset,
+        definition.build(),
+        prop,
+    );
+
+    if collectible {
+        let item = item.unwrap_or_else(|| ItemStack { item_id: template_name.to_string(), count: 1 });
+        commands.entity(entity).insert((
+            RigidBody::Fixed,
+            Sensor, // Items are sensors for pickup detection
+            Collider::ball(PICKUP_RADIUS),
+            Item { item_id: item.item_id, count: item.count },
+        ));
+        if template.effects.disappear.is_none() {
+            commands.entity(entity).insert(DisappearEffect(ITEM_PICKUP_EFFECT));
+        }
+    }
+    Ok(entity)
+}
+
+/// Items scattered over the terrain with the count of one pickup; which one lands on a tile is
+/// weighted by the rarity of its definition
+const SCATTERED_ITEMS: [(&str, u32); 7] = [("coin", 10), ("resource", 5), ("powerup", 1), ("gem", 1), ("relic", 1), ("axe", 1), ("pickaxe", 1)];
+
+/// Scatter collectible items over the rendered subpixels (sparse, deterministic per subpixel)
+pub fn scatter_item_props(
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    planisphere: &Planisphere,
+    terrain_center: &TerrainCenter,
+    object_templates: &ObjectTemplates,
+    item_definitions: &ItemDefinitions,
+    seed: WorldSeed,
+) -> Vec<Entity> {
+    let spawn_threshold = (crate::config::terrain::SPAWN_PROBABILITY * 1000.0) as usize;
+    let weights: Vec<u32> = SCATTERED_ITEMS.iter().map(|(item_id, _)| item_definitions.rarity(item_id).spawn_weight()).collect();
+    let total_weight: u32 = weights.iter().sum();
+    let mut entities = Vec::new();
+    for &(i, j, k, _) in terrain_center.rendered_subpixels.subpixels.iter() {
+        // Sparse item placement using position-based randomization
+        let item_hash = seed.placement_hash(i, j, k);
+        if item_hash > spawn_threshold {
+            continue;
+        }
+        let mut pick = (seed.random(i, j, k + 8192) * total_weight as f64) as u32;
+        let Some(&(item_id, count)) = SCATTERED_ITEMS.iter().zip(&weights)
+            .find(|(_, weight)| if pick < **weight { true } else { pick -= **weight; false })
+            .map(|(item, _)| item) else { continue; };
+        match spawn_prop(commands, materials, planisphere, terrain_center, object_templates, PICKUP_TEMPLATE, (i, j, k),
+                         Some(ItemStack { item_id: item_id.to_string(), count })) {
+            Ok(entity) => {
+                let geo_coords = planisphere.subpixel_to_geo(i, j, k);
+                commands.entity(entity).insert(EntitySubpixelPosition {
+                    subpixel: (i, j, k),
+                    geo_coords,
+                    world_pos: (i, j, k).into_world_position(planisphere, terrain_center),
+                    previous_subpixel: (i, j, k),
+                    ..default()
+                });
+                entities.push(entity);
+            }
+            Err(e) => {
+                warn!("Cannot scatter items: {}", e);
+                break;
+            }
+        }
+    }
+    debug!("Created {} items", entities.len());
+    entities
+}
+
+/// Mesh and per-rarity materials of the light beams above rare items
+#[derive(Resource)]
+pub struct RarityBeams {
+    mesh: Handle<Mesh>,
+    materials: HashMap<Rarity, Handle<StandardMaterial>>,
+}
+
+impl FromWorld for RarityBeams {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world.resource_mut::<Assets<Mesh>>().add(Cylinder::new(BEAM_RADIUS, BEAM_HEIGHT));
+        let mut assets = world.resource_mut::<Assets<StandardMaterial>>();
+        let materials = Rarity::ALL.into_iter().filter(|rarity| rarity.has_beam()).map(|rarity| {
+            let material = assets.add(StandardMaterial {
+                base_color: rarity.color().with_alpha(0.35),
+                emissive: rarity.color().to_linear() * 2.0,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            });
+            (rarity, material)
+        }).collect();
+        Self { mesh, materials }
+    }
+}
+
+/// Give newly spawned items the visual treatment of their rarity: the meshes of their scene glow
+/// in the tier color (`apply_scene_tints`), and rare items get a light beam
+pub fn highlight_item_rarity(
+    mut commands: Commands,
+    item_definitions: Res<ItemDefinitions>,
+    beams: Res<RarityBeams>,
+    mut items: Query<(Entity, &Item, Option<&mut SceneTint>), Added<Item>>,
+) {
+    for (entity, item, tint) in items.iter_mut() {
+        let rarity = item_definitions.rarity(&item.item_id);
+        if rarity.glow() > 0.0 {
+            match tint {
+                Some(mut tint) => tint.glow = Some((rarity.color(), rarity.glow())),
+                None => { commands.entity(entity).insert(SceneTint::default().with_glow(rarity.color(), rarity.glow())); }
+            }
+        }
+        if let Some(material) = beams.materials.get(&rarity) {
+            commands.entity(entity).with_child((
+                Mesh3d(beams.mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                Transform::from_xyz(0.0, BEAM_HEIGHT / 2.0, 0.0),
+                NotShadowCaster,
+            ));
+        }
+    }
+}
+
+/// Toggle debug props with F3
+pub fn toggle_debug_props(keyboard_input: Res<ButtonInput<KeyCode>>, mut settings: ResMut<PropSettings>) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        settings.show_debug = !settings.show_debug;
+        info!("Debug props {}", if settings.show_debug { "shown" } else { "hidden" });
+    }
+}
+
+/// Apply tag visibility rules to props
+pub fn apply_prop_visibility(
+    settings: Res<PropSettings>,
+    mut query: Query<(Ref<Prop>, &mut Visibility)>,
+) {
+    for (prop, mut visibility) in query.iter_mut() {
+        if !(settings.is_changed() || prop.is_added()) || !prop.has_tag(tags::DEBUG) {
+            continue;
+        }
+        *visibility = if settings.show_debug { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
@@ -0,0 +1,439 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+use bevy::prelude::*;
+use bevy::time::Real;
+use bevy_rapier3d::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::remote::*;
+use crate::game_object::{spawn_template_scene, EntitySubpixelPosition, ObjectDefinition, ObjectTemplates};
+use crate::net_client::RemotePlayer;
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::player_stats::{Health, Stamina};
+use crate::settings::Settings;
+use crate::terrain::{geo_to_world, world_to_geo, TerrainBuild, TerrainCenter, TerrainReady};
+use crate::time_control::TimeScale;
+use crate::weather::{Weather, WeatherKind, WeatherSetting};
+use crate::TerrainConfig;
+
+/// HTTP/JSON endpoint for dashboards, bots and test harnesses (`--remote <port>`), listening on
+/// the loopback interface only since it can move the player and spawn objects:
+///
+/// - `GET /player`, `GET /terrain`, `GET /agents`, `GET /weather`, `GET /time`: game state
+/// - `POST /teleport {"lon", "lat"}`, `POST /spawn {"template", "lon", "lat"}`,
+///   `POST /weather {"kind": "Storm"}` (`null` goes back to the weather cycle),
+///   `POST /time {"scale": 0.25}` (0 pauses): commands
+///
+/// Commands need `Content-Type: application/json`, which browsers cannot send to another origin
+/// without a preflight request the API does not answer.
+#[derive(Resource)]
+pub struct RemoteApi {
+    listener: TcpListener,
+    clients: Vec<HttpClient>,
+}
+
+impl RemoteApi {
+    pub fn bind(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self { listener, clients: Vec::new() })
+    }
+}
+
+/// Connection of an API client: one request, one response, then closed
+struct HttpClient {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+    age: f32,          // Seconds since the connection was accepted
+    answered: bool,
+}
+
+/// Request read from a client
+struct HttpRequest {
+    method: String,
+    path: String,       // Without the query string
+    json: bool,         // `Content-Type: application/json`
+    body: Vec<u8>,
+}
+
+/// Error answered to a client: HTTP status and message
+type ApiError = (u16, String);
+
+impl HttpClient {
+    /// Read what arrived, the whole request once its headers and body are in
+    fn read_request(&mut self) -> Result<Option<HttpRequest>, String> {
+        let mut buffer = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => return Err("connection closed".to_string()),
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.to_string()),
+            }
+            if self.incoming.len() > MAX_REQUEST_BYTES {
+                return Err("request too large".to_string());
+            }
+        }
+        let Some(header_end) = self.incoming.windows(4).position(|window| window == b"\r\n\r\n") else { return Ok(None); };
+        let head = String::from_utf8_lossy(&self.incoming[..header_end]).into_owned();
+        let mut lines = head.lines();
+        let mut request_line = lines.next().unwrap_or("").split_whitespace();
+        let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+            return Err("malformed request line".to_string());
+        };
+        let headers: Vec<(&str, &str)> = lines.filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim(), value.trim()))
+            .collect();
+        let header = |name: &str| headers.iter().find(|(header, _)| header.eq_ignore_ascii_case(name)).map(|(_, value)| *value);
+        let length = match header("content-length") {
+            Some(value) => value.parse::<usize>().map_err(|_| format!("bad Content-Length '{}'", value))?,
+            None => 0,
+        };
+        if length > MAX_REQUEST_BYTES {
+            return Err(format!("Content-Length {} over the {} bytes limit", length, MAX_REQUEST_BYTES));
+        }
+        let body_start = header_end + 4;
+        let body_end = body_start.checked_add(length).ok_or_else(|| "request too large".to_string())?;
+        if self.incoming.len() < body_end {
+            return Ok(None);
+        }
+        Ok(Some(HttpRequest {
+            method: method.to_string(),
+            path: target.split('?').next().unwrap_or(target).to_string(),
+            json: header("content-type").is_some_and(|value| value.starts_with("application/json")),
+            body: self.incoming[body_start..body_end].to_vec(),
+        }))
+    }
+
+    /// Queue the response; the connection closes once it is sent
+    fn respond(&mut self, status: u16, body: &str) {
+        let reason = match status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            415 => "Unsupported Media Type",
+            _ => "Internal Server Error",
+        };
+        let _ = write!(self.outgoing, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                       status, reason, body.len(), body);
+        self.answered = true;
+    }
+
+    /// Write the queued response until the socket would block; true once all of it is sent
+    fn flush(&mut self) -> std::io::Result<bool> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => { self.outgoing.drain(..written); }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(false),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// `GET /player`
+#[derive(Serialize)]
+struct PlayerReport {
+    geo: (f64, f64),                 // (longitude, latitude) in degrees
+    tile: (usize, usize, usize),
+    altitude: f32,
+    health: (f32, f32),              // (current, max)
+    stamina: (f32, f32),
+}
+
+/// `GET /terrain`
+#[derive(Serialize)]
+struct TerrainReport {
+    center: (f64, f64),
+    center_tile: (usize, usize, usize),
+    subpixels: usize,
+    merged_quads: usize,             // Quads of the distant LOD rings
+    vertices: usize,
+    triangles: usize,
+    generation_ms: f64,
+    generations: u32,
+    building: bool,                  // A terrain build is running
+}
+
+/// Entry of `GET /agents`: the player and the other players of a `--connect` session
+#[derive(Serialize)]
+struct AgentReport {
+    kind: &'static str,              // "player" or "remote_player"
+    name: String,
+    geo: (f64, f64),
+}
+
+/// `GET /weather`
+#[derive(Serialize)]
+struct WeatherReport {
+    kind: WeatherKind,
+    fixed: bool,                     // Pinned by the settings or `POST /weather`, instead of cycling
+    cloud_cover: f32,
+    rain: f32,
+    wind: f32,
+}
+
+/// `GET /time` and `POST /time`
+#[derive(Serialize, Deserialize)]
+struct TimeReport {
+    scale: f32,                      // Simulation speed, 0.0 when paused
+}
+
+#[derive(Deserialize)]
+struct TeleportRequest {
+    lon: f64,
+    lat: f64,
+}
+
+#[derive(Deserialize)]
+struct SpawnRequest {
+    template: String,
+    lon: f64,
+    lat: f64,
+}
+
+#[derive(Deserialize)]
+struct WeatherRequest {
+    #[serde(default)]
+    kind: Option<WeatherKind>,       // None: back to the weather cycle
+}
+
+/// Parsed API call
+enum RemoteCall {
+    Player,
+    Terrain,
+    Agents,
+    Weather,
+    Time,
+    Teleport(TeleportRequest),
+    Spawn(SpawnRequest),
+    SetWeather(WeatherRequest),
+    SetTime(TimeReport),
+}
+
+fn parse_body<T: DeserializeOwned>(request: &HttpRequest) -> Result<T, ApiError> {
+    if !request.json {
+        return Err((415, "commands expect Content-Type: application/json".to_string()));
+    }
+    serde_json::from_slice(&request.body).map_err(|e| (400, format!("invalid body: {}", e)))
+}
+
+fn parse_call(request: &HttpRequest) -> Result<RemoteCall, ApiError> {
+    match (request.method.as_str(), request.path.trim_end_matches('/')) {
+        ("GET", "/player") => Ok(RemoteCall::Player),
+        ("GET", "/terrain") => Ok(RemoteCall::Terrain),
+        ("GET", "/agents") => Ok(RemoteCall::Agents),
+        ("GET", "/weather") => Ok(RemoteCall::Weather),
+        ("GET", "/time") => Ok(RemoteCall::Time),
+        ("POST", "/teleport") => parse_body(request).map(RemoteCall::Teleport),
+        ("POST", "/spawn") => parse_body(request).map(RemoteCall::Spawn),
+        ("POST", "/weather") => parse_body(request).map(RemoteCall::SetWeather),
+        ("POST", "/time") => parse_body(request).map(RemoteCall::SetTime),
+        (_, "/player" | "/terrain" | "/agents" | "/weather" | "/time" | "/teleport" | "/spawn") => {
+            Err((405, format!("{} not allowed on {}", request.method, request.path)))
+        }
+        _ => Err((404, format!("no endpoint {}", request.path))),
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String, ApiError> {
+    serde_json::to_string(value).map_err(|e| (500, e.to_string()))
+}
+
+fn check_geo(lon: f64, lat: f64) -> Result<(), ApiError> {
+    if !(-180.0..=180.0).contains(&lon) || !(-90.0..=90.0).contains(&lat) {
+        return Err((400, format!("position out of range: {}, {}", lon, lat)));
+    }
+    Ok(())
+}
+
+/// Player moved by `POST /teleport` beyond the rendered terrain, held in the air (physics off)
+/// until the terrain is rebuilt around it
+#[derive(Component)]
+pub struct TeleportHold;
+
+/// Accept API connections, answer the complete requests and close the answered connections
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn serve_remote_api(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut api: ResMut<RemoteApi>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut settings: ResMut<Settings>,
+    mut time_scale: ResMut<TimeScale>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    terrain_build: Res<TerrainBuild>,
+    terrain_config: Res<TerrainConfig>,
+    object_templates: Res<ObjectTemplates>,
+    weather: Res<Weather>,
+    mut players: Query<(Entity, &mut Transform, &EntitySubpixelPosition, &Health, &Stamina, Option<&mut Velocity>), With<Player>>,
+    remote_players: Query<(&Transform, &Name), (With<RemotePlayer>, Without<Player>)>,
+) {
+    let api = api.as_mut();
+    loop {
+        match api.listener.accept() {
+            Ok((stream, address)) => {
+                if let Err(e) = stream.set_nonblocking(true) {
+                    warn!("Remote API: cannot serve {}: {}", address, e);
+                    continue;
+                }
+                api.clients.push(HttpClient { stream, incoming: Vec::new(), outgoing: Vec::new(), age: 0.0, answered: false });
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+            Err(e) => {
+                warn!("Remote API: failed to accept a connection: {}", e);
+                break;
+            }
+        }
+    }
+
+    let dt = time.delta_secs();
+    for client in api.clients.iter_mut().filter(|client| !client.answered) {
+        client.age += dt;
+        let request = match client.read_request() {
+            Ok(Some(request)) => request,
+            Ok(None) if client.age > REQUEST_TIMEOUT_SECS => {
+                client.respond(400, &error_body("incomplete request"));
+                continue;
+            }
+            Ok(None) => continue,
+            Err(e) => {
+                client.respond(400, &error_body(&e));
+                continue;
+            }
+        };
+
+        let result = parse_call(&request).and_then(|call| match call {
+            RemoteCall::Player => {
+                let (_, _, position, health, stamina, _) = players.single().map_err(|_| (409, "no player".to_string()))?;
+                let (i, j, k) = position.subpixel;
+                to_json(&PlayerReport {
+                    geo: position.geo_coords,
+                    tile: position.subpixel,
+                    altitude: planisphere.get_alti_at_subpixel(i as i32, j as i32, k),
+                    health: (health.current, health.max),
+                    stamina: (stamina.current, stamina.max),
+                })
+            }
+            RemoteCall::Terrain => {
+                let stats = &terrain_center.stats;
+                to_json(&TerrainReport {
+                    center: (terrain_center.longitude, terrain_center.latitude),
+                    center_tile: terrain_center.subpixel,
+                    subpixels: stats.subpixel_count,
+                    merged_quads: stats.merged_quad_count,
+                    vertices: stats.vertex_count,
+                    triangles: stats.triangle_count,
+                    generation_ms: stats.generation_ms,
+                    generations: stats.generations,
+                    building: terrain_build.is_running(),
+                })
+            }
+            RemoteCall::Agents => {
+                let player = players.iter().map(|(_, _, position, ..)| AgentReport {
+                    kind: "player",
+                    name: "player".to_string(),
+                    geo: position.geo_coords,
+                });
+                let others = remote_players.iter().map(|(transform, name)| AgentReport {
+                    kind: "remote_player",
+                    name: name.as_str().trim_start_matches("Remote player ").to_string(),
+                    geo: world_to_geo(transform.translation, &planisphere, &terrain_center),
+                });
+                to_json(&player.chain(others).collect::<Vec<_>>())
+            }
+            RemoteCall::Weather => to_json(&WeatherReport {
+                kind: weather.kind,
+                fixed: matches!(settings.world.weather, WeatherSetting::Fixed(_)),
+                cloud_cover: weather.conditions.cloud_cover,
+                rain: weather.conditions.rain,
+                wind: weather.conditions.wind,
+            }),
+            RemoteCall::Time => to_json(&TimeReport { scale: time_scale.scale }),
+            RemoteCall::Teleport(TeleportRequest { lon, lat }) => {
+                check_geo(lon, lat)?;
+                let arc = planisphere.geodesic_distance((terrain_center.longitude, terrain_center.latitude), (lon, lat)) / planisphere.radius;
+                if arc.to_degrees() > MAX_TELEPORT_DEGREES {
+                    return Err((409, format!("more than {}° from the terrain center, teleport in shorter jumps", MAX_TELEPORT_DEGREES)));
+                }
+                let (player, mut transform, _, _, _, velocity) = players.single_mut().map_err(|_| (409, "no player".to_string()))?;
+                let tile = planisphere.geo_to_subpixel(lon, lat);
+                let ground = terrain_config.relief.center_height(&planisphere, tile.0, tile.1, tile.2);
+                let position = geo_to_world(lon, lat, &planisphere, &terrain_center);
+                transform.translation = Vec3::new(position.x, ground + TELEPORT_DROP_HEIGHT, position.z);
+                if let Some(mut velocity) = velocity {
+                    *velocity = Velocity::zero();
+                }
+                // Beyond the recreation threshold the terrain is rebuilt around the player
+                if position.length() > terrain_config.recreation_threshold as f32 * planisphere.mean_tile_size as f32 {
+                    commands.entity(player).insert((TeleportHold, RigidBodyDisabled));
+                }
+                info!("Remote API: teleported the player to {:.5}, {:.5}", lon, lat);
+                to_json(&tile)
+            }
+            RemoteCall::Spawn(SpawnRequest { template: name, lon, lat }) => {
+                check_geo(lon, lat)?;
+                let template = object_templates.get(&name).map_err(|e| (400, e.to_string()))?;
+                let tile = planisphere.geo_to_subpixel(lon, lat);
+                spawn_template_scene(&mut commands, &mut materials, &planisphere, &terrain_center, template, tile,
+                                     template.y_offset, ObjectDefinition::from_template(template), ());
+                info!("Remote API: spawned {} at {:?}", name, tile);
+                to_json(&tile)
+            }
+            RemoteCall::SetWeather(WeatherRequest { kind }) => {
+                settings.world.weather = kind.map_or(WeatherSetting::Cycle, WeatherSetting::Fixed);
+                info!("Remote API: weather set to {:?}", settings.world.weather);
+                to_json(&kind)
+            }
+            RemoteCall::SetTime(TimeReport { scale }) => {
+                if !scale.is_finite() {
+                    return Err((400, format!("invalid scale {}", scale)));
+                }
+                time_scale.set(scale);
+                info!("Remote API: simulation speed {}", time_scale.label());
+                to_json(&TimeReport { scale: time_scale.scale })
+            }
+        });
+        match result {
+            Ok(body) => client.respond(200, &body),
+            Err((status, message)) => client.respond(status, &error_body(&message)),
+        }
+    }
+
+    api.clients.retain_mut(|client| !client.answered || matches!(client.flush(), Ok(false)));
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+/// Give the teleported player its physics back once the terrain is rebuilt around it
+pub fn release_teleport_holds(
+    mut commands: Commands,
+    mut ready_events: EventReader<TerrainReady>,
+    planisphere: Res<Planisphere>,
+    terrain_config: Res<TerrainConfig>,
+    holds: Query<(Entity, &Transform), With<TeleportHold>>,
+) {
+    if ready_events.read().count() == 0 {
+        return;
+    }
+    let threshold = terrain_config.recreation_threshold as f32 * planisphere.mean_tile_size as f32;
+    for (entity, transform) in holds.iter() {
+        // A build started before the teleport may finish first: wait for the one around the player
+        if Vec2::new(transform.translation.x, transform.translation.z).length() <= threshold {
+            commands.entity(entity).remove::<(TeleportHold, RigidBodyDisabled)>();
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, VertexAttributeValues};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+/// How the collider of a template-spawned object is derived from its glTF scene
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Default)]
+pub enum SceneColliderMode {
+    Primitive,      // Keep whatever collider the spawner attached (e.g. the player capsule)
+    Shape,          // Use the template's `shape` (primitive or compound), no scene walk needed
+    #[default]
+    ConvexHull,     // Single convex hull around every mesh vertex of the scene
+    TriMesh,        // Exact triangle mesh (static objects only, falls back to a hull for dynamic bodies)
+    Decomposition,  // Compound of convex parts (slow to build, good for concave shapes)
+}
+
+/// Attached by `spawn_template_scene` to objects whose collider must be built once the scene is loaded
+#[derive(Component, Debug, Clone)]
+pub struct PendingSceneCollider {
+    pub mode: SceneColliderMode,
+    pub cache_key: String, // Objects with the same key share one collider shape
+}
+
+/// Colliders already built, keyed by `PendingSceneCollider::cache_key`.
+/// Rapier shapes are reference-counted so clones are cheap.
+#[derive(Resource, Default)]
+pub struct SceneColliderCache {
+    colliders: HashMap<String, Collider>,
+}
+
+/// Replace the placeholder collider of newly spawned template objects with one matching
+/// their glTF geometry. Objects stay pending until the scene and its meshes are loaded.
+pub fn build_scene_colliders(
+    mut commands: Commands,
+    mut cache: ResMut<SceneColliderCache>,
+    pending_query: Query<(Entity, &PendingSceneCollider, Option<&RigidBody>)>,
+    children_query: Query<&Children>,
+    node_query: Query<(&Transform, Option<&Mesh3d>)>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    for (entity, pending, body) in pending_query.iter() {
+        let dynamic = matches!(body, Some(RigidBody::Dynamic));
+        // Trimeshes make poor dynamic bodies, use a hull instead
+        let mode = if dynamic && pending.mode == SceneColliderMode::TriMesh { SceneColliderMode::ConvexHull } else { pending.mode };
+        let key = format!("{}:{:?}", pending.cache_key, mode);
+
+        let collider = match cache.colliders.get(&key) {
+            Some(collider) => Some(collider.clone()),
+            None => {
+                let Some((vertices, indices)) = collect_scene_geometry(entity, &children_query, &node_query, &meshes) else {
+                    continue; // Scene not instantiated or meshes still loading
+                };
+                let collider = collider_from_geometry(mode, &vertices, &indices);
+                match &collider {
+                    Some(collider) => {
+                        debug!("Built {:?} collider for {} ({} vertices)", mode, pending.cache_key, vertices.len());
+                        cache.colliders.insert(key, collider.clone());
+                    }
+                    None => warn!("Could not build {:?} collider for {}, keeping placeholder", mode, pending.cache_key),
+                }
+                collider
+            }
+        };
+
+        let mut entity_commands = commands.entity(entity);
+        entity_commands.remove::<PendingSceneCollider>();
+        if let Some(collider) = collider {
+            entity_commands.insert(collider);
+        }
+    }
+}
+
+/// Gather every mesh vertex below `root`, expressed in `root`'s local space.
+/// Returns `None` while the scene has no loaded mesh yet.
+fn collect_scene_geometry(
+    root: Entity,
+    children_query: &Query<&Children>,
+    node_query: &Query<(&Transform, Option<&Mesh3d>)>,
+    meshes: &Assets<Mesh>,
+) -> Option<(Vec<Vec3>, Vec<[u32; 3]>)> {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    let mut found_mesh = false;
+    // Walk the hierarchy accumulating local transforms; GlobalTransform is not
+    // propagated yet for scenes instantiated this frame
+    let mut stack = vec![(root, Transform::IDENTITY)];
+
+    while let Some((entity, to_root)) = stack.pop() {
+        if let Ok(children) = children_query.get(entity) {
+            for child in children.iter() {
+                let Ok((transform, mesh3d)) = node_query.get(child) else { continue; };
+                let child_to_root = to_root.mul_transform(*transform);
+                if let Some(mesh3d) = mesh3d {
+                    let mesh = meshes.get(&mesh3d.0)?; // Mesh still loading
+                    found_mesh = true;
+                    append_mesh(mesh, &child_to_root, &mut vertices, &mut indices);
+                }
+                stack.push((child, child_to_root));
+            }
+        }
+    }
+
+    (found_mesh && !vertices.is_empty()).then_some((vertices, indices))
+}
+
+/// Append a mesh's positions (transformed) and triangles to the shared buffers
+fn append_mesh(mesh: &Mesh, transform: &Transform, vertices: &mut Vec<Vec3>, indices: &mut Vec<[u32; 3]>) {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else { return; };
+    let offset = vertices.len() as u32;
+    vertices.extend(positions.iter().map(|p| transform.transform_point(Vec3::from_array(*p))));
+
+    let triangle_indices: Vec<u32> = match mesh.indices() {
+        Some(Indices::U16(values)) => values.iter().map(|i| *i as u32).collect(),
+        Some(Indices::U32(values)) => values.clone(),
+        None => (0..positions.len() as u32).collect(),
+    };
+    indices.extend(triangle_indices.chunks_exact(3).map(|t| [t[0] + offset, t[1] + offset, t[2] + offset]));
+}
+
+fn collider_from_geometry(mode: SceneColliderMode, vertices: &[Vec3], indices: &[[u32; 3]]) -> Option<Collider> {
+    match mode {
+        SceneColliderMode::Primitive | SceneColliderMode::Shape => None,
+        SceneColliderMode::ConvexHull => Collider::convex_hull(vertices),
+        SceneColliderMode::TriMesh => Collider::trimesh(vertices.to_vec(), indices.to_vec()).ok(),
+        SceneColliderMode::Decomposition => Some(Collider::convex_decomposition(vertices, indices)),
+    }
+}
@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Map, Scope, AST, INT};
+
+use crate::animation::{AnimationCommand, TemplateAnimation};
+use crate::existence::ExistenceRegistry;
+use crate::game_object::{spawn_template_scene, EntitySubpixelPosition, ObjectDefinition, ObjectEvent, ObjectTemplates};
+use crate::harvest::Harvestable;
+use crate::items::ItemDefinitions;
+use crate::message_log::{LogCategory, LogMessage};
+use crate::notifications::Notify;
+use crate::occupancy::OccupiesTile;
+use crate::planisphere::Planisphere;
+use crate::player::{CursorTarget, Player, PlayerInventory};
+use crate::terrain::{ijk_to_world, SharedPlanisphere, TerrainCenter};
+use crate::TerrainConfig;
+
+/// Directory template `script` paths are relative to, like their scenes
+const ASSET_DIR: &str = "assets";
+
+type Tile = (usize, usize, usize);
+
+/// Rhai script of a template instance, e.g. `script: Some("scripts/shrine.rhai")` in the
+/// template file. The script defines handlers called with `this` bound to a map describing the
+/// object: `#{ template, tile: [i, j, k], state: #{} }`.
+/// - `fn on_use()` runs when the player uses the object (E within reach, for objects that cannot
+///   be harvested),
+/// - `fn on_tick(dt)` runs every `config::scripting::TICK_SECS` seconds, for behaviors.
+///
+/// Handlers move the object by assigning `this.tile` and keep their own data in `this.state`.
+#[derive(Component, Debug, Clone)]
+pub struct Scripted {
+    pub script: String,         // Path under `assets/`
+    pub template: String,
+    pub tile: Option<Tile>,     // Where the object stands, None until spawned on a tile
+    pub state: Map,             // `this.state`, kept between calls
+}
+
+impl Scripted {
+    pub fn new(script: impl Into<String>, template: impl Into<String>, tile: Option<Tile>) -> Self {
+        Self { script: script.into(), template: template.into(), tile, state: Map::new() }
+    }
+}
+
+/// What scripts ask the game to do. The script API only queues actions, applied by
+/// `apply_script_actions` with the usual spawning and inventory code.
+#[derive(Debug, Clone)]
+enum ScriptAction {
+    Spawn { template: String, tile: Tile },
+    GiveItem { item_id: String, count: u32 },
+    Move { entity: Entity, tile: Tile },
+    Animate { entity: Entity, command: AnimationCommand },
+    RaiseEvent(String),
+    ClearEvent(String),
+    SetFlag { name: String, value: bool },
+    Notify(String),
+}
+
+/// A compiled script and the handlers it defines
+struct Script {
+    ast: AST,
+    on_use: bool,
+    on_tick: bool,
+}
+
+/// The Rhai engine and the scripts compiled so far (None for scripts that failed to load).
+///
+/// Scripts only see the API registered here: `spawn_template(name, tile)`,
+/// `query_tile(tile)` (`#{ lon, lat, altitude, sea }`), `neighbour(tile, di, dj)`,
+/// `give_item(item_id, count)`, `notify(message)`, and for the clips of the object's template
+/// `play_animation(clip)`, `loop_animation(clip)`, `blend_animation(clip, weight)`,
+/// `stop_animation(clip)` and `stop_animations()`, and `raise_event(name)`, `clear_event(name)`
+/// and `set_flag(name, value)` for the existence conditions of templates; `print` goes to the log. They cannot
+/// import modules or use `eval`, and each call is bounded by the `config::scripting` limits.
+#[derive(Resource)]
+pub struct ScriptEngine {
+    engine: Engine,
+    scripts: HashMap<String, Option<Script>>,
+    actions: Arc<Mutex<Vec<ScriptAction>>>,
+    tick: Timer,
+    map: Arc<Planisphere>,   // Checks the tiles scripts move their object to
+    caller: Arc<Mutex<Option<Entity>>>, // Object whose handler is running, for the functions acting on it
+}
+
+/// Create the script engine once the map is loaded (`query_tile` and `neighbour` read it)
+pub fn setup_scripting(mut commands: Commands, planisphere: Res<SharedPlanisphere>) {
+    use crate::config::scripting::*;
+
+    let actions: Arc<Mutex<Vec<ScriptAction>>> = Arc::default();
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_string_size(MAX_STRING_SIZE)
+        .set_max_array_size(MAX_ARRAY_SIZE)
+        .set_max_map_size(MAX_MAP_SIZE)
+        .set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new())
+        .disable_symbol("eval")
+        .on_print(|text| info!("[script] {}", text))
+        .on_debug(|text, source, position| debug!("[script {}] {:?}: {}", source.unwrap_or("?"), position, text));
+
+    let queue = actions.clone();
+    let map = planisphere.0.clone();
+    engine.register_fn("spawn_template", move |template: &str, tile: Array| -> Result<(), Box<EvalAltResult>> {
+        let tile = tile_from_array(&map, &tile)?;
+        queue_action(&queue, ScriptAction::Spawn { template: template.to_string(), tile });
+        Ok(())
+    });
+    let map = planisphere.0.clone();
+    engine.register_fn("query_tile", move |tile: Array| -> Result<Map, Box<EvalAltResult>> {
+        let (i, j, k) = tile_from_array(&map, &tile)?;
+        let (lon, lat) = map.subpixel_to_geo(i, j, k);
+        let mut info = Map::new();
+        info.insert("lon".into(), Dynamic::from_float(lon));
+        info.insert("lat".into(), Dynamic::from_float(lat));
+        info.insert("altitude".into(), Dynamic::from_float(map.get_alti_at_subpixel(i as i32, j as i32, k) as f64));
+        info.insert("sea".into(), Dynamic::from_bool(map.is_sea(i, j)));
+        Ok(info)
+    });
+    let map = planisphere.0.clone();
+    engine.register_fn("neighbour", move |tile: Array, di: INT, dj: INT| -> Result<Array, Box<EvalAltResult>> {
+        let (i, j, k) = tile_from_array(&map, &tile)?;
+        let clamp = |offset: INT| offset.clamp(-MAX_NEIGHBOUR_OFFSET, MAX_NEIGHBOUR_OFFSET) as i32;
+        Ok(tile_to_array(map.get_neighbour_subpixel(i, j, k, clamp(di), clamp(dj))))
+    });
+    let queue = actions.clone();
+    engine.register_fn("give_item", move |item_id: &str, count: INT| {
+        queue_action(&queue, ScriptAction::GiveItem { item_id: item_id.to_string(), count: count.clamp(0, u32::MAX as INT) as u32 });
+    });
+    let queue = actions.clone();
+    engine.register_fn("notify", move |message: &str| queue_action(&queue, ScriptAction::Notify(message.to_string())));
+
+    let queue = actions.clone();
+    engine.register_fn("raise_event", move |name: &str| queue_action(&queue, ScriptAction::RaiseEvent(name.to_string())));
+    let queue = actions.clone();
+    engine.register_fn("clear_event", move |name: &str| queue_action(&queue, ScriptAction::ClearEvent(name.to_string())));
+    let queue = actions.clone();
+    engine.register_fn("set_flag", move |name: &str, value: bool| {
+        queue_action(&queue, ScriptAction::SetFlag { name: name.to_string(), value });
+    });
+
+    let caller: Arc<Mutex<Option<Entity>>> = Arc::default();
+    let animate = move |queue: &Mutex<Vec<ScriptAction>>, caller: &Mutex<Option<Entity>>, command: AnimationCommand| {
+        if let Some(entity) = caller.lock().ok().and_then(|caller| *caller) {
+            queue_action(queue, ScriptAction::Animate { entity, command });
+        }
+    };
+    let (queue, current) = (actions.clone(), caller.clone());
+    engine.register_fn("play_animation", move |clip: &str| {
+        animate(&queue, &current, AnimationCommand::Play { clip: clip.to_string(), looped: false });
+    });
+    let (queue, current) = (actions.clone(), caller.clone());
+    engine.register_fn("loop_animation", move |clip: &str| {
+        animate(&queue, &current, AnimationCommand::Play { clip: clip.to_string(), looped: true });
+    });
+    let (queue, current) = (actions.clone(), caller.clone());
+    engine.register_fn("blend_animation", move |clip: &str, weight: rhai::FLOAT| {
+        animate(&queue, &current, AnimationCommand::Blend { clip: clip.to_string(), weight: weight.clamp(0.0, 1.0) as f32 });
+    });
+    let (queue, current) = (actions.clone(), caller.clone());
+    engine.register_fn("stop_animation", move |clip: &str| {
+        animate(&queue, &current, AnimationCommand::Stop { clip: clip.to_string() });
+    });
+    let (queue, current) = (actions.clone(), caller.clone());
+    engine.register_fn("stop_animations", move || animate(&queue, &current, AnimationCommand::StopAll));
+
+    commands.insert_resource(ScriptEngine {
+        engine,
+        scripts: HashMap::new(),
+        actions,
+        tick: Timer::from_seconds(TICK_SECS, TimerMode::Repeating),
+        map: planisphere.0.clone(),
+        caller,
+    });
+}
+
+fn queue_action(queue: &Mutex<Vec<ScriptAction>>, action: ScriptAction) {
+    if let Ok(mut actions) = queue.lock() {
+        actions.push(action);
+    }
+}
+
+fn set_caller(caller: &Mutex<Option<Entity>>, entity: Option<Entity>) {
+    if let Ok(mut caller) = caller.lock() {
+        *caller = entity;
+    }
+}
+
+/// `[i, j, k]` checked against the map
+fn tile_from_array(planisphere: &Planisphere, tile: &Array) -> Result<Tile, Box<EvalAltResult>> {
+    let coordinate = |n: usize| tile.get(n).and_then(|value| value.as_int().ok()).and_then(|value| usize::try_from(value).ok());
+    let (Some(i), Some(j), Some(k)) = (coordinate(0), coordinate(1), coordinate(2)) else {
+        return Err(format!("a tile is [i, j, k], got {:?}", tile).into());
+    };
+    let divisions = planisphere.subpixel_divisions;
+    if tile.len() != 3 || i >= planisphere.width_pixels || j >= planisphere.height_pixels || k >= divisions * divisions {
+        return Err(format!("no tile [{}, {}, {}] on this map", i, j, k).into());
+    }
+    Ok((i, j, k))
+}
+
+fn tile_to_array((i, j, k): Tile) -> Array {
+    vec![Dynamic::from_int(i as INT), Dynamic::from_int(j as INT), Dynamic::from_int(k as INT)]
+}
+
+impl ScriptEngine {
+    /// Compile `path` on first use; errors are reported once and the script is then skipped
+    fn script(&mut self, path: &str, log: &mut EventWriter<LogMessage>) -> Option<&Script> {
+        if !self.scripts.contains_key(path) {
+            let full_path = std::path::Path::new(ASSET_DIR).join(path);
+            let compiled = std::fs::read_to_string(&full_path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| self.engine.compile(source).map_err(|e| e.to_string()));
+            let script = match compiled {
+                Ok(ast) => {
+                    let defines = |name: &str| ast.iter_functions().any(|function| function.name == name);
+                    info!("Loaded script {}", path);
+                    Some(Script { on_use: defines("on_use"), on_tick: defines("on_tick"), ast })
+                }
+                Err(e) => {
+                    log.write(LogMessage::new(LogCategory::Error, format!("Script {} not loaded: {}", full_path.display(), e)));
+                    None
+                }
+            };
+            self.scripts.insert(path.to_string(), script);
+        }
+        self.scripts.get(path)?.as_ref()
+    }
+
+    /// Call `handler` with `this` bound to the object, then keep its state and queue the move
+    /// when the script changed `this.tile`
+    fn call(&mut self, entity: Entity, scripted: &mut Scripted, handler: &str, args: impl rhai::FuncArgs,
+            log: &mut EventWriter<LogMessage>) {
+        if self.script(&scripted.script, log).is_none() {
+            return;
+        }
+        let Some(Some(script)) = self.scripts.get(&scripted.script) else { return; };
+        let defined = match handler {
+            "on_use" => script.on_use,
+            "on_tick" => script.on_tick,
+            _ => false,
+        };
+        if !defined {
+            return;
+        }
+        let mut this = Map::new();
+        this.insert("template".into(), scripted.template.clone().into());
+        this.insert("tile".into(), scripted.tile.map_or(Dynamic::UNIT, |tile| tile_to_array(tile).into()));
+        this.insert("state".into(), std::mem::take(&mut scripted.state).into());
+        let mut this = Dynamic::from_map(this);
+
+        let options = CallFnOptions::new().eval_ast(false).bind_this_ptr(&mut this);
+        set_caller(&self.caller, Some(entity));
+        let result = self.engine.call_fn_with_options::<Dynamic>(options, &mut Scope::new(), &script.ast, handler, args);
+        set_caller(&self.caller, None);
+        if let Err(e) = result {
+            log.write(LogMessage::new(LogCategory::Error, format!("Script {} ({}): {}", scripted.script, handler, e)));
+        }
+
+        let Some(mut this) = this.try_cast::<Map>() else { return; };
+        if let Some(state) = this.remove("state").and_then(|state| state.try_cast::<Map>()) {
+            scripted.state = state;
+        }
+        let Some(moved_to) = this.remove("tile").and_then(|tile| tile.try_cast::<Array>()) else { return; };
+        match tile_from_array(&self.map, &moved_to) {
+            Ok(tile) if scripted.tile != Some(tile) => queue_action(&self.actions, ScriptAction::Move { entity, tile }),
+            Ok(_) => {}
+            Err(e) => {
+                log.write(LogMessage::new(LogCategory::Error, format!("Script {} ({}): cannot move: {}", scripted.script, handler, e)));
+            }
+        }
+    }
+}
+
+/// E on a scripted object within reach emits `ObjectEvent::Used`; harvestable objects are left
+/// to `harvest::handle_harvest_input`
+pub fn use_scripted_objects(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    cursor: Res<CursorTarget>,
+    player_query: Query<(Entity, &Transform), With<Player>>,
+    objects: Query<&Transform, (With<Scripted>, Without<Harvestable>)>,
+    mut object_events: EventWriter<ObjectEvent>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyE) {
+        return;
+    }
+    let Some(entity) = cursor.entity else { return; };
+    let Ok((player, player_transform)) = player_query.single() else { return; };
+    let Ok(transform) = objects.get(entity) else { return; };
+    if transform.translation.distance(player_transform.translation) <= crate::config::objects::HARVEST_REACH {
+        object_events.write(ObjectEvent::Used { entity, by: player });
+    }
+}
+
+/// Call `on_use` for used objects and `on_tick` for every scripted object on each tick
+pub fn run_script_handlers(
+    time: Res<Time>,
+    mut script_engine: ResMut<ScriptEngine>,
+    mut object_events: EventReader<ObjectEvent>,
+    mut scripted: Query<(Entity, &mut Scripted)>,
+    mut log: EventWriter<LogMessage>,
+) {
+    for event in object_events.read() {
+        if let ObjectEvent::Used { entity, .. } = event
+            && let Ok((entity, mut scripted)) = scripted.get_mut(*entity) {
+            script_engine.call(entity, &mut scripted, "on_use", (), &mut log);
+        }
+    }
+
+    if script_engine.tick.tick(time.delta()).just_finished() {
+        let dt = script_engine.tick.duration().as_secs_f64();
+        for (entity, mut scripted) in scripted.iter_mut() {
+            script_engine.call(entity, &mut scripted, "on_tick", (dt,), &mut log);
+        }
+    }
+}
+
+/// Carry out the actions queued by the scripts of the frame
+#[allow(clippy::too_many_arguments)]
+pub fn apply_script_actions(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    script_engine: Res<ScriptEngine>,
+    object_templates: Res<ObjectTemplates>,
+    item_definitions: Res<ItemDefinitions>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    terrain_config: Res<TerrainConfig>,
+    mut player_query: Query<&mut PlayerInventory, With<Player>>,
+    mut moved: Query<(&mut Transform, &mut Scripted, Option<&mut OccupiesTile>, Option<&mut EntitySubpixelPosition>)>,
+    mut animated: Query<&mut TemplateAnimation>,
+    mut existence: ResMut<ExistenceRegistry>,
+    mut notify: EventWriter<Notify>,
+    mut log: EventWriter<LogMessage>,
+) {
+    let actions = match script_engine.actions.lock() {
+        Ok(mut actions) => std::mem::take(&mut *actions),
+        Err(_) => return,
+    };
+    for action in actions {
+        match action {
+            ScriptAction::Spawn { template: name, tile } => match object_templates.get(&name) {
+                Ok(template) => {
+                    spawn_template_scene(&mut commands, &mut materials, &planisphere, &terrain_center, template, tile,
+                                         template.y_offset, ObjectDefinition::from_template(template), ());
+                }
+                Err(e) => {
+                    log.write(LogMessage::new(LogCategory::Error, format!("Script cannot spawn: {}", e)));
+                }
+            },
+            ScriptAction::GiveItem { item_id, count } => {
+                if item_definitions.get(&item_id).is_none() {
+                    log.write(LogMessage::new(LogCategory::Error, format!("Script cannot give unknown item '{}'", item_id)));
+                    continue;
+                }
+                let Ok(mut inventory) = player_query.single_mut() else { continue; };
+                inventory.add(&item_definitions, &item_id, count);
+                log.write(LogMessage::new(LogCategory::Pickup, format!("Received {} x {}", count, item_definitions.display_name(&item_id))));
+            }
+            ScriptAction::Move { entity, tile } => {
+                let Ok((mut transform, mut scripted, occupied, position)) = moved.get_mut(entity) else { continue; };
+                // Keep the object's height above the ground, the template's offset when it had no tile
+                let ground = |(i, j, k): Tile| terrain_config.relief.center_height(&planisphere, i, j, k);
+                let above_ground = match scripted.tile {
+                    Some(previous) => transform.translation.y - ground(previous),
+                    None => object_templates.get(&scripted.template).map_or(0.0, |template| template.y_offset),
+                };
+                let world = ijk_to_world(tile.0 as i32, tile.1 as i32, tile.2 as i32, &planisphere, &terrain_center);
+                transform.translation = Vec3::new(world.x, ground(tile) + above_ground, world.z);
+                scripted.tile = Some(tile);
+                if let Some(mut occupied) = occupied {
+                    occupied.0 = tile;
+                }
+                if let Some(mut position) = position {
+                    position.previous_subpixel = position.subpixel;
+                    position.subpixel = tile;
+                    position.geo_coords = planisphere.subpixel_to_geo(tile.0, tile.1, tile.2);
+                    position.world_pos = transform.translation;
+                }
+            }
+            ScriptAction::Animate { entity, command } => match animated.get_mut(entity) {
+                Ok(mut animation) => animation.queue(command),
+                Err(_) => {
+                    log.write(LogMessage::new(LogCategory::Error, format!("Script animation on an object without animations: {:?}", command)));
+                }
+            },
+            ScriptAction::RaiseEvent(name) => existence.raise_event(name),
+            ScriptAction::ClearEvent(name) => existence.clear_event(&name),
+            ScriptAction::SetFlag { name, value } => existence.set_flag(name, value),
+            ScriptAction::Notify(message) => {
+                notify.write(Notify::new(message));
+            }
+        }
+    }
+}
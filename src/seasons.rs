@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_object::{InstanceVariation, SceneTint};
+use crate::message_log::{LogCategory, LogMessage};
+use crate::notifications::Notify;
+use crate::planisphere::Planisphere;
+use crate::props::{tags, Prop};
+use crate::settings::Settings;
+use crate::terrain::{terrain_uvs, TerrainCenter, TerrainReady, Tile};
+use crate::terrain::skirt::skirt_uvs;
+
+// Atlas tiles used by the seasonal re-texturing (order of assets/textures/img)
+const DRY_GRASS: usize = 2;
+const GRASS: usize = 4;
+const SNOW: usize = 15;
+
+/// Season of the world: changes the terrain textures, the vegetation color and the landscape spawns
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Season {
+    Spring,
+    #[default]
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [Season::Spring, Season::Summer, Season::Autumn, Season::Winter];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        }
+    }
+
+    /// Atlas tile shown instead of the RGBA-selected `texture` (see `select_texture_from_rgba`)
+    pub fn texture(self, texture: usize) -> usize {
+        match (self, texture) {
+            (Season::Spring, DRY_GRASS) => GRASS,
+            (Season::Autumn, 3 | 4 | 9 | 11 | 16 | 18) => DRY_GRASS,        // Grasses and moss dry out
+            (Season::Winter, 1 | 2 | 3 | 4 | 9 | 11 | 16 | 18) => SNOW,     // Snow cover on soft ground
+            _ => texture,
+        }
+    }
+
+    /// Color mixed into the summer color of vegetation, and its weight
+    pub fn vegetation_tint(self) -> Option<(Color, f32)> {
+        match self {
+            Season::Spring => Some((Color::srgb(0.6, 1.0, 0.5), 0.25)),
+            Season::Summer => None,
+            Season::Autumn => Some((Color::srgb(0.9, 0.45, 0.1), 0.6)),
+            Season::Winter => Some((Color::srgb(0.85, 0.9, 0.95), 0.55)),
+        }
+    }
+
+    /// Declination of the sun in degrees: north of the equator in (northern) summer
+    pub fn sun_declination(self) -> f32 {
+        let tilt = crate::config::daylight::AXIAL_TILT_DEGREES;
+        match self {
+            Season::Spring | Season::Autumn => 0.0,
+            Season::Summer => tilt,
+            Season::Winter => -tilt,
+        }
+    }
+
+    /// Factor on the spawn probability of vegetation templates
+    pub fn vegetation_density(self) -> f64 {
+        match self {
+            Season::Spring => 1.1,
+            Season::Summer => 1.0,
+            Season::Autumn => 0.8,
+            Season::Winter => 0.5,
+        }
+    }
+}
+
+/// How the season is chosen, from `WorldSettings::season`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeasonSetting {
+    #[default]
+    Cycle,           // Follows game time, `WorldSettings::season_length_secs` per season
+    Fixed(Season),
+}
+
+/// Current season; systems reacting to it use change detection
+#[derive(Resource, Debug, Default)]
+pub struct SeasonState {
+    pub season: Season,
+}
+
+/// Move to the season given by the settings, or by the game time when cycling
+pub fn advance_season(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    mut elapsed: Local<f32>,
+    mut state: ResMut<SeasonState>,
+    mut notify: EventWriter<Notify>,
+    mut log: EventWriter<LogMessage>,
+) {
+    let season = match settings.world.season {
+        SeasonSetting::Fixed(season) => season,
+        SeasonSetting::Cycle => {
+            *elapsed += time.delta_secs();
+            let index = (*elapsed / settings.world.season_length_secs.max(1.0)) as usize;
+            Season::ALL[(Season::Summer as usize + index) % Season::ALL.len()]
+        }
+    };
+    if season != state.season {
+        state.season = season;
+        notify.write(Notify::new(format!("{} has come", season.label())));
+        log.write(LogMessage::new(LogCategory::Terrain, format!("Season changed to {}", season.label())));
+    }
+}
+
+/// Re-texture the terrain mesh for the season, when it changes and when a new terrain is ready.
+/// Only the UVs are rewritten, the geometry and collider are kept.
+pub fn retexture_terrain(
+    state: Res<SeasonState>,
+    mut ready_events: EventReader<TerrainReady>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    terrain_query: Query<&Mesh3d, With<Tile>>,
+) {
+    let terrain_ready = ready_events.read().count() > 0;
+    if !state.is_changed() && !terrain_ready {
+        return;
+    }
+    let season = state.season;
+    let subpixels = &terrain_center.rendered_subpixels.subpixels;
+    let quads = &terrain_center.lod_quads;
+    let skirts = &terrain_center.skirts;
+    for mesh_handle in terrain_query.iter() {
+        let Some(mesh) = meshes.get_mut(&mesh_handle.0) else { continue; };
+        if mesh.count_vertices() != (subpixels.len() + quads.len() + skirts.len()) * 4 {
+            continue; // Not a subpixel terrain (fallback plane)
+        }
+        let mut uvs = terrain_uvs(&planisphere, subpixels, quads, |texture| season.texture(texture));
+        uvs.extend(skirt_uvs(&uvs, skirts));
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    }
+}
+
+/// Tint vegetation props for the season, when it changes and when they are spawned; the meshes
+/// of their scene are tinted by `apply_scene_tints`
+pub fn tint_vegetation(
+    mut commands: Commands,
+    state: Res<SeasonState>,
+    mut props: Query<(Entity, Ref<Prop>, &InstanceVariation, Option<&mut SceneTint>)>,
+) {
+    let mix = state.season.vegetation_tint();
+    for (entity, prop, variation, tint) in props.iter_mut() {
+        if !(state.is_changed() || prop.is_added()) || !prop.has_tag(tags::VEGETATION) {
+            continue;
+        }
+        match tint {
+            Some(mut tint) => tint.mix = mix,
+            None => { commands.entity(entity).insert(SceneTint::hue(variation.hue_shift).with_mix(mix)); }
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use std::path::Path;
+use std::time::SystemTime;
+use bevy::prelude::*;
+use bevy::pbr::DirectionalLightShadowMap;
+use bevy::window::{MonitorSelection, PresentMode, PrimaryWindow, VideoModeSelection, WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{ThirdPersonCamera, ZoomMode};
+use crate::daylight::Sun;
+use crate::player::Player;
+use crate::seasons::SeasonSetting;
+use crate::terrain::TerrainCenter;
+use crate::weather::WeatherSetting;
+
+/// User settings file, written whenever a setting is changed in game
+pub const SETTINGS_PATH: &str = "config/settings.ron";
+
+/// Keys recalling camera presets 1..4 (hold Shift to store the current camera instead)
+const PRESET_KEYS: [KeyCode; 4] = [KeyCode::F5, KeyCode::F6, KeyCode::F7, KeyCode::F8];
+
+/// Named camera setup that can be recalled instantly
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CameraPreset {
+    pub name: String,
+    pub distance: f32,
+    pub height: f32,
+    pub fov_degrees: f32,
+    #[serde(default)]
+    pub shoulder_offset: f32,   // Lateral camera offset, positive = camera right of the player
+}
+
+impl CameraPreset {
+    /// Snapshot of the camera's current (target) setup
+    pub fn from_camera(name: impl Into<String>, camera: &ThirdPersonCamera) -> Self {
+        Self {
+            name: name.into(),
+            distance: camera.target_distance,
+            height: camera.height,
+            fov_degrees: camera.target_fov.to_degrees(),
+            shoulder_offset: camera.target_shoulder_offset,
+        }
+    }
+
+    /// Move the camera to this preset; distance, FOV and shoulder ease in through the zoom smoothing
+    pub fn apply(&self, camera: &mut ThirdPersonCamera) {
+        camera.target_distance = self.distance.clamp(camera.min_distance, camera.max_distance);
+        camera.height = self.height.clamp(camera.min_height, camera.max_height);
+        camera.target_fov = self.fov_degrees.to_radians().clamp(camera.min_fov, camera.max_fov);
+        camera.target_shoulder_offset = self.shoulder_offset;
+    }
+}
+
+/// Third-person camera tuning; missing fields keep the `config::camera` defaults
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CameraSettings {
+    pub distance: f32,          // Initial distance behind the player
+    pub height: f32,            // Initial height above the player
+    pub follow_speed: f32,
+    pub zoom_speed: f32,
+    pub zoom_mode: ZoomMode,    // What the mouse wheel changes: distance or field of view
+    pub zoom_smoothing: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub min_height: f32,
+    pub max_height: f32,
+    pub height_speed: f32,
+    pub min_fov_degrees: f32,
+    pub max_fov_degrees: f32,
+    pub shoulder_offset: f32,   // Initial over-the-shoulder offset
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        use crate::config::camera;
+        Self {
+            distance: camera::DISTANCE,
+            height: camera::HEIGHT,
+            follow_speed: camera::FOLLOW_SPEED,
+            zoom_speed: camera::ZOOM_SPEED,
+            zoom_mode: ZoomMode::Distance,
+            zoom_smoothing: camera::ZOOM_SMOOTHING,
+            min_distance: camera::MIN_DISTANCE,
+            max_distance: camera::MAX_DISTANCE,
+            min_height: camera::MIN_HEIGHT,
+            max_height: camera::MAX_HEIGHT,
+            height_speed: camera::HEIGHT_SPEED,
+            min_fov_degrees: camera::MIN_FOV_DEGREES,
+            max_fov_degrees: camera::MAX_FOV_DEGREES,
+            shoulder_offset: camera::SHOULDER_OFFSET,
+        }
+    }
+}
+
+/// How the game window is shown
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowModeSetting {
+    #[default]
+    Windowed,
+    Borderless,   // Borderless fullscreen on the current monitor
+    Fullscreen,   // Exclusive fullscreen at the current video mode
+}
+
+/// Shadow map resolution of the sun, or no shadows
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShadowQuality {
+    Off,
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl ShadowQuality {
+    /// Directional shadow map size, `None` when shadows are off
+    pub fn map_size(self) -> Option<usize> {
+        match self {
+            ShadowQuality::Off => None,
+            ShadowQuality::Low => Some(1024),
+            ShadowQuality::Medium => Some(2048),
+            ShadowQuality::High => Some(4096),
+        }
+    }
+}
+
+/// Display settings, applied to the window, the sun and the terrain radius
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    pub window_mode: WindowModeSetting,
+    pub vsync: bool,
+    pub shadow_quality: ShadowQuality,
+    pub render_distance: usize,   // Terrain radius in subpixels (`TerrainConfig::terrain_radius`)
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            window_mode: WindowModeSetting::Windowed,
+            vsync: true,
+            shadow_quality: ShadowQuality::Medium,
+            render_distance: crate::config::terrain::RADIUS,
+        }
+    }
+}
+
+/// Keys of the player movement actions
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub forward: KeyCode,
+    pub back: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub jump: KeyCode,
+    pub sprint: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            forward: KeyCode::KeyW,
+            back: KeyCode::KeyS,
+            left: KeyCode::KeyA,
+            right: KeyCode::KeyD,
+            jump: KeyCode::Space,
+            sprint: KeyCode::ShiftLeft,
+        }
+    }
+}
+
+/// Player input settings
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ControlSettings {
+    pub mouse_sensitivity: f32,
+    pub key_bindings: KeyBindings,
+}
+
+impl Default for ControlSettings {
+    fn default() -> Self {
+        Self {
+            mouse_sensitivity: crate::config::player::MOUSE_SENSITIVITY,
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+/// World simulation settings
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct WorldSettings {
+    pub season: SeasonSetting,
+    pub season_length_secs: f32,   // Game time per season when cycling
+    pub landscape_regrowth_secs: Option<u64>, // Real time before harvested trees and rocks grow back, never when None
+    pub day_length_secs: f32,      // Game time per day and night
+    pub weather: WeatherSetting,
+    pub start_hour: f32,           // Time of day when the game starts, 0.0 to 24.0
+}
+
+impl Default for WorldSettings {
+    fn default() -> Self {
+        Self {
+            season: SeasonSetting::Cycle,
+            season_length_secs: crate::config::seasons::LENGTH_SECS,
+            landscape_regrowth_secs: None,
+            day_length_secs: crate::config::daylight::DAY_LENGTH_SECS,
+            start_hour: crate::config::daylight::START_HOUR,
+            weather: WeatherSetting::Cycle,
+        }
+    }
+}
+
+/// Sound settings
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub music_volume: f32,   // 0.0 (off) to 1.0
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self { music_volume: crate::config::music::DEFAULT_VOLUME }
+    }
+}
+
+/// Settings persisted in `SETTINGS_PATH`
+#[derive(Resource, Serialize, Deserialize, Debug, Clone)]
+pub struct Settings {
+    #[serde(default)]
+    pub graphics: GraphicsSettings,
+    #[serde(default)]
+    pub controls: ControlSettings,
+    #[serde(default)]
+    pub camera: CameraSettings,
+    #[serde(default)]
+    pub world: WorldSettings,
+    #[serde(default)]
+    pub audio: AudioSettings,
+    #[serde(default = "default_camera_presets")]
+    pub camera_presets: Vec<CameraPreset>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            graphics: GraphicsSettings::default(),
+            controls: ControlSettings::default(),
+            camera: CameraSettings::default(),
+            world: WorldSettings::default(),
+            audio: AudioSettings::default(),
+            camera_presets: default_camera_presets(),
+        }
+    }
+}
+
+fn default_camera_presets() -> Vec<CameraPreset> {
+    vec![
+        CameraPreset { name: "close action".to_string(), distance: 8.0, height: 4.0, fov_degrees: 60.0, shoulder_offset: 1.5 },
+        CameraPreset { name: "scenic overview".to_string(), distance: 40.0, height: 30.0, fov_degrees: 45.0, shoulder_offset: 0.0 },
+    ]
+}
+
+impl Settings {
+    /// Load settings from `path`, falling back to defaults if the file is missing or invalid
+    pub fn load(path: &str) -> Self {
+        if !crate::storage::exists(path) {
+            return Self::default();
+        }
+        match load_settings_file(Path::new(path)) {
+            Ok(settings) => {
+                info!("Loaded settings from {}", path);
+                settings
+            }
+            Err(e) => {
+                warn!("Could not load settings {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let content = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(std::io::Error::other)?;
+        crate::storage::write(path, &content)
+    }
+}
+
+/// Parse a settings file
+pub fn load_settings_file(path: &Path) -> Result<Settings, String> {
+    let content = crate::storage::read_to_string(&path.to_string_lossy()).map_err(|e| e.to_string())?;
+    ron::from_str::<Settings>(&content).map_err(|e| e.to_string())
+}
+
+/// Polls the settings file so hand edits apply while the game runs
+#[derive(Resource)]
+pub struct SettingsWatcher {
+    pub timer: Timer,                    // How often the file is polled
+    pub modified: Option<SystemTime>,    // Last seen modification time
+}
+
+impl Default for SettingsWatcher {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            modified: settings_file_mtime(SETTINGS_PATH),
+        }
+    }
+}
+
+fn settings_file_mtime(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Reload the settings file when it changes on disk; a broken file keeps the current settings
+pub fn hot_reload_settings(
+    time: Res<Time<Real>>,
+    mut watcher: ResMut<SettingsWatcher>,
+    mut settings: ResMut<Settings>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let modified = settings_file_mtime(SETTINGS_PATH);
+    if modified.is_none() || modified == watcher.modified {
+        return;
+    }
+    watcher.modified = modified;
+    match load_settings_file(Path::new(SETTINGS_PATH)) {
+        Ok(loaded) => {
+            info!("Reloaded settings from {}", SETTINGS_PATH);
+            *settings = loaded;
+        }
+        Err(e) => warn!("Settings reload failed for {}: {}", SETTINGS_PATH, e),
+    }
+}
+
+/// Apply graphics and control settings whenever they change (menu, file edit or startup).
+/// A new render distance rebuilds the terrain.
+pub fn apply_settings(
+    settings: Res<Settings>,
+    mut shadow_map: ResMut<DirectionalLightShadowMap>,
+    mut terrain_config: ResMut<crate::TerrainConfig>,
+    mut terrain_center: ResMut<TerrainCenter>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    mut lights: Query<&mut DirectionalLight, With<Sun>>,
+    mut players: Query<&mut Player>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let graphics = &settings.graphics;
+
+    if let Ok(mut window) = windows.single_mut() {
+        let mode = match graphics.window_mode {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::Borderless => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+            WindowModeSetting::Fullscreen => WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current),
+        };
+        if window.mode != mode {
+            window.mode = mode;
+        }
+        let present_mode = if graphics.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync };
+        if window.present_mode != present_mode {
+            window.present_mode = present_mode;
+        }
+    }
+
+    let shadow_size = graphics.shadow_quality.map_size();
+    if let Some(size) = shadow_size
+        && shadow_map.size != size {
+        shadow_map.size = size;
+    }
+    for mut light in lights.iter_mut() {
+        if light.shadows_enabled != shadow_size.is_some() {
+            light.shadows_enabled = shadow_size.is_some();
+        }
+    }
+
+    let radius = graphics.render_distance.max(1);
+    terrain_config.terrain_radius = radius;
+    if terrain_center.max_subpixel_distance != radius {
+        info!("Render distance {} -> {} subpixels", terrain_center.max_subpixel_distance, radius);
+        terrain_config.recreation_threshold = (radius / crate::config::terrain::RECREATION_THRESHOLD_DIVISOR).max(1);
+        terrain_center.max_subpixel_distance = radius;
+        terrain_center.force_recreation = true;
+    }
+
+    for mut player in players.iter_mut() {
+        player.mouse_sensitivity = settings.controls.mouse_sensitivity;
+    }
+}
+
+/// F5..F8 recall camera presets 1..4, Shift+F5..F8 store the current camera in that slot
+pub fn handle_camera_preset_keys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut camera_query: Query<&mut ThirdPersonCamera>,
+    mut notify: EventWriter<crate::notifications::Notify>,
+) {
+    let Some(slot) = PRESET_KEYS.iter().position(|key| keyboard_input.just_pressed(*key)) else { return; };
+    let Ok(mut camera) = camera_query.single_mut() else { return; };
+
+    if keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight) {
+        let name = settings.camera_presets.get(slot)
+            .map_or_else(|| format!("preset {}", slot + 1), |preset| preset.name.clone());
+        let preset = CameraPreset::from_camera(name, &camera);
+        match settings.camera_presets.get_mut(slot) {
+            Some(existing) => *existing = preset,
+            None => settings.camera_presets.push(preset), // Next free slot
+        }
+        match settings.save(SETTINGS_PATH) {
+            Ok(()) => { notify.write(crate::notifications::Notify::new(format!("Saved camera preset {}", slot + 1))); }
+            Err(e) => error!("Could not save settings to {}: {}", SETTINGS_PATH, e),
+        }
+        return;
+    }
+
+    match settings.camera_presets.get(slot) {
+        Some(preset) => {
+            notify.write(crate::notifications::Notify::new(format!("Camera preset '{}'", preset.name)));
+            preset.apply(&mut camera);
+        }
+        None => info!("No camera preset in slot {}", slot + 1),
+    }
+}
@@ -0,0 +1,295 @@
+use bevy::prelude::*;
+
+use crate::settings::{KeyBindings, Settings, ShadowQuality, WindowModeSetting, SETTINGS_PATH};
+
+/// Render distance range offered by the menu (subpixels)
+pub const RENDER_DISTANCE_RANGE: (usize, usize) = (5, 60);
+pub const RENDER_DISTANCE_STEP: usize = 5;
+/// Mouse sensitivity is changed by this factor per click
+const SENSITIVITY_FACTOR: f32 = 1.25;
+
+/// Movement action that can be rebound
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BindingAction {
+    Forward,
+    Back,
+    Left,
+    Right,
+    Jump,
+    Sprint,
+}
+
+impl BindingAction {
+    const ALL: [BindingAction; 6] = [BindingAction::Forward, BindingAction::Back, BindingAction::Left, BindingAction::Right, BindingAction::Jump, BindingAction::Sprint];
+
+    fn label(self) -> &'static str {
+        match self {
+            BindingAction::Forward => "Forward",
+            BindingAction::Back => "Back",
+            BindingAction::Left => "Left",
+            BindingAction::Right => "Right",
+            BindingAction::Jump => "Jump",
+            BindingAction::Sprint => "Sprint",
+        }
+    }
+
+    fn key(self, bindings: &KeyBindings) -> KeyCode {
+        match self {
+            BindingAction::Forward => bindings.forward,
+            BindingAction::Back => bindings.back,
+            BindingAction::Left => bindings.left,
+            BindingAction::Right => bindings.right,
+            BindingAction::Jump => bindings.jump,
+            BindingAction::Sprint => bindings.sprint,
+        }
+    }
+
+    fn key_mut(self, bindings: &mut KeyBindings) -> &mut KeyCode {
+        match self {
+            BindingAction::Forward => &mut bindings.forward,
+            BindingAction::Back => &mut bindings.back,
+            BindingAction::Left => &mut bindings.left,
+            BindingAction::Right => &mut bindings.right,
+            BindingAction::Jump => &mut bindings.jump,
+            BindingAction::Sprint => &mut bindings.sprint,
+        }
+    }
+}
+
+/// Setting shown on one row of the menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsField {
+    WindowMode,
+    Vsync,
+    Shadows,
+    RenderDistance,
+    MouseSensitivity,
+    Binding(BindingAction),
+}
+
+/// What a menu button does when clicked
+#[derive(Component, Debug, Clone, Copy)]
+pub enum SettingsButton {
+    Cycle(SettingsField),       // Next value (window mode, vsync, shadows) or start rebinding
+    Decrease(SettingsField),
+    Increase(SettingsField),
+}
+
+/// Text showing the current value of a field
+#[derive(Component, Debug, Clone, Copy)]
+pub struct SettingsValue(pub SettingsField);
+
+/// Root node of the settings menu (Escape)
+#[derive(Component)]
+pub struct SettingsMenu;
+
+/// Menu state: open or not, and the action waiting for a key press
+#[derive(Resource, Debug, Default)]
+pub struct SettingsMenuState {
+    pub open: bool,
+    pub rebinding: Option<BindingAction>,
+}
+
+fn field_label(field: SettingsField) -> &'static str {
+    match field {
+        SettingsField::WindowMode => "Window mode",
+        SettingsField::Vsync => "VSync",
+        SettingsField::Shadows => "Shadows",
+        SettingsField::RenderDistance => "Render distance",
+        SettingsField::MouseSensitivity => "Mouse sensitivity",
+        SettingsField::Binding(action) => action.label(),
+    }
+}
+
+fn field_value(field: SettingsField, settings: &Settings, state: &SettingsMenuState) -> String {
+    match field {
+        SettingsField::WindowMode => format!("{:?}", settings.graphics.window_mode),
+        SettingsField::Vsync => (if settings.graphics.vsync { "On" } else { "Off" }).to_string(),
+        SettingsField::Shadows => format!("{:?}", settings.graphics.shadow_quality),
+        SettingsField::RenderDistance => settings.graphics.render_distance.to_string(),
+        SettingsField::MouseSensitivity => format!("{:.4}", settings.controls.mouse_sensitivity),
+        SettingsField::Binding(action) if state.rebinding == Some(action) => "Press a key...".to_string(),
+        SettingsField::Binding(action) => format!("{:?}", action.key(&settings.controls.key_bindings)),
+    }
+}
+
+fn spawn_button(parent: &mut ChildSpawnerCommands, label: &str, action: SettingsButton) {
+    parent.spawn((
+        Button,
+        Node { padding: UiRect::axes(Val::Px(8.0), Val::Px(3.0)), ..default() },
+        BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9)),
+        action,
+    )).with_children(|button| {
+        button.spawn((Text::new(label), TextFont { font_size: 13.0, ..default() }, TextColor(Color::WHITE)));
+    });
+}
+
+/// Spawn the (hidden) settings menu in the middle of the screen
+pub fn setup_settings_menu(mut commands: Commands) {
+    let mut fields = vec![
+        SettingsField::WindowMode,
+        SettingsField::Vsync,
+        SettingsField::Shadows,
+        SettingsField::RenderDistance,
+        SettingsField::MouseSensitivity,
+    ];
+    fields.extend(BindingAction::ALL.map(SettingsField::Binding));
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(50.0),
+            top: Val::Percent(20.0),
+            margin: UiRect::left(Val::Px(-160.0)),
+            width: Val::Px(320.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(12.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+        Visibility::Hidden,
+        SettingsMenu,
+    )).with_children(|panel| {
+        panel.spawn((Text::new("Settings"), TextFont { font_size: 18.0, ..default() }, TextColor(Color::WHITE)));
+        for field in fields {
+            panel.spawn(Node {
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            }).with_children(|row| {
+                row.spawn((Text::new(field_label(field)), TextFont { font_size: 13.0, ..default() }, TextColor(Color::srgb(0.8, 0.8, 0.8))));
+                row.spawn(Node { column_gap: Val::Px(4.0), align_items: AlignItems::Center, ..default() }).with_children(|controls| {
+                    let stepped = matches!(field, SettingsField::RenderDistance | SettingsField::MouseSensitivity);
+                    if stepped {
+                        spawn_button(controls, "-", SettingsButton::Decrease(field));
+                        controls.spawn((Text::new(""), TextFont { font_size: 13.0, ..default() }, TextColor(Color::WHITE), SettingsValue(field)));
+                        spawn_button(controls, "+", SettingsButton::Increase(field));
+                    } else {
+                        controls.spawn((
+                            Button,
+                            Node { padding: UiRect::axes(Val::Px(8.0), Val::Px(3.0)), ..default() },
+                            BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9)),
+                            SettingsButton::Cycle(field),
+                        )).with_children(|button| {
+                            button.spawn((Text::new(""), TextFont { font_size: 13.0, ..default() }, TextColor(Color::WHITE), SettingsValue(field)));
+                        });
+                    }
+                });
+            });
+        }
+        panel.spawn((
+            Text::new("Esc to close - changes are saved automatically"),
+            TextFont { font_size: 11.0, ..default() },
+            TextColor(Color::srgb(0.6, 0.6, 0.6)),
+        ));
+    });
+}
+
+/// Open or close the menu with Escape (Escape also cancels a pending rebind)
+pub fn toggle_settings_menu(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<SettingsMenuState>,
+    mut menu_query: Query<&mut Visibility, With<SettingsMenu>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if state.rebinding.take().is_some() {
+        return;
+    }
+    state.open = !state.open;
+    if let Ok(mut visibility) = menu_query.single_mut() {
+        *visibility = if state.open { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+fn save_settings(settings: &Settings) {
+    if let Err(e) = settings.save(SETTINGS_PATH) {
+        error!("Could not save settings to {}: {}", SETTINGS_PATH, e);
+    }
+}
+
+/// Apply menu clicks and key rebinding to the settings, saving them to the settings file
+pub fn handle_settings_menu(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut state: ResMut<SettingsMenuState>,
+    mut settings: ResMut<Settings>,
+    interaction_query: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+) {
+    if !state.open {
+        return;
+    }
+
+    if let Some(action) = state.rebinding {
+        let Some(key) = keyboard_input.get_just_pressed().find(|key| **key != KeyCode::Escape) else { return; };
+        *action.key_mut(&mut settings.controls.key_bindings) = *key;
+        state.rebinding = None;
+        info!("{} bound to {:?}", action.label(), key);
+        save_settings(&settings);
+        return;
+    }
+
+    for (interaction, button) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let graphics = &mut settings.graphics;
+        match *button {
+            SettingsButton::Cycle(SettingsField::WindowMode) => {
+                graphics.window_mode = match graphics.window_mode {
+                    WindowModeSetting::Windowed => WindowModeSetting::Borderless,
+                    WindowModeSetting::Borderless => WindowModeSetting::Fullscreen,
+                    WindowModeSetting::Fullscreen => WindowModeSetting::Windowed,
+                };
+            }
+            SettingsButton::Cycle(SettingsField::Vsync) => graphics.vsync = !graphics.vsync,
+            SettingsButton::Cycle(SettingsField::Shadows) => {
+                graphics.shadow_quality = match graphics.shadow_quality {
+                    ShadowQuality::Off => ShadowQuality::Low,
+                    ShadowQuality::Low => ShadowQuality::Medium,
+                    ShadowQuality::Medium => ShadowQuality::High,
+                    ShadowQuality::High => ShadowQuality::Off,
+                };
+            }
+            SettingsButton::Cycle(SettingsField::Binding(action)) => {
+                state.rebinding = Some(action);
+                continue; // Saved once the key is pressed
+            }
+            SettingsButton::Decrease(SettingsField::RenderDistance) => {
+                graphics.render_distance = graphics.render_distance.saturating_sub(RENDER_DISTANCE_STEP).max(RENDER_DISTANCE_RANGE.0);
+            }
+            SettingsButton::Increase(SettingsField::RenderDistance) => {
+                graphics.render_distance = (graphics.render_distance + RENDER_DISTANCE_STEP).min(RENDER_DISTANCE_RANGE.1);
+            }
+            SettingsButton::Decrease(SettingsField::MouseSensitivity) => settings.controls.mouse_sensitivity /= SENSITIVITY_FACTOR,
+            SettingsButton::Increase(SettingsField::MouseSensitivity) => settings.controls.mouse_sensitivity *= SENSITIVITY_FACTOR,
+            _ => continue,
+        }
+        save_settings(&settings);
+    }
+}
+
+/// Refresh the value texts and button highlights of the open menu
+pub fn update_settings_menu(
+    settings: Res<Settings>,
+    state: Res<SettingsMenuState>,
+    mut values: Query<(&SettingsValue, &mut Text)>,
+    mut buttons: Query<(&Interaction, &mut BackgroundColor), With<SettingsButton>>,
+) {
+    if !state.open {
+        return;
+    }
+    if settings.is_changed() || state.is_changed() {
+        for (value, mut text) in values.iter_mut() {
+            **text = field_value(value.0, &settings, &state);
+        }
+    }
+    for (interaction, mut background) in buttons.iter_mut() {
+        *background = match interaction {
+            Interaction::Hovered | Interaction::Pressed => BackgroundColor(Color::srgba(0.4, 0.4, 0.4, 0.9)),
+            Interaction::None => BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9)),
+        };
+    }
+}
@@ -0,0 +1,201 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::asset::RenderAssetUsages;
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::camera::ThirdPersonCamera;
+use crate::config::sky::*;
+use crate::daylight::{color_temperature, GameTime, SunPosition};
+use crate::weather::Weather;
+
+// Sky gradient colors
+const DAY_ZENITH: Color = Color::srgb(0.22, 0.42, 0.85);
+const DAY_HORIZON: Color = Color::srgb(0.68, 0.8, 0.95);
+const SUNSET_HORIZON: Color = Color::srgb(0.98, 0.5, 0.25);
+const NIGHT_ZENITH: Color = Color::srgb(0.005, 0.008, 0.03);
+const NIGHT_HORIZON: Color = Color::srgb(0.03, 0.04, 0.09);
+const OVERCAST: Color = Color::srgb(0.55, 0.57, 0.6);
+const MOON_DISC_COLOR: Color = Color::srgb(0.9, 0.92, 1.0);
+
+/// Root of the sky, centered on the camera so the sky never gets closer
+#[derive(Component)]
+pub struct Sky;
+
+/// Sphere around the camera painted with the sky gradient (vertex colors)
+#[derive(Component)]
+pub struct SkyDome;
+
+/// Parts of the sky placed from the sun position
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkyPart {
+    Stars,   // Star sphere turning around the celestial pole
+    Sun,
+    Moon,
+}
+
+/// Unlit material ignoring the fog, for everything in the sky
+fn sky_material(color: Color, alpha_mode: AlphaMode) -> StandardMaterial {
+    StandardMaterial { base_color: color, unlit: true, fog_enabled: false, cull_mode: None, alpha_mode, ..default() }
+}
+
+/// Spawn the sky dome, the star field and the sun and moon discs. None of them casts or
+/// receives shadows: the sun shines through the dome.
+pub fn setup_sky(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mut dome = Sphere::new(DOME_RADIUS).mesh().uv(48, 24);
+    paint_dome(&mut dome, &SunPosition::default(), 0.0);
+    let parts = [
+        (SkyPart::Stars, meshes.add(star_mesh()), sky_material(Color::WHITE.with_alpha(0.0), AlphaMode::Blend)),
+        (SkyPart::Sun, meshes.add(Circle::new(SUN_DISC_RADIUS)), sky_material(Color::WHITE, AlphaMode::Blend)),
+        (SkyPart::Moon, meshes.add(Circle::new(MOON_DISC_RADIUS)), sky_material(MOON_DISC_COLOR, AlphaMode::Blend)),
+    ];
+
+    commands.spawn((Sky, Transform::default(), Visibility::default())).with_children(|sky| {
+        sky.spawn((
+            SkyDome,
+            Mesh3d(meshes.add(dome)),
+            MeshMaterial3d(materials.add(sky_material(Color::WHITE, AlphaMode::Opaque))),
+            Transform::default(),
+            NotShadowCaster,
+            NotShadowReceiver,
+        ));
+        for (part, mesh, material) in parts {
+            sky.spawn((part, Mesh3d(mesh), MeshMaterial3d(materials.add(material)), Transform::default(), NotShadowCaster, NotShadowReceiver));
+        }
+    });
+}
+
+/// Stars as small quads on a sphere, +Y being the celestial north pole. Mostly faint, tinted
+/// from red to blue by their temperature.
+fn star_mesh() -> Mesh {
+    let mut rng = StdRng::seed_from_u64(STAR_SEED);
+    let mut positions = Vec::with_capacity(STAR_COUNT * 4);
+    let mut normals = Vec::with_capacity(STAR_COUNT * 4);
+    let mut colors = Vec::with_capacity(STAR_COUNT * 4);
+    let mut indices = Vec::with_capacity(STAR_COUNT * 6);
+    for n in 0..STAR_COUNT {
+        // Uniform direction on the sphere
+        let y: f32 = rng.gen_range(-1.0..1.0);
+        let angle: f32 = rng.gen_range(0.0..TAU);
+        let direction = Vec3::new((1.0 - y * y).sqrt() * angle.cos(), y, (1.0 - y * y).sqrt() * angle.sin());
+        let brightness = rng.r#gen::<f32>().powi(3);
+        let half_size = 0.5 * (STAR_SIZE.0 + (STAR_SIZE.1 - STAR_SIZE.0) * brightness);
+        let tint = color_temperature(rng.gen_range(3500.0..10000.0)).to_linear() * (0.4 + 0.6 * brightness);
+
+        let (u, v) = direction.any_orthonormal_pair();
+        let center = direction * STAR_RADIUS;
+        for (du, dv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            positions.push((center + (u * du + v * dv) * half_size).to_array());
+            normals.push((-direction).to_array());
+            colors.push([tint.red, tint.green, tint.blue, 1.0]);
+        }
+        let first = (n * 4) as u32;
+        indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Sky color toward `direction`: night to day gradient from the horizon to the zenith, an
+/// orange glow on the sun's side at dawn and dusk, grey under clouds
+fn sky_color(direction: Vec3, sun: &SunPosition, cloud_cover: f32) -> Color {
+    let day = sun.daylight;
+    let sun_elevation = sun.direction.y.asin().to_degrees();
+    let toward_sun = Vec2::new(direction.x, direction.z).normalize_or_zero()
+        .dot(Vec2::new(sun.direction.x, sun.direction.z).normalize_or_zero()) * 0.5 + 0.5;
+    let glow = (1.0 - sun_elevation.abs() / 15.0).max(0.0) * toward_sun * toward_sun;
+
+    let horizon = NIGHT_HORIZON.mix(&DAY_HORIZON, day).mix(&SUNSET_HORIZON, 0.8 * glow);
+    let zenith = NIGHT_ZENITH.mix(&DAY_ZENITH, day);
+    let color = if direction.y >= 0.0 {
+        horizon.mix(&zenith, direction.y.sqrt())
+    } else {
+        horizon.mix(&Color::BLACK, 0.5)
+    };
+    color.mix(&Color::BLACK.mix(&OVERCAST, 0.1 + 0.9 * day), 0.85 * cloud_cover)
+}
+
+/// Set the dome vertex colors for the sun position
+fn paint_dome(mesh: &mut Mesh, sun: &SunPosition, cloud_cover: f32) {
+    let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else { return; };
+    let colors: Vec<[f32; 4]> = positions.iter()
+        .map(|&position| sky_color(Vec3::from_array(position).normalize_or(Vec3::Y), sun, cloud_cover).to_linear().to_f32_array())
+        .collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+/// How much of a disc at `elevation` degrees shows above the horizon
+fn above_horizon(elevation: f32) -> f32 {
+    ((elevation + 1.0) / 3.0).clamp(0.0, 1.0)
+}
+
+/// Keep the sky around the camera, place the sun and moon discs, turn the stars around the
+/// celestial pole (its height above the northern horizon is the player's latitude) and, every
+/// `GRADIENT_UPDATE_SECS`, repaint the gradient and fade stars and discs with the daylight and
+/// the clouds. The clear color follows the horizon.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn update_sky(
+    time: Res<Time>,
+    game_time: Res<GameTime>,
+    sun: Res<SunPosition>,
+    weather: Res<Weather>,
+    mut clear_color: ResMut<ClearColor>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    cameras: Query<&Transform, (With<ThirdPersonCamera>, Without<Sky>, Without<SkyPart>)>,
+    mut sky: Query<&mut Transform, (With<Sky>, Without<SkyPart>)>,
+    dome: Query<&Mesh3d, With<SkyDome>>,
+    mut parts: Query<(&SkyPart, &mut Transform, &MeshMaterial3d<StandardMaterial>), Without<Sky>>,
+    mut since_update: Local<f32>,
+) {
+    let Ok(camera) = cameras.single() else { return; };
+    if let Ok(mut sky_transform) = sky.single_mut() {
+        sky_transform.translation = camera.translation;
+    }
+
+    let hour_angle = ((game_time.hour - 12.0) * 15.0).to_radians();
+    let pole_tilt = FRAC_PI_2 - sun.latitude.to_radians();
+    for (part, mut transform, _) in parts.iter_mut() {
+        *transform = match part {
+            SkyPart::Stars => Transform::from_rotation(Quat::from_rotation_x(pole_tilt) * Quat::from_rotation_y(hour_angle)),
+            SkyPart::Sun => Transform::from_translation(sun.direction * DISC_DISTANCE).looking_to(sun.direction, Vec3::Y),
+            SkyPart::Moon => Transform::from_translation(-sun.direction * DISC_DISTANCE).looking_to(-sun.direction, Vec3::Y),
+        };
+    }
+
+    *since_update += time.delta_secs();
+    if *since_update < GRADIENT_UPDATE_SECS {
+        return;
+    }
+    *since_update = 0.0;
+
+    let clouds = weather.conditions.cloud_cover;
+    if let Some(mesh) = dome.single().ok().and_then(|handle| meshes.get_mut(&handle.0)) {
+        paint_dome(mesh, &sun, clouds);
+    }
+    clear_color.0 = sky_color(Vec3::new(1.0, 0.05, 0.0).normalize(), &sun, clouds);
+
+    let elevation = sun.direction.y.asin().to_degrees();
+    for (part, _, handle) in parts.iter() {
+        let Some(material) = materials.get_mut(&handle.0) else { continue; };
+        material.base_color = match part {
+            SkyPart::Stars => Color::WHITE.with_alpha((1.0 - sun.daylight).powi(2) * (1.0 - clouds)),
+            SkyPart::Sun => {
+                let height = (elevation / 20.0).clamp(0.0, 1.0);
+                color_temperature(2000.0 + 4500.0 * height).mix(&Color::WHITE, 0.5 * height)
+                    .with_alpha(above_horizon(elevation) * (1.0 - 0.9 * clouds))
+            }
+            SkyPart::Moon => MOON_DISC_COLOR.with_alpha(above_horizon(-elevation) * (1.0 - 0.9 * clouds) * (1.0 - 0.7 * sun.daylight)),
+        };
+    }
+}
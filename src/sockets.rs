@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use crate::game_object::{spawn_template_scene, CollisionBehavior, ObjectDefinitionBuilder, ObjectTemplate, ObjectTemplates};
+use crate::items::Hotbar;
+use crate::planisphere::Planisphere;
+use crate::player::{Player, PlayerInventory};
+use crate::terrain::TerrainCenter;
+
+/// Socket of the player model the selected hotbar item is shown on
+pub const HAND_SOCKET: &str = "hand";
+/// Template shown for a held item, the same as for items lying in the world
+const HELD_ITEM_TEMPLATE: &str = "pickup";
+
+/// Attachment point of a spawned object; a child entity placed at the socket offset, named
+/// after the socket
+#[derive(Component, Debug, Clone)]
+pub struct Socket;
+
+/// Socket entities of a spawned object, keyed by socket name
+#[derive(Component, Debug, Clone, Default)]
+pub struct Sockets {
+    pub entities: HashMap<String, Entity>,
+}
+
+/// Marks an entity attached to a socket, so it can be detached later
+#[derive(Component, Debug, Clone)]
+pub struct AttachedTo {
+    pub owner: Entity,
+    pub socket: String,
+}
+
+/// Error returned when attaching to a socket the object does not declare
+#[derive(Debug, Clone)]
+pub struct MissingSocket(pub String);
+
+impl std::fmt::Display for MissingSocket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "object has no socket named '{}'", self.0)
+    }
+}
+
+impl std::error::Error for MissingSocket {}
+
+/// Spawn one socket child per template socket under `parent`. Socket offsets are in world
+/// units relative to the object origin, stretched by the instance scale variation.
+pub fn spawn_sockets(commands: &mut Commands, parent: Entity, sockets: &HashMap<String, Transform>, scale_factor: f32) -> Sockets {
+    let mut spawned = Sockets::default();
+    for (name, offset) in sockets.iter() {
+        let mut transform = *offset;
+        transform.translation *= scale_factor;
+        let socket = commands.spawn((
+            transform,
+            Visibility::default(),
+            Socket,
+            Name::new(name.clone()),
+        )).id();
+        commands.entity(parent).add_child(socket);
+        spawned.entities.insert(name.clone(), socket);
+    }
+    spawned
+}
+
+/// Parent `entity` to a socket of `owner`. The entity is snapped onto the socket and its rigid
+/// body removed, so its colliders move with the owner.
+pub fn attach_to_socket(
+    commands: &mut Commands,
+    owner: Entity,
+    sockets: &Sockets,
+    socket: &str,
+    entity: Entity,
+) -> Result<(), MissingSocket> {
+    let socket_entity = *sockets.entities.get(socket).ok_or_else(|| MissingSocket(socket.to_string()))?;
+    commands.entity(entity)
+        .remove::<(RigidBody, Velocity)>()
+        .insert((Transform::IDENTITY, AttachedTo { owner, socket: socket.to_string() }));
+    commands.entity(socket_entity).add_child(entity);
+    Ok(())
+}
+
+/// Spawn an instance of `template` directly on a socket of `owner` (torch in a hand, sign on a post)
+#[allow(clippy::too_many_arguments)]
+pub fn attach_template_to_socket(
+    commands: &mut Commands,
+    materials: &mut ResMut<Assets<StandardMaterial>>,
+    planisphere: &Planisphere,
+    terrain_center: &TerrainCenter,
+    owner: Entity,
+    sockets: &Sockets,
+    socket: &str,
+    template: &ObjectTemplate,
+) -> Result<Entity, MissingSocket> {
+    if !sockets.entities.contains_key(socket) {
+        return Err(MissingSocket(socket.to_string()));
+    }
+    let entity = spawn_template_scene(
+        commands,
+        materials,
+        planisphere,
+        terrain_center,
+        template,
+        Vec3::ZERO,
+        0.0,
+        ObjectDefinitionBuilder::from_template(template).collision(CollisionBehavior::None).build(), // The owner's body handles physics
+        (),
+    );
+    attach_to_socket(commands, owner, sockets, socket, entity)?;
+    Ok(entity)
+}
+
+/// Item shown on a socket, by item id
+#[derive(Component, Debug, Clone)]
+pub struct HeldItem(pub String);
+
+/// Show the selected hotbar item in the player's hand, swapping it when the selection or the
+/// inventory changes
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub fn hold_selected_item(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    object_templates: Res<ObjectTemplates>,
+    players: Query<(Entity, &Sockets, &PlayerInventory, &Hotbar), (With<Player>, Or<(Changed<PlayerInventory>, Changed<Hotbar>, Added<Sockets>)>)>,
+    held: Query<(Entity, &AttachedTo, &HeldItem)>,
+) {
+    let Ok((player, sockets, inventory, hotbar)) = players.single() else { return; };
+    let selected = inventory.stacks.get(hotbar.selected).map(|stack| stack.item_id.as_str());
+    let current = held.iter().find(|(_, attached, _)| attached.owner == player && attached.socket == HAND_SOCKET);
+    if current.map(|(_, _, item)| item.0.as_str()) == selected {
+        return;
+    }
+    if let Some((entity, _, _)) = current {
+        commands.entity(entity).despawn();
+    }
+    let Some(item_id) = selected else { return; };
+    let Ok(template) = object_templates.get(HELD_ITEM_TEMPLATE) else { return; };
+    match attach_template_to_socket(&mut commands, &mut materials, &planisphere, &terrain_center, player, sockets, HAND_SOCKET, template) {
+        Ok(entity) => { commands.entity(entity).insert(HeldItem(item_id.to_string())); }
+        Err(e) => warn!("Cannot show the held item: {}", e),
+    }
+}
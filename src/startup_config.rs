@@ -0,0 +1,307 @@
+use std::path::Path;
+use std::time::SystemTime;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::{RapierConfiguration, RapierContextInitialization, TimestepMode};
+use bevy_rapier3d::rapier::dynamics::IntegrationParameters;
+use toml_edit::{DocumentMut, Item};
+
+use crate::planisphere::DistanceMethod;
+use crate::terrain::TerrainCenter;
+use crate::TerrainConfig;
+
+/// Startup parameters file, written with the defaults on first run
+pub const STARTUP_CONFIG_PATH: &str = "config/tiles3d.toml";
+
+/// Parameters read when the game starts: map, spawn point, physics and terrain tuning.
+/// The `[terrain]` values are hot-reloaded into `TerrainConfig`, except `radius` which seeds the
+/// render distance setting; the map, spawn point, world and physics only apply on the next start.
+#[derive(Resource, Debug, Clone)]
+pub struct StartupConfig {
+    pub map_path: String,
+    pub sub_k: usize,            // Subpixel divisions per pixel row
+    pub initial_lon: f64,        // Spawn point in degrees
+    pub initial_lat: f64,
+    pub seed: u64,               // World seed of the deterministic placements, 0 for the default world
+    pub save_dir: String,        // Directory of the world objects and waypoints saves
+    pub physics: PhysicsConfig,
+    pub terrain: TerrainConfig,
+    pub render_distance: Option<usize>, // Forced for this run over the settings file (command line), not saved
+}
+
+impl Default for StartupConfig {
+    fn default() -> Self {
+        Self {
+            map_path: "assets/maps/sphere_texture.png".to_string(),
+            sub_k: crate::config::terrain::SUB_K,
+            initial_lon: crate::config::player::INITIAL_LON as f64,
+            initial_lat: crate::config::player::INITIAL_LAT as f64,
+            seed: 0,
+            save_dir: "saves".to_string(),
+            physics: PhysicsConfig::default(),
+            terrain: TerrainConfig::default(),
+            render_distance: None,
+        }
+    }
+}
+
+/// Fixed-timestep simulation and Rapier parameters (`[physics]`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhysicsConfig {
+    pub timestep_hz: f64,           // Rate of `FixedUpdate`, one physics step each
+    pub substeps: usize,            // Physics substeps per step
+    pub gravity: f32,               // Downward acceleration in m/s²
+    pub solver_iterations: usize,   // Rapier solver iterations per substep
+}
+
+impl Default for PhysicsConfig {
+    fn default() -> Self {
+        Self {
+            timestep_hz: crate::config::physics::TIMESTEP_HZ,
+            substeps: crate::config::physics::SUBSTEPS,
+            gravity: crate::config::physics::GRAVITY,
+            solver_iterations: crate::config::physics::SOLVER_ITERATIONS,
+        }
+    }
+}
+
+impl PhysicsConfig {
+    /// Clock of the `FixedUpdate` schedule, where the physics and the gameplay simulation run
+    pub fn fixed_time(&self) -> Time<Fixed> {
+        Time::<Fixed>::from_hz(self.timestep_hz)
+    }
+
+    /// One physics step per `FixedUpdate` run, split into `substeps`
+    pub fn timestep_mode(&self) -> TimestepMode {
+        TimestepMode::Fixed { dt: 1.0 / self.timestep_hz as f32, substeps: self.substeps }
+    }
+
+    /// Gravity and solver iterations of the default physics world
+    pub fn context_initialization(&self) -> RapierContextInitialization {
+        let integration_parameters = IntegrationParameters {
+            num_solver_iterations: std::num::NonZeroUsize::new(self.solver_iterations).unwrap_or(std::num::NonZeroUsize::MIN),
+            ..default()
+        };
+        RapierContextInitialization::InitializeDefaultRapierContext {
+            integration_parameters,
+            rapier_configuration: RapierConfiguration { gravity: Vec3::NEG_Y * self.gravity, ..RapierConfiguration::new(1.0) },
+        }
+    }
+}
+
+impl StartupConfig {
+    /// Path of a save file in the save directory
+    pub fn save_path(&self, file: &str) -> String {
+        Path::new(&self.save_dir).join(file).to_string_lossy().into_owned()
+    }
+
+    /// Read the startup parameters; a missing file is created with the defaults, a broken one is
+    /// reported and the defaults are used
+    pub fn load(path: &str) -> Self {
+        if !crate::storage::exists(path) {
+            let config = Self::default();
+            match config.save(path) {
+                Ok(()) => info!("Wrote default startup config to {}", path),
+                Err(e) => warn!("Could not write default startup config {}: {}", path, e),
+            }
+            return config;
+        }
+        match load_startup_config_file(Path::new(path)) {
+            Ok(config) => {
+                info!("Loaded startup config from {}", path);
+                config
+            }
+            Err(e) => {
+                warn!("Could not load startup config {}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the parameters as a commented TOML file, creating the parent directory if needed
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        crate::storage::write(path, &self.to_toml())
+    }
+
+    fn to_toml(&self) -> String {
+        let terrain = &self.terrain;
+        [
+            "# tiles3d startup parameters: [map], [spawn], [world] and [physics] apply on the next start, [terrain] while the game runs".to_string(),
+            String::new(),
+            "[map]".to_string(),
+            "# Elevation and texture map, and subpixel divisions per pixel row".to_string(),
+            format!("path = {:?}", self.map_path),
+            format!("sub_k = {}", self.sub_k),
+            String::new(),
+            "[spawn]".to_string(),
+            "# Spawn point in degrees".to_string(),
+            format!("lon = {:?}", self.initial_lon),
+            format!("lat = {:?}", self.initial_lat),
+            String::new(),
+            "[world]".to_string(),
+            "# Seed of the scattered objects and save directory".to_string(),
+            format!("seed = {}", self.seed),
+            format!("save_dir = {:?}", self.save_dir),
+            String::new(),
+            "[physics]".to_string(),
+            "# Fixed simulation rate in Hz (one physics step each) and physics substeps per step".to_string(),
+            format!("timestep_hz = {:?}", self.physics.timestep_hz),
+            format!("substeps = {}", self.physics.substeps),
+            "# Gravity in m/s², and solver iterations: more gives stiffer contacts at a higher cost".to_string(),
+            format!("gravity = {:?}", self.physics.gravity),
+            format!("solver_iterations = {}", self.physics.solver_iterations),
+            String::new(),
+            "[terrain]".to_string(),
+            "# Default render distance in subpixels, the settings file has the final say".to_string(),
+            format!("radius = {}", terrain.terrain_radius),
+            "# Shape of the rendered area: chebyshev (square), euclidean (circle) or manhattan (diamond)".to_string(),
+            format!("distance_method = {:?}", terrain.distance_method.name()),
+            "# Subpixels from the terrain center before it is rebuilt, and minimum seconds between rebuilds".to_string(),
+            format!("recreation_threshold = {}", terrain.recreation_threshold),
+            format!("recreation_cooldown = {:?}", terrain.recreation_cooldown),
+            "# Radii in tiles: landscape elements, collectible items, G grid overlay, agent respawn search".to_string(),
+            format!("landscape_radius = {}", terrain.landscape_radius),
+            format!("item_radius = {}", terrain.item_radius),
+            format!("grid_radius = {}", terrain.grid_radius),
+            format!("agent_search_radius = {}", terrain.agent_search_radius),
+            "# World height of the highest map altitude (0 lays the terrain flat), and bilinear corner".to_string(),
+            "# heights (true) or flat steps at each subpixel's own altitude (false)".to_string(),
+            format!("height_scale = {:?}", terrain.relief.height_scale),
+            format!("smooth_heights = {}", terrain.relief.smooth),
+            "# Outer radii in subpixels of the distant rings merged into 2x2 subpixel quads, then one quad".to_string(),
+            "# per pixel; a ring not reaching past the render distance (e.g. 0) is left out".to_string(),
+            format!("lod_block_radius = {}", terrain.lod.block_radius),
+            format!("lod_pixel_radius = {}", terrain.lod.pixel_radius),
+            "# Water surface over the sea pixels, at this altitude of the map (0.0 to 1.0, scaled by height_scale)".to_string(),
+            format!("ocean = {}", terrain.ocean.enabled),
+            format!("sea_level = {:?}", terrain.ocean.sea_level),
+        ].join("\n") + "\n"
+    }
+
+    fn from_toml(document: &DocumentMut) -> Self {
+        let mut config = Self::default();
+        let value = |table: &str, key: &str| document.get(table).and_then(|table| table.get(key));
+        let read_usize = |table: &str, key: &str, target: &mut usize| {
+            if let Some(number) = value(table, key).and_then(Item::as_integer) {
+                *target = number.max(0) as usize;
+            }
+        };
+        let read_float = |table: &str, key: &str| {
+            value(table, key).and_then(|item| item.as_float().or_else(|| item.as_integer().map(|number| number as f64)))
+        };
+
+        if let Some(path) = value("map", "path").and_then(Item::as_str) {
+            config.map_path = path.to_string();
+        }
+        read_usize("map", "sub_k", &mut config.sub_k);
+        config.initial_lon = read_float("spawn", "lon").unwrap_or(config.initial_lon);
+        config.initial_lat = read_float("spawn", "lat").unwrap_or(config.initial_lat);
+        if let Some(seed) = value("world", "seed").and_then(Item::as_integer) {
+            config.seed = seed as u64;
+        }
+        if let Some(save_dir) = value("world", "save_dir").and_then(Item::as_str) {
+            config.save_dir = save_dir.to_string();
+        }
+
+        let physics = &mut config.physics;
+        physics.timestep_hz = read_float("physics", "timestep_hz").unwrap_or(physics.timestep_hz);
+        read_usize("physics", "substeps", &mut physics.substeps);
+        physics.gravity = read_float("physics", "gravity").map_or(physics.gravity, |gravity| gravity as f32);
+        read_usize("physics", "solver_iterations", &mut physics.solver_iterations);
+        physics.timestep_hz = physics.timestep_hz.max(1.0);
+        physics.substeps = physics.substeps.max(1);
+        physics.solver_iterations = physics.solver_iterations.max(1);
+
+        let terrain = &mut config.terrain;
+        read_usize("terrain", "radius", &mut terrain.terrain_radius);
+        if let Some(name) = value("terrain", "distance_method").and_then(Item::as_str) {
+            match DistanceMethod::from_name(name) {
+                Some(method) => terrain.distance_method = method,
+                None => warn!("Unknown distance_method {:?} in [terrain], keeping {}", name, terrain.distance_method.name()),
+            }
+        }
+        read_usize("terrain", "recreation_threshold", &mut terrain.recreation_threshold);
+        terrain.recreation_cooldown = read_float("terrain", "recreation_cooldown").map_or(terrain.recreation_cooldown, |secs| secs as f32);
+        read_usize("terrain", "landscape_radius", &mut terrain.landscape_radius);
+        read_usize("terrain", "item_radius", &mut terrain.item_radius);
+        read_usize("terrain", "grid_radius", &mut terrain.grid_radius);
+        read_usize("terrain", "agent_search_radius", &mut terrain.agent_search_radius);
+        terrain.relief.height_scale = read_float("terrain", "height_scale").map_or(terrain.relief.height_scale, |scale| scale.max(0.0) as f32);
+        if let Some(smooth) = value("terrain", "smooth_heights").and_then(Item::as_bool) {
+            terrain.relief.smooth = smooth;
+        }
+        read_usize("terrain", "lod_block_radius", &mut terrain.lod.block_radius);
+        read_usize("terrain", "lod_pixel_radius", &mut terrain.lod.pixel_radius);
+        if let Some(enabled) = value("terrain", "ocean").and_then(Item::as_bool) {
+            terrain.ocean.enabled = enabled;
+        }
+        terrain.ocean.sea_level = read_float("terrain", "sea_level").map_or(terrain.ocean.sea_level, |level| level.clamp(0.0, 1.0) as f32);
+        config.sub_k = config.sub_k.max(1);
+        config
+    }
+}
+
+/// Parse a startup config file; missing keys keep their default
+pub fn load_startup_config_file(path: &Path) -> Result<StartupConfig, String> {
+    let content = crate::storage::read_to_string(&path.to_string_lossy()).map_err(|e| e.to_string())?;
+    let document = content.parse::<DocumentMut>().map_err(|e| e.to_string())?;
+    Ok(StartupConfig::from_toml(&document))
+}
+
+/// Polls the startup config file so `[terrain]` edits apply while the game runs
+#[derive(Resource)]
+pub struct StartupConfigWatcher {
+    timer: Timer,
+    modified: Option<SystemTime>,
+}
+
+impl Default for StartupConfigWatcher {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            modified: config_file_mtime(STARTUP_CONFIG_PATH),
+        }
+    }
+}
+
+fn config_file_mtime(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Reload the startup config when it changes on disk and apply the `[terrain]` values, rebuilding
+/// the terrain when its relief or LOD rings changed (the ocean and the distance method follow
+/// `TerrainConfig` on their own);
+/// the render distance stays with the settings, and map, spawn or physics changes wait for a restart
+pub fn hot_reload_startup_config(
+    time: Res<Time<Real>>,
+    mut watcher: ResMut<StartupConfigWatcher>,
+    mut config: ResMut<StartupConfig>,
+    mut terrain_config: ResMut<TerrainConfig>,
+    mut terrain_center: ResMut<TerrainCenter>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let modified = config_file_mtime(STARTUP_CONFIG_PATH);
+    if modified.is_none() || modified == watcher.modified {
+        return;
+    }
+    watcher.modified = modified;
+    let loaded = match load_startup_config_file(Path::new(STARTUP_CONFIG_PATH)) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            warn!("Startup config reload failed for {}: {}", STARTUP_CONFIG_PATH, e);
+            return;
+        }
+    };
+    if loaded.map_path != config.map_path || loaded.sub_k != config.sub_k
+        || loaded.initial_lon != config.initial_lon || loaded.initial_lat != config.initial_lat
+        || loaded.seed != config.seed || loaded.save_dir != config.save_dir || loaded.physics != config.physics {
+        info!("Map, spawn, world and physics changes in {} apply on the next start", STARTUP_CONFIG_PATH);
+    }
+    if loaded.terrain.relief != terrain_config.relief || loaded.terrain.lod != terrain_config.lod {
+        terrain_center.force_recreation = true;
+    }
+    *terrain_config = TerrainConfig { terrain_radius: terrain_config.terrain_radius, ..loaded.terrain.clone() };
+    config.terrain = loaded.terrain;
+    info!("Reloaded terrain parameters from {}", STARTUP_CONFIG_PATH);
+}
@@ -0,0 +1,61 @@
+//! Where settings and saves are kept: files on native builds, the browser's `localStorage` on
+//! the web (keyed by the same paths). Everything the game writes goes through these functions.
+
+/// Read a whole text file
+pub fn read_to_string(path: &str) -> std::io::Result<String> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::fs::read_to_string(path)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        web::local_storage()?.get_item(path).map_err(web::js_error)?
+            .ok_or_else(|| std::io::ErrorKind::NotFound.into())
+    }
+}
+
+/// Write a text file, creating its parent directory if needed
+pub fn write(path: &str, contents: &str) -> std::io::Result<()> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents)
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        web::local_storage()?.set_item(path, contents).map_err(web::js_error)
+    }
+}
+
+/// Whether a file exists
+pub fn exists(path: &str) -> bool {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::path::Path::new(path).exists()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        web::local_storage().is_ok_and(|storage| storage.get_item(path).is_ok_and(|item| item.is_some()))
+    }
+}
+
+/// Copy a file, creating the parent directory of the copy if needed
+pub fn copy(from: &str, to: &str) -> std::io::Result<()> {
+    write(to, &read_to_string(from)?)
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    /// The page's `localStorage`
+    pub fn local_storage() -> std::io::Result<web_sys::Storage> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::Unsupported, "localStorage is not available"))
+    }
+
+    pub fn js_error(error: wasm_bindgen::JsValue) -> std::io::Error {
+        std::io::Error::other(format!("{:?}", error))
+    }
+}
@@ -0,0 +1,140 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+
+use crate::game_object::{EntitySubpixelPosition, ObjectDefinition};
+use crate::message_log::{LogCategory, LogMessage};
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use super::{build_terrain_geometry, spawn_terrain_geometry, Relief, TerrainCenter, TerrainGeometry, TerrainLod, Tile};
+
+/// Read-only copy of the planisphere shared with terrain build tasks
+#[derive(Resource, Clone)]
+pub struct SharedPlanisphere(pub Arc<Planisphere>);
+
+/// Sent once a new terrain has been swapped in. World positions computed against the previous
+/// terrain center are stale from then on.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TerrainReady {
+    pub center: (usize, usize, usize),
+}
+
+/// Terrain build running on the async compute pool. The old terrain stays in place until the
+/// new geometry is ready.
+#[derive(Resource, Default)]
+pub struct TerrainBuild {
+    task: Option<Task<Option<TerrainGeometry>>>,
+    recenter: Option<(usize, usize, usize)>,   // New terrain center, applied when the build finishes
+    progress: Arc<AtomicU32>,                   // Percent, written by the task
+    pub blocking: bool,                         // Show the loading screen and pause physics
+}
+
+impl TerrainBuild {
+    /// Start building the terrain around `terrain_center`, or around `recenter` when the center
+    /// moves, with the heights of `relief` and the distant rings of `lod`. Ignored while another
+    /// build is running.
+    pub fn start(
+        &mut self,
+        planisphere: &SharedPlanisphere,
+        terrain_center: &TerrainCenter,
+        relief: Relief,
+        lod: TerrainLod,
+        recenter: Option<(usize, usize, usize)>,
+        blocking: bool,
+    ) {
+        if self.is_running() {
+            return;
+        }
+        let center = recenter.unwrap_or(terrain_center.subpixel);
+        let lonlat = match recenter {
+            Some((i, j, k)) => planisphere.0.subpixel_to_geo(i, j, k),
+            None => (terrain_center.longitude, terrain_center.latitude),
+        };
+        let max_subpixel_distance = terrain_center.max_subpixel_distance;
+        let method = terrain_center.distance_method;
+        let planisphere = planisphere.0.clone();
+        let progress = self.progress.clone();
+        progress.store(0, Ordering::Relaxed);
+
+        self.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+            build_terrain_geometry(&planisphere, center, lonlat, max_subpixel_distance, method, relief, lod, &progress)
+        }));
+        self.recenter = recenter;
+        self.blocking = blocking;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.task.is_some()
+    }
+
+    /// Progress of the running build, 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        self.progress.load(Ordering::Relaxed).min(100) as f32 / 100.0
+    }
+}
+
+/// Swap in the terrain once its build task finishes: recenter the player and objects if needed,
+/// free the old terrain assets and spawn the new terrain entity
+#[allow(clippy::too_many_arguments)]
+pub fn finish_terrain_build(
+    mut commands: Commands,
+    mut build: ResMut<TerrainBuild>,
+    mut terrain_center: ResMut<TerrainCenter>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    planisphere: Res<Planisphere>,
+    mut asset_tracker: ResMut<crate::TerrainAssetTracker>,
+    player_query: Query<(Entity, &mut Transform, &EntitySubpixelPosition, &Player)>,
+    object_query: Query<(Entity, &mut Transform, &ObjectDefinition), Without<Player>>,
+    terrain_query: Query<Entity, With<Tile>>,
+    mut notify: EventWriter<crate::notifications::Notify>,
+    mut debug_panel: ResMut<crate::ui::DebugPanel>,
+    mut log: EventWriter<LogMessage>,
+    mut ready: EventWriter<TerrainReady>,
+) {
+    let Some(task) = build.task.as_mut() else { return; };
+    let Some(mut geometry) = future::block_on(future::poll_once(task)) else { return; };
+    build.task = None;
+    build.blocking = false;
+    if let Some(e) = geometry.as_mut().and_then(|geometry| geometry.collider_error.take()) {
+        log.write(LogMessage::new(LogCategory::Error, format!("{}, using a collider per triangle", e)));
+    }
+    let first_build = terrain_center.stats.generations == 0;
+
+    if let Some((i, j, k)) = build.recenter.take() {
+        terrain_center.set_ijk(i, j, k, &planisphere);
+        crate::player::reinitialize_positions(player_query, object_query);
+    }
+
+    // Clear old triangle mapping
+    terrain_center.triangle_mapping.triangle_to_subpixel.clear();
+    // CRITICAL: Clean up old asset handles from Bevy's asset system to prevent memory leaks
+    asset_tracker.cleanup_assets(&mut meshes, &mut materials);
+
+    // Remove existing terrain entities (props follow the recentring, see populate.rs)
+    for terrain_entity in terrain_query.iter() {
+        commands.entity(terrain_entity).despawn();
+    }
+
+    spawn_terrain_geometry(
+        &mut commands,
+        &mut meshes,
+        &mut materials,
+        &asset_server,
+        &mut terrain_center,
+        geometry,
+        Some(&mut asset_tracker),
+    );
+
+    ready.write(TerrainReady { center: terrain_center.subpixel });
+    let stats = &terrain_center.stats;
+    let (i, j, k) = terrain_center.subpixel;
+    log.write(LogMessage::new(LogCategory::Terrain, format!("Terrain built at ({}, {}, {}): {} subpixels and {} LOD quads in {:.1} ms", i, j, k, stats.subpixel_count, stats.merged_quad_count, stats.generation_ms)));
+    if !first_build {
+        notify.write(crate::notifications::Notify::new("Terrain regenerated"));
+    }
+    debug_panel.set("Terrain", format!("{} subpixels + {} LOD quads, built in {:.1} ms", stats.subpixel_count, stats.merged_quad_count, stats.generation_ms));
+}
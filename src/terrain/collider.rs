@@ -1,11 +1,14 @@
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
+use crate::error::{GameError, GameResult};
+
+/// Trimesh collider of the terrain mesh, and its triangles
 pub fn terrain_collider(
-    vertices: &Vec<[f32; 3]>,
-    indices: &Vec<u32>,
-) -> (Collider, Vec<[u32; 3]>) {
-    let t0 = std::time::Instant::now();
+    vertices: &[[f32; 3]],
+    indices: &[u32],
+) -> GameResult<(Collider, Vec<[u32; 3]>)> {
+    let _span = info_span!("terrain_collider", triangles = indices.len() / 3).entered();
     let vertices_for_collider: Vec<Vec3> = vertices.iter()
         .map(|v| Vec3::new(v[0], v[1], v[2]))
         .collect();
@@ -17,14 +20,20 @@ pub fn terrain_collider(
         }
     }
 
-    let trimesh_collider = match Collider::trimesh(vertices_for_collider, triangles.clone()) {
-        Ok(collider) => collider,
-        Err(e) => {
-            eprintln!("Failed to create terrain trimesh collider: {:?}, using box fallback", e);
-            Collider::cuboid(25.0, 0.1, 25.0)  // Simple fallback collider
-        }
-    };
-    let t1 = std::time::Instant::now();
-    println!("Collider generation took {:.3} ms", (t1 - t0).as_secs_f64() * 1000.0);
-    (trimesh_collider, triangles)
+    let trimesh_collider = Collider::trimesh(vertices_for_collider, triangles.clone())
+        .map_err(|e| GameError::Collider { what: "terrain trimesh".to_string(), reason: format!("{:?}", e) })?;
+    Ok((trimesh_collider, triangles))
+}
+
+/// Collider with the same triangles as `terrain_collider`, for meshes Rapier rejects as a trimesh
+/// (degenerate or inconsistent triangles): one triangle shape per mesh triangle, in a compound
+pub fn triangle_compound_collider(vertices: &[[f32; 3]], indices: &[u32]) -> Collider {
+    let _span = info_span!("triangle_compound_collider", triangles = indices.len() / 3).entered();
+    let parts = indices.chunks_exact(3)
+        .map(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|n| Vec3::from_array(vertices[triangle[n] as usize]));
+            (Vec3::ZERO, Quat::IDENTITY, Collider::triangle(a, b, c))
+        })
+        .collect();
+    Collider::compound(parts)
 }
@@ -1,31 +1,38 @@
 use bevy::prelude::*;
 use bevy::pbr::wireframe::Wireframe;
 use bevy_rapier3d::prelude::*;
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use crate::planisphere;
+use tiles3d::profiling::StageTimer;
+use tiles3d::tiling::SubpixelCorners;
 use super::{TerrainCenter, RenderedSubpixels, TriangleSubpixelMapping, Tile};
-use super::mesh::terrain_mesh;
-use super::collider::terrain_collider;
+use super::lod::{MergedQuad, TerrainLod};
+use super::mesh::{terrain_mesh, terrain_mesh_with_lod, Relief};
+use super::skirt::{skirt_edges, skirt_mesh, skirt_uvs, SkirtEdge};
+use super::collider::{terrain_collider, triangle_compound_collider};
+use crate::error::GameError;
 
 /// Refactor your compute_mesh to return both the mesh and the updates
 pub fn compute_mesh_async(
     planisphere: &planisphere::Planisphere,
     subpixel: (usize, usize, usize),
     max_subpixel_distance: usize,
+    method: planisphere::DistanceMethod,
+    relief: Relief,
 ) -> (Mesh, RenderedSubpixels, TriangleSubpixelMapping) {
     let subpixels = planisphere.get_subpixels_by_distance_method(
         subpixel.0,
         subpixel.1,
         subpixel.2,
         max_subpixel_distance,
-        crate::planisphere::DistanceMethod::Chebyshev
+        method
     );
     let mut rendered_subpixels = RenderedSubpixels::new();
     rendered_subpixels.subpixels = subpixels.clone();
     let lonlat = planisphere.subpixel_to_geo(subpixel.0, subpixel.1, subpixel.2);
-    let (vertices, indices, uvs, mapping) = terrain_mesh(planisphere, subpixels, lonlat);
+    let (vertices, indices, uvs, mapping) = terrain_mesh(planisphere, subpixels, lonlat, relief);
     let triangle_map = TriangleSubpixelMapping { triangle_to_subpixel: mapping };
-    let (trimesh_collider, _triangles) = terrain_collider(&vertices, &indices);
 
     let mut mesh = Mesh::new(
         bevy::render::mesh::PrimitiveTopology::TriangleList,
@@ -35,8 +42,6 @@ pub fn compute_mesh_async(
     mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
     mesh.insert_indices(bevy::render::mesh::Indices::U32(indices));
     mesh.compute_smooth_normals();
-
-    let _ = trimesh_collider; // collider is computed inside terrain_collider but not returned here
     (mesh, rendered_subpixels, triangle_map)
 }
 
@@ -72,51 +77,121 @@ pub fn create_terrain_simple(
     ));
 }
 
-/// Create terrain using rectangular (Chebyshev) distance pattern
-pub fn create_terrain_gnomonic_rectangular(
+/// Geometry of one terrain build, computed off the main thread by `build_terrain_geometry`
+pub struct TerrainGeometry {
+    pub subpixels: Vec<SubpixelCorners>,
+    pub quads: Vec<MergedQuad>,                 // LOD rings, meshed after the subpixels
+    pub skirts: Vec<SkirtEdge>,                 // Border edges, their skirts meshed after the quads
+    pub vertices: Vec<[f32; 3]>,
+    pub indices: Vec<u32>,
+    pub uvs: Vec<[f32; 2]>,
+    pub mapping: Vec<(usize, usize, usize)>,   // Subpixel of each triangle
+    pub collider: Collider,
+    pub collider_error: Option<GameError>,      // Why the trimesh was rejected (`collider` is then a triangle compound)
+    pub build_ms: f64,                          // Time spent computing the geometry
+}
+
+/// Compute the terrain mesh and collider around the `center` subpixel, projected around `lonlat`,
+/// with vertex heights from `relief`, the distant rings of `lod` and skirts hanging from the border
+/// (no ECS access, safe to run in a task). The collider only covers the full resolution subpixels.
+/// `progress` is raised from 0 to 100 as the stages complete. Returns None when no subpixel falls
+/// within the distance.
+#[allow(clippy::too_many_arguments)]
+pub fn build_terrain_geometry(
+    planisphere: &planisphere::Planisphere,
+    center: (usize, usize, usize),
+    lonlat: (f64, f64),
+    max_subpixel_distance: usize,
+    method: planisphere::DistanceMethod,
+    relief: Relief,
+    lod: TerrainLod,
+    progress: &AtomicU32,
+) -> Option<TerrainGeometry> {
+    let _span = info_span!("build_terrain_geometry", ?center, max_subpixel_distance).entered();
+    let mut timer = StageTimer::start("Terrain build");
+    let t0 = bevy::platform::time::Instant::now();
+    progress.store(0, Ordering::Relaxed);
+    let subpixels = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2, max_subpixel_distance, method);
+    debug!("Selected {} subpixels within distance {} of {:?} using method {:?}", subpixels.len(), max_subpixel_distance, center, method);
+    timer.stage("subpixel selection");
+    progress.store(20, Ordering::Relaxed);
+
+    if subpixels.is_empty() {
+        progress.store(100, Ordering::Relaxed);
+        return None;
+    }
+
+    let quads = lod.quads(planisphere, center, &subpixels, max_subpixel_distance, method);
+    debug!("Merged {} LOD quads out to {:?}", quads.len(), lod);
+    timer.stage("lod rings");
+    progress.store(30, Ordering::Relaxed);
+
+    let (mut vertices, mut indices, mut uvs, mapping) = terrain_mesh_with_lod(planisphere, subpixels.clone(), &quads, lonlat, relief);
+    timer.stage("mesh");
+
+    // Skirts come last, so the vertices and triangles of the subpixels and quads keep their index
+    let skirts = skirt_edges(planisphere, &subpixels, &quads);
+    let (skirt_vertices, skirt_indices) = skirt_mesh(&vertices, &skirts, crate::config::terrain::SKIRT_DEPTH);
+    uvs.extend(skirt_uvs(&uvs, &skirts));
+    vertices.extend(skirt_vertices);
+    indices.extend(skirt_indices);
+    debug!("Hung {} skirts from the terrain border", skirts.len());
+    timer.stage("skirts");
+    progress.store(70, Ordering::Relaxed);
+
+    // The LOD quads and skirts come after the subpixels' two triangles each
+    let detail_indices = &indices[..6 * subpixels.len()];
+    let (collider, collider_error) = match terrain_collider(&vertices, detail_indices) {
+        Ok((collider, triangles)) => {
+            debug!("Terrain collider has {} triangles, the mapping {}", triangles.len(), mapping.len());
+            (collider, None)
+        }
+        Err(e) => (triangle_compound_collider(&vertices, detail_indices), Some(e)),
+    };
+    timer.stage("collider");
+    timer.finish();
+    progress.store(100, Ordering::Relaxed);
+
+    Some(TerrainGeometry {
+        subpixels,
+        quads,
+        skirts,
+        vertices,
+        indices,
+        uvs,
+        mapping,
+        collider,
+        collider_error,
+        build_ms: t0.elapsed().as_secs_f64() * 1000.0,
+    })
+}
+
+/// Spawn the terrain entity from a finished build and record it in `terrain_center`
+pub fn spawn_terrain_geometry(
     commands: &mut Commands,
     meshes: &mut ResMut<Assets<Mesh>>,
     materials: &mut ResMut<Assets<StandardMaterial>>,
     asset_server: &Res<AssetServer>,
-    planisphere: &planisphere::Planisphere,
     terrain_center: &mut TerrainCenter,
+    geometry: Option<TerrainGeometry>,
     mut asset_tracker: Option<&mut ResMut<crate::TerrainAssetTracker>>,
-    time: &Res<Time>,
 ) {
-    let t0 = std::time::Instant::now();
-    let method = terrain_center.distance_method;
-    let subpixels = planisphere.get_subpixels_by_distance_method(
-        terrain_center.subpixel.0,
-        terrain_center.subpixel.1,
-        terrain_center.subpixel.2,
-        terrain_center.max_subpixel_distance,
-        method);
-
-    println!("Generated {} subpixels within distance {} using method {:?}", subpixels.len(), terrain_center.max_subpixel_distance, method);
-    println!("center at {} {} {}", terrain_center.subpixel.0, terrain_center.subpixel.1, terrain_center.subpixel.2);
-    let t1 = std::time::Instant::now();
-    println!("Subpixel generation took {:.3} ms", (t1 - t0).as_secs_f64() * 1000.0);
-
-    if subpixels.is_empty() {
-        println!("ERROR: No subpixels generated! Falling back to simple terrain.");
+    let Some(geometry) = geometry else {
+        error!("No subpixels generated! Falling back to simple terrain.");
         create_terrain_simple(commands, meshes, materials);
         return;
-    } else {
-        terrain_center.rendered_subpixels.update_rendered_subpixels(&subpixels);
-    }
-
-    let _t0 = std::time::Instant::now();
-    // Update the rendered subpixels in terrain_center
-    let lonlat = (terrain_center.longitude, terrain_center.latitude);
-    let (vertices, indices, uvs, mapping) = terrain_mesh(planisphere, subpixels, lonlat);
-
+    };
+    let _span = info_span!("spawn_terrain_geometry").entered();
+    let mut timer = StageTimer::start("Terrain spawn");
+    let spawn_start = bevy::platform::time::Instant::now();
+    let TerrainGeometry { subpixels, quads, skirts, vertices, indices, uvs, mapping, collider: trimesh_collider, build_ms, .. } = geometry;
+    let subpixel_count = subpixels.len();
+    let merged_quad_count = quads.len();
+    terrain_center.rendered_subpixels.update_rendered_subpixels(&subpixels);
+    terrain_center.lod_quads = quads;
+    terrain_center.skirts = skirts;
     terrain_center.triangle_mapping.triangle_to_subpixel = mapping;
 
-    let (trimesh_collider, triangles) = terrain_collider(&vertices, &indices);
-
-    println!("Physics collider created with {} triangles (should match mapping size)", triangles.len());
-
-    let t0 = std::time::Instant::now();
     let mut terrain_mesh_obj = Mesh::new(
         bevy::render::mesh::PrimitiveTopology::TriangleList,
         bevy::render::render_asset::RenderAssetUsages::default()
@@ -129,20 +204,21 @@ pub fn create_terrain_gnomonic_rectangular(
     terrain_mesh_obj.compute_smooth_normals();
 
     let terrain_mesh_handle = meshes.add(terrain_mesh_obj);
-    let t1 = std::time::Instant::now();
-    println!("Mesh creation took {:.3} ms for {} vertices and {} triangles", (t1 - t0).as_secs_f64() * 1000.0, vertex_count, triangle_count);
+    timer.stage("mesh asset");
 
     // === TEXTURE ATLAS LOADING ===
     // Load the 256x256 pixel texture atlas containing all terrain textures
     // This atlas is a 16x16 grid where each 16x16 pixel tile represents a different terrain type
-    // Generated by assets/textures/atlas_creator.py from individual texture files
-    let tile_texture: Handle<Image> = asset_server.load("textures/texture_atlas.png");
+    // Generated by assets/textures/atlas_creator.py from individual texture files, or by
+    // tiles3d-preprocess (already in the asset tracker when the asset manifest lists it)
+    let tile_texture: Handle<Image> = asset_tracker.as_ref().and_then(|tracker| tracker.texture_atlas.clone())
+        .unwrap_or_else(|| asset_server.load("textures/texture_atlas.png"));
 
     // Store atlas texture handle in asset tracker (reusable across terrain recreations)
     if let Some(asset_tracker) = asset_tracker.as_deref_mut() {
         if asset_tracker.texture_atlas.is_none() {
             asset_tracker.texture_atlas = Some(tile_texture.clone());
-            println!("Stored texture atlas handle in asset tracker");
+            debug!("Stored texture atlas handle in asset tracker");
         }
     }
 
@@ -183,7 +259,7 @@ pub fn create_terrain_gnomonic_rectangular(
     if let Some(asset_tracker) = asset_tracker.as_deref_mut() {
         asset_tracker.terrain_meshes.push(terrain_mesh_handle.clone());
         asset_tracker.terrain_materials.push(terrain_material_handle.clone());
-        println!("Tracked terrain mesh and material handles ({} meshes, {} materials total)",
+        debug!("Tracked terrain mesh and material handles ({} meshes, {} materials total)",
                  asset_tracker.terrain_meshes.len(), asset_tracker.terrain_materials.len());
     }
 
@@ -198,20 +274,18 @@ pub fn create_terrain_gnomonic_rectangular(
         // Wireframe, // Disabled wireframe for normal terrain rendering
     )).id();
 
-    println!("Spawned terrain entity: {:?}", terrain_entity);
-
-    let t0 = std::time::Instant::now();
-    // Update triangle mapping in terrain_center
-    println!("Updated triangle mapping with {} triangles for terrain center ({:.6}, {:.6})",
-        terrain_center.triangle_mapping.triangle_to_subpixel.len(), terrain_center.longitude, terrain_center.latitude);
-    let t1 = std::time::Instant::now();
-    println!("Triangle mapping update took {:.3} ms", (t1 - t0).as_secs_f64() * 1000.0);
-
-    println!("=== TERRAIN MESH DEBUG ===");
-    println!("Generated single terrain mesh with {} vertices", vertex_count);
-    println!("Mesh has {} triangles", triangle_count);
-    println!("Terrain entity ID: {:?}", terrain_entity);
-    println!("==========================");
-
-    let _ = time; // suppress unused warning - kept for API compatibility
+    timer.stage("entity");
+    timer.finish();
+    debug!("Spawned terrain entity {:?}: {} vertices, {} triangles, {} mapped triangles around ({:.6}, {:.6})",
+        terrain_entity, vertex_count, triangle_count, terrain_center.triangle_mapping.triangle_to_subpixel.len(),
+        terrain_center.longitude, terrain_center.latitude);
+
+    terrain_center.stats = super::TerrainStats {
+        subpixel_count,
+        merged_quad_count,
+        vertex_count,
+        triangle_count,
+        generation_ms: build_ms + spawn_start.elapsed().as_secs_f64() * 1000.0,
+        generations: terrain_center.stats.generations + 1,
+    };
 }
@@ -5,24 +5,28 @@ use futures_lite::future;
 
 use crate::planisphere;
 use crate::game_object::EntitySubpixelPosition;
-use crate::game_object::{MouseTrackerObject, ObjectShape, ObjectDefinition, CollisionBehavior, ExistenceConditions,
-                            spawn_template_scene, ObjectTemplates, despawn_unified_objects_from_name};
-use crate::player::Player;
+use crate::game_object::{ObjectShape, ExistenceConditions};
 
 // Submodule declarations
 pub mod generation;
-pub mod mesh;
-pub mod texture;
 pub mod collider;
+pub mod build;
+pub mod ocean;
+pub mod view;
 
 // Re-exports so all public API remains accessible via `use crate::terrain::...`
-pub use generation::{create_terrain_gnomonic_rectangular, create_terrain_simple, compute_mesh_async};
-pub use mesh::terrain_mesh;
-pub use texture::{select_texture_from_rgba, determine_landscape_element_from_rgba};
+pub use tiles3d::tiling::{biome, lod, mesh, skirt, texture}; // Bevy-free tiling math from the library
+pub use generation::{build_terrain_geometry, spawn_terrain_geometry, create_terrain_simple, compute_mesh_async, TerrainGeometry};
+pub use build::{finish_terrain_build, SharedPlanisphere, TerrainBuild, TerrainReady};
+pub use biome::BiomeTable;
+pub use lod::{MergedQuad, TerrainLod};
+pub use mesh::{terrain_uvs, Relief};
+pub use tiles3d::tiling::ocean::{ocean_mesh, Ocean};
+pub use ocean::{animate_ocean, rebuild_ocean, setup_ocean_material};
+pub use view::{apply_terrain_view, handle_terrain_view_keys};
+pub use texture::{select_texture_from_rgba, texture_name};
 pub use collider::terrain_collider;
 
-// Keep the deterministic_random private re-export for use within this module only
-use texture::deterministic_random;
 
 /// Tile Component - Marks entities as part of the terrain
 /// This is attached to terrain entities so agents can detect when they touch the ground
@@ -76,11 +80,19 @@ pub fn ijk_to_world(
     // Use the proper subpixel_to_geo method instead of manually averaging corners
     // This handles edge cases like longitude discontinuities correctly
     let (center_lon, center_lat) = planisphere.subpixel_to_geo(i as usize, j as usize, k as usize);
+    geo_to_world(center_lon, center_lat, planisphere, terrain_center)
+}
 
-    // Convert the geographic center to world coordinates using the same method as terrain generation
+/// World position (Y=0) of a geographic point, using the same projection as terrain generation
+pub fn geo_to_world(
+    lon: f64,
+    lat: f64,
+    planisphere: &crate::planisphere::Planisphere,
+    terrain_center: &TerrainCenter
+) -> Vec3 {
     let (world_x, world_y) = planisphere.geo_to_gnomonic(
-        center_lon,
-        center_lat,
+        lon,
+        lat,
         terrain_center.longitude,
         terrain_center.latitude
     );
@@ -89,41 +101,38 @@ pub fn ijk_to_world(
     Vec3::new(world_x as f32 + 0.5 * planisphere.mean_tile_size as f32, 0.0, world_y as f32 + 0.5 * planisphere.mean_tile_size as f32)
 }
 
-// Usage in your terrain spawning
-pub fn entities_in_rendered_subpixels(
-    commands: &mut Commands,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-    rendered_subpixels: ResMut<RenderedSubpixels>,
-    planisphere: Res<planisphere::Planisphere>,
-    terrain_center: ResMut<TerrainCenter>,
-    object_templates: Res<ObjectTemplates>,
-    query: Query<(Entity, &mut Transform, &ObjectDefinition), (Without<Player>, Without<MouseTrackerObject>)>,
-) -> Vec<Entity> {
-    const SPAWN_THRESHOLD: f64 = 0.999;
-    let mut entities = Vec::new();
-    despawn_unified_objects_from_name(commands, "Tree", query);
-    for subpixel_pos in rendered_subpixels.subpixels.iter() {
-        let rdm0 = deterministic_random(subpixel_pos.0, subpixel_pos.1, subpixel_pos.2);
-        let (red, green, blue, alpha) = planisphere.get_rgba_at_subpixel(subpixel_pos.0 as i32, subpixel_pos.1 as i32, subpixel_pos.2);
-        if rdm0 > SPAWN_THRESHOLD && 1. - alpha > 0.5 {
-            let entity = spawn_template_scene(
-                commands,
-                materials,
-                &planisphere,
-                &terrain_center,
-                &object_templates.tree,
-                (subpixel_pos.0 as usize, subpixel_pos.1 as usize, subpixel_pos.2 as usize),
-                0.0, // y_offset
-                CollisionBehavior::Static, // Static collision for trees
-                ()
-            );
-            entities.push(entity);
-        }
-    }
-    entities
+/// Inverse of `ijk_to_world` for any world position: geographic (longitude, latitude) in degrees,
+/// NaN when the position is outside the projection
+pub fn world_to_geo(
+    world: Vec3,
+    planisphere: &crate::planisphere::Planisphere,
+    terrain_center: &TerrainCenter
+) -> (f64, f64) {
+    let half_tile = 0.5 * planisphere.mean_tile_size;
+    crate::planisphere::gnomonic_to_geo_helper(
+        world.x as f64 - half_tile,
+        world.z as f64 - half_tile,
+        terrain_center.longitude,
+        terrain_center.latitude,
+        planisphere.radius,
+    )
 }
 
+/// Subpixel of the initial terrain center, once the map is loaded
+pub fn locate_terrain_center(planisphere: Res<planisphere::Planisphere>, mut terrain_center: ResMut<TerrainCenter>) {
+    terrain_center.subpixel = planisphere.geo_to_subpixel(terrain_center.longitude, terrain_center.latitude);
+}
+
+/// Size and timing of the last terrain generation, for the diagnostics overlay
+#[derive(Debug, Clone, Default)]
+pub struct TerrainStats {
+    pub subpixel_count: usize,
+    pub merged_quad_count: usize,   // Quads of the LOD rings
+    pub vertex_count: usize,
+    pub triangle_count: usize,
+    pub generation_ms: f64,   // Wall time of the last build (geometry task + spawn)
+    pub generations: u32,     // Terrain builds since startup
+}
 
 /// Resource to track terrain center changes for object repositioning
 #[derive(Resource, Default)]
@@ -135,10 +144,17 @@ pub struct TerrainCenter {
     pub last_recreation_time: f32,
     /// Which distance metric to use when selecting subpixels for rendering
     pub distance_method: planisphere::DistanceMethod,
-    /// Set to true to force a terrain rebuild on the next frame (e.g. after changing distance_method)
+    /// Set to true to force a terrain rebuild on the next frame (e.g. after changing distance_method,
+    /// see `apply_terrain_view`)
     pub force_recreation: bool,
     pub rendered_subpixels: RenderedSubpixels,
+    /// Merged quads of the distant LOD rings, meshed after the rendered subpixels. Only the
+    /// rendered subpixels carry objects and collisions.
+    pub lod_quads: Vec<MergedQuad>,
+    /// Border edges of the subpixels and LOD quads, whose skirts are meshed last
+    pub skirts: Vec<skirt::SkirtEdge>,
     pub triangle_mapping: TriangleSubpixelMapping,
+    pub stats: TerrainStats,
 }
 
 impl TerrainCenter {
@@ -148,14 +164,15 @@ impl TerrainCenter {
         self.subpixel = (i, j, k);
         self.longitude = planisphere.subpixel_to_geo(i, j, k).0;
         self.latitude = planisphere.subpixel_to_geo(i, j, k).1;
-        let current_time = std::time::Instant::now().elapsed().as_secs_f32();
+        let current_time = bevy::platform::time::Instant::now().elapsed().as_secs_f32();
         self.last_recreation_time = current_time;
     }
 
 }
 
 /// Helper function to find the nearest free subpixel position using spiral search
-/// This ensures agents don't respawn on top of each other during terrain recreation
+/// This ensures agents don't respawn on top of each other (or inside placed structures,
+/// tracked by `TileOccupancy`) during terrain recreation
 #[allow(dead_code)]
 fn find_nearest_free_subpixel(
     planisphere: &planisphere::Planisphere,
@@ -163,10 +180,12 @@ fn find_nearest_free_subpixel(
     desired_j: usize,
     desired_k: usize,
     occupied_positions: &std::collections::HashSet<(usize, usize, usize)>,
+    occupancy: &crate::occupancy::TileOccupancy,
     terrain_config: &crate::TerrainConfig,
 ) -> (usize, usize, usize) {
+    let is_free = |tile: (usize, usize, usize)| !occupied_positions.contains(&tile) && occupancy.is_tile_free(tile);
     // If the desired position is free, use it
-    if !occupied_positions.contains(&(desired_i, desired_j, desired_k)) {
+    if is_free((desired_i, desired_j, desired_k)) {
         return (desired_i, desired_j, desired_k);
     }
 
@@ -192,7 +211,7 @@ fn find_nearest_free_subpixel(
                 // Try different subpixel positions within this pixel
                 for k in 0..subpixel_divisions {
                     let candidate = (new_i, new_j, k);
-                    if !occupied_positions.contains(&candidate) {
+                    if is_free(candidate) {
                         return candidate;
                     }
                 }
@@ -202,7 +221,7 @@ fn find_nearest_free_subpixel(
 
     // If no free position found, return the desired position anyway
     // This is a fallback that shouldn't happen in normal gameplay
-    println!("WARNING: Could not find free subpixel position near ({},{},{}), using original",
+    info!("Could not find free subpixel position near ({},{},{}), using original",
              desired_i, desired_j, desired_k);
     (desired_i, desired_j, desired_k)
 }
@@ -0,0 +1,119 @@
+use std::f32::consts::TAU;
+
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::*;
+
+use crate::config::ocean::{CALM_WAVE_HEIGHT, COLOR, OPACITY, WAVE_LENGTH, WAVE_SPEED, WIND_WAVE_HEIGHT};
+use crate::planisphere::Planisphere;
+use crate::wind::Wind;
+use crate::TerrainConfig;
+use super::{ocean_mesh, Ocean, TerrainCenter, TerrainReady};
+
+/// Water surface over the sea subpixels of the rendered terrain and its LOD rings. It has no
+/// collider: the player wades into the sea (and runs out of breath, see `player_stats`).
+#[derive(Component)]
+pub struct OceanSurface {
+    rest: Vec<[f32; 3]>,   // Vertex positions without waves
+}
+
+/// Semi-transparent material shared by the ocean surfaces
+#[derive(Resource)]
+pub struct OceanMaterial(pub Handle<StandardMaterial>);
+
+pub fn setup_ocean_material(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let (red, green, blue) = COLOR;
+    commands.insert_resource(OceanMaterial(materials.add(StandardMaterial {
+        base_color: Color::srgba(red, green, blue, OPACITY),
+        perceptual_roughness: 0.1,
+        reflectance: 0.6,
+        alpha_mode: AlphaMode::Blend,
+        cull_mode: None,
+        ..default()
+    })));
+}
+
+/// Rebuild the water surface when a new terrain is swapped in, or when the sea level or the
+/// terrain height scale change
+#[allow(clippy::too_many_arguments)]
+pub fn rebuild_ocean(
+    mut commands: Commands,
+    mut ready: EventReader<TerrainReady>,
+    terrain_config: Res<TerrainConfig>,
+    terrain_center: Res<TerrainCenter>,
+    planisphere: Res<Planisphere>,
+    material: Option<Res<OceanMaterial>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    surfaces: Query<(Entity, &Mesh3d), With<OceanSurface>>,
+    mut built: Local<Option<(Ocean, f32)>>,
+) {
+    let terrain_ready = ready.read().count() > 0;
+    let settings = (terrain_config.ocean, terrain_config.relief.height_scale);
+    let Some(material) = material else { return; };
+    if terrain_center.stats.generations == 0 || (!terrain_ready && *built == Some(settings)) {
+        return;
+    }
+    *built = Some(settings);
+
+    for (entity, mesh) in surfaces.iter() {
+        meshes.remove(&mesh.0);
+        commands.entity(entity).despawn();
+    }
+    let (ocean, height_scale) = settings;
+    if !ocean.enabled {
+        return;
+    }
+    let lonlat = (terrain_center.longitude, terrain_center.latitude);
+    let (vertices, indices) = ocean_mesh(&planisphere, &terrain_center.rendered_subpixels.subpixels,
+                                         &terrain_center.lod_quads, lonlat, ocean.sea_level * height_scale);
+    if indices.is_empty() {
+        return;
+    }
+    debug!("Ocean surface: {} quads at height {:.2}", indices.len() / 6, ocean.sea_level * height_scale);
+
+    let mut mesh = Mesh::new(
+        bevy::render::mesh::PrimitiveTopology::TriangleList,
+        bevy::render::render_asset::RenderAssetUsages::default()
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices.clone());
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; vertices.len()]);
+    mesh.insert_indices(bevy::render::mesh::Indices::U32(indices));
+    commands.spawn((
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(material.0.clone()),
+        Transform::default(),
+        NotShadowCaster,
+        OceanSurface { rest: vertices },
+    ));
+}
+
+/// Height of the waves at a world position: two swells travelling along the wind, higher as it
+/// strengthens
+fn wave_offset(x: f32, z: f32, seconds: f32, wind: &Wind) -> f32 {
+    let amplitude = CALM_WAVE_HEIGHT + WIND_WAVE_HEIGHT * wind.strength;
+    let along = x * wind.direction.x + z * wind.direction.y;
+    let across = z * wind.direction.x - x * wind.direction.y;
+    let k = TAU / WAVE_LENGTH;
+    let phase = k * (along - WAVE_SPEED * seconds);
+    amplitude * (phase.sin() + 0.4 * (1.7 * phase + 0.6 * k * across).sin()) / 1.4
+}
+
+/// Move the ocean vertices with the waves; they stop with the simulation when paused
+pub fn animate_ocean(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    surfaces: Query<(&Mesh3d, &OceanSurface)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    if time.delta_secs() == 0.0 {
+        return;
+    }
+    let seconds = time.elapsed_secs();
+    for (handle, surface) in surfaces.iter() {
+        let Some(mesh) = meshes.get_mut(&handle.0) else { continue; };
+        let positions: Vec<[f32; 3]> = surface.rest.iter()
+            .map(|&[x, y, z]| [x, y + wave_offset(x, z, seconds, &wind), z])
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.compute_smooth_normals();
+    }
+}
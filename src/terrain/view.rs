@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+use crate::notifications::Notify;
+use crate::settings_menu::{RENDER_DISTANCE_RANGE, RENDER_DISTANCE_STEP};
+use crate::ui::DebugPanel;
+use crate::TerrainConfig;
+use super::TerrainCenter;
+
+/// Apply the render radius and distance method of `TerrainConfig` to the terrain center, and
+/// rebuild the terrain when either changed (settings menu, developer panel, method buttons, view
+/// keys, startup config reload). The first terrain is built with them by `setup_physics`.
+pub fn apply_terrain_view(
+    terrain_config: Res<TerrainConfig>,
+    mut terrain_center: ResMut<TerrainCenter>,
+    mut debug_panel: ResMut<DebugPanel>,
+) {
+    if !terrain_config.is_changed() || terrain_center.stats.generations == 0 {
+        return;
+    }
+    let radius = terrain_config.terrain_radius.max(1);
+    let method = terrain_config.distance_method;
+    if terrain_center.max_subpixel_distance == radius && terrain_center.distance_method == method {
+        return;
+    }
+    info!("Terrain view: {} subpixels, {:?} -> {} subpixels, {:?}",
+        terrain_center.max_subpixel_distance, terrain_center.distance_method, radius, method);
+    terrain_center.max_subpixel_distance = radius;
+    terrain_center.distance_method = method;
+    terrain_center.force_recreation = true;
+    debug_panel.set("View", format!("{} subpixels, {}", radius, method.label()));
+}
+
+/// F12 cycles the distance method shaping the rendered area; - and = shrink and grow the render
+/// radius for this session (the settings menu keeps the saved one)
+pub fn handle_terrain_view_keys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut terrain_config: ResMut<TerrainConfig>,
+    mut notify: EventWriter<Notify>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F12) {
+        terrain_config.distance_method = terrain_config.distance_method.next();
+        notify.write(Notify::new(format!("Distance method: {}", terrain_config.distance_method.label())));
+    }
+    let radius = terrain_config.terrain_radius;
+    let resized = if keyboard_input.just_pressed(KeyCode::Minus) {
+        radius.saturating_sub(RENDER_DISTANCE_STEP).max(RENDER_DISTANCE_RANGE.0).min(radius)
+    } else if keyboard_input.just_pressed(KeyCode::Equal) {
+        (radius + RENDER_DISTANCE_STEP).min(RENDER_DISTANCE_RANGE.1).max(radius)
+    } else {
+        radius
+    };
+    if resized != radius {
+        terrain_config.terrain_radius = resized;
+        terrain_config.recreation_threshold = (resized / crate::config::terrain::RECREATION_THRESHOLD_DIVISOR).max(1);
+        notify.write(Notify::new(format!("Render distance: {} subpixels", resized)));
+    }
+}
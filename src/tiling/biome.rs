@@ -0,0 +1,102 @@
+use std::sync::OnceLock;
+
+use super::texture::texture_index;
+
+/// Biome table of the game, read at startup
+pub const BIOMES_PATH: &str = "assets/textures/biomes.ron";
+
+/// Biome table installed with `BiomeTable::install`, read by `select_texture_from_rgba`
+static BIOME_TABLE: OnceLock<BiomeTable> = OnceLock::new();
+
+/// Inclusive range of a classification value, the whole 0.0–1.0 range by default
+pub type Range = (f64, f64);
+
+#[cfg(feature = "game")]
+fn full_range() -> Range {
+    (0.0, 1.0)
+}
+
+/// Biome of the texture classification: the subpixels whose values fall in all its ranges show
+/// its texture. Values are read from the map's RGBA channels:
+///
+/// - `elevation`: altitude from all four channels, as the vertex heights (`rgba_to_alti`)
+/// - `moisture`: green channel, also the vegetation density of the landscape scatter
+/// - `temperature`: blue channel, cold to hot
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "game", derive(serde::Deserialize))]
+pub struct Biome {
+    pub name: String,
+    pub texture: String,                  // Atlas texture name (`texture::TEXTURE_NAMES`)
+    #[cfg_attr(feature = "game", serde(default = "full_range"))]
+    pub elevation: Range,
+    #[cfg_attr(feature = "game", serde(default = "full_range"))]
+    pub moisture: Range,
+    #[cfg_attr(feature = "game", serde(default = "full_range"))]
+    pub temperature: Range,
+    #[cfg_attr(feature = "game", serde(skip))]
+    pub texture_index: usize,             // Resolved from `texture` by `BiomeTable::new`
+}
+
+impl Biome {
+    pub fn contains(&self, elevation: f64, moisture: f64, temperature: f64) -> bool {
+        let within = |(min, max): Range, value: f64| min <= value && value <= max;
+        within(self.elevation, elevation) && within(self.moisture, moisture) && within(self.temperature, temperature)
+    }
+}
+
+/// Ordered biome rules mapping the map's RGBA values to atlas textures; the first biome that
+/// contains a subpixel wins. Without a table, or when no biome matches, the texture follows the
+/// elevation alone (`texture::elevation_texture`).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "game", derive(serde::Deserialize))]
+pub struct BiomeTable {
+    pub biomes: Vec<Biome>,
+}
+
+impl BiomeTable {
+    /// Table of `biomes`, checking their textures exist and their ranges are not empty
+    pub fn new(mut biomes: Vec<Biome>) -> Result<Self, String> {
+        for biome in biomes.iter_mut() {
+            biome.texture_index = texture_index(&biome.texture)
+                .ok_or_else(|| format!("biome {}: unknown texture '{}'", biome.name, biome.texture))?;
+            for (label, (min, max)) in [("elevation", biome.elevation), ("moisture", biome.moisture), ("temperature", biome.temperature)] {
+                if min > max {
+                    return Err(format!("biome {}: empty {} range ({}, {})", biome.name, label, min, max));
+                }
+            }
+        }
+        Ok(Self { biomes })
+    }
+
+    /// Parse a RON biome table (`(biomes: [(name: .., texture: .., elevation: (min, max)), ..])`)
+    #[cfg(feature = "game")]
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: Self = ron::from_str(text).map_err(|e| e.to_string())?;
+        Self::new(table.biomes)
+    }
+
+    /// Read a RON biome table file
+    #[cfg(feature = "game")]
+    pub fn load(path: &str) -> Result<Self, String> {
+        Self::parse(&std::fs::read_to_string(path).map_err(|e| e.to_string())?)
+    }
+
+    /// Atlas texture of the first biome containing the values, if any
+    pub fn classify(&self, elevation: f64, moisture: f64, temperature: f64) -> Option<usize> {
+        self.biomes.iter()
+            .find(|biome| biome.contains(elevation, moisture, temperature))
+            .map(|biome| biome.texture_index)
+    }
+
+    /// Make this the table of `select_texture_from_rgba`, for the terrain build tasks, the
+    /// minimap and the landscape scatter. The first installed table stays: returns false when
+    /// one was already installed.
+    pub fn install(self) -> bool {
+        BIOME_TABLE.set(self).is_ok()
+    }
+
+    /// Table installed with `install`, if any
+    pub fn installed() -> Option<&'static Self> {
+        BIOME_TABLE.get()
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::planisphere::{DistanceMethod, Planisphere};
+use super::SubpixelCorners;
+
+/// Distant terrain rings beyond the full resolution radius, whose subpixels are merged into
+/// coarser quads: 2x2 subpixel blocks out to `block_radius`, then one quad per pixel out to
+/// `pixel_radius`. Radii are in subpixels like the render distance; a ring whose radius does not
+/// exceed the rings inside it is skipped, so 0 disables it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TerrainLod {
+    pub block_radius: usize,
+    pub pixel_radius: usize,
+}
+
+impl Default for TerrainLod {
+    fn default() -> Self {
+        Self {
+            block_radius: crate::config::terrain::LOD_BLOCK_RADIUS,
+            pixel_radius: crate::config::terrain::LOD_PIXEL_RADIUS,
+        }
+    }
+}
+
+/// Quad of a LOD ring covering a block of subpixels of one pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MergedQuad {
+    pub pixel: (usize, usize),
+    pub first: (usize, usize),            // (sub_i, sub_j) of the block's south-west subpixel
+    pub span: (usize, usize),             // Subpixels merged along longitude and latitude
+    pub corners: [(f64, f64); 4],         // Same order as `Planisphere::get_subpixel_corners`
+}
+
+impl MergedQuad {
+    /// Quad over `span` subpixels of pixel `(i, j)` from `(sub_i, sub_j)`
+    pub fn new(planisphere: &Planisphere, (i, j): (usize, usize), (sub_i, sub_j): (usize, usize), span: (usize, usize)) -> Self {
+        let (left, _, top, _) = planisphere.get_subpixel_boundaries(i, j, sub_i, sub_j);
+        let (_, right, _, bottom) = planisphere.get_subpixel_boundaries(i, j, sub_i + span.0 - 1, sub_j + span.1 - 1);
+        Self {
+            pixel: (i, j),
+            first: (sub_i, sub_j),
+            span,
+            corners: [(left, top), (right, top), (right, bottom), (left, bottom)],
+        }
+    }
+
+    /// Middle subpixel of the block, whose texture the quad shows and which its triangles map to
+    pub fn subpixel(&self, planisphere: &Planisphere) -> (usize, usize, usize) {
+        let sub_i = self.first.0 + self.span.0 / 2;
+        let sub_j = self.first.1 + self.span.1 / 2;
+        (self.pixel.0, self.pixel.1, sub_i * planisphere.subpixel_divisions + sub_j)
+    }
+
+    /// Fractional pixel-grid bounds (left, right, top, bottom) of the quad, "top" being the south
+    /// edge as in `Planisphere::get_altitude_at_subpixel_corners`
+    pub fn pixel_coords(&self, planisphere: &Planisphere) -> (f64, f64, f64, f64) {
+        let (i, j) = self.pixel;
+        let lon_divs = planisphere.get_pixel_lon_subdivisions(i, j) as f64;
+        let divs = planisphere.subpixel_divisions as f64;
+        (
+            i as f64 + self.first.0 as f64 / lon_divs,
+            i as f64 + (self.first.0 + self.span.0) as f64 / lon_divs,
+            j as f64 + self.first.1 as f64 / divs,
+            j as f64 + (self.first.1 + self.span.1) as f64 / divs,
+        )
+    }
+}
+
+impl TerrainLod {
+    /// Merged quads of the rings around `center`, outside the `detailed` subpixels selected within
+    /// `detail_radius` at full resolution. A block becomes one quad when one of its subpixels is in
+    /// the ring and none is meshed yet; blocks cut by an inner ring fall back to the finer merges,
+    /// so the rings meet without gaps or overlaps.
+    pub fn quads(
+        &self,
+        planisphere: &Planisphere,
+        center: (usize, usize, usize),
+        detailed: &[SubpixelCorners],
+        detail_radius: usize,
+        method: DistanceMethod,
+    ) -> Vec<MergedQuad> {
+        crate::profile_span!("lod_quads", block_radius = self.block_radius, pixel_radius = self.pixel_radius);
+        let mut covered: HashSet<(usize, usize, usize)> = detailed.iter().map(|&(i, j, k, _)| (i, j, k)).collect();
+        let mut quads = Vec::new();
+        // Merge factors from the coarsest, per ring: a factor merges factor x factor subpixels
+        let rings = [(self.block_radius, vec![2]), (self.pixel_radius, vec![planisphere.subpixel_divisions, 2])];
+        let mut inner_radius = detail_radius;
+        for (radius, factors) in rings {
+            if radius <= inner_radius {
+                continue;
+            }
+            let ring: Vec<(usize, usize, usize)> = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2, radius, method)
+                .into_iter()
+                .map(|(i, j, k, _)| (i, j, k))
+                .filter(|subpixel| !covered.contains(subpixel))
+                .collect();
+            merge_blocks(planisphere, &ring, &factors, &mut covered, &mut quads);
+            inner_radius = radius;
+        }
+        quads
+    }
+}
+
+/// Cover `subpixels` with quads merging `factors[0]` x `factors[0]` blocks, falling back to the
+/// next factors (then single subpixels) for blocks partly meshed already
+fn merge_blocks(
+    planisphere: &Planisphere,
+    subpixels: &[(usize, usize, usize)],
+    factors: &[usize],
+    covered: &mut HashSet<(usize, usize, usize)>,
+    quads: &mut Vec<MergedQuad>,
+) {
+    let divs = planisphere.subpixel_divisions;
+    let Some((&factor, finer)) = factors.split_first() else {
+        for &(i, j, k) in subpixels {
+            if covered.insert((i, j, k)) {
+                quads.push(MergedQuad::new(planisphere, (i, j), (k / divs, k % divs), (1, 1)));
+            }
+        }
+        return;
+    };
+
+    // Blocks (i, j, block_i, block_j) in order of their first subpixel, for a deterministic mesh
+    let mut blocks: Vec<(_, Vec<_>)> = Vec::new();
+    let mut block_index = HashMap::new();
+    for &(i, j, k) in subpixels {
+        let key = (i, j, (k / divs) / factor, (k % divs) / factor);
+        let index = *block_index.entry(key).or_insert_with(|| {
+            blocks.push((key, Vec::new()));
+            blocks.len() - 1
+        });
+        blocks[index].1.push((i, j, k));
+    }
+
+    for ((i, j, block_i, block_j), members) in blocks {
+        let lon_divs = planisphere.get_pixel_lon_subdivisions(i, j);
+        let first = (block_i * factor, block_j * factor);
+        let span = ((first.0 + factor).min(lon_divs) - first.0, (first.1 + factor).min(divs) - first.1);
+        let block: Vec<(usize, usize, usize)> = (first.0..first.0 + span.0)
+            .flat_map(|sub_i| (first.1..first.1 + span.1).map(move |sub_j| (i, j, sub_i * divs + sub_j)))
+            .collect();
+        if block.iter().all(|subpixel| !covered.contains(subpixel)) {
+            covered.extend(block);
+            quads.push(MergedQuad::new(planisphere, (i, j), first, span));
+        } else {
+            merge_blocks(planisphere, &members, finer, covered, quads);
+        }
+    }
+}
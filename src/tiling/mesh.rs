@@ -0,0 +1,190 @@
+use crate::planisphere;
+use super::lod::MergedQuad;
+use super::texture::select_texture_from_rgba;
+use super::SubpixelCorners;
+
+/// How the terrain vertex heights follow the elevation map
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Relief {
+    pub height_scale: f32,   // World height at altitude 1.0 (the highest point of the map); 0.0 lays the terrain flat
+    pub smooth: bool,        // Bilinear altitude at each subpixel corner, else flat steps at the subpixel center altitude
+}
+
+impl Default for Relief {
+    fn default() -> Self {
+        Self { height_scale: crate::config::terrain::ALTITUDE_SCALE, smooth: true }
+    }
+}
+
+impl Relief {
+    /// World height of a subpixel's corners, in the order of `get_subpixel_corners`
+    pub fn corner_heights(&self, planisphere: &planisphere::Planisphere, i: usize, j: usize, k: usize) -> [f32; 4] {
+        let altis = if self.smooth {
+            planisphere.get_altitude_at_subpixel_corners(i as i32, j as i32, k)
+        } else {
+            [planisphere.get_alti_at_subpixel(i as i32, j as i32, k); 4]
+        };
+        altis.map(|alti| self.height_scale * alti)
+    }
+
+    /// World height of the terrain at a subpixel center
+    pub fn center_height(&self, planisphere: &planisphere::Planisphere, i: usize, j: usize, k: usize) -> f32 {
+        if self.smooth {
+            self.corner_heights(planisphere, i, j, k).iter().sum::<f32>() / 4.0
+        } else {
+            self.height_scale * planisphere.get_alti_at_subpixel(i as i32, j as i32, k)
+        }
+    }
+
+    /// World height of a merged quad's corners, like `corner_heights` over the whole block
+    pub fn quad_corner_heights(&self, planisphere: &planisphere::Planisphere, quad: &MergedQuad) -> [f32; 4] {
+        let (left, right, top, bottom) = quad.pixel_coords(planisphere);
+        let altis = if self.smooth {
+            [(left, top), (right, top), (right, bottom), (left, bottom)].map(|(fi, fj)| planisphere.alti_at_pixel_coords(fi, fj))
+        } else {
+            [planisphere.alti_at_pixel_coords(0.5 * (left + right), 0.5 * (top + bottom)); 4]
+        };
+        altis.map(|alti| self.height_scale * alti)
+    }
+}
+
+/// Vertices, indices, UVs and the subpixel of each triangle of a terrain mesh
+pub type TerrainMeshBuffers = (Vec<[f32; 3]>, Vec<u32>, Vec<[f32; 2]>, Vec<(usize, usize, usize)>);
+
+/// Vertices (four per subpixel, heights from `relief`), indices, UVs and the subpixel of each
+/// triangle of the terrain mesh, projected around `lonlat_gnomocenter`
+pub fn terrain_mesh(
+    planisphere: &planisphere::Planisphere,
+    subpixels: Vec<SubpixelCorners>,
+    lonlat_gnomocenter: (f64, f64),
+    relief: Relief,
+) -> TerrainMeshBuffers {
+    terrain_mesh_with_lod(planisphere, subpixels, &[], lonlat_gnomocenter, relief)
+}
+
+/// `terrain_mesh` followed by the merged quads of the LOD rings, whose triangles map to the
+/// middle subpixel of their block
+pub fn terrain_mesh_with_lod(
+    planisphere: &planisphere::Planisphere,
+    subpixels: Vec<SubpixelCorners>,
+    quads: &[MergedQuad],
+    lonlat_gnomocenter: (f64, f64),
+    relief: Relief,
+) -> TerrainMeshBuffers {
+    crate::profile_span!("terrain_mesh", subpixels = subpixels.len(), quads = quads.len());
+    let mut vertices = Vec::<[f32; 3]>::new();
+    let mut indices = Vec::<u32>::new();
+    let mut uvs = Vec::<[f32; 2]>::new();
+    let mut vertex_index = 0u32;
+    let mut triangle_mapping = Vec::<(usize, usize, usize)>::new();
+    for (_i, _j, _k, _corners) in subpixels.iter() {
+        let (i, j, k) = (*_i, *_j, *_k);
+        let corners = *_corners;
+        let current_pixel_norm_lat = j as f64 / planisphere.height_pixels as f64;
+        let current_latitude = current_pixel_norm_lat * 180.0 - 90.0;
+        let current_lon_subdivisions = (planisphere.subpixel_divisions as f64 * current_latitude.to_radians().cos()).max(1.0) as usize;
+        // Create vertices for this subpixel — each corner gets its own height when smoothed
+        let corner_heights = relief.corner_heights(planisphere, i, j, k);
+        for ((lon, lat), height) in corners.iter().zip(corner_heights.iter()) {
+            let (x, y) = planisphere.geo_to_gnomonic(*lon, *lat, lonlat_gnomocenter.0, lonlat_gnomocenter.1);
+            vertices.push([x as f32, *height, y as f32]);
+        }
+        // Texture selection mode - set to true for RGBA-based, false for border-based
+        let use_rgba_texture_selection = true;
+
+        let tile_index = if use_rgba_texture_selection {
+            // RGBA-based texture selection
+            let (red, green, blue, alpha) = planisphere.get_rgba_at_subpixel(i as i32, j as i32, k);
+            select_texture_from_rgba(red, green, blue, alpha)
+        } else {
+            // Original border-based texture selection
+            let mut tile_index = 5; // default texture
+
+            //north border
+            if k % planisphere.subpixel_divisions == 0 {
+                tile_index = 15; //north
+            }
+
+            //south border
+            if k % planisphere.subpixel_divisions == planisphere.subpixel_divisions - 1 {
+                tile_index = 12;
+            }
+
+            //west border
+            if k / planisphere.subpixel_divisions == 0 {
+                tile_index = 13;
+            }
+
+            //east border
+            if k / planisphere.subpixel_divisions == current_lon_subdivisions - 1 {
+                tile_index = 7;
+            }
+
+            tile_index
+        };
+
+        uvs.extend(tile_uvs(tile_index));
+
+        // Create triangles (two triangles per quad)
+        indices.extend_from_slice(&[
+            vertex_index, vertex_index + 1, vertex_index + 2,
+            vertex_index, vertex_index + 2, vertex_index + 3
+        ]);
+
+        // Map both triangles to this subpixel (i, j, k)
+        triangle_mapping.push((i, j, k)); // Triangle 1
+        triangle_mapping.push((i, j, k)); // Triangle 2
+
+        vertex_index += 4;
+    }
+
+    for quad in quads {
+        let (i, j, k) = quad.subpixel(planisphere);
+        let corner_heights = relief.quad_corner_heights(planisphere, quad);
+        for ((lon, lat), height) in quad.corners.iter().zip(corner_heights.iter()) {
+            let (x, y) = planisphere.geo_to_gnomonic(*lon, *lat, lonlat_gnomocenter.0, lonlat_gnomocenter.1);
+            vertices.push([x as f32, *height, y as f32]);
+        }
+        let (red, green, blue, alpha) = planisphere.get_rgba_at_subpixel(i as i32, j as i32, k);
+        uvs.extend(tile_uvs(select_texture_from_rgba(red, green, blue, alpha)));
+        indices.extend_from_slice(&[
+            vertex_index, vertex_index + 1, vertex_index + 2,
+            vertex_index, vertex_index + 2, vertex_index + 3
+        ]);
+        triangle_mapping.push((i, j, k));
+        triangle_mapping.push((i, j, k));
+        vertex_index += 4;
+    }
+    (vertices, indices, uvs, triangle_mapping)
+}
+
+/// UVs of the four corners of a subpixel quad showing atlas tile `tile_index`
+/// (bottom-left, bottom-right, top-right, top-left)
+pub fn tile_uvs(tile_index: usize) -> [[f32; 2]; 4] {
+    let atlas_size = crate::config::atlas::SIZE;
+    let tile_u = (tile_index % atlas_size) as f32 / atlas_size as f32;
+    let tile_v = (tile_index / atlas_size) as f32 / atlas_size as f32;
+    let tile_size = 1.0 / atlas_size as f32;
+    [
+        [tile_u, tile_v],
+        [tile_u + tile_size, tile_v],
+        [tile_u + tile_size, tile_v + tile_size],
+        [tile_u, tile_v + tile_size],
+    ]
+}
+
+/// UVs of a terrain mesh built from `subpixels` and `quads` by `terrain_mesh_with_lod`, with each
+/// RGBA-selected texture passed through `remap` (e.g. seasonal snow cover). Lets the terrain be
+/// re-textured without rebuilding its geometry.
+pub fn terrain_uvs(
+    planisphere: &planisphere::Planisphere,
+    subpixels: &[SubpixelCorners],
+    quads: &[MergedQuad],
+    remap: impl Fn(usize) -> usize,
+) -> Vec<[f32; 2]> {
+    let quad_subpixels = quads.iter().map(|quad| quad.subpixel(planisphere));
+    subpixels.iter().map(|&(i, j, k, _)| (i, j, k)).chain(quad_subpixels).flat_map(|(i, j, k)| {
+        let (red, green, blue, alpha) = planisphere.get_rgba_at_subpixel(i as i32, j as i32, k);
+        tile_uvs(remap(select_texture_from_rgba(red, green, blue, alpha)))
+    }).collect()
+}
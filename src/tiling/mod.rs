@@ -0,0 +1,21 @@
+//! Terrain tiling math shared by the game's terrain module: which atlas texture a subpixel shows
+//! (biome classification of the map's RGBA channels),
+//! the vertices, indices and UVs of the rendered subpixels and the merged quads of distant rings,
+//! the skirts hanging from the terrain border and the water surface over the sea
+
+pub mod biome;
+pub mod lod;
+pub mod mesh;
+pub mod ocean;
+pub mod skirt;
+pub mod texture;
+
+/// Subpixel `(i, j, k)` with the (longitude, latitude) of its four corners
+pub type SubpixelCorners = (usize, usize, usize, [(f64, f64); 4]);
+
+pub use biome::{Biome, BiomeTable};
+pub use lod::{MergedQuad, TerrainLod};
+pub use mesh::{terrain_mesh, terrain_mesh_with_lod, terrain_uvs, tile_uvs, Relief, TerrainMeshBuffers};
+pub use ocean::{ocean_mesh, Ocean};
+pub use skirt::{skirt_edges, skirt_mesh, skirt_uvs, SkirtEdge};
+pub use texture::{deterministic_random, select_texture_from_rgba, texture_index, texture_name, WorldSeed};
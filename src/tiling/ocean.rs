@@ -0,0 +1,46 @@
+use crate::planisphere::Planisphere;
+use super::lod::MergedQuad;
+use super::SubpixelCorners;
+
+/// Water surface drawn over the sea pixels of the map (`Planisphere::is_sea`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ocean {
+    pub enabled: bool,
+    pub sea_level: f32,   // Altitude of the surface on the 0.0–1.0 scale of the map, world height `sea_level * height_scale`
+}
+
+impl Default for Ocean {
+    fn default() -> Self {
+        Self { enabled: true, sea_level: crate::config::ocean::SEA_LEVEL }
+    }
+}
+
+/// Vertices (four per quad, all at `height`) and indices of the water surface over the sea
+/// `subpixels` and LOD `quads`, projected around `lonlat_gnomocenter` like the terrain mesh
+pub fn ocean_mesh(
+    planisphere: &Planisphere,
+    subpixels: &[SubpixelCorners],
+    quads: &[MergedQuad],
+    lonlat_gnomocenter: (f64, f64),
+    height: f32,
+) -> (Vec<[f32; 3]>, Vec<u32>) {
+    crate::profile_span!("ocean_mesh", subpixels = subpixels.len(), quads = quads.len());
+    let sea_subpixels = subpixels.iter()
+        .filter(|(i, j, _, _)| planisphere.is_sea(*i, *j))
+        .map(|(_, _, _, corners)| corners);
+    let sea_quads = quads.iter()
+        .filter(|quad| planisphere.is_sea(quad.pixel.0, quad.pixel.1))
+        .map(|quad| &quad.corners);
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for corners in sea_subpixels.chain(sea_quads) {
+        let first = vertices.len() as u32;
+        for (lon, lat) in corners {
+            let (x, y) = planisphere.geo_to_gnomonic(*lon, *lat, lonlat_gnomocenter.0, lonlat_gnomocenter.1);
+            vertices.push([x as f32, height, y as f32]);
+        }
+        indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+    }
+    (vertices, indices)
+}
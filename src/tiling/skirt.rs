@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+use crate::planisphere::Planisphere;
+use super::lod::MergedQuad;
+use super::SubpixelCorners;
+
+/// Outer edge of the terrain mesh, where a skirt hangs down to hide the gap to the sky: edge
+/// `edge` (from corner `edge` to the next one) of quad `quad`, counting the subpixels then the
+/// LOD quads in mesh order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkirtEdge {
+    pub quad: usize,
+    pub edge: usize,
+}
+
+/// Edges of the terrain made of `subpixels` and `quads` with no terrain beyond them. Each edge
+/// is probed just past its middle, so edges against coarser or finer neighbours (LOD rings,
+/// latitude rows with fewer subpixels) are not taken for the border.
+pub fn skirt_edges(
+    planisphere: &Planisphere,
+    subpixels: &[SubpixelCorners],
+    quads: &[MergedQuad],
+) -> Vec<SkirtEdge> {
+    crate::profile_span!("skirt_edges", subpixels = subpixels.len(), quads = quads.len());
+    let divs = planisphere.subpixel_divisions;
+    let mut covered: HashSet<(usize, usize, usize)> = subpixels.iter().map(|&(i, j, k, _)| (i, j, k)).collect();
+    for quad in quads {
+        for sub_i in quad.first.0..quad.first.0 + quad.span.0 {
+            for sub_j in quad.first.1..quad.first.1 + quad.span.1 {
+                covered.insert((quad.pixel.0, quad.pixel.1, sub_i * divs + sub_j));
+            }
+        }
+    }
+
+    let all_corners = subpixels.iter().map(|(_, _, _, corners)| corners).chain(quads.iter().map(|quad| &quad.corners));
+    let mut edges = Vec::new();
+    for (quad, corners) in all_corners.enumerate() {
+        let center_lon = corners.iter().map(|corner| corner.0).sum::<f64>() / 4.0;
+        let center_lat = corners.iter().map(|corner| corner.1).sum::<f64>() / 4.0;
+        for edge in 0..4 {
+            let (a, b) = (corners[edge], corners[(edge + 1) % 4]);
+            let (mid_lon, mid_lat) = (0.5 * (a.0 + b.0), 0.5 * (a.1 + b.1));
+            // A tenth of the half quad past the edge middle, away from the quad center
+            let probe_lon = mid_lon + 0.1 * (mid_lon - center_lon);
+            let probe_lat = mid_lat + 0.1 * (mid_lat - center_lat);
+            if !covered.contains(&planisphere.geo_to_subpixel(probe_lon, probe_lat)) {
+                edges.push(SkirtEdge { quad, edge });
+            }
+        }
+    }
+    edges
+}
+
+/// Vertices and indices of the skirts hanging `depth` below the `skirts` edges of a terrain mesh
+/// (`vertices`, four per quad), facing outward. Indices continue after the mesh vertices.
+pub fn skirt_mesh(vertices: &[[f32; 3]], skirts: &[SkirtEdge], depth: f32) -> (Vec<[f32; 3]>, Vec<u32>) {
+    let mut skirt_vertices = Vec::with_capacity(4 * skirts.len());
+    let mut indices = Vec::with_capacity(6 * skirts.len());
+    for skirt in skirts {
+        let quad = &vertices[4 * skirt.quad..4 * skirt.quad + 4];
+        let (a, b) = (quad[skirt.edge], quad[(skirt.edge + 1) % 4]);
+        let first = (vertices.len() + skirt_vertices.len()) as u32;
+        skirt_vertices.extend_from_slice(&[a, b, [b[0], b[1] - depth, b[2]], [a[0], a[1] - depth, a[2]]]);
+
+        // Wind the strip so its normal points away from the quad center
+        let center_x = quad.iter().map(|vertex| vertex[0]).sum::<f32>() / 4.0;
+        let center_z = quad.iter().map(|vertex| vertex[2]).sum::<f32>() / 4.0;
+        let outward = (0.5 * (a[0] + b[0]) - center_x, 0.5 * (a[2] + b[2]) - center_z);
+        // The triangle (a, b, b lowered) has the normal depth * (dz, 0, -dx)
+        let (dx, dz) = (b[0] - a[0], b[2] - a[2]);
+        if dz * outward.0 - dx * outward.1 >= 0.0 {
+            indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+        } else {
+            indices.extend_from_slice(&[first, first + 2, first + 1, first, first + 3, first + 2]);
+        }
+    }
+    (skirt_vertices, indices)
+}
+
+/// UVs of the skirt vertices built by `skirt_mesh`: each strip shows the tile of its quad, taken
+/// from the mesh `uvs`, from the edge down to the opposite edge
+pub fn skirt_uvs(uvs: &[[f32; 2]], skirts: &[SkirtEdge]) -> Vec<[f32; 2]> {
+    skirts.iter().flat_map(|skirt| {
+        let quad = &uvs[4 * skirt.quad..4 * skirt.quad + 4];
+        [0, 1, 2, 3].map(|corner| quad[(skirt.edge + corner) % 4])
+    }).collect()
+}
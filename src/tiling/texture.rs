@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Seed installed with `WorldSeed::install`, read by `deterministic_random`
+static WORLD_SEED: AtomicU64 = AtomicU64::new(0);
+
+/// Seed of the procedural world (landscape scatter, item placement, instance variations): the
+/// same seed and map always give the same world. 0 is the default world.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WorldSeed(pub u64);
+
+#[cfg(feature = "game")]
+impl bevy::prelude::Resource for WorldSeed {}
+
+impl WorldSeed {
+    /// Seed currently used by `deterministic_random`
+    pub fn current() -> Self {
+        Self(WORLD_SEED.load(Ordering::Relaxed))
+    }
+
+    /// Make this the seed of `deterministic_random`, for code without access to the resource
+    /// (terrain build tasks, template spawning)
+    pub fn install(self) {
+        WORLD_SEED.store(self.0, Ordering::Relaxed);
+    }
+
+    /// Deterministic random value (0.0 to 1.0) for (i,j,k) coordinates under this seed.
+    /// This ensures consistent landscape element placement across terrain regenerations
+    pub fn random(self, i: usize, j: usize, k: usize) -> f64 {
+        // Improved hash function with better mixing to avoid patterns
+        // Based on xxHash and other high-quality hash functions
+
+        // Convert coordinates to u64 for better mixing
+        let mut hash = self.0.wrapping_mul(0xD6E8FEB86659FD93); // Zero for seed 0
+        hash ^= (i as u64).wrapping_mul(0x9E3779B185EBCA87); // Large prime
+        hash ^= (j as u64).wrapping_mul(0xC2B2AE3D27D4EB4F);      // Another large prime
+        hash ^= (k as u64).wrapping_mul(0x165667B19E3779F9);      // Another large prime
+
+        // Additional mixing steps to break patterns
+        hash ^= hash >> 27;
+        hash = hash.wrapping_mul(0x3C79AC492BA7B653);
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(0x1C69B3F74AC4AE35);
+        hash ^= hash >> 27;
+
+        // Convert to 0.0-1.0 range
+        (hash as f64) / (u64::MAX as f64)
+    }
+
+    /// Sparse placement hash in 0..1000 of subpixel (i,j,k); the seed 0 keeps the default world
+    pub fn placement_hash(self, i: usize, j: usize, k: usize) -> usize {
+        let seed_mix = self.0.wrapping_mul(0x9E3779B97F4A7C15) as usize;
+        ((i * 8191) ^ (j * 6367) ^ (k * 5273) ^ seed_mix) % 1000
+    }
+}
+
+/// Generate a deterministic random value (0.0 to 1.0) based on (i,j,k) coordinates and the
+/// installed world seed (`WorldSeed::install`)
+pub fn deterministic_random(i: usize, j: usize, k: usize) -> f64 {
+    WorldSeed::current().random(i, j, k)
+}
+
+/// Names of the atlas textures, by index (texture_atlas.png tiles in file-name order)
+pub const TEXTURE_NAMES: [&str; 19] = [
+    "deep water", "dirt", "dry grass", "east grass", "grass",
+    "green stone", "ice", "lava", "lava stone", "moss",
+    "mossy stone", "north grass", "paved stone", "raw stone", "sand",
+    "snow", "south grass", "water", "west grass",
+];
+
+/// Display name of an atlas texture index
+pub fn texture_name(index: usize) -> &'static str {
+    TEXTURE_NAMES.get(index).copied().unwrap_or("unknown")
+}
+
+/// Atlas texture index of a display name, e.g. "dry grass" -> 2
+pub fn texture_index(name: &str) -> Option<usize> {
+    TEXTURE_NAMES.iter().position(|texture| *texture == name)
+}
+
+/// Select texture atlas tile index based on RGBA color values from geographic map data
+///
+/// This is the core texture selection function that determines which texture from the
+/// 16x16 texture atlas (256 total textures) should be applied to each terrain subpixel.
+/// The selection is based on RGBA color data extracted from sphere_texture.png.
+///
+/// # How It Works:
+/// 1. Each pixel in sphere_texture.png represents a geographic location
+/// 2. Its RGBA values give the elevation (`rgba_to_alti`), moisture (green) and temperature (blue)
+/// 3. The installed biome table (`BiomeTable::install`, loaded from assets/textures/biomes.ron)
+///    picks the texture of the first biome whose ranges contain these values
+/// 4. Without a table, or when no biome matches, the elevation alone picks the texture
+///    (`elevation_texture`)
+///
+/// # Parameters
+/// * `red`, `green`, `blue`, `alpha` - Channel values (0.0 to 1.0) of the corresponding map pixel
+///
+/// # Returns
+/// Texture atlas tile index, one of `TEXTURE_NAMES`
+pub fn select_texture_from_rgba(red: f64, green: f64, blue: f64, alpha: f64) -> usize {
+    let alti = crate::planisphere::sampling::rgba_to_alti(red, green, blue, alpha);
+    super::biome::BiomeTable::installed()
+        .and_then(|table| table.classify(alti as f64, green, blue))
+        .unwrap_or_else(|| elevation_texture(alti))
+}
+
+/// Texture of the elevation bands used without biome table: ten 0.1 bands mapped to textures 0-9
+pub fn elevation_texture(alti: f32) -> usize {
+    ((alti * 10.0).max(0.0) as usize).min(9)
+}
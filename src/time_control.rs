@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::notifications::Notify;
+use crate::ui::DebugPanel;
+
+/// Speeds `[` and `]` step through; 0.0 is paused
+pub const SPEEDS: [f32; 4] = [0.0, 0.25, 1.0, 4.0];
+
+/// Simulation speed relative to real time, applied to the virtual clock. Everything reading
+/// `Time` follows it: movement, stats and physics (the fixed timestep runs more or fewer steps),
+/// daylight, weather and objects. The camera, UI animations and music read `Time<Real>` and keep
+/// running while paused.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct TimeScale {
+    pub scale: f32,
+    resume: f32,   // Speed restored when unpausing
+}
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self { scale: 1.0, resume: 1.0 }
+    }
+}
+
+impl TimeScale {
+    /// Set the speed, up to `config::time_control::MAX_SCALE`; 0.0 pauses
+    pub fn set(&mut self, scale: f32) {
+        self.scale = scale.clamp(0.0, crate::config::time_control::MAX_SCALE);
+        if self.scale > 0.0 {
+            self.resume = self.scale;
+        }
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.scale = if self.scale > 0.0 { 0.0 } else { self.resume };
+    }
+
+    /// e.g. "Paused", "0.25×"
+    pub fn label(&self) -> String {
+        if self.scale > 0.0 { format!("{}×", self.scale) } else { "Paused".to_string() }
+    }
+}
+
+/// `[` slows the simulation down and `]` speeds it up through `SPEEDS`, Pause toggles the pause
+pub fn handle_time_keys(keyboard_input: Res<ButtonInput<KeyCode>>, mut time_scale: ResMut<TimeScale>) {
+    let scale = time_scale.scale;
+    if keyboard_input.just_pressed(KeyCode::Pause) {
+        time_scale.toggle_pause();
+    } else if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        let slower = SPEEDS.iter().rev().find(|speed| **speed < scale).copied().unwrap_or(SPEEDS[0]);
+        time_scale.set(slower);
+    } else if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        let faster = SPEEDS.iter().find(|speed| **speed > scale).copied().unwrap_or(SPEEDS[SPEEDS.len() - 1]);
+        time_scale.set(faster);
+    }
+}
+
+/// Apply the speed to the virtual clock when it changes, show it in the debug panel and notify it
+pub fn apply_time_scale(
+    time_scale: Res<TimeScale>,
+    mut time: ResMut<Time<Virtual>>,
+    mut debug_panel: ResMut<DebugPanel>,
+    mut notify: EventWriter<Notify>,
+) {
+    if !time_scale.is_changed() {
+        return;
+    }
+    if time_scale.scale > 0.0 {
+        time.set_relative_speed(time_scale.scale);
+        time.unpause();
+    } else {
+        time.pause();
+    }
+    debug_panel.set("Speed", time_scale.label());
+    if !time_scale.is_added() {
+        notify.write(Notify::new(format!("Simulation speed: {}", time_scale.label())));
+    }
+}
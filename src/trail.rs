@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use bevy::prelude::*;
+
+use crate::beacons::{spawn_beacon, BeaconAnchor, BeaconKind, BeaconPool};
+use crate::game_object::ObjectTemplates;
+use crate::planisphere::Planisphere;
+use crate::terrain::{ijk_to_world, TerrainCenter};
+
+/// Template of the small beacons marking the tiles of a trail
+pub const TRAIL_TEMPLATE: &str = "trail";
+/// Height of the ribbon above the tile centers
+const RIBBON_HEIGHT: f32 = 0.3;
+/// Samples per tile along polylines, so consecutive tiles touch
+const POLYLINE_SAMPLES_PER_TILE: f64 = 2.0;
+/// Most samples along one polyline segment
+const MAX_SEGMENT_SAMPLES: usize = 2048;
+
+/// Ordered tiles drawn as a trail: a marker beacon on every tile and a ribbon joining them.
+/// Any entity can carry one (agent planned path, measuring tool, route to a waypoint); changing
+/// it redraws the trail, removing it or despawning its entity clears the trail.
+#[derive(Component, Debug, Clone)]
+pub struct PathTrail {
+    pub tiles: Vec<(usize, usize, usize)>,
+    pub color: Color,
+}
+
+impl PathTrail {
+    pub fn new(tiles: Vec<(usize, usize, usize)>, color: Color) -> Self {
+        Self { tiles, color }
+    }
+}
+
+/// Marker beacons of every trail, by the entity carrying the `PathTrail`
+#[derive(Resource, Default)]
+pub struct TrailMarkers {
+    by_owner: HashMap<Entity, Vec<Entity>>,
+}
+
+/// Tiles along the great circle from `from` to `to`, sampled at `count` points, without
+/// consecutive repeats
+pub fn great_circle_tiles(planisphere: &Planisphere, from: (f64, f64), to: (f64, f64), count: usize) -> Vec<(usize, usize, usize)> {
+    let mut tiles: Vec<(usize, usize, usize)> = planisphere.great_circle_points(from, to, count).into_iter()
+        .map(|(lon, lat)| planisphere.geo_to_subpixel(lon, lat))
+        .collect();
+    tiles.dedup();
+    tiles
+}
+
+/// Tiles of a polyline (imported lines and tracks), along the great circles between its positions
+pub fn polyline_tiles(planisphere: &Planisphere, points: &[(f64, f64)]) -> Vec<(usize, usize, usize)> {
+    let mut tiles = Vec::new();
+    for segment in points.windows(2) {
+        let samples = (planisphere.geodesic_distance(segment[0], segment[1]) / planisphere.mean_tile_size * POLYLINE_SAMPLES_PER_TILE)
+            .ceil() as usize;
+        tiles.extend(great_circle_tiles(planisphere, segment[0], segment[1], samples.clamp(2, MAX_SEGMENT_SAMPLES)));
+    }
+    tiles.dedup();
+    tiles
+}
+
+/// Whether a tile is close enough to the terrain center to be projected; the gnomonic
+/// projection diverges toward 90° from it
+fn within_horizon(planisphere: &Planisphere, terrain_center: &TerrainCenter, (i, j, k): (usize, usize, usize)) -> bool {
+    let horizon = planisphere.radius * std::f64::consts::FRAC_PI_2 * 0.9;
+    let center = (terrain_center.longitude, terrain_center.latitude);
+    planisphere.geodesic_distance(center, planisphere.subpixel_to_geo(i, j, k)) < horizon
+}
+
+/// Respawn the markers of new and changed trails and drop those of removed trails. Markers are
+/// anchored to their tile, so they follow terrain recentring (`beacons::reanchor_beacons`).
+#[allow(clippy::too_many_arguments)]
+pub fn sync_path_trails(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut markers: ResMut<TrailMarkers>,
+    mut beacon_pool: ResMut<BeaconPool>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    object_templates: Res<ObjectTemplates>,
+    changed: Query<(Entity, &PathTrail), Changed<PathTrail>>,
+    mut removed: RemovedComponents<PathTrail>,
+) {
+    let markers = markers.as_mut();
+    let stale: Vec<Entity> = removed.read().chain(changed.iter().map(|(owner, _)| owner)).collect();
+    for owner in stale {
+        for marker in markers.by_owner.remove(&owner).unwrap_or_default() {
+            commands.entity(marker).try_despawn(); // May already be culled by the beacon pool
+        }
+    }
+
+    for (owner, trail) in changed.iter() {
+        let mut spawned = Vec::with_capacity(trail.tiles.len());
+        for &tile in trail.tiles.iter().filter(|tile| within_horizon(&planisphere, &terrain_center, **tile)) {
+            match spawn_beacon(&mut commands, &mut beacon_pool, &mut materials, &planisphere, &terrain_center, &object_templates,
+                               TRAIL_TEMPLATE, BeaconAnchor::Tile(tile), BeaconKind::Trail) {
+                Ok(entity) => spawned.push(entity),
+                Err(e) => {
+                    warn!("Cannot draw trail markers: {}", e);
+                    break;
+                }
+            }
+        }
+        markers.by_owner.insert(owner, spawned);
+    }
+}
+
+/// Draw the ribbon of every trail through its tile centers, broken where it leaves the horizon
+pub fn draw_path_trails(
+    mut gizmos: Gizmos,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    trails: Query<&PathTrail>,
+) {
+    for trail in trails.iter() {
+        let mut segment: Vec<Vec3> = Vec::new();
+        for &(i, j, k) in trail.tiles.iter() {
+            if within_horizon(&planisphere, &terrain_center, (i, j, k)) {
+                segment.push(ijk_to_world(i as i32, j as i32, k as i32, &planisphere, &terrain_center) + Vec3::Y * RIBBON_HEIGHT);
+            } else if !segment.is_empty() {
+                gizmos.linestrip(std::mem::take(&mut segment), trail.color);
+            }
+        }
+        if segment.len() > 1 {
+            gizmos.linestrip(segment, trail.color);
+        }
+    }
+}
@@ -2,102 +2,164 @@ use bevy::prelude::*;
 use crate::planisphere::{self, DistanceMethod};
 use crate::player::Player;
 use crate::game_object::EntitySubpixelPosition;
-use crate::terrain::TerrainCenter;
 
 // ── Marker components ────────────────────────────────────────────────────────
 
 #[derive(Component)]
-pub struct CoordinateDisplay;
+pub struct DebugPanelText;
+
+/// Info box under the crosshair describing what the cursor hovers
+#[derive(Component)]
+pub struct HoverInfoText;
 
 /// Attached to each method button so the handler knows which method it represents.
 #[derive(Component, Clone, Copy)]
 pub struct MethodButton(pub DistanceMethod);
 
+// ── Debug panel ──────────────────────────────────────────────────────────────
+
+/// Named lines shown in the top-left debug panel, in registration order.
+/// Any system can publish its state: `debug_panel.set("Terrain", format!("{} subpixels", n))`.
+#[derive(Resource, Default, Debug)]
+pub struct DebugPanel {
+    lines: Vec<(String, String)>,
+}
+
+impl DebugPanel {
+    /// Add or replace a line
+    pub fn set(&mut self, name: &str, value: impl Into<String>) {
+        let value = value.into();
+        match self.lines.iter_mut().find(|(line, _)| line == name) {
+            Some((_, current)) => *current = value,
+            None => self.lines.push((name.to_string(), value)),
+        }
+    }
+
+    /// Remove a line, e.g. once the state it describes is gone
+    pub fn remove(&mut self, name: &str) {
+        self.lines.retain(|(line, _)| line != name);
+    }
+}
+
 // ── Setup ────────────────────────────────────────────────────────────────────
 
 pub fn setup_ui(mut commands: Commands) {
-    // --- coordinate info panel (top-left) ---
-    commands.spawn((
-        Node {
-            position_type: PositionType::Absolute,
-            left: Val::Px(10.0),
-            top: Val::Px(10.0),
-            padding: UiRect::all(Val::Px(10.0)),
-            ..default()
-        },
-        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
-    )).with_children(|panel| {
-        panel.spawn((
-            Text::new(""),
-            TextFont { font_size: 14.0, ..default() },
-            TextColor(Color::WHITE),
-            CoordinateDisplay,
-        ));
+    // Left column: debug panel with the distance method selector below it
+    commands.spawn(Node {
+        position_type: PositionType::Absolute,
+        left: Val::Px(10.0),
+        top: Val::Px(10.0),
+        flex_direction: FlexDirection::Column,
+        align_items: AlignItems::FlexStart,
+        row_gap: Val::Px(10.0),
+        ..default()
+    }).with_children(|column| {
+        // --- debug info panel ---
+        column.spawn((
+            Node { padding: UiRect::all(Val::Px(10.0)), ..default() },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        )).with_children(|panel| {
+            panel.spawn((
+                Text::new(""),
+                TextFont { font_size: 14.0, ..default() },
+                TextColor(Color::WHITE),
+                DebugPanelText,
+            ));
+        });
+
+        // --- distance method selector ---
+        column.spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        )).with_children(|panel| {
+            panel.spawn((
+                Text::new("Distance method:"),
+                TextFont { font_size: 12.0, ..default() },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+            for method in DistanceMethod::ALL {
+                panel.spawn((
+                    Button,
+                    Node { padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)), ..default() },
+                    BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9)),
+                    MethodButton(method),
+                )).with_children(|btn| {
+                    btn.spawn((
+                        Text::new(method.label()),
+                        TextFont { font_size: 13.0, ..default() },
+                        TextColor(Color::WHITE),
+                    ));
+                });
+            }
+        });
     });
+}
 
-    // --- distance method selector (top-left, below the info panel) ---
-    commands.spawn((
-        Node {
-            position_type: PositionType::Absolute,
-            left: Val::Px(10.0),
-            top: Val::Px(110.0),
-            flex_direction: FlexDirection::Column,
-            row_gap: Val::Px(4.0),
-            padding: UiRect::all(Val::Px(8.0)),
-            ..default()
-        },
-        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
-    )).with_children(|panel| {
-        panel.spawn((
-            Text::new("Distance method:"),
-            TextFont { font_size: 12.0, ..default() },
-            TextColor(Color::srgb(0.7, 0.7, 0.7)),
+/// Center-screen crosshair with the hover info box below it
+pub fn setup_crosshair(mut commands: Commands) {
+    commands.spawn(Node {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }).with_children(|screen| {
+        // Crosshair: two thin bars crossing at the center
+        screen.spawn((
+            Node { position_type: PositionType::Absolute, width: Val::Px(16.0), height: Val::Px(2.0), ..default() },
+            BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.8)),
         ));
-        for (label, method) in [
-            ("Chebyshev (square)",  DistanceMethod::Chebyshev),
-            ("Euclidean (circle)",  DistanceMethod::Euclidean),
-            ("Manhattan (diamond)", DistanceMethod::Manhattan),
-        ] {
-            panel.spawn((
-                Button,
-                Node { padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)), ..default() },
-                BackgroundColor(Color::srgba(0.2, 0.2, 0.2, 0.9)),
-                MethodButton(method),
-            )).with_children(|btn| {
-                btn.spawn((
-                    Text::new(label),
-                    TextFont { font_size: 13.0, ..default() },
-                    TextColor(Color::WHITE),
-                ));
-            });
-        }
+        screen.spawn((
+            Node { position_type: PositionType::Absolute, width: Val::Px(2.0), height: Val::Px(16.0), ..default() },
+            BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.8)),
+        ));
+        screen.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Percent(50.0),
+                margin: UiRect::top(Val::Px(20.0)),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        )).with_children(|info| {
+            info.spawn((
+                Text::new(""),
+                TextFont { font_size: 12.0, ..default() },
+                TextColor(Color::WHITE),
+                HoverInfoText,
+            ));
+        });
     });
 }
 
 // ── Systems ───────────────────────────────────────────────────────────────────
 
-/// Handles clicks on the distance-method buttons and triggers terrain recreation.
+/// Handles clicks on the distance-method buttons; the terrain is rebuilt by `terrain::apply_terrain_view`.
 pub fn handle_method_buttons(
     interaction_query: Query<(&Interaction, &MethodButton), Changed<Interaction>>,
-    mut terrain_center: ResMut<TerrainCenter>,
+    mut terrain_config: ResMut<crate::TerrainConfig>,
 ) {
     for (interaction, btn) in &interaction_query {
-        if *interaction == Interaction::Pressed {
-            if terrain_center.distance_method != btn.0 {
-                terrain_center.distance_method = btn.0;
-                terrain_center.force_recreation = true;
-            }
+        if *interaction == Interaction::Pressed && terrain_config.distance_method != btn.0 {
+            terrain_config.distance_method = btn.0;
         }
     }
 }
 
 /// Colours buttons to show which method is active and highlights hovered ones.
 pub fn update_method_button_colors(
-    terrain_center: Res<TerrainCenter>,
+    terrain_config: Res<crate::TerrainConfig>,
     mut button_query: Query<(&Interaction, &MethodButton, &mut BackgroundColor)>,
 ) {
     for (interaction, btn, mut bg) in &mut button_query {
-        *bg = if btn.0 == terrain_center.distance_method {
+        *bg = if btn.0 == terrain_config.distance_method {
             // Active method — bright highlight
             BackgroundColor(Color::srgb(0.1, 0.5, 0.9))
         } else if *interaction == Interaction::Hovered {
@@ -108,21 +170,85 @@ pub fn update_method_button_colors(
     }
 }
 
-/// Updates the coordinate text with current player position.
+/// Publishes the player and cursor position to the debug panel.
 pub fn update_coordinate_display(
     player_query: Query<(&Transform, &EntitySubpixelPosition), With<Player>>,
-    mut text_query: Query<&mut Text, With<CoordinateDisplay>>,
+    cursor: Res<crate::player::CursorTarget>,
+    mut debug_panel: ResMut<DebugPanel>,
     planisphere: Res<planisphere::Planisphere>,
-    terrain_center: Res<TerrainCenter>,
 ) {
     let Ok((transform, ijkpos)) = player_query.single() else { return; };
-    let Ok(mut text) = text_query.single_mut() else { return; };
 
     let (lon, lat) = planisphere.subpixel_to_geo(ijkpos.subpixel.0, ijkpos.subpixel.1, ijkpos.subpixel.2);
     let (i, j, k) = ijkpos.subpixel;
     let Vec3 { x, y, z } = transform.translation;
 
+    debug_panel.set("World", format!("({x:.2}, {y:.2}, {z:.2})"));
+    debug_panel.set("Geo", format!("({lon:.6}°, {lat:.6}°)"));
+    debug_panel.set("Tile", format!("({i}, {j}, {k})"));
+    match cursor.tile {
+        Some((ci, cj, ck)) => debug_panel.set("Cursor", format!("({ci}, {cj}, {ck})")),
+        None => debug_panel.remove("Cursor"),
+    }
+}
+
+/// Renders the registered debug lines.
+pub fn update_debug_panel(
+    debug_panel: Res<DebugPanel>,
+    mut text_query: Query<&mut Text, With<DebugPanelText>>,
+) {
+    if !debug_panel.is_changed() {
+        return;
+    }
+    let Ok(mut text) = text_query.single_mut() else { return; };
+    **text = debug_panel.lines.iter()
+        .map(|(name, value)| format!("{name}: {value}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+}
+
+/// Describes the hovered tile (indices, biome, elevation) and the object under the cursor.
+#[allow(clippy::too_many_arguments)]
+pub fn update_hover_info(
+    cursor: Res<crate::player::CursorTarget>,
+    planisphere: Res<planisphere::Planisphere>,
+    spawn_config: Res<crate::populate::SpawnConfig>,
+    parents: Query<&ChildOf>,
+    objects: Query<&crate::game_object::ObjectDefinition>,
+    tiles: Query<(), With<crate::terrain::Tile>>,
+    mut text_query: Query<(&mut Text, &ChildOf), With<HoverInfoText>>,
+    mut box_query: Query<&mut Visibility>,
+) {
+    if !cursor.is_changed() {
+        return;
+    }
+    let Ok((mut text, info_box)) = text_query.single_mut() else { return; };
+    let Ok(mut visibility) = box_query.get_mut(info_box.parent()) else { return; };
+    let Some((i, j, k)) = cursor.tile else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+
+    let (red, green, blue, alpha) = planisphere.get_rgba_at_subpixel(i as i32, j as i32, k);
+    let texture = crate::terrain::select_texture_from_rgba(red, green, blue, alpha);
+    let biome = spawn_config.biomes.iter().find(|biome| biome.textures.contains(&texture))
+        .map_or("-", |biome| biome.name.as_str());
+    let elevation = planisphere.get_alti_at_subpixel(i as i32, j as i32, k);
+    let height = cursor.hit_point.map_or(0.0, |point| point.y);
+
+    // Colliders sit on the object or on one of its scene children
+    let hovered = cursor.entity.map(|entity| {
+        if tiles.contains(entity) {
+            return "terrain".to_string();
+        }
+        std::iter::once(entity).chain(parents.iter_ancestors(entity))
+            .find_map(|entity| objects.get(entity).ok())
+            .map_or(format!("{entity}"), |definition| definition.object_type.clone())
+    }).unwrap_or_else(|| "-".to_string());
+
     **text = format!(
-        "World: ({x:.2}, {y:.2}, {z:.2})\nGeo: ({lon:.6}°, {lat:.6}°)\nTile: ({i}, {j}, {k})"
+        "Tile: ({i}, {j}, {k})\nTexture: {} ({biome})\nElevation: {elevation:.3} (y {height:.1})\nObject: {hovered}",
+        crate::terrain::texture_name(texture)
     );
 }
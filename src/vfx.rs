@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+/// Visual effect played when an object appears or disappears
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub enum Effect {
+    ScalePop { duration: f32 },                 // Grow from / shrink to nothing
+    Particles { count: u32, duration: f32 },   // Burst of small fading particles
+}
+
+/// Effects declared by a template, e.g. `(appear: Some(ScalePop(duration: 0.3)), disappear: None)`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct TemplateEffects {
+    #[serde(default)]
+    pub appear: Option<Effect>,
+    #[serde(default)]
+    pub disappear: Option<Effect>,
+}
+
+/// Effect to play once when the entity is spawned (consumed by `start_appear_effects`)
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AppearEffect(pub Effect);
+
+/// Effect to play when the entity is removed through `request_despawn`
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DisappearEffect(pub Effect);
+
+/// Marks an entity that should disappear (with its `DisappearEffect` if any)
+#[derive(Component)]
+pub struct DespawnRequested;
+
+/// Scale interpolation, optionally despawning the entity at the end
+#[derive(Component, Debug)]
+pub struct ScaleAnimation {
+    pub timer: Timer,
+    pub from: Vec3,
+    pub to: Vec3,
+    pub despawn_when_done: bool,
+}
+
+/// One particle of a burst, moved without physics and shrunk until it expires
+#[derive(Component, Debug)]
+pub struct Particle {
+    pub velocity: Vec3,
+    pub timer: Timer,
+}
+
+/// Mesh and material shared by every particle
+#[derive(Resource)]
+pub struct VfxAssets {
+    particle_mesh: Handle<Mesh>,
+    particle_material: Handle<StandardMaterial>,
+}
+
+impl FromWorld for VfxAssets {
+    fn from_world(world: &mut World) -> Self {
+        let particle_mesh = world.resource_mut::<Assets<Mesh>>().add(Sphere::new(0.08));
+        let particle_material = world.resource_mut::<Assets<StandardMaterial>>().add(StandardMaterial {
+            base_color: Color::srgb(1.0, 1.0, 0.8),
+            emissive: Color::srgb(0.6, 0.6, 0.4).into(),
+            ..default()
+        });
+        Self { particle_mesh, particle_material }
+    }
+}
+
+/// Remove an entity, letting its `DisappearEffect` play first
+pub fn request_despawn(commands: &mut Commands, entity: Entity) {
+    commands.entity(entity).insert(DespawnRequested);
+}
+
+/// Start the appear effect of newly spawned entities
+pub fn start_appear_effects(
+    mut commands: Commands,
+    vfx_assets: Res<VfxAssets>,
+    mut query: Query<(Entity, &AppearEffect, &mut Transform)>,
+) {
+    for (entity, effect, mut transform) in query.iter_mut() {
+        commands.entity(entity).remove::<AppearEffect>();
+        match effect.0 {
+            Effect::ScalePop { duration } => {
+                let target = transform.scale;
+                transform.scale = Vec3::ZERO;
+                commands.entity(entity).insert(ScaleAnimation {
+                    timer: Timer::from_seconds(duration, TimerMode::Once),
+                    from: Vec3::ZERO,
+                    to: target,
+                    despawn_when_done: false,
+                });
+            }
+            Effect::Particles { count, duration } => {
+                spawn_particle_burst(&mut commands, &vfx_assets, transform.translation, count, duration);
+            }
+        }
+    }
+}
+
+/// Play the disappear effect of entities marked with `DespawnRequested`, or despawn them directly
+pub fn process_despawn_requests(
+    mut commands: Commands,
+    vfx_assets: Res<VfxAssets>,
+    query: Query<(Entity, &Transform, Option<&DisappearEffect>), Added<DespawnRequested>>,
+) {
+    for (entity, transform, effect) in query.iter() {
+        match effect.map(|effect| effect.0) {
+            Some(Effect::ScalePop { duration }) => {
+                // Stop interacting while shrinking
+                commands.entity(entity).remove::<Collider>().insert(ScaleAnimation {
+                    timer: Timer::from_seconds(duration, TimerMode::Once),
+                    from: transform.scale,
+                    to: Vec3::ZERO,
+                    despawn_when_done: true,
+                });
+            }
+            Some(Effect::Particles { count, duration }) => {
+                spawn_particle_burst(&mut commands, &vfx_assets, transform.translation, count, duration);
+                commands.entity(entity).despawn();
+            }
+            None => {
+                commands.entity(entity).despawn();
+            }
+        }
+    }
+}
+
+/// Advance scale animations
+pub fn animate_scale(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut ScaleAnimation, &mut Transform)>,
+) {
+    for (entity, mut animation, mut transform) in query.iter_mut() {
+        animation.timer.tick(time.delta());
+        // Ease-out so the pop feels snappy
+        let t = animation.timer.fraction();
+        let eased = 1.0 - (1.0 - t) * (1.0 - t);
+        transform.scale = animation.from.lerp(animation.to, eased);
+        if animation.timer.finished() {
+            if animation.despawn_when_done {
+                commands.entity(entity).despawn();
+            } else {
+                commands.entity(entity).remove::<ScaleAnimation>();
+            }
+        }
+    }
+}
+
+/// Move, shrink and expire particles
+pub fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut Particle, &mut Transform)>,
+) {
+    let dt = time.delta_secs();
+    for (entity, mut particle, mut transform) in query.iter_mut() {
+        particle.timer.tick(time.delta());
+        particle.velocity.y -= 9.81 * dt;
+        transform.translation += particle.velocity * dt;
+        transform.scale = Vec3::splat(1.0 - particle.timer.fraction());
+        if particle.timer.finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn spawn_particle_burst(
+    commands: &mut Commands,
+    vfx_assets: &VfxAssets,
+    position: Vec3,
+    count: u32,
+    duration: f32,
+) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..count {
+        let velocity = Vec3::new(rng.gen_range(-2.0..2.0), rng.gen_range(2.0..4.0), rng.gen_range(-2.0..2.0));
+        commands.spawn((
+            Mesh3d(vfx_assets.particle_mesh.clone()),
+            MeshMaterial3d(vfx_assets.particle_material.clone()),
+            Transform::from_translation(position),
+            Particle { velocity, timer: Timer::from_seconds(duration, TimerMode::Once) },
+        ));
+    }
+}
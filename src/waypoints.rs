@@ -0,0 +1,536 @@
+use std::collections::{HashMap, HashSet};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::beacons::{spawn_beacon, BeaconAnchor, BeaconKind, BeaconPool};
+use crate::game_object::{EntitySubpixelPosition, ObjectTemplates};
+use crate::gpx::{GpxTracks, TRACK_COLOR};
+use crate::message_log::{LogCategory, LogMessage};
+use crate::minimap::MinimapMarker;
+use crate::notifications::Notify;
+use crate::planisphere::Planisphere;
+use crate::player::{CursorTarget, Player};
+use crate::terrain::{world_to_geo, TerrainCenter};
+use crate::trail::{great_circle_tiles, PathTrail};
+
+/// File in the save directory where placed waypoints are stored between sessions
+pub const WAYPOINTS_SAVE_FILE: &str = "waypoints.json";
+/// Template of the beacon rendered at each waypoint
+pub const WAYPOINT_TEMPLATE: &str = "waypoint";
+/// Minimap and compass color of waypoint beacons
+const WAYPOINT_COLOR: Color = Color::srgb(0.2, 0.9, 0.9);
+
+/// Named point of interest. Active waypoints outside the viewport get a screen-edge indicator.
+#[derive(Component, Debug, Clone)]
+pub struct Waypoint {
+    pub name: String,
+    pub active: bool,
+}
+
+/// Beacon of a waypoint placed by the player, backed by a `WaypointRecord`
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PlacedWaypoint;
+
+/// Serialized waypoint, stored in geographic coordinates so it does not depend on the terrain center
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WaypointRecord {
+    pub id: u64,
+    pub name: String,
+    pub lon: f64,   // Degrees
+    pub lat: f64,   // Degrees
+    pub active: bool,
+}
+
+/// On-disk layout of the waypoints file
+#[derive(Serialize, Deserialize, Default)]
+struct WaypointsFile {
+    next_id: u64,
+    waypoints: Vec<WaypointRecord>,
+}
+
+/// Player-placed waypoints in placement order, whether or not their beacon is spawned
+#[derive(Resource, Default)]
+pub struct Waypoints {
+    pub records: Vec<WaypointRecord>,
+    pub route: Option<u64>,                          // Waypoint the player is routed to, not saved
+    next_id: u64,
+    live: HashMap<u64, Entity>,                      // Record id -> beacon entity
+    path: String,                                    // File the waypoints were loaded from and are saved to
+    dirty: bool,                                     // Records changed since the last save
+}
+
+impl Waypoints {
+    /// Load waypoints from `path`, starting empty if the file is missing or unreadable
+    pub fn load(path: &str) -> Self {
+        let mut waypoints = Self { path: path.to_string(), ..default() };
+        let Ok(content) = crate::storage::read_to_string(path) else { return waypoints; };
+        match serde_json::from_str::<WaypointsFile>(&content) {
+            Ok(file) => {
+                waypoints.next_id = file.next_id;
+                waypoints.records = file.waypoints;
+                info!("Loaded {} waypoints from {}", waypoints.records.len(), path);
+            }
+            Err(e) => warn!("Ignoring unreadable waypoints file {}: {}", path, e),
+        }
+        waypoints
+    }
+
+    /// Write all waypoints to `path`, creating the parent directory if needed
+    pub fn save(&mut self, path: &str) -> std::io::Result<()> {
+        let file = WaypointsFile { next_id: self.next_id, waypoints: self.records.clone() };
+        let json = serde_json::to_string_pretty(&file).map_err(std::io::Error::other)?;
+        crate::storage::write(path, &json)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    /// Add an active waypoint at a geographic position, named after its id
+    pub fn add(&mut self, lon: f64, lat: f64) -> &WaypointRecord {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.records.push(WaypointRecord { id, name: format!("Waypoint {}", id + 1), lon, lat, active: true });
+        self.dirty = true;
+        &self.records[self.records.len() - 1]
+    }
+
+    pub fn remove(&mut self, id: u64) -> Option<WaypointRecord> {
+        let index = self.records.iter().position(|record| record.id == id)?;
+        if self.route == Some(id) {
+            self.route = None;
+        }
+        self.dirty = true;
+        Some(self.records.remove(index))
+    }
+
+    pub fn toggle_active(&mut self, id: u64) {
+        if let Some(record) = self.records.iter_mut().find(|record| record.id == id) {
+            record.active = !record.active;
+            self.dirty = true;
+        }
+    }
+}
+
+/// Screen-edge indicator (arrow and distance) pointing toward a waypoint entity
+#[derive(Component)]
+pub struct WaypointIndicator(pub Entity);
+
+#[derive(Component)]
+pub struct WaypointIndicatorArrow;
+
+#[derive(Component)]
+pub struct WaypointIndicatorLabel;
+
+/// Arrow glyphs by screen direction, counter-clockwise from right in 45° steps
+const ARROWS: [&str; 8] = ["→", "↗", "↑", "↖", "←", "↙", "↓", "↘"];
+
+/// Format a distance in meters, switching to kilometers past 1 km
+pub fn format_distance(meters: f64) -> String {
+    if meters < 1000.0 {
+        format!("{:.0} m", meters)
+    } else {
+        format!("{:.2} km", meters / 1000.0)
+    }
+}
+
+/// Point where the ray from the screen center along `direction` (screen space, y down) leaves
+/// the viewport shrunk by `margin`
+fn clamp_to_edge(viewport_size: Vec2, direction: Vec2, margin: f32) -> Vec2 {
+    let center = viewport_size / 2.0;
+    let half = (center - Vec2::splat(margin)).max(Vec2::ONE);
+    let scale_x = if direction.x.abs() > f32::EPSILON { half.x / direction.x.abs() } else { f32::MAX };
+    let scale_y = if direction.y.abs() > f32::EPSILON { half.y / direction.y.abs() } else { f32::MAX };
+    center + direction * scale_x.min(scale_y)
+}
+
+/// Place an arrow on the screen edge for every active waypoint outside the viewport, with its
+/// geodesic distance from the player. Uses the same projection as `update_entity_ui_overlays`;
+/// waypoints behind the camera point toward the side they are on.
+#[allow(clippy::too_many_arguments)]
+pub fn update_waypoint_indicators(
+    mut commands: Commands,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    window_query: Query<&Window>,
+    player_query: Query<&Transform, With<Player>>,
+    waypoints: Query<(Entity, &GlobalTransform, &Waypoint)>,
+    mut indicators: Query<(Entity, &WaypointIndicator, &mut Node, &Children)>,
+    mut arrows: Query<&mut Text, (With<WaypointIndicatorArrow>, Without<WaypointIndicatorLabel>)>,
+    mut labels: Query<&mut Text, (With<WaypointIndicatorLabel>, Without<WaypointIndicatorArrow>)>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.single() else { return; };
+    let Ok(window) = window_query.single() else { return; };
+    let Ok(player_transform) = player_query.single() else { return; };
+    let viewport_size = window.size();
+    let player_geo = world_to_geo(player_transform.translation, &planisphere, &terrain_center);
+    let margin = crate::config::waypoints::EDGE_MARGIN_PX;
+
+    // Screen position of the indicator, None when the waypoint is visible on screen
+    let edge_position = |position: Vec3| -> Option<Vec2> {
+        let local = camera_transform.affine().inverse().transform_point3(position);
+        if local.z < 0.0
+            && let Ok(screen_pos) = camera.world_to_viewport(camera_transform, position) {
+            let on_screen = screen_pos.x >= 0.0 && screen_pos.y >= 0.0 && screen_pos.x <= viewport_size.x && screen_pos.y <= viewport_size.y;
+            if on_screen {
+                return None;
+            }
+            return Some(clamp_to_edge(viewport_size, (screen_pos - viewport_size / 2.0).normalize_or(Vec2::Y), margin));
+        }
+        // Behind the camera: point toward its side, or down when straight behind
+        Some(clamp_to_edge(viewport_size, Vec2::new(local.x, -local.y).normalize_or(Vec2::Y), margin))
+    };
+
+    let mut shown = HashSet::new();
+    for (indicator, target, mut node, children) in indicators.iter_mut() {
+        let Ok((_, transform, waypoint)) = waypoints.get(target.0) else {
+            commands.entity(indicator).despawn();
+            continue;
+        };
+        shown.insert(target.0);
+        let position = transform.translation();
+        let edge = if waypoint.active { edge_position(position) } else { None };
+        let Some(edge) = edge else {
+            node.display = Display::None;
+            continue;
+        };
+        node.display = Display::Flex;
+        node.left = Val::Px(edge.x - 30.0);
+        node.top = Val::Px(edge.y - 20.0);
+
+        let direction = edge - viewport_size / 2.0;
+        let angle = (-direction.y).atan2(direction.x);
+        let octant = ((angle / std::f32::consts::FRAC_PI_4).round() as i32).rem_euclid(8) as usize;
+        let geo = world_to_geo(position, &planisphere, &terrain_center);
+        let distance = if geo.0.is_nan() || player_geo.0.is_nan() {
+            position.distance(player_transform.translation) as f64
+        } else {
+            planisphere.geodesic_distance(player_geo, geo)
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = arrows.get_mut(child) {
+                **text = ARROWS[octant].to_string();
+            } else if let Ok(mut text) = labels.get_mut(child) {
+                **text = format!("{}\n{}", waypoint.name, format_distance(distance));
+            }
+        }
+    }
+
+    for (entity, _, _) in waypoints.iter() {
+        if shown.contains(&entity) {
+            continue;
+        }
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                width: Val::Px(60.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                display: Display::None, // Placed on the next update
+                ..default()
+            },
+            WaypointIndicator(entity),
+        )).with_children(|indicator| {
+            indicator.spawn((
+                Text::new(""),
+                TextFont { font_size: 22.0, ..default() },
+                TextColor(Color::srgb(0.2, 0.9, 0.9)),
+                WaypointIndicatorArrow,
+            ));
+            indicator.spawn((
+                Text::new(""),
+                TextFont { font_size: 11.0, ..default() },
+                TextColor(Color::WHITE),
+                TextLayout::new_with_justify(JustifyText::Center),
+                WaypointIndicatorLabel,
+            ));
+        });
+    }
+}
+
+/// Place a waypoint with P on the player's tile, or with Shift+P on the tile under the cursor
+pub fn place_waypoint(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    planisphere: Res<Planisphere>,
+    cursor: Res<CursorTarget>,
+    player_query: Query<&EntitySubpixelPosition, With<Player>>,
+    mut waypoints: ResMut<Waypoints>,
+    mut log: EventWriter<LogMessage>,
+    mut notify: EventWriter<Notify>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    let at_cursor = keyboard_input.pressed(KeyCode::ShiftLeft) || keyboard_input.pressed(KeyCode::ShiftRight);
+    let tile = if at_cursor {
+        cursor.tile
+    } else {
+        player_query.single().ok().map(|position| position.subpixel)
+    };
+    let Some((i, j, k)) = tile else {
+        notify.write(Notify::new("No tile to place a waypoint on"));
+        return;
+    };
+    let (lon, lat) = planisphere.subpixel_to_geo(i, j, k);
+    let record = waypoints.add(lon, lat);
+    log.write(LogMessage::new(LogCategory::Waypoint, format!("Placed {} at ({}, {}, {}), {:.4}°, {:.4}°", record.name, i, j, k, lon, lat)));
+    notify.write(Notify::new(format!("{} placed", record.name)));
+}
+
+/// Spawn a beacon for every waypoint, despawn the beacons of deleted ones and copy names and
+/// active flags. Beacons are anchored to the waypoint's geographic position, so they follow
+/// terrain recentring (`beacons::reanchor_beacons`).
+#[allow(clippy::too_many_arguments)]
+pub fn sync_waypoint_beacons(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut waypoints: ResMut<Waypoints>,
+    mut beacon_pool: ResMut<BeaconPool>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    object_templates: Res<ObjectTemplates>,
+    mut beacons: Query<&mut Waypoint, With<PlacedWaypoint>>,
+) {
+    let waypoints = waypoints.as_mut();
+
+    let deleted: Vec<u64> = waypoints.live.keys().copied()
+        .filter(|id| !waypoints.records.iter().any(|record| record.id == *id))
+        .collect();
+    for id in deleted {
+        if let Some(entity) = waypoints.live.remove(&id) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for record in waypoints.records.iter() {
+        if let Some(entity) = waypoints.live.get(&record.id) {
+            if let Ok(mut waypoint) = beacons.get_mut(*entity)
+                && (waypoint.name != record.name || waypoint.active != record.active) {
+                waypoint.name = record.name.clone();
+                waypoint.active = record.active;
+            }
+            continue;
+        }
+        match spawn_beacon(&mut commands, &mut beacon_pool, &mut materials, &planisphere, &terrain_center, &object_templates,
+                           WAYPOINT_TEMPLATE, BeaconAnchor::Geo(record.lon, record.lat), BeaconKind::Waypoint) {
+            Ok(entity) => {
+                commands.entity(entity).insert((
+                    Waypoint { name: record.name.clone(), active: record.active },
+                    PlacedWaypoint,
+                    MinimapMarker(WAYPOINT_COLOR),
+                ));
+                waypoints.live.insert(record.id, entity);
+            }
+            Err(e) => {
+                warn!("Cannot spawn waypoint beacons: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Keep a `PathTrail` on the player along the great circle to the routed waypoint, recomputed
+/// when the player changes tile or the route changes
+#[allow(clippy::type_complexity)]
+pub fn update_waypoint_route(
+    mut commands: Commands,
+    waypoints: Res<Waypoints>,
+    planisphere: Res<Planisphere>,
+    player_query: Query<(Entity, &EntitySubpixelPosition, Option<&PathTrail>), With<Player>>,
+    mut routed: Local<Option<(u64, (usize, usize, usize))>>,   // Waypoint and player tile of the current trail
+) {
+    let Ok((player, position, trail)) = player_query.single() else { return; };
+    let Some(record) = waypoints.route.and_then(|id| waypoints.records.iter().find(|record| record.id == id)) else {
+        if trail.is_some() {
+            commands.entity(player).remove::<PathTrail>();
+        }
+        *routed = None;
+        return;
+    };
+    if *routed == Some((record.id, position.subpixel)) && trail.is_some() {
+        return;
+    }
+    *routed = Some((record.id, position.subpixel));
+    let (i, j, k) = position.subpixel;
+    let from = planisphere.subpixel_to_geo(i, j, k);
+    let to = (record.lon, record.lat);
+    let tiles = (planisphere.geodesic_distance(from, to) / planisphere.mean_tile_size).ceil() as usize + 1;
+    let tiles = great_circle_tiles(&planisphere, from, to, tiles.clamp(2, crate::config::measure::MAX_TRAIL_MARKERS));
+    commands.entity(player).insert(PathTrail::new(tiles, WAYPOINT_COLOR));
+}
+
+/// Write the waypoints to disk as soon as they change
+pub fn save_waypoints(mut waypoints: ResMut<Waypoints>) {
+    if !waypoints.dirty {
+        return;
+    }
+    let path = waypoints.path.clone();
+    if let Err(e) = waypoints.save(&path) {
+        error!("Failed to save waypoints: {}", e);
+        waypoints.dirty = false; // Retried on the next change
+    }
+}
+
+/// Root node of the waypoint list panel, toggled with F9
+#[derive(Component)]
+pub struct WaypointPanel;
+
+/// Column holding one row per waypoint
+#[derive(Component)]
+pub struct WaypointList;
+
+/// What a waypoint row button does when clicked
+#[derive(Component, Debug, Clone, Copy)]
+pub enum WaypointButton {
+    Route(u64),
+    ToggleActive(u64),
+    Delete(u64),
+    NavigateTrack(usize),   // Index in `GpxTracks::tracks`
+}
+
+/// Spawn the (hidden) waypoint list panel in the bottom-left corner
+pub fn setup_waypoint_panel(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            width: Val::Px(280.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Visibility::Hidden,
+        WaypointPanel,
+    )).with_children(|panel| {
+        panel.spawn((
+            Text::new("Waypoints (F9) - P: here, Shift+P: cursor"),
+            TextFont { font_size: 12.0, ..default() },
+            TextColor(Color::WHITE),
+        ));
+        panel.spawn((Node { flex_direction: FlexDirection::Column, row_gap: Val::Px(2.0), ..default() }, WaypointList));
+    });
+}
+
+/// Toggle the waypoint list with F9
+pub fn toggle_waypoint_panel(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut panel_query: Query<&mut Visibility, With<WaypointPanel>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F9) {
+        return;
+    }
+    if let Ok(mut visibility) = panel_query.single_mut() {
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn spawn_row_button(parent: &mut ChildSpawnerCommands, label: &str, action: WaypointButton) {
+    parent.spawn((
+        Button,
+        Node { padding: UiRect::axes(Val::Px(6.0), Val::Px(1.0)), ..default() },
+        BackgroundColor(Color::srgba(0.25, 0.25, 0.25, 0.9)),
+        action,
+    )).with_children(|button| {
+        button.spawn((Text::new(label), TextFont { font_size: 11.0, ..default() }, TextColor(Color::WHITE)));
+    });
+}
+
+/// Apply row clicks and rebuild the list when the waypoints, the tracks or the panel visibility
+/// change. Routing to a waypoint and navigating a track exclude each other.
+pub fn update_waypoint_panel(
+    mut commands: Commands,
+    mut waypoints: ResMut<Waypoints>,
+    mut tracks: ResMut<GpxTracks>,
+    mut log: EventWriter<LogMessage>,
+    panel_query: Query<Ref<Visibility>, With<WaypointPanel>>,
+    list_query: Query<Entity, With<WaypointList>>,
+    buttons: Query<(&Interaction, &WaypointButton), Changed<Interaction>>,
+) {
+    let Ok(visibility) = panel_query.single() else { return; };
+    if *visibility == Visibility::Hidden {
+        return;
+    }
+    let Ok(list) = list_query.single() else { return; };
+
+    for (interaction, button) in buttons.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match *button {
+            WaypointButton::Route(id) => {
+                waypoints.route = if waypoints.route == Some(id) { None } else { Some(id) };
+                if waypoints.route.is_some() {
+                    tracks.navigation = None;
+                }
+            }
+            WaypointButton::ToggleActive(id) => waypoints.toggle_active(id),
+            WaypointButton::Delete(id) => {
+                if let Some(record) = waypoints.remove(id) {
+                    log.write(LogMessage::new(LogCategory::Waypoint, format!("Deleted {}", record.name)));
+                }
+            }
+            WaypointButton::NavigateTrack(track) => {
+                tracks.toggle_navigation(track);
+                if tracks.navigation.is_some() {
+                    waypoints.route = None;
+                }
+            }
+        }
+    }
+
+    if !waypoints.is_changed() && !tracks.is_changed() && !visibility.is_changed() {
+        return;
+    }
+    commands.entity(list).despawn_related::<Children>();
+    commands.entity(list).with_children(|list| {
+        if waypoints.records.is_empty() {
+            list.spawn((Text::new("No waypoints"), TextFont { font_size: 11.0, ..default() }, TextColor(Color::srgb(0.6, 0.6, 0.6))));
+        }
+        for record in waypoints.records.iter() {
+            list.spawn(Node {
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                ..default()
+            }).with_children(|row| {
+                let color = if record.active { WAYPOINT_COLOR } else { Color::srgb(0.5, 0.5, 0.5) };
+                row.spawn((
+                    Text::new(format!("{} ({:.3}°, {:.3}°)", record.name, record.lon, record.lat)),
+                    TextFont { font_size: 11.0, ..default() },
+                    TextColor(color),
+                ));
+                row.spawn(Node { column_gap: Val::Px(4.0), ..default() }).with_children(|controls| {
+                    spawn_row_button(controls, if waypoints.route == Some(record.id) { "Unroute" } else { "Route" }, WaypointButton::Route(record.id));
+                    spawn_row_button(controls, if record.active { "Hide" } else { "Show" }, WaypointButton::ToggleActive(record.id));
+                    spawn_row_button(controls, "Delete", WaypointButton::Delete(record.id));
+                });
+            });
+        }
+        if !tracks.tracks.is_empty() {
+            list.spawn((Text::new("Tracks"), TextFont { font_size: 12.0, ..default() }, TextColor(Color::WHITE)));
+        }
+        for (n, track) in tracks.tracks.iter().enumerate() {
+            let navigated = tracks.navigation.filter(|navigation| navigation.track == n);
+            list.spawn(Node {
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                ..default()
+            }).with_children(|row| {
+                let progress = match navigated.and_then(|navigation| navigation.next) {
+                    Some(next) => format!("{}/{}", next + 1, track.points.len()),
+                    None => format!("{} points", track.points.len()),
+                };
+                row.spawn((
+                    Text::new(format!("{} ({})", track.name, progress)),
+                    TextFont { font_size: 11.0, ..default() },
+                    TextColor(TRACK_COLOR),
+                ));
+                spawn_row_button(row, if navigated.is_some() { "Stop" } else { "Navigate" }, WaypointButton::NavigateTrack(n));
+            });
+        }
+    });
+}
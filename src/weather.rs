@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+
+use bevy::audio::Volume;
+use bevy::pbr::{DistanceFog, FogFalloff, NotShadowCaster};
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::camera::ThirdPersonCamera;
+use crate::config::weather::*;
+use crate::message_log::{LogCategory, LogMessage};
+use crate::planisphere::Planisphere;
+use crate::player::Player;
+use crate::settings::Settings;
+use crate::terrain::texture::WorldSeed;
+use crate::terrain::{world_to_geo, TerrainCenter, Tile};
+use crate::wind::Wind;
+
+/// Fog color under a clear sky and under storm clouds (daylight)
+const FOG_CLEAR_COLOR: Color = Color::srgb(0.75, 0.82, 0.9);
+const FOG_STORM_COLOR: Color = Color::srgb(0.35, 0.37, 0.4);
+
+/// Weather of a region, the states of its state machine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WeatherKind {
+    #[default]
+    Clear,
+    Overcast,
+    Rain,
+    Storm,
+}
+
+impl WeatherKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            WeatherKind::Clear => "Clear",
+            WeatherKind::Overcast => "Overcast",
+            WeatherKind::Rain => "Rain",
+            WeatherKind::Storm => "Storm",
+        }
+    }
+
+    /// State of a region seen for the first time, for a uniform `roll` in [0, 1)
+    fn initial(roll: f64) -> Self {
+        use WeatherKind::*;
+        pick(&[(Clear, 0.4), (Overcast, 0.3), (Rain, 0.2), (Storm, 0.1)], roll)
+    }
+
+    /// Next state of the state machine, for a uniform `roll` in [0, 1): storms only build up
+    /// from overcast or rainy weather and die down through rain
+    fn next(self, roll: f64) -> Self {
+        use WeatherKind::*;
+        let transitions: &[(WeatherKind, f64)] = match self {
+            Clear => &[(Clear, 0.6), (Overcast, 0.4)],
+            Overcast => &[(Clear, 0.3), (Overcast, 0.3), (Rain, 0.3), (Storm, 0.1)],
+            Rain => &[(Overcast, 0.4), (Rain, 0.4), (Storm, 0.2)],
+            Storm => &[(Overcast, 0.3), (Rain, 0.7)],
+        };
+        pick(transitions, roll)
+    }
+
+    /// Conditions the weather settles to
+    pub fn conditions(self) -> Conditions {
+        match self {
+            WeatherKind::Clear => Conditions { cloud_cover: 0.1, rain: 0.0, wind: 0.2, visibility: CLEAR_VISIBILITY },
+            WeatherKind::Overcast => Conditions { cloud_cover: 0.7, rain: 0.0, wind: 0.35, visibility: CLEAR_VISIBILITY * 0.6 },
+            WeatherKind::Rain => Conditions { cloud_cover: 0.85, rain: 0.5, wind: 0.5, visibility: CLEAR_VISIBILITY * 0.3 },
+            WeatherKind::Storm => Conditions { cloud_cover: 1.0, rain: 1.0, wind: 0.9, visibility: STORM_VISIBILITY },
+        }
+    }
+}
+
+/// Weighted choice for a uniform `roll` in [0, 1); weights sum to 1
+fn pick(weights: &[(WeatherKind, f64)], roll: f64) -> WeatherKind {
+    let mut left = roll;
+    for &(kind, weight) in weights {
+        if left < weight {
+            return kind;
+        }
+        left -= weight;
+    }
+    weights[weights.len() - 1].0
+}
+
+/// How the weather is chosen, from `WorldSettings::weather`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WeatherSetting {
+    #[default]
+    Cycle,              // Each region follows its own state machine
+    Fixed(WeatherKind),
+}
+
+/// What the weather effects show, blended between the conditions of the weather states
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Conditions {
+    pub cloud_cover: f32,   // 0.0 to 1.0, dims the sun
+    pub rain: f32,          // 0.0 to 1.0, share of the rain drops shown and rain sound volume
+    pub wind: f32,          // Wind strength
+    pub visibility: f32,    // Fog visibility distance, in world units
+}
+
+impl Conditions {
+    fn lerp(self, other: Conditions, t: f32) -> Conditions {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        Conditions {
+            cloud_cover: mix(self.cloud_cover, other.cloud_cover),
+            rain: mix(self.rain, other.rain),
+            wind: mix(self.wind, other.wind),
+            visibility: mix(self.visibility, other.visibility),
+        }
+    }
+}
+
+/// Weather state machine of a region at its last visit
+#[derive(Debug, Clone, Copy)]
+struct RegionWeather {
+    kind: WeatherKind,
+    step: u64,
+}
+
+/// Weather around the player. Regions of `REGION_DEGREES` each run a state machine stepping
+/// every `STEP_SECS`, drawn from the world seed so a region's weather only depends on the seed
+/// and the time; regions are advanced when the player is in them.
+#[derive(Resource, Debug)]
+pub struct Weather {
+    pub kind: WeatherKind,              // State of the player's region
+    pub region: Option<(usize, usize)>, // Player's region, longitude and latitude indices
+    pub conditions: Conditions,         // Moving toward `kind.conditions()`
+    pub wetness: f32,                   // 0.0 (dry) to 1.0 (soaked), follows the rain slowly
+    regions: HashMap<(usize, usize), RegionWeather>,
+    elapsed: f32,
+}
+
+impl Default for Weather {
+    fn default() -> Self {
+        Self {
+            kind: WeatherKind::Clear,
+            region: None,
+            conditions: WeatherKind::Clear.conditions(),
+            wetness: 0.0,
+            regions: HashMap::new(),
+            elapsed: 0.0,
+        }
+    }
+}
+
+impl Weather {
+    /// State of `region` at `step`, replaying its state machine since the last visit
+    fn region_state(&mut self, seed: WorldSeed, region: (usize, usize), step: u64) -> WeatherKind {
+        let roll = |step: u64| seed.random(region.0, region.1, (step * 4) as usize);
+        let state = self.regions.entry(region)
+            .or_insert_with(|| RegionWeather { kind: WeatherKind::initial(roll(step)), step });
+        for step in state.step.max(step.saturating_sub(MAX_CATCH_UP_STEPS))..step {
+            state.kind = state.kind.next(roll(step + 1));
+        }
+        state.step = state.step.max(step);
+        state.kind
+    }
+}
+
+/// Region of a geographic position
+fn region_of(lon: f64, lat: f64) -> (usize, usize) {
+    (((lon + 180.0) / REGION_DEGREES).floor() as usize, ((lat + 90.0) / REGION_DEGREES).floor() as usize)
+}
+
+/// Step the weather of the player's region, move the conditions toward it and set the wind
+#[allow(clippy::too_many_arguments)]
+pub fn update_weather(
+    time: Res<Time>,
+    settings: Res<Settings>,
+    world_seed: Res<WorldSeed>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    player_query: Query<&Transform, With<Player>>,
+    mut weather: ResMut<Weather>,
+    mut wind: ResMut<Wind>,
+    mut log: EventWriter<LogMessage>,
+) {
+    let dt = time.delta_secs();
+    weather.elapsed += dt;
+    let Ok(transform) = player_query.single() else { return; };
+    let (lon, lat) = world_to_geo(transform.translation, &planisphere, &terrain_center);
+    if lon.is_nan() || lat.is_nan() {
+        return;
+    }
+    let region = region_of(lon, lat);
+    let step = (weather.elapsed / STEP_SECS) as u64;
+    let kind = match settings.world.weather {
+        WeatherSetting::Fixed(kind) => kind,
+        WeatherSetting::Cycle => weather.region_state(*world_seed, region, step),
+    };
+    if kind != weather.kind {
+        log.write(LogMessage::new(LogCategory::Terrain, format!("Weather: {}", kind.label())));
+        weather.kind = kind;
+    }
+    weather.region = Some(region);
+
+    let blend = 1.0 - (-dt / TRANSITION_SECS).exp();
+    weather.conditions = weather.conditions.lerp(kind.conditions(), blend);
+    let rain = weather.conditions.rain;
+    let wetting = if rain > 0.05 { rain / WETTING_SECS } else { -1.0 / DRYING_SECS };
+    weather.wetness = (weather.wetness + wetting * dt).clamp(0.0, 1.0);
+
+    // Each region and step has its own prevailing wind direction
+    let angle = world_seed.random(region.0, region.1, (step * 4 + 2) as usize) as f32 * std::f32::consts::TAU;
+    let direction = wind.direction.lerp(Vec2::from_angle(angle), blend);
+    wind.set(direction, weather.conditions.wind);
+}
+
+/// Distance fog on the camera from the weather visibility, darkened at night
+pub fn apply_weather_fog(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    ambient: Res<AmbientLight>,
+    mut cameras: Query<(Entity, Option<&mut DistanceFog>), With<ThirdPersonCamera>>,
+) {
+    let daylight = (ambient.brightness / crate::config::daylight::AMBIENT_DAY).clamp(0.1, 1.0);
+    let color = Color::BLACK.mix(&FOG_CLEAR_COLOR.mix(&FOG_STORM_COLOR, weather.conditions.cloud_cover), daylight);
+    let falloff = FogFalloff::from_visibility(weather.conditions.visibility);
+    for (entity, fog) in cameras.iter_mut() {
+        match fog {
+            Some(mut fog) => {
+                fog.color = color;
+                fog.falloff = falloff.clone();
+            }
+            None => {
+                commands.entity(entity).insert(DistanceFog { color, falloff: falloff.clone(), ..default() });
+            }
+        }
+    }
+}
+
+/// Rain drop of the pool falling around the camera
+#[derive(Component)]
+pub struct RainDrop {
+    index: usize,   // Drops below `rain * RAIN_DROPS` are shown
+}
+
+/// Spawn the pool of hidden rain drops
+pub fn setup_rain(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let mesh = meshes.add(Cuboid::new(0.02, 0.6, 0.02));
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgba(0.7, 0.8, 1.0, 0.4),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        ..default()
+    });
+    for index in 0..RAIN_DROPS {
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::default(),
+            Visibility::Hidden,
+            NotShadowCaster,
+            RainDrop { index },
+        ));
+    }
+}
+
+/// Make the drops fall around the camera, slanted by the wind. Drops leaving the box around
+/// the camera start again from its top, newly shown ones anywhere in it.
+pub fn update_rain(
+    time: Res<Time>,
+    weather: Res<Weather>,
+    wind: Res<Wind>,
+    cameras: Query<&Transform, (With<ThirdPersonCamera>, Without<RainDrop>)>,
+    mut drops: Query<(&RainDrop, &mut Transform, &mut Visibility)>,
+) {
+    let Ok(camera) = cameras.single() else { return; };
+    let shown = (weather.conditions.rain * RAIN_DROPS as f32) as usize;
+    let drift = wind.direction * wind.strength * RAIN_WIND_DRIFT;
+    let velocity = Vec3::new(drift.x, -RAIN_FALL_SPEED * (0.6 + 0.4 * weather.conditions.rain), drift.y);
+    let rotation = Quat::from_rotation_arc(Vec3::Y, -velocity.normalize());
+    let center = camera.translation;
+    let mut rng = rand::thread_rng();
+    let mut random_offset = |height: f32| Vec3::new(
+        rng.gen_range(-RAIN_RADIUS..RAIN_RADIUS),
+        height,
+        rng.gen_range(-RAIN_RADIUS..RAIN_RADIUS),
+    );
+
+    for (drop, mut transform, mut visibility) in drops.iter_mut() {
+        if drop.index >= shown {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        let offset = transform.translation - center;
+        if *visibility == Visibility::Hidden {
+            let height = (drop.index as f32 * 0.618).fract() * 2.0 - 1.0; // Spread over the box height
+            transform.translation = center + random_offset(height * RAIN_HEIGHT);
+        } else if offset.y < -RAIN_HEIGHT || offset.xz().length() > RAIN_RADIUS {
+            transform.translation = center + random_offset(RAIN_HEIGHT);
+        } else {
+            transform.translation += velocity * time.delta_secs();
+        }
+        transform.rotation = rotation;
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+/// Darken the terrain and make it glossier as it gets wet. The dry look of each terrain
+/// material is kept to scale from (terrain builds bring new materials).
+pub fn apply_wet_surfaces(
+    weather: Res<Weather>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tiles: Query<&MeshMaterial3d<StandardMaterial>, With<Tile>>,
+    mut dry: Local<HashMap<AssetId<StandardMaterial>, (Color, f32)>>,
+) {
+    dry.retain(|id, _| materials.contains(*id));
+    for handle in tiles.iter() {
+        let Some(material) = materials.get(&handle.0) else { continue; };
+        let (color, roughness) = *dry.entry(handle.0.id()).or_insert((material.base_color, material.perceptual_roughness));
+        let wet_roughness = roughness * (1.0 - (1.0 - WET_ROUGHNESS) * weather.wetness);
+        if (material.perceptual_roughness - wet_roughness).abs() < 0.005 {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(&handle.0) {
+            material.base_color = Color::BLACK.mix(&color, 1.0 - WET_DARKENING * weather.wetness);
+            material.perceptual_roughness = wet_roughness;
+        }
+    }
+}
+
+/// Looping rain sound, its volume following the rain
+#[derive(Component)]
+pub struct RainSound;
+
+/// Whether a sound file is present under `assets/` (the game ships without sounds)
+fn sound_exists(path: &str) -> bool {
+    std::path::Path::new("assets").join(path).exists()
+}
+
+/// Start the rain loop, silent until it rains
+pub fn setup_weather_audio(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if !sound_exists(RAIN_SOUND) {
+        info!("No assets/{}, rain is silent", RAIN_SOUND);
+        return;
+    }
+    commands.spawn((
+        AudioPlayer::new(asset_server.load(RAIN_SOUND)),
+        PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+        RainSound,
+    ));
+}
+
+/// Set the rain volume and play thunderclaps at random during storms
+pub fn update_weather_audio(
+    mut commands: Commands,
+    time: Res<Time>,
+    weather: Res<Weather>,
+    asset_server: Res<AssetServer>,
+    mut sinks: Query<&mut AudioSink, With<RainSound>>,
+) {
+    for mut sink in sinks.iter_mut() {
+        sink.set_volume(Volume::Linear(weather.conditions.rain));
+    }
+    let mut rng = rand::thread_rng();
+    if weather.kind == WeatherKind::Storm
+        && rng.r#gen::<f32>() < time.delta_secs() / THUNDER_INTERVAL_SECS
+        && sound_exists(THUNDER_SOUND)
+    {
+        commands.spawn((
+            AudioPlayer::new(asset_server.load(THUNDER_SOUND)),
+            PlaybackSettings::DESPAWN.with_volume(Volume::Linear(rng.gen_range(0.4..1.0))),
+        ));
+    }
+}
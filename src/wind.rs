@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+
+use crate::config::wind::{GUST_WAVELENGTH, MAX_TILT, SWAY_HZ};
+use crate::props::{tags, Prop};
+
+/// Global wind, read by the vegetation sway. Weather and scripts change it through `set`.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct Wind {
+    pub direction: Vec2,   // Horizontal direction the wind blows toward (x, z), normalized
+    pub strength: f32,     // 0.0 (calm) to 1.0 (storm)
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self { direction: Vec2::X, strength: 0.3 }
+    }
+}
+
+impl Wind {
+    pub fn set(&mut self, direction: Vec2, strength: f32) {
+        self.direction = direction.try_normalize().unwrap_or(self.direction);
+        self.strength = strength.clamp(0.0, 1.0);
+    }
+}
+
+/// Scene part of a vegetation prop swayed by the wind, with its rest rotation
+#[derive(Component, Debug, Clone, Copy)]
+pub struct WindSway {
+    base_rotation: Quat,
+    phase: f32,
+}
+
+/// Make the scene of newly spawned vegetation props sway. The scene child is rotated rather
+/// than the prop itself so colliders stay still.
+pub fn attach_wind_sway(
+    mut commands: Commands,
+    props: Query<(Entity, &Prop, &Children), Added<Prop>>,
+    parts: Query<&Transform, With<SceneRoot>>,
+) {
+    for (entity, prop, children) in props.iter() {
+        if !prop.has_tag(tags::VEGETATION) {
+            continue;
+        }
+        let phase = (entity.index() % 32) as f32 * 0.2;
+        for child in children.iter() {
+            if let Ok(transform) = parts.get(child) {
+                commands.entity(child).insert(WindSway { base_rotation: transform.rotation, phase });
+            }
+        }
+    }
+}
+
+/// Tilt swaying scenes away from the wind around their base, with gusts travelling along
+/// the wind direction
+pub fn apply_wind_sway(
+    time: Res<Time>,
+    wind: Res<Wind>,
+    mut parts: Query<(&WindSway, &mut Transform, &GlobalTransform)>,
+) {
+    let t = time.elapsed_secs();
+    // Horizontal axis perpendicular to the wind: rotating around it leans toward the wind direction
+    let axis = Vec3::new(wind.direction.y, 0.0, -wind.direction.x);
+    for (sway, mut transform, global) in parts.iter_mut() {
+        let along = global.translation().xz().dot(wind.direction) / GUST_WAVELENGTH;
+        let gust = 0.5 + 0.5 * (std::f32::consts::TAU * (along - 0.2 * t)).sin();
+        let flutter = (std::f32::consts::TAU * SWAY_HZ * t + sway.phase).sin();
+        let angle = wind.strength * MAX_TILT * (0.5 * gust + 0.5 * flutter);
+        transform.rotation = Quat::from_axis_angle(axis, angle) * sway.base_rotation;
+    }
+}
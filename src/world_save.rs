@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_object::EntitySubpixelPosition;
+use crate::items::{Hotbar, ItemStack};
+use crate::notifications::Notify;
+use crate::persistence::{unix_time_secs, PersistedObjects, WORLD_OBJECTS_SAVE_FILE};
+use crate::planisphere::Planisphere;
+use crate::player::{Player, PlayerInventory};
+use crate::player_stats::{Health, Stamina};
+use crate::terrain::{geo_to_world, TerrainCenter};
+use crate::terrain::texture::WorldSeed;
+use crate::waypoints::{Waypoints, WAYPOINTS_SAVE_FILE};
+
+/// File in the save directory with the format version, terrain center and player state
+pub const WORLD_SAVE_FILE: &str = "world.json";
+/// Version written to `WORLD_SAVE_FILE`; saves from a newer version are not loaded
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+/// Directory holding the save slots created in game (Ctrl+F10)
+pub const SAVE_SLOTS_DIR: &str = "saves/slots";
+
+/// Player state kept in a save
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayerSave {
+    pub geo: (f64, f64),   // (longitude, latitude) in degrees
+    pub inventory: Vec<ItemStack>,
+    pub hotbar_selected: usize,
+    pub health: f32,
+    pub stamina: f32,
+}
+
+/// On-disk layout of `WORLD_SAVE_FILE`; placed and harvested objects and waypoints have their
+/// own files in the same directory (`persistence`, `waypoints`)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct WorldSaveFile {
+    version: u32,
+    saved_at: u64,                 // Unix time in seconds
+    #[serde(default)]
+    seed: u64,                     // World seed the save was made with
+    terrain_center: (f64, f64),    // (longitude, latitude) in degrees
+    player: Option<PlayerSave>,
+}
+
+/// Save slot in use: a directory with every save file of the world. The loaded state is
+/// restored once at startup (terrain center, then the player when it spawns). A slot whose world
+/// file could not be loaded (unreadable, or from a newer version) is locked: saving into it is
+/// refused so the save is not lost, and Ctrl+F10 still saves to a new slot.
+#[derive(Resource, Debug)]
+pub struct WorldSave {
+    pub dir: String,
+    pending: Option<WorldSaveFile>,   // Loaded state not restored yet
+    locked: bool,
+}
+
+impl WorldSave {
+    /// Open the slot in `dir`, reading its world file if there is a compatible one
+    pub fn load(dir: &str) -> Self {
+        let path = Path::new(dir).join(WORLD_SAVE_FILE);
+        let mut save = Self { dir: dir.to_string(), pending: None, locked: false };
+        let Ok(content) = crate::storage::read_to_string(&path.to_string_lossy()) else { return save; };
+        match serde_json::from_str::<WorldSaveFile>(&content) {
+            Ok(file) if file.version > SAVE_FORMAT_VERSION => {
+                warn!("Ignoring {}: save format {} is newer than {}, not saving over it", path.display(), file.version, SAVE_FORMAT_VERSION);
+                save.locked = true;
+            }
+            Ok(file) => {
+                info!("Loaded world save from {}", path.display());
+                save.pending = Some(file);
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable world save {}, not saving over it: {}", path.display(), e);
+                save.locked = true;
+            }
+        }
+        save
+    }
+}
+
+/// Request to write the world to disk
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveWorld {
+    Current,   // Into the slot in use (F10, and on exit)
+    NewSlot,   // Into the slot in use (unless locked), then copied to a new slot under `SAVE_SLOTS_DIR` (Ctrl+F10)
+}
+
+/// Move the terrain center to the saved one before the first terrain build
+pub fn restore_terrain_center(
+    save: Res<WorldSave>,
+    world_seed: Res<WorldSeed>,
+    planisphere: Res<Planisphere>,
+    mut terrain_center: ResMut<TerrainCenter>,
+) {
+    let Some(file) = &save.pending else { return; };
+    if file.seed != world_seed.0 {
+        warn!("Save in {} was made with world seed {}, running with {}: landscape removals will not match",
+                  save.dir, file.seed, world_seed.0);
+    }
+    let (lon, lat) = file.terrain_center;
+    terrain_center.longitude = lon;
+    terrain_center.latitude = lat;
+    terrain_center.subpixel = planisphere.geo_to_subpixel(lon, lat);
+}
+
+/// Give the spawned player its saved position, inventory and stats
+pub fn restore_player_state(
+    mut save: ResMut<WorldSave>,
+    planisphere: Res<Planisphere>,
+    terrain_center: Res<TerrainCenter>,
+    mut players: Query<(&mut Transform, &mut PlayerInventory, &mut Hotbar, &mut Health, &mut Stamina), Added<Player>>,
+) {
+    let Ok((mut transform, mut inventory, mut hotbar, mut health, mut stamina)) = players.single_mut() else { return; };
+    let Some(player) = save.pending.take().and_then(|file| file.player) else { return; };
+    let position = geo_to_world(player.geo.0, player.geo.1, &planisphere, &terrain_center);
+    transform.translation.x = position.x; // Keep the spawn height, the player falls onto the terrain
+    transform.translation.z = position.z;
+    inventory.stacks = player.inventory;
+    hotbar.selected = player.hotbar_selected;
+    health.current = player.health.min(health.max);
+    stamina.current = player.stamina.min(stamina.max);
+}
+
+/// F10 saves the world into the slot in use, Ctrl+F10 into a new slot as well
+pub fn handle_save_keys(keyboard_input: Res<ButtonInput<KeyCode>>, mut requests: EventWriter<SaveWorld>) {
+    if !keyboard_input.just_pressed(KeyCode::F10) {
+        return;
+    }
+    let new_slot = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    requests.write(if new_slot { SaveWorld::NewSlot } else { SaveWorld::Current });
+}
+
+/// Write every part of the world (world file, persistent objects, waypoints) on request and
+/// when the app exits
+#[allow(clippy::too_many_arguments)]
+pub fn save_world(
+    mut requests: EventReader<SaveWorld>,
+    mut exit_events: EventReader<AppExit>,
+    save: Res<WorldSave>,
+    mut persisted: ResMut<PersistedObjects>,
+    mut waypoints: ResMut<Waypoints>,
+    terrain_center: Res<TerrainCenter>,
+    world_seed: Res<WorldSeed>,
+    players: Query<(&EntitySubpixelPosition, &PlayerInventory, &Hotbar, &Health, &Stamina), With<Player>>,
+    mut notify: EventWriter<Notify>,
+) {
+    let mut request = requests.read().copied().max_by_key(|request| *request == SaveWorld::NewSlot);
+    if exit_events.read().next().is_some() {
+        request.get_or_insert(SaveWorld::Current);
+    }
+    let Some(request) = request else { return; };
+    let saved_at = unix_time_secs();
+    let new_slot = Path::new(SAVE_SLOTS_DIR).join(format!("slot-{}", saved_at));
+    let dir = match request {
+        SaveWorld::Current if save.locked => {
+            warn!("Not saving to {}: it holds a save this version cannot load", save.dir);
+            notify.write(Notify::new("Not saved: the save slot holds a newer or unreadable save (Ctrl+F10 saves to a new slot)")
+                .with_color(Color::srgb(1.0, 0.3, 0.3)));
+            return;
+        }
+        SaveWorld::NewSlot if save.locked => new_slot.clone(),   // Straight into the new slot
+        _ => PathBuf::from(&save.dir),
+    };
+    let file_path = |file: &str| dir.join(file).to_string_lossy().into_owned();
+
+    let file = WorldSaveFile {
+        version: SAVE_FORMAT_VERSION,
+        saved_at,
+        seed: world_seed.0,
+        terrain_center: (terrain_center.longitude, terrain_center.latitude),
+        player: players.single().ok().map(|(position, inventory, hotbar, health, stamina)| PlayerSave {
+            geo: position.geo_coords,
+            inventory: inventory.stacks.clone(),
+            hotbar_selected: hotbar.selected,
+            health: health.current,
+            stamina: stamina.current,
+        }),
+    };
+    let result = write_world_file(&file_path(WORLD_SAVE_FILE), &file)
+        .and_then(|()| persisted.save(&file_path(WORLD_OBJECTS_SAVE_FILE)))
+        .and_then(|()| waypoints.save(&file_path(WAYPOINTS_SAVE_FILE)));
+    if let Err(e) = result {
+        error!("Failed to save the world to {}: {}", dir.display(), e);
+        notify.write(Notify::new("Save failed").with_color(Color::srgb(1.0, 0.3, 0.3)));
+        return;
+    }
+    info!("Saved the world to {}", dir.display());
+
+    if request == SaveWorld::NewSlot && dir != new_slot {
+        match copy_save_files(&dir, &new_slot) {
+            Ok(()) => notify.write(Notify::new(format!("Saved to {}", new_slot.display()))),
+            Err(e) => {
+                error!("Failed to create save slot {}: {}", new_slot.display(), e);
+                notify.write(Notify::new("Save failed").with_color(Color::srgb(1.0, 0.3, 0.3)))
+            }
+        };
+    } else if request == SaveWorld::NewSlot {
+        notify.write(Notify::new(format!("Saved to {}", new_slot.display())));
+    } else {
+        notify.write(Notify::new("World saved"));
+    }
+}
+
+fn write_world_file(path: &str, file: &WorldSaveFile) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(file).map_err(std::io::Error::other)?;
+    crate::storage::write(path, &json)
+}
+
+/// Copy the save files of a slot into another slot directory
+fn copy_save_files(from: &Path, to: &Path) -> std::io::Result<()> {
+    for file in [WORLD_SAVE_FILE, WORLD_OBJECTS_SAVE_FILE, WAYPOINTS_SAVE_FILE] {
+        let (source, copy) = (from.join(file), to.join(file));
+        if crate::storage::exists(&source.to_string_lossy()) {
+            crate::storage::copy(&source.to_string_lossy(), &copy.to_string_lossy())?;
+        }
+    }
+    Ok(())
+}
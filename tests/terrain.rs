@@ -0,0 +1,337 @@
+//! Headless checks of terrain generation and positioning on a tiny procedural planisphere,
+//! through the Bevy-free library (`cargo test --no-default-features` runs them without Bevy).
+
+use std::collections::HashSet;
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use tiles3d::config;
+use tiles3d::planisphere::{gnomonic_to_geo_helper, DistanceMethod, Planisphere};
+use tiles3d::tiling::biome::{Biome, BiomeTable};
+use tiles3d::tiling::lod::TerrainLod;
+use tiles3d::tiling::mesh::{terrain_mesh, terrain_mesh_with_lod, Relief};
+use tiles3d::tiling::ocean::ocean_mesh;
+use tiles3d::tiling::skirt::{skirt_edges, skirt_mesh};
+use tiles3d::tiling::texture::{elevation_texture, texture_index};
+
+type Subpixel = (usize, usize, usize);
+
+/// 64x32 map: elevation and red channel rising eastward (several textures), green northward
+fn tiny_planisphere() -> Planisphere {
+    let image = RgbaImage::from_fn(64, 32, |x, y| Rgba([(x * 4) as u8, (y * 8) as u8, 128, 255]));
+    let mut planisphere = Planisphere::from_image(DynamicImage::ImageRgba8(image), config::terrain::SUB_K);
+    planisphere.set_radius(config::terrain::PLANET_RADIUS as f64);
+    planisphere
+}
+
+/// Terrain as the game builds it (`build_terrain_geometry`): subpixels around `center`,
+/// projected around its geographic position
+struct Terrain {
+    center_geo: (f64, f64),
+    subpixels: Vec<Subpixel>,
+    vertices: Vec<[f32; 3]>,
+    indices: Vec<u32>,
+    uvs: Vec<[f32; 2]>,
+    mapping: Vec<Subpixel>,
+}
+
+fn build_terrain(planisphere: &Planisphere, center: Subpixel) -> Terrain {
+    let subpixels = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2,
+                                                                 config::terrain::RADIUS, DistanceMethod::Chebyshev);
+    let center_geo = planisphere.subpixel_to_geo(center.0, center.1, center.2);
+    let rendered = subpixels.iter().map(|&(i, j, k, _)| (i, j, k)).collect();
+    let (vertices, indices, uvs, mapping) = terrain_mesh(planisphere, subpixels, center_geo, Relief::default());
+    Terrain { center_geo, subpixels: rendered, vertices, indices, uvs, mapping }
+}
+
+/// Geographic center of a subpixel (`subpixel_to_geo` gives its south-west corner)
+fn subpixel_center(planisphere: &Planisphere, (i, j, k): Subpixel) -> (f64, f64) {
+    let corners = planisphere.get_subpixel_corners(i, j, k);
+    let lon = corners.iter().map(|corner| corner.0).sum::<f64>() / 4.0;
+    let lat = corners.iter().map(|corner| corner.1).sum::<f64>() / 4.0;
+    (lon, lat)
+}
+
+/// World (x, z) of a geographic point on `terrain`, as the mesh vertices are projected
+fn world_position(planisphere: &Planisphere, terrain: &Terrain, (lon, lat): (f64, f64)) -> (f64, f64) {
+    planisphere.geo_to_gnomonic(lon, lat, terrain.center_geo.0, terrain.center_geo.1)
+}
+
+/// Subpixel of the terrain triangle under a world position, like the game's raycast tile lookup
+fn tile_at(terrain: &Terrain, (x, z): (f64, f64)) -> Option<Subpixel> {
+    terrain.indices.chunks(3).position(|triangle| {
+        let [a, b, c] = [0, 1, 2].map(|n| {
+            let vertex = terrain.vertices[triangle[n] as usize];
+            (vertex[0] as f64, vertex[2] as f64)
+        });
+        let side = |(x1, z1): (f64, f64), (x2, z2): (f64, f64)| (x2 - x1) * (z - z1) - (z2 - z1) * (x - x1);
+        let (ab, bc, ca) = (side(a, b), side(b, c), side(c, a));
+        (ab >= 0.0 && bc >= 0.0 && ca >= 0.0) || (ab <= 0.0 && bc <= 0.0 && ca <= 0.0)
+    }).map(|triangle| terrain.mapping[triangle])
+}
+
+/// Terrain centers on the equator, at mid latitude and toward a pole
+fn centers(planisphere: &Planisphere) -> Vec<Subpixel> {
+    [(10.0, 0.0), (-60.0, 45.0), (120.0, -70.0)].iter()
+        .map(|&(lon, lat)| planisphere.geo_to_subpixel(lon, lat))
+        .collect()
+}
+
+#[test]
+fn triangle_mapping_matches_the_mesh() {
+    let planisphere = tiny_planisphere();
+    for center in centers(&planisphere) {
+        let terrain = build_terrain(&planisphere, center);
+        assert!(!terrain.subpixels.is_empty());
+        assert_eq!(terrain.mapping.len(), terrain.indices.len() / 3, "one mapped subpixel per triangle around {:?}", center);
+        assert_eq!(terrain.vertices.len(), 4 * terrain.subpixels.len());
+        assert_eq!(terrain.uvs.len(), terrain.vertices.len());
+        assert!(terrain.indices.iter().all(|&index| (index as usize) < terrain.vertices.len()));
+    }
+}
+
+#[test]
+fn every_rendered_subpixel_is_mapped() {
+    let planisphere = tiny_planisphere();
+    for center in centers(&planisphere) {
+        let terrain = build_terrain(&planisphere, center);
+        let mapped: HashSet<Subpixel> = terrain.mapping.iter().copied().collect();
+        let rendered: HashSet<Subpixel> = terrain.subpixels.iter().copied().collect();
+        assert!(rendered.contains(&center), "terrain around {:?} does not render its center", center);
+        let unmapped: Vec<_> = rendered.difference(&mapped).collect();
+        assert!(unmapped.is_empty(), "rendered but not mapped around {:?}: {:?}", center, unmapped);
+        assert!(mapped.is_subset(&rendered));
+    }
+}
+
+#[test]
+fn spawn_positions_round_trip_through_tile_lookup() {
+    let planisphere = tiny_planisphere();
+    for center in centers(&planisphere) {
+        let terrain = build_terrain(&planisphere, center);
+        // The center and a sample of the terrain, as spawn points
+        for &spawn in terrain.subpixels.iter().step_by(7) {
+            let geo = subpixel_center(&planisphere, spawn);
+            assert_eq!(planisphere.geo_to_subpixel(geo.0, geo.1), spawn, "geographic round trip of {:?}", spawn);
+            let world = world_position(&planisphere, &terrain, geo);
+            assert_eq!(tile_at(&terrain, world), Some(spawn), "tile lookup at the world position of {:?}", spawn);
+            let back = gnomonic_to_geo_helper(world.0, world.1, terrain.center_geo.0, terrain.center_geo.1, planisphere.radius);
+            assert!((back.0 - geo.0).abs() < 1e-9 && (back.1 - geo.1).abs() < 1e-9, "{:?} projected back to {:?}", geo, back);
+        }
+    }
+}
+
+#[test]
+fn terrain_recreation_preserves_object_positions() {
+    let planisphere = tiny_planisphere();
+    for old_center in centers(&planisphere) {
+        let old_terrain = build_terrain(&planisphere, old_center);
+        // The player walks to the recreation threshold, where the terrain is rebuilt around it
+        let threshold = (config::terrain::RADIUS / config::terrain::RECREATION_THRESHOLD_DIVISOR) as i32;
+        let new_center = planisphere.get_neighbour_subpixel(old_center.0, old_center.1, old_center.2, threshold, threshold);
+        let new_terrain = build_terrain(&planisphere, new_center);
+        // The player stands on the middle of the new center tile, before and after the recentring
+        let player_geo = subpixel_center(&planisphere, new_center);
+        let player_before = world_position(&planisphere, &old_terrain, player_geo);
+        let player_after = world_position(&planisphere, &new_terrain, player_geo);
+
+        // Objects around the player move with it, like in `player::reinitialize_positions`, and
+        // must still stand on their tile. The translation is rigid while the gnomonic projection
+        // is not, so far objects drift: this tiny map has 25-unit tiles and strong distortion.
+        let around_player = planisphere.get_subpixels_by_distance_method(new_center.0, new_center.1, new_center.2,
+                                                                         threshold as usize, DistanceMethod::Chebyshev);
+        for object in around_player.into_iter().map(|(i, j, k, _)| (i, j, k)) {
+            let (x, z) = world_position(&planisphere, &old_terrain, subpixel_center(&planisphere, object));
+            let moved = (x - player_before.0 + player_after.0, z - player_before.1 + player_after.1);
+            assert_eq!(tile_at(&new_terrain, moved), Some(object),
+                       "object on {:?} after recreating the terrain from {:?} around {:?}", object, old_center, new_center);
+        }
+    }
+}
+
+#[test]
+fn vertex_heights_follow_the_relief() {
+    // Same gradients with alpha 0: opaque pixels have no altitude, so the tiny map is flat
+    let image = RgbaImage::from_fn(64, 32, |x, y| Rgba([(x * 4) as u8, (y * 8) as u8, 128, 0]));
+    let mut planisphere = Planisphere::from_image(DynamicImage::ImageRgba8(image), config::terrain::SUB_K);
+    planisphere.set_radius(config::terrain::PLANET_RADIUS as f64);
+    let center = planisphere.geo_to_subpixel(10.0, 0.0);
+    let subpixels = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2,
+                                                                 config::terrain::RADIUS, DistanceMethod::Chebyshev);
+    let center_geo = planisphere.subpixel_to_geo(center.0, center.1, center.2);
+    let heights = |relief: Relief| -> Vec<f32> {
+        terrain_mesh(&planisphere, subpixels.clone(), center_geo, relief).0.iter().map(|vertex| vertex[1]).collect()
+    };
+
+    let flat = heights(Relief { height_scale: 0.0, smooth: true });
+    assert!(flat.iter().all(|&height| height == 0.0), "a zero height scale lays the terrain flat");
+
+    // The map rises eastward, so smoothed corners differ within a subpixel while steps do not
+    let smooth = heights(Relief::default());
+    let stepped = heights(Relief { smooth: false, ..Relief::default() });
+    assert!(smooth.chunks(4).any(|quad| quad.iter().any(|&height| height != quad[0])));
+    for (quad, &(i, j, k, _)) in stepped.chunks(4).zip(subpixels.iter()) {
+        let expected = config::terrain::ALTITUDE_SCALE * planisphere.get_alti_at_subpixel(i as i32, j as i32, k);
+        assert!(quad.iter().all(|&height| height == expected), "stepped heights of {:?}: {:?}", (i, j, k), quad);
+    }
+
+    let doubled = heights(Relief { height_scale: 2.0 * config::terrain::ALTITUDE_SCALE, smooth: true });
+    assert!(smooth.iter().zip(doubled.iter()).all(|(&one, &two)| (two - 2.0 * one).abs() < 1e-3));
+}
+
+#[test]
+fn lod_rings_cover_the_view_distance_once() {
+    let planisphere = tiny_planisphere();
+    let divs = planisphere.subpixel_divisions;
+    let lod = TerrainLod { block_radius: 2 * config::terrain::RADIUS, pixel_radius: 4 * config::terrain::RADIUS };
+    for center in centers(&planisphere) {
+        let detailed = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2,
+                                                                    config::terrain::RADIUS, DistanceMethod::Chebyshev);
+        let quads = lod.quads(&planisphere, center, &detailed, config::terrain::RADIUS, DistanceMethod::Chebyshev);
+
+        // Every subpixel within the outer ring is meshed exactly once, by a subpixel or a quad
+        let mut meshed: Vec<Subpixel> = detailed.iter().map(|&(i, j, k, _)| (i, j, k)).collect();
+        for quad in quads.iter() {
+            for sub_i in quad.first.0..quad.first.0 + quad.span.0 {
+                for sub_j in quad.first.1..quad.first.1 + quad.span.1 {
+                    meshed.push((quad.pixel.0, quad.pixel.1, sub_i * divs + sub_j));
+                }
+            }
+        }
+        let unique: HashSet<Subpixel> = meshed.iter().copied().collect();
+        assert_eq!(unique.len(), meshed.len(), "subpixels meshed twice around {:?}", center);
+        let view = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2,
+                                                                lod.pixel_radius, DistanceMethod::Chebyshev);
+        let missing: Vec<_> = view.iter().map(|&(i, j, k, _)| (i, j, k)).filter(|subpixel| !unique.contains(subpixel)).collect();
+        assert!(missing.is_empty(), "not meshed around {:?}: {:?}", center, missing);
+
+        // Merging keeps the far rings cheaper than full resolution
+        assert!(quads.len() < view.len() - detailed.len(), "{} quads for {} subpixels", quads.len(), view.len() - detailed.len());
+        let center_geo = planisphere.subpixel_to_geo(center.0, center.1, center.2);
+        let (vertices, indices, uvs, mapping) = terrain_mesh_with_lod(&planisphere, detailed.clone(), &quads, center_geo, Relief::default());
+        assert_eq!(vertices.len(), 4 * (detailed.len() + quads.len()));
+        assert_eq!(uvs.len(), vertices.len());
+        assert_eq!(mapping.len(), indices.len() / 3);
+    }
+}
+
+#[test]
+fn biome_table_picks_the_first_matching_biome() {
+    let biome = |name: &str, texture: &str, elevation, temperature| Biome {
+        name: name.to_string(),
+        texture: texture.to_string(),
+        elevation,
+        moisture: (0.0, 1.0),
+        temperature,
+        texture_index: 0,
+    };
+    let table = BiomeTable::new(vec![
+        biome("glacier", "ice", (0.8, 1.0), (0.0, 0.3)),
+        biome("snowcap", "snow", (0.8, 1.0), (0.0, 1.0)),
+        biome("meadow", "grass", (0.1, 0.8), (0.0, 1.0)),
+    ]).unwrap();
+    assert_eq!(table.classify(0.9, 0.5, 0.1), texture_index("ice"));
+    assert_eq!(table.classify(0.9, 0.5, 0.9), texture_index("snow"));
+    assert_eq!(table.classify(0.8, 0.5, 0.5), texture_index("snow"), "ranges are inclusive, earlier biomes first");
+    assert_eq!(table.classify(0.5, 0.5, 0.5), texture_index("grass"));
+    assert_eq!(table.classify(0.05, 0.5, 0.5), None);
+    assert_eq!(elevation_texture(0.05), 0);
+    assert_eq!(elevation_texture(0.95), 9);
+
+    assert!(BiomeTable::new(vec![biome("unknown", "marble", (0.0, 1.0), (0.0, 1.0))]).is_err());
+    assert!(BiomeTable::new(vec![biome("empty", "grass", (0.6, 0.4), (0.0, 1.0))]).is_err());
+}
+
+#[test]
+fn ocean_covers_the_sea_pixels_at_sea_level() {
+    let planisphere = tiny_planisphere();
+    let lod = TerrainLod::default();
+    let mut sea_seen = false;
+    // The tiny map's sea lies along its north-west edge, where red and green are low
+    for center in [planisphere.geo_to_subpixel(-170.0, 70.0), planisphere.geo_to_subpixel(10.0, 0.0)] {
+        let detailed = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2,
+                                                                    config::terrain::RADIUS, DistanceMethod::Chebyshev);
+        let quads = lod.quads(&planisphere, center, &detailed, config::terrain::RADIUS, DistanceMethod::Chebyshev);
+        let center_geo = planisphere.subpixel_to_geo(center.0, center.1, center.2);
+        let (vertices, indices) = ocean_mesh(&planisphere, &detailed, &quads, center_geo, 0.5);
+
+        let sea_subpixels = detailed.iter().filter(|&&(i, j, _, _)| planisphere.is_sea(i, j)).count();
+        let sea_quads = quads.iter().filter(|quad| planisphere.is_sea(quad.pixel.0, quad.pixel.1)).count();
+        sea_seen |= sea_subpixels > 0;
+        assert_eq!(vertices.len(), 4 * (sea_subpixels + sea_quads), "around {:?}", center);
+        assert_eq!(indices.len(), 6 * (sea_subpixels + sea_quads));
+        assert!(vertices.iter().all(|vertex| vertex[1] == 0.5));
+
+        // Sea subpixels share their corners with the terrain mesh
+        let (terrain_vertices, ..) = terrain_mesh_with_lod(&planisphere, detailed.clone(), &quads, center_geo, Relief::default());
+        let sea_terrain: Vec<[f32; 2]> = terrain_vertices.chunks(4)
+            .zip(detailed.iter().map(|&(i, j, _, _)| (i, j)).chain(quads.iter().map(|quad| quad.pixel)))
+            .filter(|(_, (i, j))| planisphere.is_sea(*i, *j))
+            .flat_map(|(corners, _)| corners.iter().map(|vertex| [vertex[0], vertex[2]]).collect::<Vec<_>>())
+            .collect();
+        assert_eq!(sea_terrain, vertices.iter().map(|vertex| [vertex[0], vertex[2]]).collect::<Vec<_>>());
+    }
+    assert!(sea_seen, "no sea subpixel rendered");
+}
+
+#[test]
+fn skirts_hang_from_the_outer_border_only() {
+    let planisphere = tiny_planisphere();
+    for center in centers(&planisphere) {
+        let detailed = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2,
+                                                                    config::terrain::RADIUS, DistanceMethod::Chebyshev);
+        let center_geo = planisphere.subpixel_to_geo(center.0, center.1, center.2);
+        for lod in [TerrainLod { block_radius: 0, pixel_radius: 0 }, TerrainLod::default()] {
+            let quads = lod.quads(&planisphere, center, &detailed, config::terrain::RADIUS, DistanceMethod::Chebyshev);
+            let skirts = skirt_edges(&planisphere, &detailed, &quads);
+            assert!(!skirts.is_empty(), "no border around {:?}", center);
+            // The border is the outermost ring: the LOD quads when there are some
+            let first_border_quad = if quads.is_empty() { 0 } else { detailed.len() };
+            assert!(skirts.iter().all(|skirt| skirt.quad >= first_border_quad), "skirt inside the terrain around {:?}", center);
+
+            let (vertices, ..) = terrain_mesh_with_lod(&planisphere, detailed.clone(), &quads, center_geo, Relief::default());
+            let (skirt_vertices, skirt_indices) = skirt_mesh(&vertices, &skirts, config::terrain::SKIRT_DEPTH);
+            assert_eq!(skirt_vertices.len(), 4 * skirts.len());
+            assert!(skirt_indices.iter().all(|&index| index as usize >= vertices.len()));
+            for (skirt, strip) in skirts.iter().zip(skirt_vertices.chunks(4)) {
+                let quad = &vertices[4 * skirt.quad..4 * skirt.quad + 4];
+                assert_eq!([strip[0], strip[1]], [quad[skirt.edge], quad[(skirt.edge + 1) % 4]]);
+                assert_eq!(strip[2][1], strip[1][1] - config::terrain::SKIRT_DEPTH);
+                assert_eq!(strip[3][1], strip[0][1] - config::terrain::SKIRT_DEPTH);
+            }
+            // Strips face away from their quad
+            for (skirt, triangle) in skirts.iter().zip(skirt_indices.chunks(6)) {
+                let [a, b, c] = [0, 1, 2].map(|n| skirt_vertices[triangle[n] as usize - vertices.len()]);
+                let normal = (
+                    (b[1] - a[1]) * (c[2] - a[2]) - (b[2] - a[2]) * (c[1] - a[1]),
+                    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]),
+                );
+                let quad = &vertices[4 * skirt.quad..4 * skirt.quad + 4];
+                let center_x = quad.iter().map(|vertex| vertex[0]).sum::<f32>() / 4.0;
+                let center_z = quad.iter().map(|vertex| vertex[2]).sum::<f32>() / 4.0;
+                assert!(normal.0 * (a[0] - center_x) + normal.1 * (a[2] - center_z) > 0.0, "skirt facing inward around {:?}", center);
+            }
+        }
+    }
+}
+
+#[test]
+fn every_distance_method_selects_a_terrain_around_the_center() {
+    let planisphere = tiny_planisphere();
+    let mut method = DistanceMethod::default();
+    for _ in DistanceMethod::ALL {
+        assert_eq!(DistanceMethod::from_name(&method.name().to_uppercase()), Some(method));
+        for center in centers(&planisphere) {
+            let subpixels = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2, config::terrain::RADIUS, method);
+            let selected: HashSet<Subpixel> = subpixels.iter().map(|&(i, j, k, _)| (i, j, k)).collect();
+            assert!(selected.contains(&center), "{:?} misses its center {:?}", method, center);
+            assert_eq!(selected.len(), subpixels.len(), "{:?} selects subpixels twice around {:?}", method, center);
+            // Larger radii only add subpixels, so the render radius can grow and shrink at runtime
+            let wider = planisphere.get_subpixels_by_distance_method(center.0, center.1, center.2, 2 * config::terrain::RADIUS, method);
+            assert!(wider.len() > subpixels.len());
+            assert_eq!(wider.iter().filter(|&&(i, j, k, _)| selected.contains(&(i, j, k))).count(), subpixels.len());
+        }
+        method = method.next();
+    }
+    assert_eq!(method, DistanceMethod::default(), "next() cycles through all methods");
+    assert_eq!(DistanceMethod::from_name("hexagonal"), None);
+}